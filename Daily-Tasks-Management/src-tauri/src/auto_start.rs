@@ -0,0 +1,120 @@
+use crate::error::AppResult;
+use crate::model::Service;
+use crate::service_graph::{self, Dependencies};
+use crate::service_groups::DEFAULT_READY_TIMEOUT;
+use crate::services::{LaunchOptions, ServiceManager};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+
+/// Tauri event carrying each `AutoStartEvent`.
+pub const AUTO_START_EVENT: &str = "service-auto-start";
+
+/// Progress of starting the `auto_start` services at launch.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum AutoStartEvent {
+    /// Nothing was started, e.g. in safe mode.
+    Skipped { reason: String },
+    Starting {
+        #[serde(rename = "serviceId")]
+        service_id: u32,
+        name: String,
+        /// 1-based position in the start order.
+        index: usize,
+        total: usize,
+    },
+    Started {
+        #[serde(rename = "serviceId")]
+        service_id: u32,
+        name: String,
+        pid: u32,
+    },
+    Failed {
+        #[serde(rename = "serviceId")]
+        service_id: u32,
+        name: String,
+        error: String,
+    },
+    Finished { started: usize, failed: usize },
+}
+
+/// Events of this launch's auto-start, kept so a window that loads after
+/// they were emitted can still show them.
+#[derive(Default)]
+pub struct AutoStartLog {
+    events: Mutex<Vec<AutoStartEvent>>,
+}
+
+impl AutoStartLog {
+    pub fn new() -> Self {
+        AutoStartLog::default()
+    }
+
+    pub fn push(&self, event: AutoStartEvent) -> AppResult<()> {
+        self.events.lock()?.push(event);
+        Ok(())
+    }
+
+    pub fn events(&self) -> AppResult<Vec<AutoStartEvent>> {
+        Ok(self.events.lock()?.clone())
+    }
+}
+
+/// The services to start at launch, each after the auto-start services it
+/// depends on, as a group start orders them. Ties go by id so the order
+/// does not depend on how the store lists them.
+pub fn auto_start_order(services: Vec<Service>, dependencies: &Dependencies) -> Vec<Service> {
+    let mut services: BTreeMap<u32, Service> = services.into_iter().filter(|s| s.auto_start).map(|s| (s.id, s)).collect();
+    let ids: Vec<u32> = services.keys().copied().collect();
+    // Saved dependencies never form a cycle, but launch must not fail on one
+    let order = service_graph::start_order(&ids, dependencies).unwrap_or(ids);
+    order.into_iter().filter_map(|id| services.remove(&id)).collect()
+}
+
+/// Starts `services` one after another, each once the auto-start services
+/// it depends on are ready. A service that fails is reported and the rest
+/// still start. `launch` resolves what each one starts with.
+pub async fn auto_start_services(
+    manager: &ServiceManager,
+    services: Vec<Service>,
+    dependencies: &Dependencies,
+    launch: impl Fn(&Service) -> AppResult<LaunchOptions>,
+    mut report: impl FnMut(AutoStartEvent),
+) {
+    let services = auto_start_order(services, dependencies);
+    let total = services.len();
+    let (mut started, mut failed) = (0, 0);
+    let mut launched = BTreeSet::new();
+
+    for (i, service) in services.iter().enumerate() {
+        report(AutoStartEvent::Starting {
+            service_id: service.id,
+            name: service.name.clone(),
+            index: i + 1,
+            total,
+        });
+
+        let result = async {
+            for dependency in dependencies.get(&service.id).into_iter().flatten() {
+                if launched.contains(dependency) {
+                    manager.wait_until_ready(*dependency, DEFAULT_READY_TIMEOUT).await?;
+                }
+            }
+            manager.start_service_with(service.id, service, launch(service)?).await
+        }.await;
+        match result {
+            Ok(pid) => {
+                started += 1;
+                launched.insert(service.id);
+                report(AutoStartEvent::Started { service_id: service.id, name: service.name.clone(), pid });
+            }
+            Err(e) => {
+                failed += 1;
+                report(AutoStartEvent::Failed { service_id: service.id, name: service.name.clone(), error: e.to_string() });
+            }
+        }
+    }
+
+    report(AutoStartEvent::Finished { started, failed });
+}
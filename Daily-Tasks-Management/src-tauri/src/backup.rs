@@ -0,0 +1,363 @@
+use crate::database::Database;
+use crate::error::{AppError, AppResult, Context};
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const BACKUPS_DIR: &str = "backups";
+pub const KEEP_DAILY_FLAG: &str = "--backup-keep-daily";
+pub const KEEP_WEEKLY_FLAG: &str = "--backup-keep-weekly";
+
+/// How often the scheduler checks whether a snapshot is due.
+pub const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Age of the newest snapshot after which a scheduled one is taken.
+pub const SCHEDULED_SNAPSHOT_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+const SNAPSHOT_EXTENSION: &str = "db";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupReason {
+    Scheduled,
+    Manual,
+    Migration,
+    Import,
+    Optimize,
+    Restore,
+    Edit,
+}
+
+impl BackupReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackupReason::Scheduled => "scheduled",
+            BackupReason::Manual => "manual",
+            BackupReason::Migration => "migration",
+            BackupReason::Import => "import",
+            BackupReason::Optimize => "optimize",
+            BackupReason::Restore => "restore",
+            BackupReason::Edit => "edit",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "scheduled" => Some(BackupReason::Scheduled),
+            "manual" => Some(BackupReason::Manual),
+            "migration" => Some(BackupReason::Migration),
+            "import" => Some(BackupReason::Import),
+            "optimize" => Some(BackupReason::Optimize),
+            "restore" => Some(BackupReason::Restore),
+            "edit" => Some(BackupReason::Edit),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Snapshot {
+    pub id: String,
+    pub reason: BackupReason,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "sizeBytes")]
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SnapshotDetails {
+    pub snapshot: Snapshot,
+    /// Result of `PRAGMA integrity_check`; "ok" when the file is sound.
+    pub integrity: String,
+    #[serde(rename = "tableRowCounts")]
+    pub table_row_counts: BTreeMap<String, u32>,
+}
+
+/// Newest snapshot of each of the last `keep_daily` days and of each of the
+/// last `keep_weekly` ISO weeks is kept, as is anything taken in the past
+/// day; everything else is pruned.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct RetentionPolicy {
+    #[serde(rename = "keepDaily")]
+    pub keep_daily: usize,
+    #[serde(rename = "keepWeekly")]
+    pub keep_weekly: usize,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    pub fn from_args(args: &[String]) -> Self {
+        let default = RetentionPolicy::default();
+        let count = |flag| {
+            crate::workspace::flag_value(args, flag).and_then(|v| v.parse().ok())
+        };
+
+        RetentionPolicy {
+            keep_daily: count(KEEP_DAILY_FLAG).unwrap_or(default.keep_daily),
+            keep_weekly: count(KEEP_WEEKLY_FLAG).unwrap_or(default.keep_weekly),
+        }
+    }
+}
+
+/// Snapshots live in a `backups` directory next to the database file they
+/// protect, so every workspace keeps its own history.
+pub struct BackupManager {
+    policy: RetentionPolicy,
+}
+
+impl BackupManager {
+    pub fn new(policy: RetentionPolicy) -> Self {
+        BackupManager { policy }
+    }
+
+    pub fn backup_dir(db_path: &Path) -> PathBuf {
+        db_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(BACKUPS_DIR)
+    }
+
+    pub fn create_snapshot(&self, db: &Database, reason: BackupReason) -> AppResult<Snapshot> {
+        let snapshot = self.write_snapshot(db, reason)?;
+        self.prune(&Self::backup_dir(&database_path(db)?))?;
+        Ok(snapshot)
+    }
+
+    fn write_snapshot(&self, db: &Database, reason: BackupReason) -> AppResult<Snapshot> {
+        let dir = Self::backup_dir(&database_path(db)?);
+        fs::create_dir_all(&dir)
+            .map_err(|e| AppError::io("Failed to create backup directory", &dir, e))?;
+
+        let created_at = Utc::now();
+        let id = format!(
+            "{}-{}.{}",
+            created_at.format(TIMESTAMP_FORMAT),
+            reason.as_str(),
+            SNAPSHOT_EXTENSION
+        );
+
+        // Write under a temporary name so a failed backup never looks like a snapshot
+        let partial = dir.join(format!("{}.partial", id));
+        let target = dir.join(&id);
+        if let Err(e) = db.backup_to(&partial).context("Failed to back up database") {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+        fs::rename(&partial, &target)
+            .map_err(|e| AppError::io("Failed to finalize backup", &target, e))?;
+
+        describe(&target).ok_or_else(|| AppError::internal(format!("Failed to read back snapshot {}", id)))
+    }
+
+    /// Takes a scheduled snapshot when the newest one is older than `max_age`.
+    pub fn snapshot_if_due(&self, db: &Database, max_age: Duration) -> AppResult<Option<Snapshot>> {
+        let snapshots = self.list_snapshots(&database_path(db)?)?;
+        let due = match snapshots.first().and_then(|s| parse_timestamp(&s.created_at)) {
+            Some(newest) => Utc::now().signed_duration_since(newest).to_std().unwrap_or_default() >= max_age,
+            None => true,
+        };
+
+        if due {
+            self.create_snapshot(db, BackupReason::Scheduled).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Snapshots of the database at `db_path`, newest first.
+    pub fn list_snapshots(&self, db_path: &Path) -> AppResult<Vec<Snapshot>> {
+        let dir = Self::backup_dir(db_path);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| AppError::io("Failed to read backup directory", &dir, e))?;
+
+        let mut snapshots: Vec<Snapshot> = entries
+            .flatten()
+            .filter_map(|entry| describe(&entry.path()))
+            .collect();
+        snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(snapshots)
+    }
+
+    pub fn inspect_snapshot(&self, db_path: &Path, id: &str) -> AppResult<SnapshotDetails> {
+        let path = self.snapshot_path(db_path, id)?;
+        let snapshot = describe(&path).ok_or_else(|| AppError::not_found("Snapshot", id))?;
+
+        let conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .context("Failed to open snapshot")?;
+
+        let integrity: String = conn
+            .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+            .context("Failed to check snapshot integrity")?;
+
+        let mut stmt = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+            .context("Failed to read snapshot schema")?;
+        let tables = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .context("Failed to read snapshot schema")?;
+
+        let mut table_row_counts = BTreeMap::new();
+        for table in tables {
+            let count: u32 = conn
+                .query_row(&format!("SELECT COUNT(*) FROM \"{}\"", table.replace('"', "\"\"")), [], |row| row.get(0))
+                .unwrap_or(0);
+            table_row_counts.insert(table, count);
+        }
+
+        Ok(SnapshotDetails {
+            snapshot,
+            integrity,
+            table_row_counts,
+        })
+    }
+
+    /// Restores a snapshot into the live database. The current state is
+    /// snapshotted first so the restore itself can be undone.
+    pub fn restore_snapshot(&self, db: &mut Database, id: &str) -> AppResult<Snapshot> {
+        let db_path = database_path(db)?;
+        let path = self.snapshot_path(&db_path, id)?;
+        if !path.is_file() {
+            return Err(AppError::not_found("Snapshot", id));
+        }
+
+        // Pruning waits until after the restore so it cannot remove the source
+        let safety = self.write_snapshot(db, BackupReason::Restore)?;
+        db.restore_from(&path).context("Failed to restore snapshot")?;
+        self.prune(&Self::backup_dir(&db_path))?;
+        Ok(safety)
+    }
+
+    /// Deletes snapshots that fall outside the retention policy and returns their ids.
+    pub fn prune(&self, dir: &Path) -> AppResult<Vec<String>> {
+        let entries = fs::read_dir(dir)
+            .map_err(|e| AppError::io("Failed to read backup directory", dir, e))?;
+        let mut snapshots: Vec<Snapshot> = entries
+            .flatten()
+            .filter_map(|entry| describe(&entry.path()))
+            .collect();
+        snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+
+        let keep = retained_ids(&snapshots, self.policy, Utc::now());
+        let mut removed = Vec::new();
+        for snapshot in snapshots {
+            if !keep.contains(&snapshot.id) {
+                let path = dir.join(&snapshot.id);
+                fs::remove_file(&path)
+                    .map_err(|e| AppError::io("Failed to remove snapshot", &path, e))?;
+                removed.push(snapshot.id);
+            }
+        }
+        Ok(removed)
+    }
+
+    fn snapshot_path(&self, db_path: &Path, id: &str) -> AppResult<PathBuf> {
+        if parse_snapshot_id(id).is_none() {
+            return Err(AppError::invalid_field("id", format!("Invalid snapshot id '{}'", id)));
+        }
+        Ok(Self::backup_dir(db_path).join(id))
+    }
+}
+
+/// Snapshots a database just imported from elsewhere, so the data can be
+/// restored as it arrived whatever opening and editing it does later.
+pub fn snapshot_import(db_path: &Path, backups: &BackupManager) -> AppResult<Snapshot> {
+    let db = Database::open(&db_path.to_string_lossy())?;
+    backups.create_snapshot(&db, BackupReason::Import)
+}
+
+/// Opens a database, taking a migration snapshot first if opening it
+/// would rewrite an existing schema.
+pub fn open_database(db_path: &Path, backups: &BackupManager) -> AppResult<Database> {
+    let db = Database::open(&db_path.to_string_lossy())?;
+
+    if db.needs_migration().context("Failed to inspect database schema")? {
+        backups.create_snapshot(&db, BackupReason::Migration)?;
+    }
+
+    db.migrate().context("Failed to migrate database")?;
+    Ok(db)
+}
+
+/// Picks the snapshots kept by `policy` from a newest-first list.
+fn retained_ids(snapshots: &[Snapshot], policy: RetentionPolicy, now: DateTime<Utc>) -> HashSet<String> {
+    // The newest snapshot always survives, even with a zero policy
+    let mut keep: HashSet<String> = snapshots.first().map(|s| s.id.clone()).into_iter().collect();
+    let mut days = Vec::new();
+    let mut weeks = Vec::new();
+
+    for snapshot in snapshots {
+        let Some(created_at) = parse_timestamp(&snapshot.created_at) else {
+            continue;
+        };
+
+        let age = now.signed_duration_since(created_at).to_std().unwrap_or_default();
+        if age < SCHEDULED_SNAPSHOT_AGE {
+            keep.insert(snapshot.id.clone());
+        }
+
+        let day = created_at.date_naive();
+        if days.len() < policy.keep_daily && !days.contains(&day) {
+            days.push(day);
+            keep.insert(snapshot.id.clone());
+        }
+
+        let week = (created_at.iso_week().year(), created_at.iso_week().week());
+        if weeks.len() < policy.keep_weekly && !weeks.contains(&week) {
+            weeks.push(week);
+            keep.insert(snapshot.id.clone());
+        }
+    }
+
+    keep
+}
+
+fn database_path(db: &Database) -> AppResult<PathBuf> {
+    db.path()
+        .map(PathBuf::from)
+        .ok_or_else(|| AppError::validation("In-memory databases cannot be backed up"))
+}
+
+fn describe(path: &Path) -> Option<Snapshot> {
+    let id = path.file_name()?.to_str()?.to_string();
+    let (created_at, reason) = parse_snapshot_id(&id)?;
+    let size_bytes = fs::metadata(path).ok()?.len();
+
+    Some(Snapshot {
+        id,
+        reason,
+        created_at: created_at.to_rfc3339(),
+        size_bytes,
+    })
+}
+
+/// Snapshot ids are file names of the form `<timestamp>-<reason>.db`.
+fn parse_snapshot_id(id: &str) -> Option<(DateTime<Utc>, BackupReason)> {
+    let stem = id.strip_suffix(&format!(".{}", SNAPSHOT_EXTENSION))?;
+    let (timestamp, reason) = stem.split_once('-')?;
+    let created_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+    Some((created_at.and_utc(), BackupReason::parse(reason)?))
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
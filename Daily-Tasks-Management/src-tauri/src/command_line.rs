@@ -0,0 +1,157 @@
+use crate::error::{AppError, AppResult};
+
+/// A service command split the way a POSIX shell would, without running
+/// one: leading `NAME=value` words become environment variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCommand {
+    pub env: Vec<(String, String)>,
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+/// Characters that only mean something to a shell, so a command using
+/// them unquoted needs shell mode.
+const SHELL_OPERATORS: &[char] = &['|', '&', ';', '<', '>', '(', ')', '`'];
+
+struct Word {
+    text: String,
+    // Length of the unquoted prefix, used to tell `A=b` from `"A=b"`
+    unquoted_prefix: usize,
+    quoted: bool,
+}
+
+/// Splits a command into words. Single quotes keep everything literally,
+/// double quotes allow `\"`, `\\`, `` \` `` and `\$`, and a backslash
+/// outside quotes escapes the next character. Pipes, redirections,
+/// command substitution and variable expansion are rejected with a hint
+/// to use shell mode.
+pub fn parse_command(command: &str) -> AppResult<ParsedCommand> {
+    let mut words = split_words(command)?.into_iter().peekable();
+
+    let mut env = Vec::new();
+    while let Some((name, value)) = words.peek().and_then(assignment) {
+        env.push((name, value));
+        words.next();
+    }
+
+    let program = words.next()
+        .map(|w| w.text)
+        .ok_or_else(|| AppError::invalid_field("command", "Empty command"))?;
+    Ok(ParsedCommand {
+        env,
+        program,
+        args: words.map(|w| w.text).collect(),
+    })
+}
+
+/// True for names a shell accepts as variables: letters, digits and `_`,
+/// not starting with a digit.
+pub(crate) fn is_env_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn assignment(word: &Word) -> Option<(String, String)> {
+    let (name, value) = word.text.split_once('=')?;
+    // The name and `=` must be unquoted, as in a shell
+    (is_env_name(name) && word.unquoted_prefix > name.len()).then(|| (name.to_string(), value.to_string()))
+}
+
+fn shell_syntax(found: impl std::fmt::Display) -> AppError {
+    AppError::invalid_field(
+        "command",
+        format!("Command uses shell syntax ({}); turn on shell mode to run it", found),
+    )
+}
+
+// `$` followed by a name, `{` or `(` is an expansion; a lone `$` is literal
+fn expands(next: Option<&char>) -> bool {
+    next.is_some_and(|&c| c == '{' || c == '(' || c == '_' || c.is_ascii_alphanumeric())
+}
+
+fn split_words(command: &str) -> AppResult<Vec<Word>> {
+    let mut words = Vec::new();
+    let mut current: Option<Word> = None;
+    let mut chars = command.chars().peekable();
+
+    fn word(current: &mut Option<Word>) -> &mut Word {
+        current.get_or_insert_with(|| Word {
+            text: String::new(),
+            unquoted_prefix: 0,
+            quoted: false,
+        })
+    }
+
+    // Pushes an unquoted character, extending the unquoted prefix if no quote came yet
+    fn push_plain(current: &mut Option<Word>, c: char) {
+        let word = word(current);
+        word.text.push(c);
+        if !word.quoted {
+            word.unquoted_prefix = word.text.len();
+        }
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(word) = current.take() {
+                    words.push(word);
+                }
+            }
+            '\'' => {
+                let word = word(&mut current);
+                word.quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.text.push(c),
+                        None => return Err(AppError::invalid_field("command", "Unterminated single quote")),
+                    }
+                }
+            }
+            '"' => {
+                let word = word(&mut current);
+                word.quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '`' | '$')) => word.text.push(c),
+                            // A backslash before a newline joins the lines
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.text.push('\\');
+                                word.text.push(c);
+                            }
+                            None => return Err(AppError::invalid_field("command", "Unterminated double quote")),
+                        },
+                        Some('`') => return Err(shell_syntax("`")),
+                        Some('$') if expands(chars.peek()) => return Err(shell_syntax("$")),
+                        Some(c) => word.text.push(c),
+                        None => return Err(AppError::invalid_field("command", "Unterminated double quote")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some(c) => {
+                    let word = word(&mut current);
+                    word.text.push(c);
+                    word.quoted = true;
+                }
+                None => return Err(AppError::invalid_field("command", "Command ends with a backslash")),
+            },
+            '$' if expands(chars.peek()) => return Err(shell_syntax("$")),
+            // Comments only start at the beginning of a word
+            '#' if current.is_none() => break,
+            c if SHELL_OPERATORS.contains(&c) => return Err(shell_syntax(c)),
+            c => push_plain(&mut current, c),
+        }
+    }
+
+    if let Some(word) = current {
+        words.push(word);
+    }
+    Ok(words)
+}
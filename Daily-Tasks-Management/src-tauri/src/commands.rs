@@ -1,15 +1,44 @@
 use crate::model::*;
+use crate::auto_start::{AutoStartEvent, AutoStartLog};
+use crate::backup::{self, BackupManager, BackupReason, Snapshot, SnapshotDetails};
 use crate::database::Database;
-use crate::optimization::optimize_task_schedule;
-use crate::projects::ProjectManager;
-use crate::services::ServiceManager;
-use crate::git;
-use std::sync::Mutex;
+use crate::database_browser::{self, QueryParameter, QueryParams, QueryResult, TableInfo};
+use crate::database_connector::{self, ConnectorTarget};
+use crate::database_diff::{self, DatabaseDiff, DiffOptions};
+use crate::database_edit::{self, ChangesetSummary, EditableRows, RowEdit, WriteMode, WriteModes};
+use crate::database_export::{self, ExportFormat, ExportSource, ExportSummary};
+use crate::database_query::{QueryOptions, QueryPage, QueryProgress, QuerySessions, QueryStarted};
+use crate::database_schema::DatabaseSchema;
+use crate::database_discovery::{self, ProjectDatabases, WORKSPACE_DATABASE_ID};
+use crate::error::{AppError, AppResult};
+use crate::handlers;
+use crate::log_store::{LogStore, LogWriter};
+use crate::secrets::{self, FileSecretStore, SecretStore};
+use crate::service_env::ResolvedEnvVar;
+use crate::service_metrics::MetricsHistory;
+use crate::services::{LaunchOptions, ServiceManager};
+use crate::service_groups::{self, GroupStartReport};
+use crate::settings::{AppSettings, SettingsManager};
+use crate::workspace::{validate_workspace_name, WorkspaceManager};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::State;
 
 // Database instance will be managed by Tauri's state management
 pub type DbState = Mutex<Database>;
 pub type ServiceMgrState = ServiceManager;
+pub type WorkspaceState = WorkspaceManager;
+pub type BackupState = BackupManager;
+pub type SecretsState = FileSecretStore;
+// Shared so a fetch can wait for its page on a blocking thread
+pub type QuerySessionsState = Arc<QuerySessions>;
+pub type WriteModesState = WriteModes;
+pub type SettingsState = SettingsManager;
+pub type AutoStartLogState = AutoStartLog;
+pub type MetricsState = MetricsHistory;
+pub type LogStoreState = Mutex<LogStore>;
+pub type LogWriterState = LogWriter;
 
 #[tauri::command]
 pub fn greet(name: &str) -> String {
@@ -18,53 +47,46 @@ pub fn greet(name: &str) -> String {
 
 // Task management commands
 #[tauri::command]
-pub fn create_task(db: tauri::State<DbState>, form_data: TaskFormData) -> Result<Task, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.create_task(form_data).map_err(|e| format!("Failed to create task: {}", e))
+pub fn create_task(db: tauri::State<DbState>, form_data: TaskFormData) -> AppResult<Task> {
+    let db = db.lock()?;
+    handlers::create_task(&*db, form_data)
 }
 
 #[tauri::command]
-pub fn get_all_tasks(db: tauri::State<DbState>) -> Result<Vec<Task>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_all_tasks().map_err(|e| format!("Failed to get tasks: {}", e))
+pub fn get_all_tasks(db: tauri::State<DbState>) -> AppResult<Vec<Task>> {
+    let db = db.lock()?;
+    handlers::get_all_tasks(&*db)
 }
 
 #[tauri::command]
-pub fn get_task(db: tauri::State<DbState>, id: u32) -> Result<Option<Task>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_task_by_id(id).map_err(|e| format!("Failed to get task: {}", e))
+pub fn get_task(db: tauri::State<DbState>, id: u32) -> AppResult<Option<Task>> {
+    let db = db.lock()?;
+    handlers::get_task(&*db, id)
 }
 
 #[tauri::command]
-pub fn update_task(db: tauri::State<DbState>, task: Task) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.update_task(&task).map_err(|e| format!("Failed to update task: {}", e))
+pub fn update_task(db: tauri::State<DbState>, task: Task) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::update_task(&*db, &task)
 }
 
 #[tauri::command]
-pub fn delete_task(db: tauri::State<DbState>, id: u32) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.delete_task(id).map_err(|e| format!("Failed to delete task: {}", e))
+pub fn delete_task(db: tauri::State<DbState>, id: u32) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::delete_task(&*db, id)
 }
 
 #[tauri::command]
-pub fn toggle_task_completion(db: tauri::State<DbState>, id: u32, completed: bool) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.mark_task_completed(id, completed).map_err(|e| format!("Failed to toggle task completion: {}", e))
+pub fn toggle_task_completion(db: tauri::State<DbState>, id: u32, completed: bool) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::toggle_task_completion(&*db, id, completed)
 }
 
 #[tauri::command]
-pub fn optimize_tasks(db: tauri::State<DbState>) -> Result<Vec<Task>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    let tasks = db.get_all_tasks().map_err(|e| format!("Failed to get tasks: {}", e))?;
-    let optimized_tasks = optimize_task_schedule(tasks);
-    
-    // Update each task in the database with the new scheduled start time
-    for task in &optimized_tasks {
-        db.update_task(&task).map_err(|e| format!("Failed to update task: {}", e))?;
-    }
-
-    Ok(optimized_tasks)
+pub fn optimize_tasks(db: tauri::State<DbState>, backups: tauri::State<BackupState>) -> AppResult<Vec<Task>> {
+    let db = db.lock()?;
+    backups.create_snapshot(&db, BackupReason::Optimize)?;
+    handlers::optimize_tasks(&*db)
 }
 
 // Project management commands
@@ -73,43 +95,74 @@ pub fn add_project(
     db: State<DbState>,
     name: String,
     path: String,
-) -> Result<Project, String> {
-    // Validate path
-    ProjectManager::validate_project_path(&path)?;
-    
-    // Get project info
-    let (detected_name, project_type) = ProjectManager::get_project_info(&path)
-        .map_err(|e| format!("Failed to get project info: {}", e))?;
-    
-    let final_name = if name.is_empty() { detected_name } else { name };
-    
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.create_project(final_name, path, project_type, None)
-        .map_err(|e| format!("Failed to create project: {}", e))
+) -> AppResult<Project> {
+    let db = db.lock()?;
+    handlers::add_project(&*db, name, path)
 }
 
 #[tauri::command]
-pub fn remove_project(db: State<DbState>, id: u32) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.delete_project(id).map_err(|e| format!("Failed to delete project: {}", e))
+pub fn preview_project_removal(db: State<DbState>, id: u32) -> AppResult<ProjectDeletionImpact> {
+    let db = db.lock()?;
+    handlers::preview_project_removal(&*db, id)
+}
+
+#[tauri::command]
+pub async fn remove_project(
+    db: State<'_, DbState>,
+    service_mgr: State<'_, ServiceMgrState>,
+    workspaces: State<'_, WorkspaceState>,
+    secrets: State<'_, SecretsState>,
+    id: u32,
+    mode: Option<ProjectDeleteMode>,
+) -> AppResult<ProjectDeletionImpact> {
+    let mode = mode.unwrap_or_default();
+    let workspace = workspaces.active_workspace()?;
+
+    // Services deleted along with the project must not keep running unmanaged
+    let mut service_secret_keys = Vec::new();
+    if mode == ProjectDeleteMode::Cascade {
+        let impact = {
+            let db = db.lock()?;
+            let impact = handlers::preview_project_removal(&*db, id)?;
+            service_secret_keys = handlers::service_secret_keys(&*db, &workspace, &impact.service_ids)?;
+            impact
+        };
+        for service_id in impact.service_ids {
+            let _ = service_mgr.stop_service(service_id).await;
+        }
+    }
+
+    let impact = {
+        let db = db.lock()?;
+        handlers::remove_project(&*db, id, mode)?
+    };
+
+    // Profiles always go with the project; their passwords must not outlive them
+    for profile_id in &impact.connection_profile_ids {
+        let _ = secrets.delete_secret(&secrets::connection_password_key(&workspace, *profile_id));
+    }
+    for key in service_secret_keys {
+        let _ = secrets.delete_secret(&key);
+    }
+    Ok(impact)
 }
 
 #[tauri::command]
-pub fn get_all_projects(db: State<DbState>) -> Result<Vec<Project>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_all_projects().map_err(|e| format!("Failed to get projects: {}", e))
+pub fn get_all_projects(db: State<DbState>) -> AppResult<Vec<Project>> {
+    let db = db.lock()?;
+    handlers::get_all_projects(&*db)
 }
 
 #[tauri::command]
-pub fn get_project_by_id(db: State<DbState>, id: u32) -> Result<Option<Project>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_project_by_id(id).map_err(|e| format!("Failed to get project: {}", e))
+pub fn get_project_by_id(db: State<DbState>, id: u32) -> AppResult<Option<Project>> {
+    let db = db.lock()?;
+    handlers::get_project_by_id(&*db, id)
 }
 
 #[tauri::command]
-pub fn update_project(db: State<DbState>, project: Project) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.update_project(&project).map_err(|e| format!("Failed to update project: {}", e))
+pub fn update_project(db: State<DbState>, project: Project) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::update_project(&*db, &project)
 }
 
 // Service management commands
@@ -121,52 +174,91 @@ pub fn register_service(
     working_dir: Option<String>,
     project_id: Option<u32>,
     auto_start: bool,
-) -> Result<Service, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.create_service(name, command, working_dir, project_id, auto_start)
-        .map_err(|e| format!("Failed to create service: {}", e))
+    shell: Option<bool>,
+) -> AppResult<Service> {
+    let db = db.lock()?;
+    handlers::register_service(&*db, name, command, working_dir, project_id, auto_start, shell.unwrap_or(false))
+}
+
+/// Changes take effect the next time the service starts.
+#[tauri::command]
+pub fn update_service(db: State<DbState>, service: Service) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::update_service(&*db, &service)
 }
 
 #[tauri::command]
-pub fn unregister_service(db: State<DbState>, id: u32) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.delete_service(id).map_err(|e| format!("Failed to delete service: {}", e))
+pub fn unregister_service(
+    db: State<DbState>,
+    workspaces: State<WorkspaceState>,
+    secrets: State<SecretsState>,
+    metrics: State<MetricsState>,
+    id: u32,
+) -> AppResult<()> {
+    let workspace = workspaces.active_workspace()?;
+    let db = db.lock()?;
+    let secret_keys = handlers::service_secret_keys(&*db, &workspace, &[id])?;
+    handlers::unregister_service(&*db, id)?;
+    for key in secret_keys {
+        let _ = secrets.delete_secret(&key);
+    }
+    // A later service may get the same id
+    metrics.clear(id)
 }
 
 #[tauri::command]
-pub fn get_all_services(db: State<DbState>) -> Result<Vec<Service>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_all_services().map_err(|e| format!("Failed to get services: {}", e))
+pub fn get_all_services(db: State<DbState>) -> AppResult<Vec<Service>> {
+    let db = db.lock()?;
+    handlers::get_all_services(&*db)
 }
 
 #[tauri::command]
-pub fn get_service_by_id(db: State<DbState>, id: u32) -> Result<Option<Service>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    db.get_service_by_id(id).map_err(|e| format!("Failed to get service: {}", e))
+pub fn get_service_by_id(db: State<DbState>, id: u32) -> AppResult<Option<Service>> {
+    let db = db.lock()?;
+    handlers::get_service_by_id(&*db, id)
+}
+
+/// A service with the variables, restart policy, probes and stop grace
+/// period it starts with.
+pub(crate) fn service_launch(
+    db: &DbState,
+    workspaces: &WorkspaceState,
+    secrets: &SecretsState,
+    settings: &SettingsState,
+    id: u32,
+) -> AppResult<(Service, LaunchOptions)> {
+    let stop_grace = settings.get()?.stop_grace_period();
+    let workspace = workspaces.active_workspace()?;
+    let db = db.lock()?;
+    let service = handlers::require_service(&*db, id)?;
+    let env = handlers::service_launch_environment(&*db, secrets, &workspace, &service)?
+        .into_iter()
+        .filter_map(|var| var.value.map(|value| (var.name, value)))
+        .collect();
+    let restart = handlers::get_restart_policy(&*db, id)?;
+    let health = handlers::get_health_checks(&*db, id)?;
+    let log_parser = handlers::get_log_parser(&*db, id)?;
+    Ok((service, LaunchOptions { env, restart, health, log_parser, stop_grace }))
 }
 
 #[tauri::command]
 pub async fn start_service(
     service_mgr: State<'_, ServiceMgrState>,
     db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    secrets: State<'_, SecretsState>,
+    settings: State<'_, SettingsState>,
     id: u32,
-) -> Result<u32, String> {
-    // Get service from database
-    let service = {
-        let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-        db.get_service_by_id(id)
-            .map_err(|e| format!("Failed to get service: {}", e))?
-            .ok_or_else(|| "Service not found".to_string())?
-    };
-    
-    service_mgr.start_service_by_id(id, &service).await
+) -> AppResult<u32> {
+    let (service, options) = service_launch(&db, &workspaces, &secrets, &settings, id)?;
+    service_mgr.start_service_with(id, &service, options).await
 }
 
 #[tauri::command]
 pub async fn stop_service(
     service_mgr: State<'_, ServiceMgrState>,
     id: u32,
-) -> Result<(), String> {
+) -> AppResult<()> {
     service_mgr.stop_service(id).await
 }
 
@@ -174,27 +266,23 @@ pub async fn stop_service(
 pub async fn restart_service(
     service_mgr: State<'_, ServiceMgrState>,
     db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    secrets: State<'_, SecretsState>,
+    settings: State<'_, SettingsState>,
     id: u32,
-) -> Result<u32, String> {
-    // Stop first
+) -> AppResult<u32> {
+    // Resolve the environment first, so a broken env file leaves the service running
+    let (service, options) = service_launch(&db, &workspaces, &secrets, &settings, id)?;
+
     service_mgr.stop_service(id).await?;
-    
-    // Then start
-    let service = {
-        let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-        db.get_service_by_id(id)
-            .map_err(|e| format!("Failed to get service: {}", e))?
-            .ok_or_else(|| "Service not found".to_string())?
-    };
-    
-    service_mgr.start_service_by_id(id, &service).await
+    service_mgr.start_service_with(id, &service, options).await
 }
 
 #[tauri::command]
 pub async fn get_service_status(
     service_mgr: State<'_, ServiceMgrState>,
     id: u32,
-) -> Result<bool, String> {
+) -> AppResult<bool> {
     Ok(service_mgr.is_service_running(id).await)
 }
 
@@ -203,73 +291,263 @@ pub async fn get_service_logs(
     service_mgr: State<'_, ServiceMgrState>,
     service_id: u32,
     limit: Option<usize>,
-) -> Result<Vec<LogEntry>, String> {
+) -> AppResult<Vec<LogEntry>> {
     Ok(service_mgr.get_service_logs(service_id, limit).await)
 }
 
+/// Why a service is or is not running, and how often it was restarted.
+#[tauri::command]
+pub async fn get_service_state(
+    service_mgr: State<'_, ServiceMgrState>,
+    id: u32,
+) -> AppResult<ServiceStatus> {
+    Ok(service_mgr.service_status(id).await)
+}
+
+/// Stored log lines matching `query`, newest first.
+#[tauri::command]
+pub fn query_service_logs(log_store: State<LogStoreState>, query: LogQuery) -> AppResult<LogPage> {
+    let log_store = log_store.lock()?;
+    log_store.query(&query)
+}
+
+/// Clears both the recent lines in memory and the stored ones.
+#[tauri::command]
+pub async fn clear_service_logs(
+    service_mgr: State<'_, ServiceMgrState>,
+    log_store: State<'_, LogStoreState>,
+    log_writer: State<'_, LogWriterState>,
+    id: u32,
+) -> AppResult<()> {
+    service_mgr.clear_service_logs(id).await;
+    // Lines still on their way would otherwise be stored after the delete
+    log_writer.flush().await;
+    log_store.lock()?.delete_service(id)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_log_retention(db: State<DbState>, id: u32) -> AppResult<LogRetention> {
+    let db = db.lock()?;
+    handlers::get_log_retention(&*db, id)
+}
+
+#[tauri::command]
+pub fn set_log_retention(db: State<DbState>, id: u32, retention: LogRetention) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::set_log_retention(&*db, id, &retention)
+}
+
+/// Applies from the service's next start.
+#[tauri::command]
+pub fn get_log_parser(db: State<DbState>, id: u32) -> AppResult<LogParser> {
+    let db = db.lock()?;
+    handlers::get_log_parser(&*db, id)
+}
+
+#[tauri::command]
+pub fn set_log_parser(db: State<DbState>, id: u32, parser: LogParser) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::set_log_parser(&*db, id, &parser)
+}
+
+/// CPU, memory, thread and port samples of the service, oldest first.
+#[tauri::command]
+pub fn get_service_metrics(metrics: State<MetricsState>, id: u32, limit: Option<usize>) -> AppResult<Vec<ResourceSample>> {
+    metrics.samples(id, limit)
+}
+
+#[tauri::command]
+pub async fn get_service_crashes(
+    service_mgr: State<'_, ServiceMgrState>,
+    id: u32,
+) -> AppResult<Vec<CrashRecord>> {
+    Ok(service_mgr.get_service_crashes(id).await)
+}
+
+/// Applies from the next start of the service.
+#[tauri::command]
+pub fn get_restart_policy(db: State<DbState>, id: u32) -> AppResult<RestartPolicy> {
+    let db = db.lock()?;
+    handlers::get_restart_policy(&*db, id)
+}
+
+#[tauri::command]
+pub fn set_restart_policy(db: State<DbState>, id: u32, policy: RestartPolicy) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::set_restart_policy(&*db, id, &policy)
+}
+
+// Health check commands
+
+/// Apply from the next start of the service.
+#[tauri::command]
+pub fn get_health_checks(db: State<DbState>, id: u32) -> AppResult<HealthChecks> {
+    let db = db.lock()?;
+    handlers::get_health_checks(&*db, id)
+}
+
+#[tauri::command]
+pub fn set_health_checks(db: State<DbState>, id: u32, checks: HealthChecks) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::set_health_checks(&*db, id, &checks)
+}
+
+// Service dependency and group commands
+#[tauri::command]
+pub fn get_service_dependencies(db: State<DbState>, id: u32) -> AppResult<Vec<u32>> {
+    let db = db.lock()?;
+    handlers::get_service_dependencies(&*db, id)
+}
+
+#[tauri::command]
+pub fn set_service_dependencies(db: State<DbState>, id: u32, depends_on: Vec<u32>) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::set_service_dependencies(&*db, id, &depends_on)
+}
+
+#[tauri::command]
+pub fn get_service_groups(db: State<DbState>) -> AppResult<Vec<ServiceGroup>> {
+    let db = db.lock()?;
+    handlers::get_service_groups(&*db)
+}
+
+#[tauri::command]
+pub fn create_service_group(db: State<DbState>, name: String, service_ids: Vec<u32>) -> AppResult<ServiceGroup> {
+    let db = db.lock()?;
+    handlers::create_service_group(&*db, name, &service_ids)
+}
+
+#[tauri::command]
+pub fn update_service_group(db: State<DbState>, group: ServiceGroup) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::update_service_group(&*db, &group)
+}
+
+#[tauri::command]
+pub fn delete_service_group(db: State<DbState>, id: u32) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::delete_service_group(&*db, id)
+}
+
+/// Starts a group and its dependencies in dependency order, each once the
+/// services it depends on are ready.
+#[tauri::command]
+pub async fn start_service_group(
+    service_mgr: State<'_, ServiceMgrState>,
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    secrets: State<'_, SecretsState>,
+    settings: State<'_, SettingsState>,
+    id: u32,
+    ready_timeout_secs: Option<u64>,
+) -> AppResult<GroupStartReport> {
+    let services = {
+        let db = db.lock()?;
+        handlers::group_start_order(&*db, id)?
+    };
+    let ready_timeout = ready_timeout_secs.map(Duration::from_secs).unwrap_or(service_groups::DEFAULT_READY_TIMEOUT);
+    let launch = |service: &Service| {
+        service_launch(&db, &workspaces, &secrets, &settings, service.id).map(|(_, options)| options)
+    };
+    Ok(service_groups::start_in_order(&service_mgr, services, launch, ready_timeout).await)
+}
+
+/// Stops a group's services, dependents first. Returns the ids stopped.
+#[tauri::command]
+pub async fn stop_service_group(
+    service_mgr: State<'_, ServiceMgrState>,
+    db: State<'_, DbState>,
+    id: u32,
+) -> AppResult<Vec<u32>> {
+    let services = {
+        let db = db.lock()?;
+        handlers::group_stop_order(&*db, id)?
+    };
+    service_groups::stop_in_order(&service_mgr, services).await
+}
+
+// Service environment commands
+#[tauri::command]
+pub fn get_service_environment(db: State<DbState>, id: u32) -> AppResult<ServiceEnvironment> {
+    let db = db.lock()?;
+    handlers::get_service_environment(&*db, id)
+}
+
+/// Secret values are written to the secret store, never to the database.
+#[tauri::command]
+pub fn set_service_environment(
+    db: State<DbState>,
+    workspaces: State<WorkspaceState>,
+    secrets: State<SecretsState>,
+    id: u32,
+    environment: ServiceEnvironment,
+) -> AppResult<ServiceEnvironment> {
+    let workspace = workspaces.active_workspace()?;
+    let db = db.lock()?;
+    handlers::set_service_environment(&*db, &*secrets, &workspace, id, &environment)
+}
+
+#[tauri::command]
+pub fn preview_service_environment(
+    db: State<DbState>,
+    workspaces: State<WorkspaceState>,
+    secrets: State<SecretsState>,
+    id: u32,
+) -> AppResult<Vec<ResolvedEnvVar>> {
+    let workspace = workspaces.active_workspace()?;
+    let db = db.lock()?;
+    handlers::preview_service_environment(&*db, &*secrets, &workspace, id)
+}
+
+#[tauri::command]
+pub fn find_service_env_files(db: State<DbState>, id: u32) -> AppResult<Vec<String>> {
+    let db = db.lock()?;
+    handlers::find_service_env_files(&*db, id)
+}
+
+// Settings and auto-start commands
+#[tauri::command]
+pub fn get_app_settings(settings: State<SettingsState>) -> AppResult<AppSettings> {
+    settings.get()
+}
+
+#[tauri::command]
+pub fn update_app_settings(settings: State<SettingsState>, new_settings: AppSettings) -> AppResult<()> {
+    settings.update(new_settings)
+}
+
+#[tauri::command]
+pub fn is_safe_mode(settings: State<SettingsState>) -> bool {
+    settings.safe_mode()
+}
+
+#[tauri::command]
+pub fn get_settings_load_error(settings: State<SettingsState>) -> Option<String> {
+    settings.load_error().map(str::to_string)
+}
+
+/// Everything auto-start reported so far in this launch, for windows that
+/// missed the events.
+#[tauri::command]
+pub fn get_auto_start_events(log: State<AutoStartLogState>) -> AppResult<Vec<AutoStartEvent>> {
+    log.events()
+}
+
 // Git commands
 #[tauri::command]
 pub fn get_git_status_for_project(
     db: State<DbState>,
     project_id: u32,
-) -> Result<Option<GitStatus>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    
-    // Get project to find repo path
-    let project = db.get_project_by_id(project_id)
-        .map_err(|e| format!("Failed to get project: {}", e))?
-        .ok_or_else(|| "Project not found".to_string())?;
-    
-    // Find git repo
-    let repo_path = ProjectManager::find_git_repository(&project.path)
-        .ok_or_else(|| "No git repository found in project".to_string())?;
-    
-    // Get git status
-    let mut git_status = git::get_git_status(&repo_path)
-        .map_err(|e| format!("Failed to get git status: {}", e))?;
-    
-    // Update git_status with project info
-    git_status.id = db.get_git_repo_by_project_id(project_id)
-        .map_err(|e| format!("Failed to get git repo: {}", e))?
-        .map(|g| g.id)
-        .unwrap_or(0);
-    git_status.project_id = project_id;
-    
-    // Update or create git_repo record
-    if git_status.id == 0 {
-        let _ = db.create_git_repo(project_id, repo_path.clone());
-        git_status.id = db.get_git_repo_by_project_id(project_id)
-            .map_err(|e| format!("Failed to get git repo: {}", e))?
-            .map(|g| g.id)
-            .unwrap_or(0);
-    } else {
-        db.update_git_repo(&git_status)
-            .map_err(|e| format!("Failed to update git repo: {}", e))?;
-    }
-    
-    Ok(Some(git_status))
+) -> AppResult<Option<GitStatus>> {
+    let db = db.lock()?;
+    handlers::get_git_status_for_project(&*db, project_id)
 }
 
 #[tauri::command]
-pub fn get_all_git_statuses(db: State<DbState>) -> Result<Vec<GitStatus>, String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    let projects = db.get_all_projects()
-        .map_err(|e| format!("Failed to get projects: {}", e))?;
-    
-    let mut statuses = Vec::new();
-    for project in projects {
-        if let Some(repo_path) = ProjectManager::find_git_repository(&project.path) {
-            if let Ok(mut git_status) = git::get_git_status(&repo_path) {
-                git_status.project_id = project.id;
-                if let Ok(Some(repo)) = db.get_git_repo_by_project_id(project.id) {
-                    git_status.id = repo.id;
-                }
-                statuses.push(git_status);
-            }
-        }
-    }
-    
-    Ok(statuses)
+pub fn get_all_git_statuses(db: State<DbState>) -> AppResult<Vec<GitStatus>> {
+    let db = db.lock()?;
+    handlers::get_all_git_statuses(&*db)
 }
 
 #[tauri::command]
@@ -277,93 +555,525 @@ pub fn git_commit(
     db: State<DbState>,
     project_id: u32,
     message: String,
-) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    
-    let project = db.get_project_by_id(project_id)
-        .map_err(|e| format!("Failed to get project: {}", e))?
-        .ok_or_else(|| "Project not found".to_string())?;
-    
-    let repo_path = ProjectManager::find_git_repository(&project.path)
-        .ok_or_else(|| "No git repository found".to_string())?;
-    
-    let repo = git2::Repository::open(&repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
-    
-    let mut index = repo.index()
-        .map_err(|e| format!("Failed to get index: {}", e))?;
-    
-    // Get status to find all changed files
-    let mut opts = git2::StatusOptions::new();
-    opts.include_untracked(true);
-    opts.include_ignored(false);
-    
-    let statuses = repo.statuses(Some(&mut opts))
-        .map_err(|e| format!("Failed to get status: {}", e))?;
-    
-    // Stage all modified, deleted, and untracked files
-    for entry in statuses.iter() {
-        if let Some(path) = entry.path() {
-            let status = entry.status();
-            
-            // Add modified, new, or deleted files
-            if status.is_wt_new() || status.is_wt_modified() || status.is_wt_deleted() {
-                if status.is_wt_deleted() {
-                    // Remove deleted files from index
-                    index.remove_path(std::path::Path::new(path))
-                        .map_err(|e| format!("Failed to remove {} from index: {}", path, e))?;
-                } else {
-                    // Add new or modified files to index
-                    index.add_path(std::path::Path::new(path))
-                        .map_err(|e| format!("Failed to add {} to index: {}", path, e))?;
-                }
-            }
-        }
+) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::git_commit(&*db, project_id, &message)
+}
+
+// Workspace commands
+#[tauri::command]
+pub fn list_workspaces(workspaces: State<WorkspaceState>) -> AppResult<Vec<Workspace>> {
+    workspaces.list_workspaces()
+}
+
+#[tauri::command]
+pub fn get_active_workspace(workspaces: State<WorkspaceState>) -> AppResult<Workspace> {
+    workspaces.get_active()
+}
+
+#[tauri::command]
+pub fn create_workspace(workspaces: State<WorkspaceState>, name: String) -> AppResult<Workspace> {
+    workspaces.create_workspace(&name)
+}
+
+#[tauri::command]
+pub async fn switch_workspace(
+    workspaces: State<'_, WorkspaceState>,
+    db: State<'_, DbState>,
+    service_mgr: State<'_, ServiceMgrState>,
+    backups: State<'_, BackupState>,
+    log_store: State<'_, LogStoreState>,
+    log_writer: State<'_, LogWriterState>,
+    metrics: State<'_, MetricsState>,
+    name: String,
+) -> AppResult<Workspace> {
+    validate_workspace_name(&name)?;
+    if workspaces.active_workspace()? == name {
+        return workspaces.get_active();
     }
-    
-    // Write the index to disk
-    index.write()
-        .map_err(|e| format!("Failed to write index: {}", e))?;
-    
-    // Get the updated index and write tree
-    let mut index = repo.index()
-        .map_err(|e| format!("Failed to get updated index: {}", e))?;
-    
-    let tree_id = index.write_tree()
-        .map_err(|e| format!("Failed to write tree: {}", e))?;
-    let tree = repo.find_tree(tree_id)
-        .map_err(|e| format!("Failed to find tree: {}", e))?;
-    
-    let sig = repo.signature()
-        .map_err(|e| format!("Failed to get signature: {}", e))?;
-    
-    // Get parent commit (if exists)
-    let parents = if let Ok(head) = repo.head() {
-        if let Ok(parent) = head.peel_to_commit() {
-            vec![parent]
-        } else {
-            Vec::new()
+    if !workspaces.workspace_dir(&name).is_dir() {
+        return Err(AppError::not_found("Workspace", &name));
+    }
+
+    // Service ids are per-database, so processes from the old workspace must not outlive it
+    service_mgr.stop_all_services().await;
+    // Their last lines belong in the old workspace's log store
+    log_writer.flush().await;
+    service_mgr.clear_all().await;
+    metrics.clear_all()?;
+
+    let db_path = workspaces.database_path(&name);
+    let new_db = backup::open_database(&db_path, &backups)?;
+    let new_logs = LogStore::new(&workspaces.logs_path(&name).to_string_lossy())?;
+
+    {
+        let mut db = db.lock()?;
+        *db = new_db;
+    }
+    *log_store.lock()? = new_logs;
+    workspaces.set_active(&name)?;
+
+    workspaces.get_active()
+}
+
+// Backup commands
+#[tauri::command]
+pub fn list_backups(workspaces: State<WorkspaceState>, backups: State<BackupState>) -> AppResult<Vec<Snapshot>> {
+    backups.list_snapshots(&workspaces.active_database_path()?)
+}
+
+#[tauri::command]
+pub fn inspect_backup(
+    workspaces: State<WorkspaceState>,
+    backups: State<BackupState>,
+    id: String,
+) -> AppResult<SnapshotDetails> {
+    backups.inspect_snapshot(&workspaces.active_database_path()?, &id)
+}
+
+#[tauri::command]
+pub fn create_backup(db: State<DbState>, backups: State<BackupState>) -> AppResult<Snapshot> {
+    let db = db.lock()?;
+    backups.create_snapshot(&db, BackupReason::Manual)
+}
+
+/// Returns the snapshot of the pre-restore state.
+#[tauri::command]
+pub async fn restore_backup(
+    db: State<'_, DbState>,
+    backups: State<'_, BackupState>,
+    service_mgr: State<'_, ServiceMgrState>,
+    id: String,
+) -> AppResult<Snapshot> {
+    // Restored service rows may not match what is currently running
+    service_mgr.stop_all_services().await;
+
+    let mut db = db.lock()?;
+    backups.restore_snapshot(&mut db, &id)
+}
+
+// Database browser commands
+#[tauri::command]
+pub async fn list_project_databases(
+    db: State<'_, DbState>,
+    project_id: Option<u32>,
+) -> AppResult<Vec<ProjectDatabases>> {
+    let projects = {
+        let db = db.lock()?;
+        database_discovery::projects_to_scan(&*db, project_id)?
+    };
+    // Walking project trees can take a while; other commands need the lock
+    tokio::task::spawn_blocking(move || database_discovery::scan_projects(&projects))
+        .await
+        .map_err(|e| AppError::internal(format!("Database scan failed: {}", e)))
+}
+
+// Without an id the browser opens the active workspace's database
+fn browser_database_path(
+    db: &DbState,
+    workspaces: &WorkspaceManager,
+    database_id: Option<String>,
+) -> AppResult<String> {
+    let id = database_id.unwrap_or_else(|| WORKSPACE_DATABASE_ID.to_string());
+    if database_connector::profile_id(&id)?.is_some() {
+        return Err(AppError::invalid_field("databaseId", "This is only available for SQLite databases"));
+    }
+    let workspace_db = workspaces.active_database_path()?;
+    let db = db.lock()?;
+    let path = database_discovery::resolve_database(&*db, &workspace_db, &id)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// Ids of the form `profile:<id>` go to a connection profile, anything else to a SQLite file
+fn browser_target(
+    db: &DbState,
+    workspaces: &WorkspaceManager,
+    secrets: &SecretsState,
+    database_id: Option<String>,
+) -> AppResult<ConnectorTarget> {
+    let id = database_id.unwrap_or_else(|| WORKSPACE_DATABASE_ID.to_string());
+    match database_connector::profile_id(&id)? {
+        Some(profile_id) => {
+            let workspace = workspaces.active_workspace()?;
+            let db = db.lock()?;
+            handlers::connection_profile_target(&*db, secrets, &workspace, profile_id)
         }
-    } else {
-        Vec::new()
+        None => browser_database_path(db, workspaces, Some(id)).map(ConnectorTarget::Sqlite),
+    }
+}
+
+// Database work blocks, so it must not happen on the async runtime
+async fn run_blocking<T: Send + 'static>(run: impl FnOnce() -> AppResult<T> + Send + 'static) -> AppResult<T> {
+    tokio::task::spawn_blocking(run)
+        .await
+        .map_err(|e| AppError::internal(format!("Database task failed: {}", e)))?
+}
+
+async fn with_connector<T: Send + 'static>(
+    target: ConnectorTarget,
+    run: impl FnOnce(&mut dyn database_connector::DatabaseConnector) -> AppResult<T> + Send + 'static,
+) -> AppResult<T> {
+    run_blocking(move || {
+        let mut connector = target.connect(database_browser::QUERY_TIMEOUT)?;
+        run(connector.as_mut())
+    })
+    .await
+}
+
+// Async so a slow query runs off the main thread
+#[tauri::command]
+pub async fn list_database_tables(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    secrets: State<'_, SecretsState>,
+    database_id: Option<String>,
+) -> AppResult<Vec<TableInfo>> {
+    let target = browser_target(&db, &workspaces, &secrets, database_id)?;
+    with_connector(target, |connector| connector.list_tables()).await
+}
+
+#[tauri::command]
+pub async fn get_database_schema(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    database_id: Option<String>,
+) -> AppResult<DatabaseSchema> {
+    let db_path = browser_database_path(&db, &workspaces, database_id)?;
+    run_blocking(move || database_browser::get_schema(&db_path, database_browser::QUERY_TIMEOUT)).await
+}
+
+#[tauri::command]
+pub async fn get_table_data(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    secrets: State<'_, SecretsState>,
+    database_id: Option<String>,
+    table: String,
+    limit: u32,
+    offset: u32,
+) -> AppResult<QueryResult> {
+    let target = browser_target(&db, &workspaces, &secrets, database_id)?;
+    with_connector(target, move |connector| connector.get_table_data(&table, limit, offset)).await
+}
+
+/// Runs a read-only query and records it in the query history.
+#[tauri::command]
+pub async fn execute_database_query(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    secrets: State<'_, SecretsState>,
+    database_id: Option<String>,
+    query: String,
+    params: Option<QueryParams>,
+) -> AppResult<QueryResult> {
+    let database_id = database_id.unwrap_or_else(|| WORKSPACE_DATABASE_ID.to_string());
+    let target = browser_target(&db, &workspaces, &secrets, Some(database_id.clone()))?;
+
+    let started = Instant::now();
+    let sql = query.clone();
+    let result = with_connector(target, move |connector| {
+        connector.execute_query(&sql, &params.unwrap_or_default())
+    }).await;
+
+    // History is best-effort; failing to record it must not hide the result
+    let db = db.lock()?;
+    let _ = handlers::record_query(&*db, database_id, query, started.elapsed(), &result);
+    result
+}
+
+/// Prepares a paged query; rows are read with `fetch_query_page`. Only
+/// SQLite databases support paging, connection profiles go through
+/// `execute_database_query`, which stops at `DEFAULT_ROW_LIMIT` rows.
+#[tauri::command]
+pub async fn start_database_query(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    queries: State<'_, QuerySessionsState>,
+    database_id: Option<String>,
+    query: String,
+    params: Option<QueryParams>,
+    options: Option<QueryOptions>,
+) -> AppResult<QueryStarted> {
+    let database_id = database_id.unwrap_or_else(|| WORKSPACE_DATABASE_ID.to_string());
+    let db_path = browser_database_path(&db, &workspaces, Some(database_id.clone()))?;
+
+    let started = Instant::now();
+    let (queries, sql) = (Arc::clone(&queries), query.clone());
+    let result = run_blocking(move || {
+        queries.start(&db_path, &sql, &params.unwrap_or_default(), &options.unwrap_or_default())
+    }).await;
+
+    let db = db.lock()?;
+    let _ = handlers::record_query_start(&*db, database_id, query, started.elapsed(), &result);
+    result
+}
+
+#[tauri::command]
+pub async fn fetch_query_page(
+    queries: State<'_, QuerySessionsState>,
+    handle: u64,
+    page_size: Option<usize>,
+) -> AppResult<QueryPage> {
+    let queries = Arc::clone(&queries);
+    run_blocking(move || queries.fetch(handle, page_size)).await
+}
+
+#[tauri::command]
+pub fn get_query_progress(queries: State<QuerySessionsState>, handle: u64) -> AppResult<QueryProgress> {
+    queries.progress(handle)
+}
+
+#[tauri::command]
+pub fn cancel_database_query(queries: State<QuerySessionsState>, handle: u64) -> AppResult<QueryProgress> {
+    queries.cancel(handle)
+}
+
+#[tauri::command]
+pub async fn get_query_parameters(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    database_id: Option<String>,
+    query: String,
+) -> AppResult<Vec<QueryParameter>> {
+    let db_path = browser_database_path(&db, &workspaces, database_id)?;
+    run_blocking(move || database_browser::query_parameters(&db_path, &query, database_browser::QUERY_TIMEOUT)).await
+}
+
+#[tauri::command]
+pub async fn export_database_data(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    database_id: Option<String>,
+    source: ExportSource,
+    format: ExportFormat,
+    output_path: String,
+) -> AppResult<ExportSummary> {
+    let db_path = browser_database_path(&db, &workspaces, database_id)?;
+    run_blocking(move || {
+        database_export::export_to_file(&db_path, &source, format, &output_path, database_export::EXPORT_TIMEOUT)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn diff_databases(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    from_database_id: String,
+    to_database_id: String,
+    options: Option<DiffOptions>,
+) -> AppResult<DatabaseDiff> {
+    let from_path = browser_database_path(&db, &workspaces, Some(from_database_id))?;
+    let to_path = browser_database_path(&db, &workspaces, Some(to_database_id))?;
+    let options = options.unwrap_or_default();
+    run_blocking(move || database_diff::diff_databases(&from_path, &to_path, &options, database_diff::DIFF_TIMEOUT)).await
+}
+
+// Row editing commands
+fn write_mode(
+    write_modes: &WriteModes,
+    workspaces: &WorkspaceManager,
+    db_path: &str,
+) -> AppResult<WriteMode> {
+    let path = Path::new(db_path);
+    Ok(WriteMode {
+        enabled: write_modes.is_enabled(path)?,
+        app_database: database_edit::is_app_database(path, workspaces.data_dir()),
+    })
+}
+
+#[tauri::command]
+pub async fn get_database_write_mode(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    write_modes: State<'_, WriteModesState>,
+    database_id: Option<String>,
+) -> AppResult<WriteMode> {
+    let db_path = browser_database_path(&db, &workspaces, database_id)?;
+    write_mode(&write_modes, &workspaces, &db_path)
+}
+
+/// The app's own databases only become writable with `allow_app_database`.
+#[tauri::command]
+pub async fn set_database_write_mode(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    write_modes: State<'_, WriteModesState>,
+    database_id: Option<String>,
+    enabled: bool,
+    allow_app_database: Option<bool>,
+) -> AppResult<WriteMode> {
+    let db_path = browser_database_path(&db, &workspaces, database_id)?;
+    let mode = write_mode(&write_modes, &workspaces, &db_path)?;
+    if enabled && mode.app_database && !allow_app_database.unwrap_or(false) {
+        return Err(AppError::invalid_field(
+            "databaseId",
+            "Writes to the app's own database are disabled unless explicitly allowed",
+        ));
+    }
+    write_modes.set(Path::new(&db_path), enabled)?;
+    write_mode(&write_modes, &workspaces, &db_path)
+}
+
+#[tauri::command]
+pub async fn get_editable_rows(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    database_id: Option<String>,
+    table: String,
+    limit: u32,
+    offset: u32,
+) -> AppResult<EditableRows> {
+    let db_path = browser_database_path(&db, &workspaces, database_id)?;
+    run_blocking(move || database_edit::get_editable_rows(&db_path, &table, limit, offset, database_browser::QUERY_TIMEOUT))
+        .await
+}
+
+#[tauri::command]
+pub async fn preview_database_changes(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    database_id: Option<String>,
+    changes: Vec<RowEdit>,
+) -> AppResult<Vec<String>> {
+    let db_path = browser_database_path(&db, &workspaces, database_id)?;
+    run_blocking(move || database_edit::preview_changes(&db_path, &changes)).await
+}
+
+#[tauri::command]
+pub async fn apply_database_changes(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    write_modes: State<'_, WriteModesState>,
+    backups: State<'_, BackupState>,
+    database_id: Option<String>,
+    changes: Vec<RowEdit>,
+) -> AppResult<ChangesetSummary> {
+    let db_path = browser_database_path(&db, &workspaces, database_id)?;
+    if !write_modes.is_enabled(Path::new(&db_path))? {
+        return Err(AppError::invalid_field("databaseId", "Write mode is off for this database"));
+    }
+
+    // Edits to the open task database can be undone from its snapshots.
+    // The lock is only needed for the snapshot; applying waits on the
+    // file's own locks, which must not hold up every other command.
+    if Path::new(&db_path) == workspaces.active_database_path()? {
+        let db = db.lock()?;
+        backups.create_snapshot(&db, BackupReason::Edit)?;
+    }
+    run_blocking(move || database_edit::apply_changes(&db_path, &changes, database_edit::WRITE_TIMEOUT)).await
+}
+
+// Query history and saved query commands
+#[tauri::command]
+pub fn get_query_history(
+    db: State<DbState>,
+    search: Option<String>,
+    database_id: Option<String>,
+    limit: Option<u32>,
+) -> AppResult<Vec<QueryHistoryEntry>> {
+    let db = db.lock()?;
+    handlers::get_query_history(&*db, search.as_deref(), database_id.as_deref(), limit.unwrap_or(100))
+}
+
+#[tauri::command]
+pub fn clear_query_history(db: State<DbState>) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::clear_query_history(&*db)
+}
+
+#[tauri::command]
+pub fn save_query(
+    db: State<DbState>,
+    name: String,
+    query: String,
+    database_id: Option<String>,
+    description: Option<String>,
+) -> AppResult<SavedQuery> {
+    let db = db.lock()?;
+    handlers::save_query(&*db, name, query, database_id, description)
+}
+
+#[tauri::command]
+pub fn get_saved_queries(db: State<DbState>, search: Option<String>) -> AppResult<Vec<SavedQuery>> {
+    let db = db.lock()?;
+    handlers::get_saved_queries(&*db, search.as_deref())
+}
+
+#[tauri::command]
+pub fn update_saved_query(db: State<DbState>, saved_query: SavedQuery) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::update_saved_query(&*db, &saved_query)
+}
+
+#[tauri::command]
+pub fn delete_saved_query(db: State<DbState>, id: u32) -> AppResult<()> {
+    let db = db.lock()?;
+    handlers::delete_saved_query(&*db, id)
+}
+
+// Connection profile commands
+#[tauri::command]
+pub fn create_connection_profile(
+    db: State<DbState>,
+    workspaces: State<WorkspaceState>,
+    secrets: State<SecretsState>,
+    form: ConnectionProfileForm,
+) -> AppResult<ConnectionProfile> {
+    let workspace = workspaces.active_workspace()?;
+    let db = db.lock()?;
+    handlers::create_connection_profile(&*db, &*secrets, &workspace, form)
+}
+
+#[tauri::command]
+pub fn get_connection_profiles(db: State<DbState>, project_id: Option<u32>) -> AppResult<Vec<ConnectionProfile>> {
+    let db = db.lock()?;
+    handlers::get_connection_profiles(&*db, project_id)
+}
+
+#[tauri::command]
+pub fn update_connection_profile(
+    db: State<DbState>,
+    workspaces: State<WorkspaceState>,
+    secrets: State<SecretsState>,
+    profile: ConnectionProfile,
+    password: Option<String>,
+) -> AppResult<()> {
+    let workspace = workspaces.active_workspace()?;
+    let db = db.lock()?;
+    handlers::update_connection_profile(&*db, &*secrets, &workspace, &profile, password)
+}
+
+#[tauri::command]
+pub fn delete_connection_profile(
+    db: State<DbState>,
+    workspaces: State<WorkspaceState>,
+    secrets: State<SecretsState>,
+    id: u32,
+) -> AppResult<()> {
+    let workspace = workspaces.active_workspace()?;
+    let db = db.lock()?;
+    handlers::delete_connection_profile(&*db, &*secrets, &workspace, id)
+}
+
+/// Connects with a saved profile and returns the server version.
+#[tauri::command]
+pub async fn test_connection_profile(
+    db: State<'_, DbState>,
+    workspaces: State<'_, WorkspaceState>,
+    secrets: State<'_, SecretsState>,
+    id: u32,
+) -> AppResult<String> {
+    let target = {
+        let workspace = workspaces.active_workspace()?;
+        let db = db.lock()?;
+        handlers::connection_profile_target(&*db, &*secrets, &workspace, id)?
     };
-    
-    // Create commit
-    let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
-    repo.commit(
-        Some("HEAD"),
-        &sig,
-        &sig,
-        &message,
-        &tree,
-        &parent_refs,
-    ).map_err(|e| format!("Failed to commit: {}", e))?;
-    
-    Ok(())
+    with_connector(target, |connector| connector.server_version()).await
 }
 
 #[tauri::command]
-pub fn open_in_vscode(project_path: String) -> Result<(), String> {
+pub fn open_in_vscode(project_path: String) -> AppResult<()> {
     use std::process::Command;
     
     #[cfg(target_os = "windows")]
@@ -371,7 +1081,7 @@ pub fn open_in_vscode(project_path: String) -> Result<(), String> {
         Command::new("code")
             .arg(&project_path)
             .spawn()
-            .map_err(|e| format!("Failed to open VS Code: {}. Make sure VS Code is installed and 'code' is in your PATH.", e))?;
+            .map_err(|e| AppError::process("Failed to open VS Code. Make sure VS Code is installed and 'code' is in your PATH.", Some(e)))?;
     }
     
     #[cfg(target_os = "macos")]
@@ -379,7 +1089,7 @@ pub fn open_in_vscode(project_path: String) -> Result<(), String> {
         Command::new("open")
             .args(&["-a", "Visual Studio Code", &project_path])
             .spawn()
-            .map_err(|e| format!("Failed to open VS Code: {}. Make sure VS Code is installed.", e))?;
+            .map_err(|e| AppError::process("Failed to open VS Code. Make sure VS Code is installed.", Some(e)))?;
     }
     
     #[cfg(target_os = "linux")]
@@ -408,25 +1118,21 @@ pub fn open_in_vscode(project_path: String) -> Result<(), String> {
             }
         }
         
-        Err("Failed to open VS Code. Please install VS Code and ensure 'code' command is available, or install VS Code from snap store.".to_string())
+        Err(AppError::process("Failed to open VS Code. Please install VS Code and ensure 'code' command is available, or install VS Code from snap store.", None))
     }
     
     #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     {
-        Err("Unsupported platform".to_string())
+        Err(AppError::process("Unsupported platform", None))
     }
 }
 
 #[tauri::command]
-pub fn git_push(db: State<DbState>, project_id: u32) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    
-    let project = db.get_project_by_id(project_id)
-        .map_err(|e| format!("Failed to get project: {}", e))?
-        .ok_or_else(|| "Project not found".to_string())?;
-    
-    let repo_path = ProjectManager::find_git_repository(&project.path)
-        .ok_or_else(|| "No git repository found".to_string())?;
+pub fn git_push(db: State<DbState>, project_id: u32) -> AppResult<()> {
+    let repo_path = {
+        let db = db.lock()?;
+        handlers::project_repository_path(&*db, project_id)?
+    };
     
     // Open VS Code so user can use integrated git features
     open_in_vscode(repo_path)?;
@@ -435,15 +1141,11 @@ pub fn git_push(db: State<DbState>, project_id: u32) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn git_pull(db: State<DbState>, project_id: u32) -> Result<(), String> {
-    let db = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
-    
-    let project = db.get_project_by_id(project_id)
-        .map_err(|e| format!("Failed to get project: {}", e))?
-        .ok_or_else(|| "Project not found".to_string())?;
-    
-    let repo_path = ProjectManager::find_git_repository(&project.path)
-        .ok_or_else(|| "No git repository found".to_string())?;
+pub fn git_pull(db: State<DbState>, project_id: u32) -> AppResult<()> {
+    let repo_path = {
+        let db = db.lock()?;
+        handlers::project_repository_path(&*db, project_id)?
+    };
     
     // Open VS Code so user can use integrated git features
     open_in_vscode(repo_path)?;
@@ -1,150 +1,405 @@
-use crate::model::{Task, TaskFormData, Project, Service, GitStatus};
-use rusqlite::{params, Connection, Result};
+use crate::model::{
+    Task, TaskFormData, Project, Service, GitStatus, ProjectDeleteMode, ProjectDeletionImpact, QueryHistoryEntry,
+    NewQueryHistory, SavedQuery, ConnectionProfile, ConnectionProfileForm, ConnectorKind, ServiceEnvVar,
+    ServiceEnvironment, RestartMode, RestartPolicy, ServiceGroup, HealthChecks, Probe, ProbeCheck, LogRetention, LogFormat, LogParser,
+};
+use rusqlite::backup::Progress;
+use crate::error::{AppResult, Context};
+use crate::repository::{
+    task_from_form, ConnectionProfileRepository, GitRepoRepository, ProjectRepository, QueryRepository, ServiceGroupRepository,
+    ServiceRepository, TaskRepository, MAX_QUERY_HISTORY,
+};
+use rusqlite::{params, Connection, DatabaseName, OptionalExtension};
 use chrono::Utc;
+use std::path::Path;
+
+const PROJECTS_TABLE: &str = "CREATE TABLE IF NOT EXISTS projects (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    path TEXT NOT NULL UNIQUE,
+    project_type TEXT,
+    description TEXT,
+    last_accessed TEXT,
+    created_at TEXT NOT NULL
+)";
+
+// Services and tasks outlive their project (project_id is cleared);
+// git_repos rows only describe a project's repository and go with it.
+const SERVICES_TABLE: &str = "CREATE TABLE IF NOT EXISTS services (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    command TEXT NOT NULL,
+    working_dir TEXT,
+    project_id INTEGER,
+    auto_start BOOLEAN DEFAULT 0,
+    created_at TEXT NOT NULL,
+    shell BOOLEAN NOT NULL DEFAULT 0,
+    FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE SET NULL
+)";
+const SERVICES_COLUMNS: &str = "id, name, command, working_dir, project_id, auto_start, created_at, shell";
+
+const GIT_REPOS_TABLE: &str = "CREATE TABLE IF NOT EXISTS git_repos (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL,
+    repo_path TEXT NOT NULL,
+    current_branch TEXT,
+    last_checked TEXT,
+    FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+)";
+const GIT_REPOS_COLUMNS: &str = "id, project_id, repo_path, current_branch, last_checked";
+
+const TASKS_TABLE: &str = "CREATE TABLE IF NOT EXISTS tasks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL,
+    priority INTEGER NOT NULL,
+    deadline TEXT NOT NULL,
+    estimated_time INTEGER NOT NULL,
+    start_date TEXT,
+    scheduled_start TEXT,
+    completed BOOLEAN NOT NULL DEFAULT 0,
+    locked BOOLEAN NOT NULL DEFAULT 0,
+    category TEXT,
+    reminder_enabled BOOLEAN NOT NULL DEFAULT 0,
+    reminder_minutes INTEGER NOT NULL DEFAULT 0,
+    project_id INTEGER,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE SET NULL
+)";
+const TASKS_COLUMNS: &str = "id, title, priority, deadline, estimated_time, start_date, scheduled_start, \
+    completed, locked, category, reminder_enabled, reminder_minutes, project_id, created_at";
+
+const QUERY_HISTORY_TABLE: &str = "CREATE TABLE IF NOT EXISTS query_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    database_id TEXT NOT NULL,
+    query TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    row_count INTEGER,
+    error TEXT,
+    executed_at TEXT NOT NULL
+)";
+const QUERY_HISTORY_COLUMNS: &str = "id, database_id, query, duration_ms, row_count, error, executed_at";
+
+const SAVED_QUERIES_TABLE: &str = "CREATE TABLE IF NOT EXISTS saved_queries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    query TEXT NOT NULL,
+    database_id TEXT,
+    description TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+)";
+const SAVED_QUERIES_COLUMNS: &str = "id, name, query, database_id, description, created_at, updated_at";
+
+const CONNECTION_PROFILES_TABLE: &str = "CREATE TABLE IF NOT EXISTS connection_profiles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    host TEXT NOT NULL,
+    port INTEGER NOT NULL,
+    database TEXT NOT NULL,
+    username TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY(project_id) REFERENCES projects(id) ON DELETE CASCADE
+)";
+const CONNECTION_PROFILES_COLUMNS: &str = "id, project_id, name, kind, host, port, database, username, created_at";
+
+// What services add to their environment; secret values are kept in the secret store
+const SERVICE_ENV_VARS_TABLE: &str = "CREATE TABLE IF NOT EXISTS service_env_vars (
+    service_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    value TEXT,
+    secret BOOLEAN NOT NULL DEFAULT 0,
+    PRIMARY KEY(service_id, name),
+    FOREIGN KEY(service_id) REFERENCES services(id) ON DELETE CASCADE
+)";
+
+const SERVICE_ENV_FILES_TABLE: &str = "CREATE TABLE IF NOT EXISTS service_env_files (
+    service_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    PRIMARY KEY(service_id, position),
+    FOREIGN KEY(service_id) REFERENCES services(id) ON DELETE CASCADE
+)";
+
+const SERVICE_RESTART_POLICIES_TABLE: &str = "CREATE TABLE IF NOT EXISTS service_restart_policies (
+    service_id INTEGER PRIMARY KEY,
+    mode TEXT NOT NULL,
+    max_retries INTEGER NOT NULL,
+    initial_backoff_ms INTEGER NOT NULL,
+    max_backoff_ms INTEGER NOT NULL,
+    FOREIGN KEY(service_id) REFERENCES services(id) ON DELETE CASCADE
+)";
+
+// One row per probe; `target` is the URL, port, pattern or command of its kind
+const SERVICE_HEALTH_CHECKS_TABLE: &str = "CREATE TABLE IF NOT EXISTS service_health_checks (
+    service_id INTEGER NOT NULL,
+    probe TEXT NOT NULL CHECK(probe IN ('readiness', 'liveness')),
+    kind TEXT NOT NULL,
+    target TEXT NOT NULL,
+    expected_status INTEGER,
+    interval_ms INTEGER NOT NULL,
+    timeout_ms INTEGER NOT NULL,
+    failure_threshold INTEGER NOT NULL,
+    PRIMARY KEY(service_id, probe),
+    FOREIGN KEY(service_id) REFERENCES services(id) ON DELETE CASCADE
+)";
+
+// NULL turns a limit off
+const SERVICE_LOG_RETENTION_TABLE: &str = "CREATE TABLE IF NOT EXISTS service_log_retention (
+    service_id INTEGER PRIMARY KEY,
+    max_age_days INTEGER,
+    max_lines INTEGER,
+    max_bytes INTEGER,
+    FOREIGN KEY(service_id) REFERENCES services(id) ON DELETE CASCADE
+)";
+
+const SERVICE_LOG_PARSERS_TABLE: &str = "CREATE TABLE IF NOT EXISTS service_log_parsers (
+    service_id INTEGER PRIMARY KEY,
+    format TEXT NOT NULL,
+    keep_ansi INTEGER NOT NULL,
+    stderr_level TEXT NOT NULL,
+    FOREIGN KEY(service_id) REFERENCES services(id) ON DELETE CASCADE
+)";
+
+const SERVICE_DEPENDENCIES_TABLE: &str = "CREATE TABLE IF NOT EXISTS service_dependencies (
+    service_id INTEGER NOT NULL,
+    depends_on INTEGER NOT NULL,
+    PRIMARY KEY(service_id, depends_on),
+    FOREIGN KEY(service_id) REFERENCES services(id) ON DELETE CASCADE,
+    FOREIGN KEY(depends_on) REFERENCES services(id) ON DELETE CASCADE
+)";
+
+const SERVICE_GROUPS_TABLE: &str = "CREATE TABLE IF NOT EXISTS service_groups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    created_at TEXT NOT NULL
+)";
+
+const SERVICE_GROUP_MEMBERS_TABLE: &str = "CREATE TABLE IF NOT EXISTS service_group_members (
+    group_id INTEGER NOT NULL,
+    service_id INTEGER NOT NULL,
+    PRIMARY KEY(group_id, service_id),
+    FOREIGN KEY(group_id) REFERENCES service_groups(id) ON DELETE CASCADE,
+    FOREIGN KEY(service_id) REFERENCES services(id) ON DELETE CASCADE
+)";
 
 pub struct Database {
     conn: Connection,
 }
 
+// Columns added to existing tables since the first release, as (table,
+// column, definition)
+const ADDED_COLUMNS: [(&str, &str, &str); 2] = [
+    ("tasks", "project_id", "INTEGER"),
+    ("services", "shell", "BOOLEAN NOT NULL DEFAULT 0"),
+];
+
+// Child tables and the ON DELETE rule their project_id foreign key must carry
+const FOREIGN_KEY_RULES: [(&str, &str, &str, &str); 3] = [
+    ("services", SERVICES_TABLE, SERVICES_COLUMNS, "SET NULL"),
+    ("git_repos", GIT_REPOS_TABLE, GIT_REPOS_COLUMNS, "CASCADE"),
+    ("tasks", TASKS_TABLE, TASKS_COLUMNS, "SET NULL"),
+];
+
 impl Database {
-    pub fn new(db_path: &str) -> Result<Self> {
-        let conn = Connection::open(db_path)?;
-        let db = Database { conn };
-        db.init_tables()?;
+    pub fn new(db_path: &str) -> AppResult<Self> {
+        let db = Database::open(db_path)?;
+        db.migrate()?;
         Ok(db)
     }
 
-    fn init_tables(&self) -> Result<()> {
-        // Create projects table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS projects (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                path TEXT NOT NULL UNIQUE,
-                project_type TEXT,
-                description TEXT,
-                last_accessed TEXT,
-                created_at TEXT NOT NULL
-            )",
+    /// Opens the file without touching its schema. Call `migrate` before use.
+    pub fn open(db_path: &str) -> AppResult<Self> {
+        let conn = Connection::open(db_path)
+            .context(format!("Failed to open database {}", db_path))?;
+        Ok(Database { conn })
+    }
+
+    /// Brings the schema up to date and turns on foreign key enforcement.
+    pub fn migrate(&self) -> AppResult<()> {
+        self.init_tables()?;
+        self.conn.execute_batch("PRAGMA foreign_keys = ON")?;
+        Ok(())
+    }
+
+    /// True when an existing database would be rewritten by `migrate`.
+    /// A brand new file only needs tables created, which is not counted.
+    pub fn needs_migration(&self) -> AppResult<bool> {
+        let has_projects: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'projects')",
             [],
+            |row| row.get(0),
         )?;
 
+        Ok(has_projects && (!self.outdated_foreign_keys()?.is_empty() || !self.missing_columns()?.is_empty()))
+    }
+
+    /// Location of the database file, or `None` for in-memory databases.
+    pub fn path(&self) -> Option<&str> {
+        self.conn.path().filter(|p| !p.is_empty())
+    }
+
+    fn init_tables(&self) -> AppResult<()> {
+        // Create projects table
+        self.conn.execute(PROJECTS_TABLE, [])?;
+
         // Create services table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS services (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                command TEXT NOT NULL,
-                working_dir TEXT,
-                project_id INTEGER,
-                auto_start BOOLEAN DEFAULT 0,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY(project_id) REFERENCES projects(id)
-            )",
-            [],
-        )?;
+        self.conn.execute(SERVICES_TABLE, [])?;
 
         // Create git_repos table
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS git_repos (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                project_id INTEGER NOT NULL,
-                repo_path TEXT NOT NULL,
-                current_branch TEXT,
-                last_checked TEXT,
-                FOREIGN KEY(project_id) REFERENCES projects(id)
-            )",
-            [],
-        )?;
+        self.conn.execute(GIT_REPOS_TABLE, [])?;
 
         // Create tasks table (with project_id support)
-        self.conn.execute(
-            "CREATE TABLE IF NOT EXISTS tasks (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                title TEXT NOT NULL,
-                priority INTEGER NOT NULL,
-                deadline TEXT NOT NULL,
-                estimated_time INTEGER NOT NULL,
-                start_date TEXT,
-                scheduled_start TEXT,
-                completed BOOLEAN NOT NULL DEFAULT 0,
-                locked BOOLEAN NOT NULL DEFAULT 0,
-                category TEXT,
-                reminder_enabled BOOLEAN NOT NULL DEFAULT 0,
-                reminder_minutes INTEGER NOT NULL DEFAULT 0,
-                project_id INTEGER,
-                created_at TEXT NOT NULL,
-                FOREIGN KEY(project_id) REFERENCES projects(id)
-            )",
-            [],
+        self.conn.execute(TASKS_TABLE, [])?;
+
+        // Database browser history and saved queries
+        self.conn.execute(QUERY_HISTORY_TABLE, [])?;
+        self.conn.execute(SAVED_QUERIES_TABLE, [])?;
+
+        // Database server connections; passwords are kept in the secret store
+        self.conn.execute(CONNECTION_PROFILES_TABLE, [])?;
+
+        // Before the foreign key migration, which copies every current column
+        for (table, column, definition) in self.missing_columns()? {
+            self.conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
+        }
+
+        self.migrate_foreign_keys()?;
+
+        // Created after the migration above, which rebuilds the services
+        // table and would leave references to its renamed copy
+        self.conn.execute(SERVICE_ENV_VARS_TABLE, [])?;
+        self.conn.execute(SERVICE_ENV_FILES_TABLE, [])?;
+        self.conn.execute(SERVICE_RESTART_POLICIES_TABLE, [])?;
+        self.conn.execute(SERVICE_HEALTH_CHECKS_TABLE, [])?;
+        self.conn.execute(SERVICE_LOG_RETENTION_TABLE, [])?;
+        self.conn.execute(SERVICE_LOG_PARSERS_TABLE, [])?;
+        self.conn.execute(SERVICE_DEPENDENCIES_TABLE, [])?;
+        self.conn.execute(SERVICE_GROUPS_TABLE, [])?;
+        self.conn.execute(SERVICE_GROUP_MEMBERS_TABLE, [])?;
+
+        Ok(())
+    }
+
+    /// Entries of `ADDED_COLUMNS` that an existing table lacks.
+    fn missing_columns(&self) -> AppResult<Vec<(&'static str, &'static str, &'static str)>> {
+        let mut missing = Vec::new();
+        for (table, column, definition) in ADDED_COLUMNS {
+            let lacks: bool = self.conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?1))
+                    AND NOT EXISTS(SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)",
+                params![table, column],
+                |row| row.get(0),
+            )?;
+            if lacks {
+                missing.push((table, column, definition));
+            }
+        }
+        Ok(missing)
+    }
+
+    fn outdated_foreign_keys(&self) -> AppResult<Vec<(&'static str, &'static str, &'static str)>> {
+        let mut outdated = Vec::new();
+        for (table, definition, columns, rule) in FOREIGN_KEY_RULES {
+            let on_delete: Option<String> = self.conn.query_row(
+                &format!(
+                    "SELECT on_delete FROM pragma_foreign_key_list('{}') WHERE \"from\" = 'project_id'",
+                    table
+                ),
+                [],
+                |row| row.get(0),
+            ).optional()?;
+
+            if on_delete.as_deref() != Some(rule) {
+                outdated.push((table, definition, columns));
+            }
+        }
+        Ok(outdated)
+    }
+
+    /// Rebuilds child tables created before their foreign keys declared an
+    /// ON DELETE rule. SQLite cannot alter a constraint in place, so the table
+    /// is renamed, recreated from the current definition and copied back.
+    fn migrate_foreign_keys(&self) -> AppResult<()> {
+        let outdated = self.outdated_foreign_keys()?;
+        if outdated.is_empty() {
+            return Ok(());
+        }
+
+        // Foreign keys must be off while tables are swapped, and this pragma
+        // is a no-op inside a transaction
+        self.conn.execute_batch("PRAGMA foreign_keys = OFF")?;
+
+        let tx = self.conn.unchecked_transaction()?;
+
+        // Clear dangling references left behind by the old delete_project
+        tx.execute_batch(
+            "UPDATE services SET project_id = NULL
+                 WHERE project_id IS NOT NULL AND project_id NOT IN (SELECT id FROM projects);
+             UPDATE tasks SET project_id = NULL
+                 WHERE project_id IS NOT NULL AND project_id NOT IN (SELECT id FROM projects);
+             DELETE FROM git_repos WHERE project_id NOT IN (SELECT id FROM projects);"
         )?;
 
-        // Migrate existing tasks table to add project_id if it doesn't exist
-        // This is safe to run multiple times
-        let _ = self.conn.execute(
-            "ALTER TABLE tasks ADD COLUMN project_id INTEGER",
-            [],
-        );
+        for (table, definition, columns) in outdated {
+            tx.execute_batch(&format!(
+                "ALTER TABLE {table} RENAME TO {table}_old;
+                 {definition};
+                 INSERT INTO {table} ({columns}) SELECT {columns} FROM {table}_old;
+                 DROP TABLE {table}_old;"
+            ))?;
+        }
 
+        tx.commit()?;
         Ok(())
     }
 
-    pub fn create_task(&self, form_data: TaskFormData) -> Result<Task> {
-        let created_at = Utc::now().to_rfc3339();
-        
-        // Convert form data to proper types
-        let estimated_hours: u32 = form_data.estimated_hours.parse().unwrap_or(0);
-        let estimated_minutes: u32 = form_data.estimated_minutes.parse().unwrap_or(0);
-        let total_estimated_time = estimated_hours * 60 + estimated_minutes;
-        let priority: u8 = form_data.priority.parse().unwrap_or(3);
-        
-        let start_date = if form_data.start_date.is_empty() { None } else { Some(form_data.start_date.clone()) };
+    /// Copies the live database to `dst_path` with SQLite's online backup API.
+    pub fn backup_to(&self, dst_path: &Path) -> AppResult<()> {
+        self.conn.backup(DatabaseName::Main, dst_path, None)?;
+        Ok(())
+    }
+
+    /// Replaces the live database with the contents of `src_path`, then
+    /// migrates it in case the snapshot predates the current schema.
+    pub fn restore_from(&mut self, src_path: &Path) -> AppResult<()> {
+        self.conn.restore(DatabaseName::Main, src_path, None::<fn(Progress)>)?;
+        self.migrate()
+    }
+}
+
+impl TaskRepository for Database {
+    fn create_task(&self, form_data: TaskFormData) -> AppResult<Task> {
+        let mut task = task_from_form(form_data, Utc::now().to_rfc3339());
 
         self.conn.execute(
             "INSERT INTO tasks (title, priority, deadline, estimated_time, start_date, 
              scheduled_start, completed, locked, category, reminder_enabled, reminder_minutes, project_id, created_at)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
-                form_data.title,
-                priority,
-                form_data.deadline,
-                total_estimated_time,
-                start_date,
-                None::<String>, // scheduled_start
-                false, // completed
-                false, // locked
-                None::<String>, // category
-                false, // reminder_enabled
-                0u32, // reminder_minutes
-                None::<u32>, // project_id
-                created_at
+                task.title,
+                task.priority,
+                task.deadline,
+                task.estimated_time,
+                task.start_date,
+                task.scheduled_start,
+                task.completed,
+                task.locked,
+                task.category,
+                task.reminder_enabled,
+                task.reminder_minutes,
+                task.project_id,
+                task.created_at
             ],
         )?;
 
-        let task_id = self.conn.last_insert_rowid() as u32;
-        
-        // Return the created task
-        let task = Task {
-            id: task_id,
-            title: form_data.title,
-            priority,
-            deadline: form_data.deadline,
-            estimated_time: total_estimated_time,
-            start_date: if form_data.start_date.is_empty() { None } else { Some(form_data.start_date) },
-            scheduled_start: None,
-            completed: false,
-            locked: false,
-            category: None,
-            reminder_enabled: false,
-            reminder_minutes: 0,
-            project_id: None,
-            created_at,
-        };
-
+        task.id = self.conn.last_insert_rowid() as u32;
         Ok(task)
     }
 
-    pub fn get_all_tasks(&self) -> Result<Vec<Task>> {
+    fn get_all_tasks(&self) -> AppResult<Vec<Task>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, title, priority, deadline, estimated_time, start_date, 
              scheduled_start, completed, locked, category, reminder_enabled, 
@@ -177,7 +432,7 @@ impl Database {
         Ok(tasks)
     }
 
-    pub fn get_task_by_id(&self, id: u32) -> Result<Option<Task>> {
+    fn get_task_by_id(&self, id: u32) -> AppResult<Option<Task>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, title, priority, deadline, estimated_time, start_date, 
              scheduled_start, completed, locked, category, reminder_enabled, 
@@ -209,7 +464,7 @@ impl Database {
         }
     }
 
-    pub fn update_task(&self, task: &Task) -> Result<()> {
+    fn update_task(&self, task: &Task) -> AppResult<()> {
         self.conn.execute(
             "UPDATE tasks SET title = ?1, priority = ?2, deadline = ?3, estimated_time = ?4,
              start_date = ?5, scheduled_start = ?6, completed = ?7, locked = ?8, 
@@ -233,21 +488,22 @@ impl Database {
         Ok(())
     }
 
-    pub fn delete_task(&self, id: u32) -> Result<()> {
+    fn delete_task(&self, id: u32) -> AppResult<()> {
         self.conn.execute("DELETE FROM tasks WHERE id = ?1", params![id])?;
         Ok(())
     }
 
-    pub fn mark_task_completed(&self, id: u32, completed: bool) -> Result<()> {
+    fn mark_task_completed(&self, id: u32, completed: bool) -> AppResult<()> {
         self.conn.execute(
             "UPDATE tasks SET completed = ?1 WHERE id = ?2",
             params![completed, id],
         )?;
         Ok(())
     }
+}
 
-    // Project operations
-    pub fn create_project(&self, name: String, path: String, project_type: Option<String>, description: Option<String>) -> Result<Project> {
+impl ProjectRepository for Database {
+    fn create_project(&self, name: String, path: String, project_type: Option<String>, description: Option<String>) -> AppResult<Project> {
         let created_at = Utc::now().to_rfc3339();
         
         self.conn.execute(
@@ -293,7 +549,7 @@ impl Database {
         })
     }
 
-    pub fn get_all_projects(&self) -> Result<Vec<Project>> {
+    fn get_all_projects(&self) -> AppResult<Vec<Project>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, name, path, project_type, description, last_accessed, created_at 
              FROM projects ORDER BY last_accessed DESC, created_at DESC"
@@ -318,7 +574,7 @@ impl Database {
         Ok(projects)
     }
 
-    pub fn get_project_by_id(&self, id: u32) -> Result<Option<Project>> {
+    fn get_project_by_id(&self, id: u32) -> AppResult<Option<Project>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, name, path, project_type, description, last_accessed, created_at 
              FROM projects WHERE id = ?1"
@@ -342,7 +598,7 @@ impl Database {
         }
     }
 
-    pub fn update_project(&self, project: &Project) -> Result<()> {
+    fn update_project(&self, project: &Project) -> AppResult<()> {
         let last_accessed = Utc::now().to_rfc3339();
         self.conn.execute(
             "UPDATE projects SET name = ?1, path = ?2, project_type = ?3, description = ?4, last_accessed = ?5 WHERE id = ?6",
@@ -358,24 +614,58 @@ impl Database {
         Ok(())
     }
 
-    pub fn delete_project(&self, id: u32) -> Result<()> {
+    fn get_project_deletion_impact(&self, project_id: u32) -> AppResult<ProjectDeletionImpact> {
+        let ids_for = |sql: &str| -> rusqlite::Result<Vec<u32>> {
+            let mut stmt = self.conn.prepare(sql)?;
+            let ids = stmt.query_map([project_id], |row| row.get(0))?;
+            ids.collect()
+        };
+
+        Ok(ProjectDeletionImpact {
+            project_id,
+            service_ids: ids_for("SELECT id FROM services WHERE project_id = ?1 ORDER BY id")?,
+            task_ids: ids_for("SELECT id FROM tasks WHERE project_id = ?1 ORDER BY id")?,
+            git_repo_ids: ids_for("SELECT id FROM git_repos WHERE project_id = ?1 ORDER BY id")?,
+            connection_profile_ids: ids_for("SELECT id FROM connection_profiles WHERE project_id = ?1 ORDER BY id")?,
+        })
+    }
+
+    /// Deletes a project using the schema's rules: services and tasks are
+    /// detached and git_repos rows are removed.
+    fn delete_project(&self, id: u32) -> AppResult<()> {
         self.conn.execute("DELETE FROM projects WHERE id = ?1", params![id])?;
         Ok(())
     }
 
-    // Service operations
-    pub fn create_service(&self, name: String, command: String, working_dir: Option<String>, project_id: Option<u32>, auto_start: bool) -> Result<Service> {
+    fn delete_project_with_mode(&self, id: u32, mode: ProjectDeleteMode) -> AppResult<ProjectDeletionImpact> {
+        let impact = self.get_project_deletion_impact(id)?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        if mode == ProjectDeleteMode::Cascade {
+            tx.execute("DELETE FROM services WHERE project_id = ?1", params![id])?;
+            tx.execute("DELETE FROM tasks WHERE project_id = ?1", params![id])?;
+        }
+        tx.execute("DELETE FROM projects WHERE id = ?1", params![id])?;
+        tx.commit()?;
+
+        Ok(impact)
+    }
+}
+
+impl ServiceRepository for Database {
+    fn create_service(&self, name: String, command: String, working_dir: Option<String>, project_id: Option<u32>, auto_start: bool, shell: bool) -> AppResult<Service> {
         let created_at = Utc::now().to_rfc3339();
         
         self.conn.execute(
-            "INSERT INTO services (name, command, working_dir, project_id, auto_start, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO services (name, command, working_dir, project_id, auto_start, shell, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
                 name,
                 command,
                 working_dir,
                 project_id,
                 auto_start,
+                shell,
                 created_at
             ],
         )?;
@@ -389,13 +679,14 @@ impl Database {
             working_dir,
             project_id,
             auto_start,
+            shell,
             created_at,
         })
     }
 
-    pub fn get_all_services(&self) -> Result<Vec<Service>> {
+    fn get_all_services(&self) -> AppResult<Vec<Service>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, command, working_dir, project_id, auto_start, created_at 
+            "SELECT id, name, command, working_dir, project_id, auto_start, created_at, shell
              FROM services ORDER BY created_at DESC"
         )?;
 
@@ -408,6 +699,7 @@ impl Database {
                 project_id: row.get(4)?,
                 auto_start: row.get(5)?,
                 created_at: row.get(6)?,
+                shell: row.get(7)?,
             })
         })?;
 
@@ -418,9 +710,9 @@ impl Database {
         Ok(services)
     }
 
-    pub fn get_service_by_id(&self, id: u32) -> Result<Option<Service>> {
+    fn get_service_by_id(&self, id: u32) -> AppResult<Option<Service>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, command, working_dir, project_id, auto_start, created_at 
+            "SELECT id, name, command, working_dir, project_id, auto_start, created_at, shell
              FROM services WHERE id = ?1"
         )?;
 
@@ -433,6 +725,7 @@ impl Database {
                 project_id: row.get(4)?,
                 auto_start: row.get(5)?,
                 created_at: row.get(6)?,
+                shell: row.get(7)?,
             })
         })?;
 
@@ -442,28 +735,302 @@ impl Database {
         }
     }
 
-    pub fn update_service(&self, service: &Service) -> Result<()> {
+    fn update_service(&self, service: &Service) -> AppResult<()> {
         self.conn.execute(
-            "UPDATE services SET name = ?1, command = ?2, working_dir = ?3, project_id = ?4, auto_start = ?5 WHERE id = ?6",
+            "UPDATE services SET name = ?1, command = ?2, working_dir = ?3, project_id = ?4, auto_start = ?5, shell = ?6
+             WHERE id = ?7",
             params![
                 service.name,
                 service.command,
                 service.working_dir,
                 service.project_id,
                 service.auto_start,
+                service.shell,
                 service.id
             ],
         )?;
         Ok(())
     }
 
-    pub fn delete_service(&self, id: u32) -> Result<()> {
+    fn delete_service(&self, id: u32) -> AppResult<()> {
         self.conn.execute("DELETE FROM services WHERE id = ?1", params![id])?;
         Ok(())
     }
 
-    // Git repo operations
-    pub fn create_git_repo(&self, project_id: u32, repo_path: String) -> Result<u32> {
+    fn get_service_environment(&self, service_id: u32) -> AppResult<ServiceEnvironment> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, value, secret FROM service_env_vars WHERE service_id = ?1 ORDER BY position"
+        )?;
+        let variables = stmt
+            .query_map(params![service_id], |row| {
+                Ok(ServiceEnvVar {
+                    name: row.get(0)?,
+                    value: row.get(1)?,
+                    secret: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = self.conn.prepare(
+            "SELECT path FROM service_env_files WHERE service_id = ?1 ORDER BY position"
+        )?;
+        let env_files = stmt
+            .query_map(params![service_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ServiceEnvironment { variables, env_files })
+    }
+
+    fn set_service_environment(&self, service_id: u32, environment: &ServiceEnvironment) -> AppResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM service_env_vars WHERE service_id = ?1", params![service_id])?;
+        tx.execute("DELETE FROM service_env_files WHERE service_id = ?1", params![service_id])?;
+
+        for (position, var) in environment.variables.iter().enumerate() {
+            let value = if var.secret { None } else { var.value.as_deref() };
+            tx.execute(
+                "INSERT INTO service_env_vars (service_id, position, name, value, secret) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![service_id, position as u32, var.name, value, var.secret],
+            )?;
+        }
+        for (position, path) in environment.env_files.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO service_env_files (service_id, position, path) VALUES (?1, ?2, ?3)",
+                params![service_id, position as u32, path],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_restart_policy(&self, service_id: u32) -> AppResult<RestartPolicy> {
+        let policy = self.conn.query_row(
+            "SELECT mode, max_retries, initial_backoff_ms, max_backoff_ms FROM service_restart_policies WHERE service_id = ?1",
+            params![service_id],
+            |row| {
+                let mode: String = row.get(0)?;
+                Ok(RestartPolicy {
+                    mode: RestartMode::parse(&mode).ok_or_else(|| {
+                        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, format!("Unknown restart mode {}", mode).into())
+                    })?,
+                    max_retries: row.get(1)?,
+                    initial_backoff_ms: row.get(2)?,
+                    max_backoff_ms: row.get(3)?,
+                })
+            },
+        ).optional()?;
+        Ok(policy.unwrap_or_default())
+    }
+
+    fn set_restart_policy(&self, service_id: u32, policy: &RestartPolicy) -> AppResult<()> {
+        self.conn.execute(
+            "INSERT INTO service_restart_policies (service_id, mode, max_retries, initial_backoff_ms, max_backoff_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(service_id) DO UPDATE SET mode = ?2, max_retries = ?3, initial_backoff_ms = ?4, max_backoff_ms = ?5",
+            params![service_id, policy.mode.as_str(), policy.max_retries, policy.initial_backoff_ms, policy.max_backoff_ms],
+        )?;
+        Ok(())
+    }
+
+    fn get_health_checks(&self, service_id: u32) -> AppResult<HealthChecks> {
+        let mut stmt = self.conn.prepare(
+            "SELECT probe, kind, target, expected_status, interval_ms, timeout_ms, failure_threshold
+             FROM service_health_checks WHERE service_id = ?1"
+        )?;
+        let rows = stmt
+            .query_map(params![service_id], |row| Ok((row.get::<_, String>(0)?, probe_from_row(row)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut checks = HealthChecks::default();
+        for (name, probe) in rows {
+            match name.as_str() {
+                "readiness" => checks.readiness = Some(probe),
+                _ => checks.liveness = Some(probe),
+            }
+        }
+        Ok(checks)
+    }
+
+    fn set_health_checks(&self, service_id: u32, checks: &HealthChecks) -> AppResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM service_health_checks WHERE service_id = ?1", params![service_id])?;
+        for (name, probe) in [("readiness", &checks.readiness), ("liveness", &checks.liveness)] {
+            let Some(probe) = probe else { continue };
+            let (kind, target, expected_status) = match &probe.check {
+                ProbeCheck::Http { url, expected_status } => ("http", url.clone(), Some(*expected_status)),
+                ProbeCheck::Tcp { port } => ("tcp", port.to_string(), None),
+                ProbeCheck::Log { pattern } => ("log", pattern.clone(), None),
+                ProbeCheck::Command { command } => ("command", command.clone(), None),
+            };
+            tx.execute(
+                "INSERT INTO service_health_checks
+                 (service_id, probe, kind, target, expected_status, interval_ms, timeout_ms, failure_threshold)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    service_id, name, kind, target, expected_status,
+                    probe.interval_ms, probe.timeout_ms, probe.failure_threshold,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_log_retention(&self, service_id: u32) -> AppResult<LogRetention> {
+        let retention = self.conn.query_row(
+            "SELECT max_age_days, max_lines, max_bytes FROM service_log_retention WHERE service_id = ?1",
+            params![service_id],
+            |row| Ok(LogRetention { max_age_days: row.get(0)?, max_lines: row.get(1)?, max_bytes: row.get(2)? }),
+        ).optional()?;
+        Ok(retention.unwrap_or_default())
+    }
+
+    fn set_log_retention(&self, service_id: u32, retention: &LogRetention) -> AppResult<()> {
+        self.conn.execute(
+            "INSERT INTO service_log_retention (service_id, max_age_days, max_lines, max_bytes)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(service_id) DO UPDATE SET max_age_days = ?2, max_lines = ?3, max_bytes = ?4",
+            params![service_id, retention.max_age_days, retention.max_lines, retention.max_bytes],
+        )?;
+        Ok(())
+    }
+
+    fn get_log_parser(&self, service_id: u32) -> AppResult<LogParser> {
+        let parser = self.conn.query_row(
+            "SELECT format, keep_ansi, stderr_level FROM service_log_parsers WHERE service_id = ?1",
+            params![service_id],
+            |row| {
+                let format: String = row.get(0)?;
+                Ok(LogParser {
+                    format: LogFormat::parse(&format).ok_or_else(|| {
+                        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, format!("Unknown log format {}", format).into())
+                    })?,
+                    keep_ansi: row.get(1)?,
+                    stderr_level: row.get(2)?,
+                })
+            },
+        ).optional()?;
+        Ok(parser.unwrap_or_default())
+    }
+
+    fn set_log_parser(&self, service_id: u32, parser: &LogParser) -> AppResult<()> {
+        self.conn.execute(
+            "INSERT INTO service_log_parsers (service_id, format, keep_ansi, stderr_level)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(service_id) DO UPDATE SET format = ?2, keep_ansi = ?3, stderr_level = ?4",
+            params![service_id, parser.format.as_str(), parser.keep_ansi, parser.stderr_level],
+        )?;
+        Ok(())
+    }
+
+    fn get_service_dependencies(&self, service_id: u32) -> AppResult<Vec<u32>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT depends_on FROM service_dependencies WHERE service_id = ?1 ORDER BY depends_on"
+        )?;
+        let depends_on = stmt
+            .query_map(params![service_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(depends_on)
+    }
+
+    fn set_service_dependencies(&self, service_id: u32, depends_on: &[u32]) -> AppResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM service_dependencies WHERE service_id = ?1", params![service_id])?;
+        for dependency in depends_on {
+            tx.execute(
+                "INSERT OR IGNORE INTO service_dependencies (service_id, depends_on) VALUES (?1, ?2)",
+                params![service_id, dependency],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+impl ServiceGroupRepository for Database {
+    fn create_service_group(&self, name: String, service_ids: &[u32]) -> AppResult<ServiceGroup> {
+        let created_at = Utc::now().to_rfc3339();
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO service_groups (name, created_at) VALUES (?1, ?2)",
+            params![name, created_at],
+        )?;
+        let id = tx.last_insert_rowid() as u32;
+        set_group_members(&tx, id, service_ids)?;
+        tx.commit()?;
+
+        let mut service_ids = service_ids.to_vec();
+        service_ids.sort_unstable();
+        service_ids.dedup();
+        Ok(ServiceGroup { id, name, service_ids, created_at })
+    }
+
+    fn get_service_groups(&self) -> AppResult<Vec<ServiceGroup>> {
+        let mut stmt = self.conn.prepare("SELECT id, name, created_at FROM service_groups ORDER BY name COLLATE NOCASE, id")?;
+        let groups = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<(u32, String, String)>, _>>()?;
+
+        groups.into_iter()
+            .map(|(id, name, created_at)| {
+                Ok(ServiceGroup { id, name, service_ids: self.group_members(id)?, created_at })
+            })
+            .collect()
+    }
+
+    fn get_service_group_by_id(&self, id: u32) -> AppResult<Option<ServiceGroup>> {
+        let group = self.conn.query_row(
+            "SELECT name, created_at FROM service_groups WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional()?;
+
+        match group {
+            Some((name, created_at)) => Ok(Some(ServiceGroup { id, name, service_ids: self.group_members(id)?, created_at })),
+            None => Ok(None),
+        }
+    }
+
+    fn update_service_group(&self, group: &ServiceGroup) -> AppResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("UPDATE service_groups SET name = ?1 WHERE id = ?2", params![group.name, group.id])?;
+        set_group_members(&tx, group.id, &group.service_ids)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_service_group(&self, id: u32) -> AppResult<()> {
+        self.conn.execute("DELETE FROM service_groups WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+}
+
+impl Database {
+    fn group_members(&self, group_id: u32) -> AppResult<Vec<u32>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT service_id FROM service_group_members WHERE group_id = ?1 ORDER BY service_id"
+        )?;
+        let members = stmt
+            .query_map(params![group_id], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(members)
+    }
+}
+
+fn set_group_members(conn: &Connection, group_id: u32, service_ids: &[u32]) -> AppResult<()> {
+    conn.execute("DELETE FROM service_group_members WHERE group_id = ?1", params![group_id])?;
+    for service_id in service_ids {
+        conn.execute(
+            "INSERT OR IGNORE INTO service_group_members (group_id, service_id) VALUES (?1, ?2)",
+            params![group_id, service_id],
+        )?;
+    }
+    Ok(())
+}
+
+impl GitRepoRepository for Database {
+    fn create_git_repo(&self, project_id: u32, repo_path: String) -> AppResult<u32> {
         let last_checked = Utc::now().to_rfc3339();
         
         self.conn.execute(
@@ -475,7 +1042,7 @@ impl Database {
         Ok(self.conn.last_insert_rowid() as u32)
     }
 
-    pub fn get_git_repo_by_project_id(&self, project_id: u32) -> Result<Option<GitStatus>> {
+    fn get_git_repo_by_project_id(&self, project_id: u32) -> AppResult<Option<GitStatus>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, project_id, repo_path, current_branch, last_checked 
              FROM git_repos WHERE project_id = ?1"
@@ -500,7 +1067,7 @@ impl Database {
         }
     }
 
-    pub fn update_git_repo(&self, git_status: &GitStatus) -> Result<()> {
+    fn update_git_repo(&self, git_status: &GitStatus) -> AppResult<()> {
         let last_checked = Utc::now().to_rfc3339();
         self.conn.execute(
             "UPDATE git_repos SET current_branch = ?1, last_checked = ?2 WHERE id = ?3",
@@ -509,8 +1076,277 @@ impl Database {
         Ok(())
     }
 
-    pub fn delete_git_repo(&self, id: u32) -> Result<()> {
+    fn delete_git_repo(&self, id: u32) -> AppResult<()> {
         self.conn.execute("DELETE FROM git_repos WHERE id = ?1", params![id])?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+// Columns 1-6 of a service_health_checks row
+fn probe_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Probe> {
+    let kind: String = row.get(1)?;
+    let target: String = row.get(2)?;
+    let invalid = |message: String| {
+        rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Text, message.into())
+    };
+    let check = match kind.as_str() {
+        "http" => ProbeCheck::Http { url: target, expected_status: row.get::<_, Option<u16>>(3)?.unwrap_or(200) },
+        "tcp" => ProbeCheck::Tcp {
+            port: target.parse().map_err(|_| invalid(format!("Invalid port {}", target)))?,
+        },
+        "log" => ProbeCheck::Log { pattern: target },
+        "command" => ProbeCheck::Command { command: target },
+        _ => return Err(invalid(format!("Unknown probe kind {}", kind))),
+    };
+    Ok(Probe {
+        check,
+        interval_ms: row.get(4)?,
+        timeout_ms: row.get(5)?,
+        failure_threshold: row.get(6)?,
+    })
+}
+
+/// `%term%` for LIKE, with wildcards in the term matched literally.
+pub(crate) fn like_pattern(search: &str) -> String {
+    let escaped = search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+fn query_history_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<QueryHistoryEntry> {
+    Ok(QueryHistoryEntry {
+        id: row.get(0)?,
+        database_id: row.get(1)?,
+        query: row.get(2)?,
+        duration_ms: row.get(3)?,
+        row_count: row.get(4)?,
+        error: row.get(5)?,
+        executed_at: row.get(6)?,
+    })
+}
+
+fn saved_query_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SavedQuery> {
+    Ok(SavedQuery {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        query: row.get(2)?,
+        database_id: row.get(3)?,
+        description: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+impl QueryRepository for Database {
+    fn add_query_history(&self, entry: NewQueryHistory) -> AppResult<QueryHistoryEntry> {
+        let executed_at = Utc::now().to_rfc3339();
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO query_history (database_id, query, duration_ms, row_count, error, executed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![entry.database_id, entry.query, entry.duration_ms, entry.row_count, entry.error, executed_at],
+        )?;
+        let id = tx.last_insert_rowid() as u32;
+
+        // Keep only the newest entries
+        tx.execute(
+            "DELETE FROM query_history WHERE id NOT IN
+                 (SELECT id FROM query_history ORDER BY id DESC LIMIT ?1)",
+            params![MAX_QUERY_HISTORY],
+        )?;
+        tx.commit()?;
+
+        Ok(QueryHistoryEntry {
+            id,
+            database_id: entry.database_id,
+            query: entry.query,
+            duration_ms: entry.duration_ms,
+            row_count: entry.row_count,
+            error: entry.error,
+            executed_at,
+        })
+    }
+
+    fn search_query_history(&self, search: Option<&str>, database_id: Option<&str>, limit: u32) -> AppResult<Vec<QueryHistoryEntry>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM query_history
+             WHERE (?1 IS NULL OR query LIKE ?1 ESCAPE '\\')
+               AND (?2 IS NULL OR database_id = ?2)
+             ORDER BY id DESC LIMIT ?3",
+            QUERY_HISTORY_COLUMNS
+        ))?;
+
+        let entries = stmt
+            .query_map(params![search.map(like_pattern), database_id, limit], query_history_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    fn clear_query_history(&self) -> AppResult<()> {
+        self.conn.execute("DELETE FROM query_history", [])?;
+        Ok(())
+    }
+
+    fn create_saved_query(&self, name: String, query: String, database_id: Option<String>, description: Option<String>) -> AppResult<SavedQuery> {
+        let created_at = Utc::now().to_rfc3339();
+
+        self.conn.execute(
+            "INSERT INTO saved_queries (name, query, database_id, description, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            params![name, query, database_id, description, created_at],
+        )?;
+
+        Ok(SavedQuery {
+            id: self.conn.last_insert_rowid() as u32,
+            name,
+            query,
+            database_id,
+            description,
+            updated_at: created_at.clone(),
+            created_at,
+        })
+    }
+
+    fn search_saved_queries(&self, search: Option<&str>) -> AppResult<Vec<SavedQuery>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM saved_queries
+             WHERE ?1 IS NULL
+                OR name LIKE ?1 ESCAPE '\\'
+                OR query LIKE ?1 ESCAPE '\\'
+                OR description LIKE ?1 ESCAPE '\\'
+             ORDER BY name COLLATE NOCASE, id",
+            SAVED_QUERIES_COLUMNS
+        ))?;
+
+        let queries = stmt
+            .query_map(params![search.map(like_pattern)], saved_query_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(queries)
+    }
+
+    fn get_saved_query_by_id(&self, id: u32) -> AppResult<Option<SavedQuery>> {
+        let saved_query = self.conn.query_row(
+            &format!("SELECT {} FROM saved_queries WHERE id = ?1", SAVED_QUERIES_COLUMNS),
+            params![id],
+            saved_query_from_row,
+        ).optional()?;
+        Ok(saved_query)
+    }
+
+    fn update_saved_query(&self, saved_query: &SavedQuery) -> AppResult<()> {
+        self.conn.execute(
+            "UPDATE saved_queries SET name = ?1, query = ?2, database_id = ?3, description = ?4, updated_at = ?5
+             WHERE id = ?6",
+            params![
+                saved_query.name,
+                saved_query.query,
+                saved_query.database_id,
+                saved_query.description,
+                Utc::now().to_rfc3339(),
+                saved_query.id
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn delete_saved_query(&self, id: u32) -> AppResult<()> {
+        self.conn.execute("DELETE FROM saved_queries WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+}
+
+fn connection_profile_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ConnectionProfile> {
+    let kind: String = row.get(3)?;
+    Ok(ConnectionProfile {
+        id: row.get(0)?,
+        project_id: row.get(1)?,
+        name: row.get(2)?,
+        kind: ConnectorKind::parse(&kind).ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, format!("Unknown connector kind {}", kind).into())
+        })?,
+        host: row.get(4)?,
+        port: row.get(5)?,
+        database: row.get(6)?,
+        username: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+impl ConnectionProfileRepository for Database {
+    fn create_connection_profile(&self, form: &ConnectionProfileForm) -> AppResult<ConnectionProfile> {
+        let created_at = Utc::now().to_rfc3339();
+
+        self.conn.execute(
+            "INSERT INTO connection_profiles (project_id, name, kind, host, port, database, username, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                form.project_id,
+                form.name,
+                form.kind.as_str(),
+                form.host,
+                form.port,
+                form.database,
+                form.username,
+                created_at
+            ],
+        )?;
+
+        Ok(ConnectionProfile {
+            id: self.conn.last_insert_rowid() as u32,
+            project_id: form.project_id,
+            name: form.name.clone(),
+            kind: form.kind,
+            host: form.host.clone(),
+            port: form.port,
+            database: form.database.clone(),
+            username: form.username.clone(),
+            created_at,
+        })
+    }
+
+    fn get_connection_profiles(&self, project_id: Option<u32>) -> AppResult<Vec<ConnectionProfile>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM connection_profiles WHERE ?1 IS NULL OR project_id = ?1
+             ORDER BY name COLLATE NOCASE, id",
+            CONNECTION_PROFILES_COLUMNS
+        ))?;
+
+        let profiles = stmt
+            .query_map(params![project_id], connection_profile_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(profiles)
+    }
+
+    fn get_connection_profile_by_id(&self, id: u32) -> AppResult<Option<ConnectionProfile>> {
+        let profile = self.conn.query_row(
+            &format!("SELECT {} FROM connection_profiles WHERE id = ?1", CONNECTION_PROFILES_COLUMNS),
+            params![id],
+            connection_profile_from_row,
+        ).optional()?;
+        Ok(profile)
+    }
+
+    fn update_connection_profile(&self, profile: &ConnectionProfile) -> AppResult<()> {
+        self.conn.execute(
+            "UPDATE connection_profiles SET project_id = ?1, name = ?2, kind = ?3, host = ?4, port = ?5,
+                 database = ?6, username = ?7
+             WHERE id = ?8",
+            params![
+                profile.project_id,
+                profile.name,
+                profile.kind.as_str(),
+                profile.host,
+                profile.port,
+                profile.database,
+                profile.username,
+                profile.id
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn delete_connection_profile(&self, id: u32) -> AppResult<()> {
+        self.conn.execute("DELETE FROM connection_profiles WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+}
@@ -1,133 +1,358 @@
-use rusqlite::{Connection, Result};
+use crate::database_schema::{self, DatabaseSchema};
+use crate::database_value::CellValue;
+use crate::error::{AppError, AppResult, IntoAppError};
+use rusqlite::hooks::{AuthAction, AuthContext, Authorization};
+use rusqlite::{Connection, OpenFlags, Row, Statement};
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a single browser call may run before SQLite is interrupted.
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Number of SQLite VM instructions between deadline checks
+pub(crate) const PROGRESS_INTERVAL: i32 = 1000;
+
+// Introspection pragmas whose argument names a table or index
+const PRAGMAS_WITH_ARGUMENT: [&str; 6] = [
+    "table_info",
+    "table_xinfo",
+    "index_list",
+    "index_info",
+    "index_xinfo",
+    "foreign_key_list",
+];
+
+// Pragmas that are read-only when called without a value
+const PRAGMAS_WITHOUT_VALUE: [&str; 10] = [
+    "table_list",
+    "database_list",
+    "collation_list",
+    "function_list",
+    "user_version",
+    "schema_version",
+    "page_count",
+    "page_size",
+    "freelist_count",
+    "encoding",
+];
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TableInfo {
     pub name: String,
+    #[serde(rename = "rowCount")]
     pub row_count: u32,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResultColumn {
+    pub name: String,
+    /// Type from the column's declaration, `None` for expressions.
+    #[serde(rename = "declaredType")]
+    pub declared_type: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QueryResult {
-    pub columns: Vec<String>,
-    pub rows: Vec<Vec<String>>,
+    pub columns: Vec<ResultColumn>,
+    pub rows: Vec<Vec<CellValue>>,
+    #[serde(rename = "rowCount")]
     pub row_count: usize,
+    /// Stopped at the row cap with rows left unread.
+    pub truncated: bool,
 }
 
-pub fn list_tables(db_path: &str) -> Result<Vec<TableInfo>> {
-    let conn = Connection::open(db_path)?;
-    
-    let mut stmt = conn.prepare(
-        "SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name"
-    )?;
-    
-    let table_iter = stmt.query_map([], |row| {
-        let name: String = row.get(0)?;
-        Ok(name)
-    })?;
-    
+/// Values for a query's placeholders. `?` and `?NNN` take
+/// `positional[index - 1]`, where the index is SQLite's parameter number
+/// (plain `?` placeholders count up from the previous one); `:name`,
+/// `@name` and `$name` take `named["name"]`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct QueryParams {
+    #[serde(default)]
+    pub positional: Vec<CellValue>,
+    #[serde(default)]
+    pub named: BTreeMap<String, CellValue>,
+}
+
+/// A placeholder found in a prepared query.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QueryParameter {
+    pub index: usize,
+    /// Name without its `:`, `@` or `$` prefix; `None` for `?` placeholders.
+    pub name: Option<String>,
+}
+
+/// Connection that can only read: the file is opened with
+/// `SQLITE_OPEN_READ_ONLY`, an authorizer rejects anything but reads
+/// (including ATTACH and writable pragmas), and a progress handler
+/// interrupts work that runs past the deadline.
+pub(crate) struct ReadOnlyConnection {
+    conn: Connection,
+    denied: Arc<Mutex<Option<String>>>,
+    timeout: Duration,
+}
+
+impl ReadOnlyConnection {
+    pub(crate) fn open(db_path: &str, timeout: Duration) -> AppResult<Self> {
+        let deadline = Instant::now() + timeout;
+        ReadOnlyConnection::open_interruptible(db_path, timeout, move || Instant::now() > deadline)
+    }
+
+    /// Opens a connection whose statements are interrupted whenever
+    /// `should_stop` returns true. `timeout` is only used in error messages.
+    pub(crate) fn open_interruptible(
+        db_path: &str,
+        timeout: Duration,
+        should_stop: impl FnMut() -> bool + Send + 'static,
+    ) -> AppResult<Self> {
+        if !Path::new(db_path).is_file() {
+            return Err(AppError::not_found("Database file", db_path));
+        }
+
+        let flags = OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let conn = Connection::open_with_flags(db_path, flags)
+            .map_err(|e| e.into_app_error(format!("Failed to open database {}", db_path)))?;
+
+        let denied = Arc::new(Mutex::new(None));
+        let denied_by_hook = Arc::clone(&denied);
+        conn.authorizer(Some(move |ctx: AuthContext<'_>| {
+            let decision = authorize(&ctx.action);
+            if decision == Authorization::Deny {
+                if let Ok(mut denied) = denied_by_hook.lock() {
+                    *denied = Some(format!("{:?}", ctx.action));
+                }
+            }
+            decision
+        }));
+
+        conn.progress_handler(PROGRESS_INTERVAL, Some(should_stop));
+
+        Ok(ReadOnlyConnection { conn, denied, timeout })
+    }
+
+    /// Turns interrupts and authorizer denials into errors the user can act on.
+    pub(crate) fn explain(&self, err: rusqlite::Error, context: &str) -> AppError {
+        match err.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::OperationInterrupted) => AppError::timeout(format!(
+                "Query exceeded the {} second time limit",
+                self.timeout.as_secs_f32()
+            )),
+            Some(rusqlite::ErrorCode::AuthorizationForStatementDenied) => {
+                let action = self.denied.lock().ok().and_then(|mut d| d.take());
+                let message = match action {
+                    Some(action) => format!("Not allowed in the read-only browser: {}", action),
+                    None => "Not allowed in the read-only browser".to_string(),
+                };
+                AppError::invalid_field("query", message)
+            }
+            _ => err.into_app_error(context),
+        }
+    }
+
+    /// Runs a statement and returns every cell with its storage class.
+    pub(crate) fn query(&self, sql: &str, params: &QueryParams, context: &str) -> AppResult<QueryResult> {
+        let mut rows = Vec::new();
+        let columns = self.for_each_row(sql, params, context, |_| Ok(()), |cells| {
+            rows.push(cells.to_vec());
+            Ok(())
+        })?;
+
+        Ok(QueryResult {
+            columns,
+            row_count: rows.len(),
+            rows,
+            truncated: false,
+        })
+    }
+
+    fn for_each_row(
+        &self,
+        sql: &str,
+        params: &QueryParams,
+        context: &str,
+        on_columns: impl FnOnce(&[ResultColumn]) -> AppResult<()>,
+        mut on_row: impl FnMut(&[CellValue]) -> AppResult<()>,
+    ) -> AppResult<Vec<ResultColumn>> {
+        let (mut stmt, columns) = self.prepare(sql, params, context)?;
+        on_columns(&columns)?;
+
+        let mut rows = stmt.raw_query();
+        while let Some(row) = rows.next().map_err(|e| self.explain(e, context))? {
+            let cells = read_row(row, columns.len()).map_err(|e| self.explain(e, context))?;
+            on_row(&cells)?;
+        }
+        Ok(columns)
+    }
+
+    pub(crate) fn schema(&self) -> AppResult<DatabaseSchema> {
+        database_schema::introspect(&self.conn).map_err(|e| self.explain(e, "Failed to read schema"))
+    }
+
+    /// Whether a table has a rowid, i.e. was not created `WITHOUT ROWID`.
+    pub(crate) fn has_rowid(&self, table: &str) -> AppResult<bool> {
+        self.conn
+            .query_row("SELECT NOT wr FROM pragma_table_list WHERE schema = 'main' AND name = ?1", [table], |row| row.get(0))
+            .map_err(|e| self.explain(e, "Failed to read table list"))
+    }
+
+    /// Prepares a statement with its parameters bound, ready to step.
+    pub(crate) fn prepare(
+        &self,
+        sql: &str,
+        params: &QueryParams,
+        context: &str,
+    ) -> AppResult<(Statement<'_>, Vec<ResultColumn>)> {
+        let mut stmt = self.conn.prepare(sql).map_err(|e| self.explain(e, context))?;
+        bind_params(&mut stmt, params)?;
+        let columns = result_columns(&stmt);
+        Ok((stmt, columns))
+    }
+}
+
+/// Allows plain reads, functions and read-only introspection pragmas.
+fn authorize(action: &AuthAction<'_>) -> Authorization {
+    match action {
+        AuthAction::Select | AuthAction::Read { .. } | AuthAction::Function { .. } | AuthAction::Recursive => {
+            Authorization::Allow
+        }
+        AuthAction::Pragma { pragma_name, pragma_value } => {
+            let name = pragma_name.to_ascii_lowercase();
+            if PRAGMAS_WITH_ARGUMENT.contains(&name.as_str())
+                || (pragma_value.is_none() && PRAGMAS_WITHOUT_VALUE.contains(&name.as_str()))
+            {
+                Authorization::Allow
+            } else {
+                Authorization::Deny
+            }
+        }
+        _ => Authorization::Deny,
+    }
+}
+
+fn bind_params(stmt: &mut Statement<'_>, params: &QueryParams) -> AppResult<()> {
+    for index in 1..=stmt.parameter_count() {
+        let value = match stmt.parameter_name(index) {
+            Some(name) if !name.starts_with('?') => params.named.get(&name[1..]),
+            _ => params.positional.get(index - 1),
+        };
+        let value = value.ok_or_else(|| {
+            let name = stmt.parameter_name(index).map(str::to_string).unwrap_or_else(|| format!("?{}", index));
+            AppError::invalid_field("params", format!("No value given for parameter {}", name))
+        })?;
+        stmt.raw_bind_parameter(index, value)?;
+    }
+    Ok(())
+}
+
+fn result_columns(stmt: &Statement<'_>) -> Vec<ResultColumn> {
+    stmt.columns()
+        .iter()
+        .map(|c| ResultColumn {
+            name: c.name().to_string(),
+            declared_type: c.decl_type().map(|t| t.to_string()),
+        })
+        .collect()
+}
+
+pub(crate) fn read_row(row: &Row<'_>, column_count: usize) -> rusqlite::Result<Vec<CellValue>> {
+    (0..column_count)
+        .map(|i| row.get_ref(i).map(CellValue::from))
+        .collect()
+}
+
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+pub fn list_tables(db_path: &str, timeout: Duration) -> AppResult<Vec<TableInfo>> {
+    let conn = ReadOnlyConnection::open(db_path, timeout)?;
+
+    let names = conn.conn
+        .prepare("SELECT name FROM sqlite_master WHERE type='table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+        .and_then(|mut stmt| stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<Vec<String>>>())
+        .map_err(|e| conn.explain(e, "Failed to list tables"))?;
+
     let mut tables = Vec::new();
-    for table in table_iter {
-        let table_name = table?;
-        
+    for table_name in names {
         // Get row count
-        let count: u32 = conn.query_row(
-            &format!("SELECT COUNT(*) FROM \"{}\"", table_name),
+        let count: u32 = conn.conn.query_row(
+            &format!("SELECT COUNT(*) FROM {}", quote_identifier(&table_name)),
             [],
-            |row| row.get(0)
-        ).unwrap_or(0);
-        
+            |row| row.get(0),
+        ).map_err(|e| conn.explain(e, "Failed to count rows"))?;
+
         tables.push(TableInfo {
             name: table_name,
             row_count: count,
         });
     }
-    
+
     Ok(tables)
 }
 
-pub fn get_table_data(db_path: &str, table: &str, limit: u32, offset: u32) -> Result<QueryResult> {
-    let conn = Connection::open(db_path)?;
-    
-    // Get column names
-    let mut stmt = conn.prepare(&format!("SELECT * FROM \"{}\" LIMIT 1", table))?;
-    let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
-    
-    // Get data with limit and offset
-    let query = format!("SELECT * FROM \"{}\" LIMIT ? OFFSET ?", table);
-    let mut stmt = conn.prepare(&query)?;
-    
-    let rows_iter = stmt.query_map([limit, offset], |row| {
-        let mut values = Vec::new();
-        for i in 0..columns.len() {
-            let value: String = row.get(i).unwrap_or_else(|_| "NULL".to_string());
-            values.push(value);
-        }
-        Ok(values)
-    })?;
-    
-    let mut rows = Vec::new();
-    for row in rows_iter {
-        rows.push(row?);
-    }
-    
-    let row_count = rows.len();
-    
-    Ok(QueryResult {
-        columns,
-        rows,
-        row_count,
-    })
-}
-
-pub fn execute_query(db_path: &str, query: &str) -> Result<QueryResult> {
-    // Safety check: only allow SELECT queries
-    let query_upper = query.trim().to_uppercase();
-    if !query_upper.starts_with("SELECT") {
-        return Err(rusqlite::Error::SqliteFailure(
-            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
-            Some("Only SELECT queries are allowed for safety".to_string())
-        ));
-    }
-    
-    // Additional safety: block dangerous keywords
-    let dangerous_keywords = ["DROP", "DELETE", "UPDATE", "INSERT", "ALTER", "CREATE", "TRUNCATE"];
-    for keyword in &dangerous_keywords {
-        if query_upper.contains(keyword) {
-            return Err(rusqlite::Error::SqliteFailure(
-                rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_MISUSE),
-                Some(format!("Query contains forbidden keyword: {}", keyword))
-            ));
-        }
-    }
-    
-    let conn = Connection::open(db_path)?;
-    let mut stmt = conn.prepare(query)?;
-    
-    // Get column names
-    let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
-    
-    // Execute query
-    let rows_iter = stmt.query_map([], |row| {
-        let mut values = Vec::new();
-        for i in 0..columns.len() {
-            let value: String = row.get(i).unwrap_or_else(|_| "NULL".to_string());
-            values.push(value);
-        }
-        Ok(values)
+/// Columns, indexes, foreign keys, triggers and views with their `CREATE` SQL.
+pub fn get_schema(db_path: &str, timeout: Duration) -> AppResult<DatabaseSchema> {
+    ReadOnlyConnection::open(db_path, timeout)?.schema()
+}
+
+pub fn get_table_data(db_path: &str, table: &str, limit: u32, offset: u32, timeout: Duration) -> AppResult<QueryResult> {
+    let conn = ReadOnlyConnection::open(db_path, timeout)?;
+
+    let query = format!("SELECT * FROM {} LIMIT ?1 OFFSET ?2", quote_identifier(table));
+    let params = QueryParams {
+        positional: vec![CellValue::Integer(limit.into()), CellValue::Integer(offset.into())],
+        ..QueryParams::default()
+    };
+    conn.query(&query, &params, "Failed to read table")
+}
+
+/// Runs arbitrary SQL against a read-only connection. Anything that would
+/// write, attach another file or change settings is rejected by the
+/// authorizer when the statement is prepared.
+pub fn execute_query(db_path: &str, query: &str, timeout: Duration) -> AppResult<QueryResult> {
+    execute_query_with_params(db_path, query, &QueryParams::default(), timeout)
+}
+
+/// Like `execute_query`, binding `params` to the query's placeholders.
+pub fn execute_query_with_params(db_path: &str, query: &str, params: &QueryParams, timeout: Duration) -> AppResult<QueryResult> {
+    let conn = ReadOnlyConnection::open(db_path, timeout)?;
+    conn.query(query, params, "Failed to execute query")
+}
+
+/// Lists the placeholders a query expects, in parameter order.
+pub fn query_parameters(db_path: &str, query: &str, timeout: Duration) -> AppResult<Vec<QueryParameter>> {
+    let conn = ReadOnlyConnection::open(db_path, timeout)?;
+    let stmt = conn.conn.prepare(query).map_err(|e| conn.explain(e, "Failed to prepare query"))?;
+
+    Ok((1..=stmt.parameter_count())
+        .map(|index| QueryParameter {
+            index,
+            name: stmt.parameter_name(index)
+                .filter(|name| !name.starts_with('?'))
+                .map(|name| name[1..].to_string()),
+        })
+        .collect())
+}
+
+/// Runs a read-only query and hands each row to `on_row` as soon as it is
+/// read, so results of any size can be processed in constant memory.
+/// Returns the number of rows visited.
+pub fn stream_query(
+    db_path: &str,
+    query: &str,
+    params: &QueryParams,
+    timeout: Duration,
+    on_columns: impl FnOnce(&[ResultColumn]) -> AppResult<()>,
+    mut on_row: impl FnMut(&[CellValue]) -> AppResult<()>,
+) -> AppResult<u64> {
+    let conn = ReadOnlyConnection::open(db_path, timeout)?;
+
+    let mut count = 0;
+    conn.for_each_row(query, params, "Failed to execute query", on_columns, |cells| {
+        count += 1;
+        on_row(cells)
     })?;
-    
-    let mut rows = Vec::new();
-    for row in rows_iter {
-        rows.push(row?);
-    }
-    
-    let row_count = rows.len();
-    
-    Ok(QueryResult {
-        columns,
-        rows,
-        row_count,
-    })
+    Ok(count)
 }
@@ -0,0 +1,83 @@
+use crate::database_browser::{self, QueryParams, QueryResult, TableInfo};
+use crate::error::{AppError, AppResult};
+use crate::model::{ConnectionProfile, ConnectorKind};
+use crate::postgres_connector::PostgresConnector;
+use std::time::Duration;
+
+/// Prefix of database ids that name a connection profile, e.g. `profile:3`.
+pub const PROFILE_DATABASE_PREFIX: &str = "profile:";
+
+/// Read-only access to a database shown in the browser. Every
+/// implementation rejects writes and interrupts work past its timeout.
+pub trait DatabaseConnector {
+    /// Product and version of the database, e.g. to check a profile works.
+    fn server_version(&mut self) -> AppResult<String>;
+    fn list_tables(&mut self) -> AppResult<Vec<TableInfo>>;
+    fn get_table_data(&mut self, table: &str, limit: u32, offset: u32) -> AppResult<QueryResult>;
+    fn execute_query(&mut self, query: &str, params: &QueryParams) -> AppResult<QueryResult>;
+}
+
+/// A SQLite file, opened read-only for each call.
+pub struct SqliteConnector {
+    path: String,
+    timeout: Duration,
+}
+
+impl SqliteConnector {
+    pub fn new(path: impl Into<String>, timeout: Duration) -> Self {
+        SqliteConnector {
+            path: path.into(),
+            timeout,
+        }
+    }
+}
+
+impl DatabaseConnector for SqliteConnector {
+    fn server_version(&mut self) -> AppResult<String> {
+        Ok(format!("SQLite {}", rusqlite::version()))
+    }
+
+    fn list_tables(&mut self) -> AppResult<Vec<TableInfo>> {
+        database_browser::list_tables(&self.path, self.timeout)
+    }
+
+    fn get_table_data(&mut self, table: &str, limit: u32, offset: u32) -> AppResult<QueryResult> {
+        database_browser::get_table_data(&self.path, table, limit, offset, self.timeout)
+    }
+
+    fn execute_query(&mut self, query: &str, params: &QueryParams) -> AppResult<QueryResult> {
+        database_browser::execute_query_with_params(&self.path, query, params, self.timeout)
+    }
+}
+
+/// Where a browser call goes, resolved from a database id while the app
+/// database is locked. Connecting happens later, off the async runtime.
+pub enum ConnectorTarget {
+    Sqlite(String),
+    Profile {
+        profile: ConnectionProfile,
+        password: Option<String>,
+    },
+}
+
+impl ConnectorTarget {
+    pub fn connect(self, timeout: Duration) -> AppResult<Box<dyn DatabaseConnector>> {
+        match self {
+            ConnectorTarget::Sqlite(path) => Ok(Box::new(SqliteConnector::new(path, timeout))),
+            ConnectorTarget::Profile { profile, password } => match profile.kind {
+                ConnectorKind::Postgres => Ok(Box::new(PostgresConnector::connect(&profile, password.as_deref(), timeout)?)),
+            },
+        }
+    }
+}
+
+/// The profile id in a `profile:<id>` database id, or `None` for other ids.
+pub fn profile_id(database_id: &str) -> AppResult<Option<u32>> {
+    match database_id.strip_prefix(PROFILE_DATABASE_PREFIX) {
+        Some(id) => id
+            .parse()
+            .map(Some)
+            .map_err(|_| AppError::invalid_field("databaseId", format!("Invalid database id '{}'", database_id))),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,696 @@
+use crate::database_browser::{self, quote_identifier, QueryParams, ReadOnlyConnection};
+use crate::database_schema::{ColumnInfo, DatabaseSchema, IndexInfo, TableSchema, TriggerInfo, ViewSchema};
+use crate::database_value::{compare_cells, sql_literal, CellValue};
+use crate::error::AppResult;
+use rusqlite::{Rows, Statement};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+/// Diffs read both databases in full, so they get as long as exports.
+pub const DIFF_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Changed rows listed per table unless the caller asks for more. Counts
+/// and migration scripts always cover every row.
+pub const DEFAULT_DIFF_ROWS: usize = 100;
+
+// Name the old copy of a rebuilt table has while its rows are moved
+const REBUILD_SUFFIX: &str = "__diff_old";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationMode {
+    /// Schema changes only; rebuilt tables keep the rows of the columns
+    /// both versions share.
+    Schema,
+    /// Schema changes plus the inserts, updates and deletes that make
+    /// every table's rows match the target.
+    Full,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DiffOptions {
+    #[serde(rename = "includeData", default = "default_include_data")]
+    pub include_data: bool,
+    #[serde(rename = "maxRows")]
+    pub max_rows: Option<usize>,
+    /// Also produce a script that turns the first database into the second.
+    pub migration: Option<MigrationMode>,
+}
+
+fn default_include_data() -> bool {
+    true
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            include_data: true,
+            max_rows: None,
+            migration: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One schema object that differs, with both versions where they exist.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SchemaChange<T> {
+    pub name: String,
+    pub change: ChangeKind,
+    pub before: Option<T>,
+    pub after: Option<T>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RowChange {
+    pub change: ChangeKind,
+    /// Values of the key columns identifying the row.
+    pub key: Vec<CellValue>,
+    pub before: Option<Vec<CellValue>>,
+    pub after: Option<Vec<CellValue>>,
+    /// Columns whose values differ, for changed rows.
+    #[serde(rename = "changedColumns")]
+    pub changed_columns: Vec<String>,
+}
+
+/// Row differences of a table present in both databases, over the columns
+/// both versions have.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DataDiff {
+    /// Columns rows are matched on: the primary key, or `rowid` for tables
+    /// without one.
+    pub key: Vec<String>,
+    pub columns: Vec<String>,
+    pub added: u64,
+    pub removed: u64,
+    pub changed: u64,
+    pub rows: Vec<RowChange>,
+    /// More changes exist than are listed in `rows`.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TableDiff {
+    pub name: String,
+    /// `None` when only the rows differ.
+    pub change: Option<ChangeKind>,
+    pub columns: Vec<SchemaChange<ColumnInfo>>,
+    pub indexes: Vec<SchemaChange<IndexInfo>>,
+    pub triggers: Vec<SchemaChange<TriggerInfo>>,
+    #[serde(rename = "foreignKeysChanged")]
+    pub foreign_keys_changed: bool,
+    pub data: Option<DataDiff>,
+    /// Why rows were not compared, e.g. when the primary keys differ.
+    #[serde(rename = "dataSkipped")]
+    pub data_skipped: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DatabaseDiff {
+    /// Only tables that differ, by name.
+    pub tables: Vec<TableDiff>,
+    pub views: Vec<SchemaChange<ViewSchema>>,
+    pub migration: Option<String>,
+}
+
+/// Compares `from` with `to`: "added" objects and rows exist only in `to`.
+pub fn diff_databases(from_path: &str, to_path: &str, options: &DiffOptions, timeout: Duration) -> AppResult<DatabaseDiff> {
+    let from = ReadOnlyConnection::open(from_path, timeout)?;
+    let to = ReadOnlyConnection::open(to_path, timeout)?;
+    let from_schema = from.schema()?;
+    let to_schema = to.schema()?;
+
+    let max_rows = options.max_rows.unwrap_or(DEFAULT_DIFF_ROWS);
+    let full_migration = options.migration == Some(MigrationMode::Full);
+    let mut script = options.migration.map(|_| Migration::default());
+
+    let mut tables = Vec::new();
+    for name in object_names(from_schema.tables.iter().map(|t| &t.name), to_schema.tables.iter().map(|t| &t.name)) {
+        let before = from_schema.tables.iter().find(|t| t.name == name);
+        let after = to_schema.tables.iter().find(|t| t.name == name);
+
+        let mut diff = match (before, after) {
+            (Some(before), Some(after)) => compare_tables(before, after),
+            (Some(before), None) => table_change(before, ChangeKind::Removed),
+            (None, Some(after)) => table_change(after, ChangeKind::Added),
+            (None, None) => continue,
+        };
+
+        let mut row_statements = RowStatements::default();
+        if let (Some(before), Some(after)) = (before, after) {
+            if options.include_data || full_migration {
+                match row_key(&from, before, &to, after)? {
+                    Ok(key) => {
+                        // Rebuilt tables are refilled from `to` instead of patched row by row
+                        let patch = full_migration && !needs_rebuild(before, after);
+                        let statements = patch.then_some(&mut row_statements);
+                        diff.data = Some(compare_rows(&from, &to, before, after, &key, max_rows, statements)?);
+                    }
+                    Err(reason) => diff.data_skipped = Some(reason),
+                }
+            }
+        }
+
+        if let Some(script) = script.as_mut() {
+            script.table(&to, before, after, full_migration, row_statements)?;
+        }
+        let data_differs = diff.data.as_ref().is_some_and(|d| d.added + d.removed + d.changed > 0);
+        if diff.change.is_some() || data_differs {
+            tables.push(diff);
+        }
+    }
+
+    let views = compare_views(&from_schema, &to_schema);
+    if let Some(script) = script.as_mut() {
+        script.views(&views);
+    }
+
+    Ok(DatabaseDiff {
+        tables,
+        views,
+        migration: script.map(Migration::finish),
+    })
+}
+
+/// Names present in either list, sorted and without duplicates.
+fn object_names<'a>(a: impl Iterator<Item = &'a String>, b: impl Iterator<Item = &'a String>) -> BTreeSet<String> {
+    a.chain(b).cloned().collect()
+}
+
+fn table_change(table: &TableSchema, change: ChangeKind) -> TableDiff {
+    TableDiff {
+        name: table.name.clone(),
+        change: Some(change),
+        columns: Vec::new(),
+        indexes: Vec::new(),
+        triggers: Vec::new(),
+        foreign_keys_changed: false,
+        data: None,
+        data_skipped: None,
+    }
+}
+
+fn compare_tables(before: &TableSchema, after: &TableSchema) -> TableDiff {
+    let columns = compare_named(&before.columns, &after.columns, |c| &c.name);
+    // Automatic indexes follow from constraints, which show up as column or table SQL changes
+    let explicit = |indexes: &[IndexInfo]| indexes.iter().filter(|i| i.sql.is_some()).cloned().collect::<Vec<_>>();
+    let indexes = compare_named(&explicit(&before.indexes), &explicit(&after.indexes), |i| &i.name);
+    let triggers = compare_named(&before.triggers, &after.triggers, |t| &t.name);
+    let foreign_keys_changed = before.foreign_keys != after.foreign_keys;
+
+    let changed = !columns.is_empty()
+        || !indexes.is_empty()
+        || !triggers.is_empty()
+        || foreign_keys_changed
+        || needs_rebuild(before, after);
+
+    TableDiff {
+        name: after.name.clone(),
+        change: changed.then_some(ChangeKind::Changed),
+        columns,
+        indexes,
+        triggers,
+        foreign_keys_changed,
+        data: None,
+        data_skipped: None,
+    }
+}
+
+fn compare_named<T: Clone + PartialEq>(before: &[T], after: &[T], name: impl Fn(&T) -> &String) -> Vec<SchemaChange<T>> {
+    let names = object_names(before.iter().map(&name), after.iter().map(&name));
+    names
+        .into_iter()
+        .filter_map(|n| {
+            let old = before.iter().find(|item| *name(item) == n);
+            let new = after.iter().find(|item| *name(item) == n);
+            let change = match (old, new) {
+                (Some(old), Some(new)) if old == new => return None,
+                (Some(_), Some(_)) => ChangeKind::Changed,
+                (Some(_), None) => ChangeKind::Removed,
+                (None, _) => ChangeKind::Added,
+            };
+            Some(SchemaChange {
+                name: n,
+                change,
+                before: old.cloned(),
+                after: new.cloned(),
+            })
+        })
+        .collect()
+}
+
+fn compare_views(from: &DatabaseSchema, to: &DatabaseSchema) -> Vec<SchemaChange<ViewSchema>> {
+    compare_named(&from.views, &to.views, |v| &v.name)
+}
+
+/// Whether the `CREATE TABLE` statements differ beyond whitespace, which
+/// SQLite can only apply by rebuilding the table.
+fn needs_rebuild(before: &TableSchema, after: &TableSchema) -> bool {
+    let normalize = |sql: &Option<String>| sql.as_deref().unwrap_or_default().split_whitespace().collect::<Vec<_>>().join(" ");
+    normalize(&before.sql) != normalize(&after.sql)
+}
+
+enum RowKey {
+    Columns(Vec<String>),
+    Rowid,
+}
+
+impl RowKey {
+    fn names(&self) -> Vec<String> {
+        match self {
+            RowKey::Columns(columns) => columns.clone(),
+            RowKey::Rowid => vec!["rowid".to_string()],
+        }
+    }
+
+    fn expressions(&self) -> Vec<String> {
+        match self {
+            RowKey::Columns(columns) => columns.iter().map(|c| quote_identifier(c)).collect(),
+            RowKey::Rowid => vec!["rowid".to_string()],
+        }
+    }
+}
+
+fn primary_key(table: &TableSchema) -> Vec<String> {
+    let mut key: Vec<&ColumnInfo> = table.columns.iter().filter(|c| c.primary_key > 0).collect();
+    key.sort_by_key(|c| c.primary_key);
+    key.into_iter().map(|c| c.name.clone()).collect()
+}
+
+/// How rows of a table are matched, or why they cannot be.
+fn row_key(
+    from: &ReadOnlyConnection,
+    before: &TableSchema,
+    to: &ReadOnlyConnection,
+    after: &TableSchema,
+) -> AppResult<Result<RowKey, String>> {
+    let (old_key, new_key) = (primary_key(before), primary_key(after));
+    if old_key != new_key {
+        return Ok(Err(format!(
+            "Primary key changed from ({}) to ({})",
+            old_key.join(", "),
+            new_key.join(", ")
+        )));
+    }
+    if !old_key.is_empty() {
+        return Ok(Ok(RowKey::Columns(old_key)));
+    }
+    if from.has_rowid(&before.name)? && to.has_rowid(&after.name)? {
+        Ok(Ok(RowKey::Rowid))
+    } else {
+        Ok(Err("Table has no primary key to match rows on".to_string()))
+    }
+}
+
+/// `SELECT` reading a table's key and `columns` in key order.
+fn ordered_select(table: &str, key: &RowKey, columns: &[String]) -> String {
+    let keys = key.expressions();
+    let order: Vec<String> = keys.iter().map(|k| format!("{} COLLATE BINARY", k)).collect();
+    let select: Vec<String> = keys.into_iter().chain(columns.iter().map(|c| quote_identifier(c))).collect();
+    format!(
+        "SELECT {} FROM {} ORDER BY {}",
+        select.join(", "),
+        quote_identifier(table),
+        order.join(", ")
+    )
+}
+
+/// Rows of one side, split into key and values, with the current row held
+/// until the walk moves past it.
+struct SortedRows<'a> {
+    conn: &'a ReadOnlyConnection,
+    rows: Rows<'a>,
+    key_len: usize,
+    width: usize,
+    current: Option<(Vec<CellValue>, Vec<CellValue>)>,
+}
+
+impl<'a> SortedRows<'a> {
+    fn new(conn: &'a ReadOnlyConnection, stmt: &'a mut Statement<'_>, key_len: usize) -> AppResult<Self> {
+        let width = stmt.column_count();
+        let mut rows = SortedRows {
+            conn,
+            rows: stmt.raw_query(),
+            key_len,
+            width,
+            current: None,
+        };
+        rows.advance()?;
+        Ok(rows)
+    }
+
+    /// Takes the current row and reads the next one.
+    fn advance(&mut self) -> AppResult<Option<(Vec<CellValue>, Vec<CellValue>)>> {
+        let next = match self.rows.next().map_err(|e| self.conn.explain(e, "Failed to read rows"))? {
+            Some(row) => {
+                let mut cells = database_browser::read_row(row, self.width)
+                    .map_err(|e| self.conn.explain(e, "Failed to read rows"))?;
+                let values = cells.split_off(self.key_len);
+                Some((cells, values))
+            }
+            None => None,
+        };
+        Ok(std::mem::replace(&mut self.current, next))
+    }
+}
+
+fn compare_keys(a: &[CellValue], b: &[CellValue]) -> Ordering {
+    a.iter().zip(b).map(|(x, y)| compare_cells(x, y)).find(|o| o.is_ne()).unwrap_or(Ordering::Equal)
+}
+
+/// Row statements of a table patched in place, grouped so deletes run
+/// before inserts that may reuse their keys.
+#[derive(Default)]
+struct RowStatements {
+    deletes: Vec<String>,
+    updates: Vec<String>,
+    inserts: Vec<String>,
+}
+
+/// Walks both tables in key order side by side, so memory use does not
+/// depend on table size. Statements that patch the rows are collected into
+/// `statements` when given.
+fn compare_rows(
+    from: &ReadOnlyConnection,
+    to: &ReadOnlyConnection,
+    before: &TableSchema,
+    after: &TableSchema,
+    key: &RowKey,
+    max_rows: usize,
+    mut statements: Option<&mut RowStatements>,
+) -> AppResult<DataDiff> {
+    let columns: Vec<String> = before.columns
+        .iter()
+        .map(|c| c.name.clone())
+        .filter(|name| after.columns.iter().any(|c| &c.name == name))
+        .collect();
+
+    let mut diff = DataDiff {
+        key: key.names(),
+        columns: columns.clone(),
+        added: 0,
+        removed: 0,
+        changed: 0,
+        rows: Vec::new(),
+        truncated: false,
+    };
+
+    let key_len = diff.key.len();
+    let (mut old_stmt, _) = from.prepare(&ordered_select(&before.name, key, &columns), &QueryParams::default(), "Failed to read rows")?;
+    let (mut new_stmt, _) = to.prepare(&ordered_select(&after.name, key, &columns), &QueryParams::default(), "Failed to read rows")?;
+    let mut old_rows = SortedRows::new(from, &mut old_stmt, key_len)?;
+    let mut new_rows = SortedRows::new(to, &mut new_stmt, key_len)?;
+
+    loop {
+        let order = match (&old_rows.current, &new_rows.current) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some((old_key, _)), Some((new_key, _))) => compare_keys(old_key, new_key),
+        };
+
+        let change = match order {
+            Ordering::Less => {
+                let (key, values) = old_rows.advance()?.expect("row is present");
+                RowChange {
+                    change: ChangeKind::Removed,
+                    key,
+                    before: Some(values),
+                    after: None,
+                    changed_columns: Vec::new(),
+                }
+            }
+            Ordering::Greater => {
+                let (key, values) = new_rows.advance()?.expect("row is present");
+                RowChange {
+                    change: ChangeKind::Added,
+                    key,
+                    before: None,
+                    after: Some(values),
+                    changed_columns: Vec::new(),
+                }
+            }
+            Ordering::Equal => {
+                let (_, old_values) = old_rows.advance()?.expect("row is present");
+                let (key, new_values) = new_rows.advance()?.expect("row is present");
+                // 1 and 1.0 sort together but are different values
+                let changed_columns: Vec<String> = columns
+                    .iter()
+                    .zip(old_values.iter().zip(&new_values))
+                    .filter(|(_, (a, b))| a != b)
+                    .map(|(name, _)| name.clone())
+                    .collect();
+                if changed_columns.is_empty() {
+                    continue;
+                }
+                RowChange {
+                    change: ChangeKind::Changed,
+                    key,
+                    before: Some(old_values),
+                    after: Some(new_values),
+                    changed_columns,
+                }
+            }
+        };
+
+        match change.change {
+            ChangeKind::Added => diff.added += 1,
+            ChangeKind::Removed => diff.removed += 1,
+            ChangeKind::Changed => diff.changed += 1,
+        }
+        if let Some(statements) = statements.as_mut() {
+            statements.push(&after.name, key, &columns, &change);
+        }
+        if diff.rows.len() < max_rows {
+            diff.rows.push(change);
+        } else {
+            diff.truncated = true;
+        }
+    }
+    Ok(diff)
+}
+
+impl RowStatements {
+    fn push(&mut self, table: &str, key: &RowKey, columns: &[String], change: &RowChange) {
+        let table = quote_identifier(table);
+        let condition = || {
+            key.expressions()
+                .iter()
+                .zip(&change.key)
+                .map(|(k, v)| format!("{} IS {}", k, sql_literal(v)))
+                .collect::<Vec<_>>()
+                .join(" AND ")
+        };
+
+        match (change.change, &change.after) {
+            (ChangeKind::Removed, _) => self.deletes.push(format!("DELETE FROM {} WHERE {};", table, condition())),
+            (ChangeKind::Added, Some(values)) => {
+                // Rowid tables keep their rowids so later diffs line up
+                let (mut names, mut literals) = match key {
+                    RowKey::Rowid => (vec!["rowid".to_string()], vec![sql_literal(&change.key[0])]),
+                    RowKey::Columns(_) => (Vec::new(), Vec::new()),
+                };
+                names.extend(columns.iter().map(|c| quote_identifier(c)));
+                literals.extend(values.iter().map(sql_literal));
+                self.inserts.push(format!(
+                    "INSERT INTO {} ({}) VALUES ({});",
+                    table,
+                    names.join(", "),
+                    literals.join(", ")
+                ));
+            }
+            (ChangeKind::Changed, Some(values)) => {
+                let assignments: Vec<String> = columns
+                    .iter()
+                    .zip(values)
+                    .filter(|(name, _)| change.changed_columns.contains(name))
+                    .map(|(name, value)| format!("{} = {}", quote_identifier(name), sql_literal(value)))
+                    .collect();
+                self.updates.push(format!("UPDATE {} SET {} WHERE {};", table, assignments.join(", "), condition()));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Script turning the first database into the second, built in three
+/// phases: drops, table changes, then indexes, triggers and views, so each
+/// object is created once everything it refers to exists.
+#[derive(Default)]
+struct Migration {
+    drops: Vec<String>,
+    tables: Vec<String>,
+    creates: Vec<String>,
+}
+
+impl Migration {
+    fn table(
+        &mut self,
+        to: &ReadOnlyConnection,
+        before: Option<&TableSchema>,
+        after: Option<&TableSchema>,
+        full: bool,
+        rows: RowStatements,
+    ) -> AppResult<()> {
+        match (before, after) {
+            (Some(before), None) => {
+                self.tables.push(format!("DROP TABLE {};", quote_identifier(&before.name)));
+            }
+            (None, Some(after)) => {
+                self.create_table(after);
+                if full {
+                    self.copy_rows(to, after)?;
+                }
+                self.create_dependents(after);
+            }
+            (Some(before), Some(after)) if needs_rebuild(before, after) => {
+                // The old copy takes its indexes and triggers with it when dropped
+                let old_name = format!("{}{}", after.name, REBUILD_SUFFIX);
+                self.tables.push(format!(
+                    "ALTER TABLE {} RENAME TO {};",
+                    quote_identifier(&before.name),
+                    quote_identifier(&old_name)
+                ));
+                self.create_table(after);
+                if full {
+                    self.copy_rows(to, after)?;
+                } else {
+                    let common: Vec<String> = after.columns
+                        .iter()
+                        .filter(|c| before.columns.iter().any(|b| b.name == c.name))
+                        .map(|c| quote_identifier(&c.name))
+                        .collect();
+                    if !common.is_empty() {
+                        self.tables.push(format!(
+                            "INSERT INTO {} ({cols}) SELECT {cols} FROM {};",
+                            quote_identifier(&after.name),
+                            quote_identifier(&old_name),
+                            cols = common.join(", ")
+                        ));
+                    }
+                }
+                self.tables.push(format!("DROP TABLE {};", quote_identifier(&old_name)));
+                self.create_dependents(after);
+            }
+            (Some(before), Some(after)) => {
+                let explicit = |t: &TableSchema| t.indexes.iter().filter(|i| i.sql.is_some()).cloned().collect::<Vec<_>>();
+                for index in compare_named(&explicit(before), &explicit(after), |i| &i.name) {
+                    if index.before.is_some() {
+                        self.drops.push(format!("DROP INDEX {};", quote_identifier(&index.name)));
+                    }
+                    if let Some(sql) = index.after.and_then(|i| i.sql) {
+                        self.creates.push(format!("{};", sql));
+                    }
+                }
+                // Triggers would fire again for rows the script already patches,
+                // so in full mode every trigger is recreated after the data
+                let triggers = if full {
+                    let removed = before.triggers.iter().map(|t| SchemaChange {
+                        name: t.name.clone(),
+                        change: ChangeKind::Removed,
+                        before: Some(t.clone()),
+                        after: None,
+                    });
+                    let added = after.triggers.iter().map(|t| SchemaChange {
+                        name: t.name.clone(),
+                        change: ChangeKind::Added,
+                        before: None,
+                        after: Some(t.clone()),
+                    });
+                    removed.chain(added).collect()
+                } else {
+                    compare_named(&before.triggers, &after.triggers, |t| &t.name)
+                };
+                for trigger in triggers {
+                    if trigger.before.is_some() {
+                        self.drops.push(format!("DROP TRIGGER {};", quote_identifier(&trigger.name)));
+                    }
+                    if let Some(sql) = trigger.after.and_then(|t| t.sql) {
+                        self.creates.push(format!("{};", sql));
+                    }
+                }
+                self.tables.extend(rows.deletes);
+                self.tables.extend(rows.updates);
+                self.tables.extend(rows.inserts);
+            }
+            (None, None) => {}
+        }
+        Ok(())
+    }
+
+    fn create_table(&mut self, table: &TableSchema) {
+        if let Some(sql) = &table.sql {
+            self.tables.push(format!("{};", sql));
+        }
+    }
+
+    fn create_dependents(&mut self, table: &TableSchema) {
+        let indexes = table.indexes.iter().filter_map(|i| i.sql.as_ref());
+        let triggers = table.triggers.iter().filter_map(|t| t.sql.as_ref());
+        self.creates.extend(indexes.chain(triggers).map(|sql| format!("{};", sql)));
+    }
+
+    /// Inserts every row the table has in `to`.
+    fn copy_rows(&mut self, to: &ReadOnlyConnection, table: &TableSchema) -> AppResult<()> {
+        let primary_key = primary_key(table);
+        let key = if primary_key.is_empty() { RowKey::Rowid } else { RowKey::Columns(primary_key) };
+        let columns: Vec<String> = table.columns.iter().map(|c| c.name.clone()).collect();
+        let (mut stmt, _) = to.prepare(&ordered_select(&table.name, &key, &columns), &QueryParams::default(), "Failed to read rows")?;
+        let key_len = key.names().len();
+        let mut rows = SortedRows::new(to, &mut stmt, key_len)?;
+        let mut statements = RowStatements::default();
+        while let Some((key_values, values)) = rows.advance()? {
+            let change = RowChange {
+                change: ChangeKind::Added,
+                key: key_values,
+                before: None,
+                after: Some(values),
+                changed_columns: Vec::new(),
+            };
+            statements.push(&table.name, &key, &columns, &change);
+        }
+        self.tables.extend(statements.inserts);
+        Ok(())
+    }
+
+    fn views(&mut self, views: &[SchemaChange<ViewSchema>]) {
+        for view in views {
+            // Dropping a view also drops its INSTEAD OF triggers
+            if view.before.is_some() {
+                self.drops.insert(0, format!("DROP VIEW {};", quote_identifier(&view.name)));
+            }
+            if let Some(after) = &view.after {
+                if let Some(sql) = &after.sql {
+                    self.creates.push(format!("{};", sql));
+                }
+                self.creates.extend(after.triggers.iter().filter_map(|t| t.sql.as_ref()).map(|sql| format!("{};", sql)));
+            }
+        }
+    }
+
+    fn finish(self) -> String {
+        // Foreign keys stay off while tables are rebuilt, and legacy renames
+        // leave references to a rebuilt table pointing at its new version
+        let mut lines = vec![
+            "PRAGMA foreign_keys = OFF;".to_string(),
+            "PRAGMA legacy_alter_table = ON;".to_string(),
+            "BEGIN;".to_string(),
+        ];
+        lines.extend(self.drops);
+        lines.extend(self.tables);
+        lines.extend(self.creates);
+        lines.push("COMMIT;".to_string());
+        lines.push("PRAGMA legacy_alter_table = OFF;".to_string());
+        lines.push("PRAGMA foreign_keys = ON;".to_string());
+        lines.join("\n") + "\n"
+    }
+}
@@ -0,0 +1,176 @@
+use crate::error::{AppError, AppResult, Context};
+use crate::model::Project;
+use crate::repository::ProjectRepository;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+/// Id of the active workspace's own database.
+pub const WORKSPACE_DATABASE_ID: &str = "workspace";
+
+/// First 16 bytes of every SQLite 3 database file.
+const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+const SQLITE_EXTENSIONS: [&str; 3] = ["db", "sqlite", "sqlite3"];
+
+// Dependency and build output directories never hold a project's own data
+const SKIPPED_DIRS: [&str; 7] = ["node_modules", "target", "dist", "build", "venv", "__pycache__", "vendor"];
+const MAX_SCAN_DEPTH: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DiscoveredDatabase {
+    /// `<project id>:<path relative to the project>`, stable across scans.
+    pub id: String,
+    #[serde(rename = "projectId")]
+    pub project_id: u32,
+    pub path: String,
+    #[serde(rename = "relativePath")]
+    pub relative_path: String,
+    #[serde(rename = "sizeBytes")]
+    pub size_bytes: u64,
+    #[serde(rename = "modifiedAt")]
+    pub modified_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectDatabases {
+    #[serde(rename = "projectId")]
+    pub project_id: u32,
+    #[serde(rename = "projectName")]
+    pub project_name: String,
+    pub databases: Vec<DiscoveredDatabase>,
+}
+
+/// True when the file starts with the SQLite header, whatever its name.
+pub fn is_sqlite_file(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map(|_| &header == SQLITE_MAGIC)
+        .unwrap_or(false)
+}
+
+/// Scans registered projects for SQLite files, either all of them or just `project_id`.
+pub fn discover_databases<R: ProjectRepository>(repo: &R, project_id: Option<u32>) -> AppResult<Vec<ProjectDatabases>> {
+    Ok(scan_projects(&projects_to_scan(repo, project_id)?))
+}
+
+/// The one project asked for, or all of them.
+pub fn projects_to_scan<R: ProjectRepository>(repo: &R, project_id: Option<u32>) -> AppResult<Vec<Project>> {
+    match project_id {
+        Some(id) => Ok(vec![repo.get_project_by_id(id)
+            .context("Failed to get project")?
+            .ok_or_else(|| AppError::not_found("Project", id))?]),
+        None => repo.get_all_projects().context("Failed to get projects"),
+    }
+}
+
+/// Scans the directories of `projects`, without touching the repository.
+pub fn scan_projects(projects: &[Project]) -> Vec<ProjectDatabases> {
+    projects
+        .iter()
+        .map(|project| ProjectDatabases {
+            project_id: project.id,
+            project_name: project.name.clone(),
+            databases: scan_project(project),
+        })
+        .collect()
+}
+
+/// Finds SQLite files under a project's directory. Unreadable directories
+/// are skipped rather than failing the whole scan.
+pub fn scan_project(project: &Project) -> Vec<DiscoveredDatabase> {
+    let root = Path::new(&project.path);
+    let mut files = Vec::new();
+    scan_dir(root, 0, &mut files);
+    files.sort();
+
+    files
+        .into_iter()
+        .filter_map(|path| describe(project.id, root, path))
+        .collect()
+}
+
+fn scan_dir(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    if depth > MAX_SCAN_DEPTH {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        // DirEntry::file_type does not follow symlinks, so links are never scanned
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if file_type.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                scan_dir(&path, depth + 1, files);
+            }
+        } else if file_type.is_file() && has_sqlite_extension(&path) && is_sqlite_file(&path) {
+            files.push(path);
+        }
+    }
+}
+
+fn has_sqlite_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| SQLITE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+fn describe(project_id: u32, root: &Path, path: PathBuf) -> Option<DiscoveredDatabase> {
+    let relative_path = path.strip_prefix(root).ok()?.to_string_lossy().to_string();
+    let metadata = fs::metadata(&path).ok()?;
+
+    Some(DiscoveredDatabase {
+        id: format!("{}:{}", project_id, relative_path),
+        project_id,
+        path: path.to_string_lossy().to_string(),
+        relative_path,
+        size_bytes: metadata.len(),
+        modified_at: metadata.modified().ok().map(|t| DateTime::<Utc>::from(t).to_rfc3339()),
+    })
+}
+
+/// Maps a database id back to its file. Only SQLite files inside a
+/// registered project resolve; `workspace` resolves to `workspace_db`.
+pub fn resolve_database<R: ProjectRepository>(repo: &R, workspace_db: &Path, id: &str) -> AppResult<PathBuf> {
+    if id == WORKSPACE_DATABASE_ID {
+        return Ok(workspace_db.to_path_buf());
+    }
+
+    let (project_id, relative_path) = id
+        .split_once(':')
+        .and_then(|(project_id, relative)| Some((project_id.parse::<u32>().ok()?, Path::new(relative))))
+        .ok_or_else(|| AppError::invalid_field("databaseId", format!("Invalid database id '{}'", id)))?;
+
+    // Ids never contain `..`, absolute paths or prefixes
+    if relative_path.as_os_str().is_empty() || !relative_path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(AppError::invalid_field("databaseId", format!("Invalid database id '{}'", id)));
+    }
+
+    let project = repo.get_project_by_id(project_id)
+        .context("Failed to get project")?
+        .ok_or_else(|| AppError::not_found("Project", project_id))?;
+
+    let root = Path::new(&project.path);
+    let path = root.join(relative_path);
+
+    // A symlinked directory could still point outside the project
+    let inside_project = match (path.canonicalize(), root.canonicalize()) {
+        (Ok(path), Ok(root)) => path.starts_with(root),
+        _ => false,
+    };
+    if !inside_project || !is_sqlite_file(&path) {
+        return Err(AppError::not_found("Database", id));
+    }
+
+    Ok(path)
+}
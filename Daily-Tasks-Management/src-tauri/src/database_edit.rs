@@ -0,0 +1,330 @@
+use crate::database_browser::{quote_identifier, QueryParams, QueryResult, ReadOnlyConnection};
+use crate::database_schema::{self, TableSchema};
+use crate::database_value::{sql_literal, CellValue};
+use crate::error::{AppError, AppResult, IntoAppError};
+use rusqlite::{params_from_iter, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long a changeset waits for other connections to release the file.
+pub const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Key column for tables matched by rowid rather than their primary key.
+pub const ROWID_KEY: &str = "rowid";
+
+/// One queued row change. Rows are identified by `key`, which holds either
+/// every primary key column or just `rowid`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum RowEdit {
+    Insert {
+        table: String,
+        values: BTreeMap<String, CellValue>,
+    },
+    Update {
+        table: String,
+        key: BTreeMap<String, CellValue>,
+        values: BTreeMap<String, CellValue>,
+    },
+    Delete {
+        table: String,
+        key: BTreeMap<String, CellValue>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ChangesetSummary {
+    pub statements: usize,
+    #[serde(rename = "rowsAffected")]
+    pub rows_affected: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct WriteMode {
+    pub enabled: bool,
+    /// The database belongs to the app itself and needs an explicit override.
+    #[serde(rename = "appDatabase")]
+    pub app_database: bool,
+}
+
+/// A page of table rows with the key that identifies each of them.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EditableRows {
+    /// Key columns: the primary key, or `rowid` for tables without one.
+    pub key: Vec<String>,
+    /// Key values of each row in `data`, in the same order.
+    pub keys: Vec<Vec<CellValue>>,
+    pub data: QueryResult,
+}
+
+/// Databases the browser may write to, by resolved path. Nothing is
+/// writable until switched on, and the setting lasts until the app exits.
+#[derive(Default)]
+pub struct WriteModes {
+    enabled: Mutex<HashSet<PathBuf>>,
+}
+
+impl WriteModes {
+    pub fn new() -> Self {
+        WriteModes::default()
+    }
+
+    pub fn set(&self, db_path: &Path, enabled: bool) -> AppResult<()> {
+        let mut modes = self.enabled.lock()?;
+        if enabled {
+            modes.insert(db_path.to_path_buf());
+        } else {
+            modes.remove(db_path);
+        }
+        Ok(())
+    }
+
+    pub fn is_enabled(&self, db_path: &Path) -> AppResult<bool> {
+        Ok(self.enabled.lock()?.contains(db_path))
+    }
+}
+
+/// Whether a database lives in the app's data directory, i.e. is a
+/// workspace's `tasks.db` or one of its snapshots.
+pub fn is_app_database(db_path: &Path, data_dir: &Path) -> bool {
+    match (db_path.canonicalize(), data_dir.canonicalize()) {
+        (Ok(path), Ok(dir)) => path.starts_with(dir),
+        _ => false,
+    }
+}
+
+/// A planned statement with its values bound, plus the same statement with
+/// the values written out for the preview.
+struct EditStatement {
+    sql: String,
+    preview: String,
+    params: Vec<CellValue>,
+    // Updates and deletes must hit exactly one row
+    single_row: bool,
+}
+
+impl EditStatement {
+    fn new(single_row: bool) -> Self {
+        EditStatement {
+            sql: String::new(),
+            preview: String::new(),
+            params: Vec::new(),
+            single_row,
+        }
+    }
+
+    fn push_sql(&mut self, text: &str) {
+        self.sql.push_str(text);
+        self.preview.push_str(text);
+    }
+
+    fn push_value(&mut self, value: &CellValue) {
+        self.sql.push('?');
+        self.preview.push_str(&sql_literal(value));
+        self.params.push(value.clone());
+    }
+
+    fn push_condition(&mut self, key: &BTreeMap<String, CellValue>) {
+        self.push_sql(" WHERE ");
+        for (i, (column, value)) in key.iter().enumerate() {
+            if i > 0 {
+                self.push_sql(" AND ");
+            }
+            let column = if column == ROWID_KEY { ROWID_KEY.to_string() } else { quote_identifier(column) };
+            self.push_sql(&format!("{} IS ", column));
+            self.push_value(value);
+        }
+    }
+}
+
+/// The changeset as SQL, one statement per change, without touching the
+/// database. Fails on the same validation errors `apply_changes` would.
+pub fn preview_changes(db_path: &str, edits: &[RowEdit]) -> AppResult<Vec<String>> {
+    let conn = open(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    Ok(plan(&conn, edits)?.into_iter().map(|s| s.preview + ";").collect())
+}
+
+/// Runs every change in one transaction. Any failure, including an update
+/// or delete whose row no longer exists, rolls the whole changeset back.
+pub fn apply_changes(db_path: &str, edits: &[RowEdit], timeout: Duration) -> AppResult<ChangesetSummary> {
+    let mut conn = open(db_path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+    conn.busy_timeout(timeout)?;
+    let statements = plan(&conn, edits)?;
+
+    let tx = conn.transaction().map_err(|e| e.into_app_error("Failed to start transaction"))?;
+    let mut rows_affected = 0;
+    for (i, statement) in statements.iter().enumerate() {
+        let changed = tx
+            .execute(&statement.sql, params_from_iter(&statement.params))
+            .map_err(|e| e.into_app_error(format!("Change {} failed: {}", i + 1, statement.preview)))?;
+        if statement.single_row && changed != 1 {
+            return Err(AppError::conflict(format!(
+                "Change {} matched {} rows instead of one: {}",
+                i + 1,
+                changed,
+                statement.preview
+            )));
+        }
+        rows_affected += changed;
+    }
+    tx.commit().map_err(|e| e.into_app_error("Failed to commit changes"))?;
+
+    Ok(ChangesetSummary {
+        statements: statements.len(),
+        rows_affected,
+    })
+}
+
+/// Like `get_table_data`, plus the key of every row so it can be edited.
+pub fn get_editable_rows(db_path: &str, table: &str, limit: u32, offset: u32, timeout: Duration) -> AppResult<EditableRows> {
+    let conn = ReadOnlyConnection::open(db_path, timeout)?;
+    let schema = conn.schema()?;
+    let table = schema.tables.iter()
+        .find(|t| t.name == table)
+        .ok_or_else(|| AppError::not_found("Table", table))?;
+
+    let mut primary_key: Vec<_> = table.columns.iter().filter(|c| c.primary_key > 0).collect();
+    primary_key.sort_by_key(|c| c.primary_key);
+    // Without a primary key the rowid is read as an extra leading column
+    let select = if primary_key.is_empty() { "rowid, *" } else { "*" };
+    let query = format!("SELECT {} FROM {} LIMIT ?1 OFFSET ?2", select, quote_identifier(&table.name));
+    let params = QueryParams {
+        positional: vec![CellValue::Integer(limit.into()), CellValue::Integer(offset.into())],
+        ..QueryParams::default()
+    };
+    let mut data = conn.query(&query, &params, "Failed to read table")?;
+
+    if primary_key.is_empty() {
+        data.columns.remove(0);
+        let keys = data.rows.iter_mut().map(|row| vec![row.remove(0)]).collect();
+        return Ok(EditableRows {
+            key: vec![ROWID_KEY.to_string()],
+            keys,
+            data,
+        });
+    }
+
+    let positions: Vec<usize> = primary_key
+        .iter()
+        .filter_map(|pk| table.columns.iter().position(|c| c.name == pk.name))
+        .collect();
+    let keys = data.rows.iter().map(|row| positions.iter().map(|&i| row[i].clone()).collect()).collect();
+    Ok(EditableRows {
+        key: primary_key.into_iter().map(|c| c.name.clone()).collect(),
+        keys,
+        data,
+    })
+}
+
+fn open(db_path: &str, flags: OpenFlags) -> AppResult<Connection> {
+    if !Path::new(db_path).is_file() {
+        return Err(AppError::not_found("Database file", db_path));
+    }
+    Connection::open_with_flags(db_path, flags | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map_err(|e| e.into_app_error(format!("Failed to open database {}", db_path)))
+}
+
+fn plan(conn: &Connection, edits: &[RowEdit]) -> AppResult<Vec<EditStatement>> {
+    if edits.is_empty() {
+        return Err(AppError::invalid_field("changes", "No changes to apply"));
+    }
+    let schema = database_schema::introspect(conn).map_err(|e| e.into_app_error("Failed to read schema"))?;
+    let table = |name: &str| {
+        schema.tables.iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| AppError::not_found("Table", name))
+    };
+
+    edits
+        .iter()
+        .map(|edit| match edit {
+            RowEdit::Insert { table: name, values } => {
+                let table = table(name)?;
+                check_columns(table, values)?;
+                let mut statement = EditStatement::new(false);
+                statement.push_sql(&format!("INSERT INTO {}", quote_identifier(name)));
+                if values.is_empty() {
+                    statement.push_sql(" DEFAULT VALUES");
+                    return Ok(statement);
+                }
+                // Columns go in table order rather than by name
+                let columns: Vec<_> = table.columns.iter().filter(|c| values.contains_key(&c.name)).collect();
+                let names: Vec<String> = columns.iter().map(|c| quote_identifier(&c.name)).collect();
+                statement.push_sql(&format!(" ({}) VALUES (", names.join(", ")));
+                for (i, column) in columns.iter().enumerate() {
+                    if i > 0 {
+                        statement.push_sql(", ");
+                    }
+                    statement.push_value(&values[&column.name]);
+                }
+                statement.push_sql(")");
+                Ok(statement)
+            }
+            RowEdit::Update { table: name, key, values } => {
+                let table = table(name)?;
+                check_key(conn, table, key)?;
+                check_columns(table, values)?;
+                if values.is_empty() {
+                    return Err(AppError::invalid_field("values", "An update needs at least one value"));
+                }
+                let mut statement = EditStatement::new(true);
+                statement.push_sql(&format!("UPDATE {} SET ", quote_identifier(name)));
+                let columns: Vec<_> = table.columns.iter().filter(|c| values.contains_key(&c.name)).collect();
+                for (i, column) in columns.iter().enumerate() {
+                    if i > 0 {
+                        statement.push_sql(", ");
+                    }
+                    statement.push_sql(&format!("{} = ", quote_identifier(&column.name)));
+                    statement.push_value(&values[&column.name]);
+                }
+                statement.push_condition(key);
+                Ok(statement)
+            }
+            RowEdit::Delete { table: name, key } => {
+                let table = table(name)?;
+                check_key(conn, table, key)?;
+                let mut statement = EditStatement::new(true);
+                statement.push_sql(&format!("DELETE FROM {}", quote_identifier(name)));
+                statement.push_condition(key);
+                Ok(statement)
+            }
+        })
+        .collect()
+}
+
+fn check_columns(table: &TableSchema, values: &BTreeMap<String, CellValue>) -> AppResult<()> {
+    match values.keys().find(|name| !table.columns.iter().any(|c| &&c.name == name)) {
+        Some(name) => Err(AppError::invalid_field(
+            "values",
+            format!("Table {} has no column {}", table.name, name),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// A key is the full primary key, or `rowid` for tables that have one.
+fn check_key(conn: &Connection, table: &TableSchema, key: &BTreeMap<String, CellValue>) -> AppResult<()> {
+    let primary_key: Vec<&str> = table.columns.iter().filter(|c| c.primary_key > 0).map(|c| c.name.as_str()).collect();
+    let given: Vec<&str> = key.keys().map(String::as_str).collect();
+
+    if given == [ROWID_KEY] && has_rowid(conn, &table.name)? {
+        return Ok(());
+    }
+    if !primary_key.is_empty() && given.len() == primary_key.len() && given.iter().all(|c| primary_key.contains(c)) {
+        return Ok(());
+    }
+
+    let expected = if primary_key.is_empty() { ROWID_KEY.to_string() } else { primary_key.join(", ") };
+    Err(AppError::invalid_field(
+        "key",
+        format!("Rows of {} are identified by {}", table.name, expected),
+    ))
+}
+
+fn has_rowid(conn: &Connection, table: &str) -> AppResult<bool> {
+    conn.query_row("SELECT NOT wr FROM pragma_table_list WHERE schema = 'main' AND name = ?1", [table], |row| row.get(0))
+        .map_err(|e| e.into_app_error("Failed to read table list"))
+}
@@ -0,0 +1,261 @@
+use crate::database_browser::{self, quote_identifier, QueryParams, ResultColumn};
+use crate::database_value::{hex, sql_literal, CellValue};
+use crate::error::{AppError, AppResult};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// Exports read far more rows than the browser shows, so they get longer.
+pub const EXPORT_TIMEOUT: Duration = Duration::from_secs(300);
+
+// Table name used in INSERT statements when exporting a query
+const QUERY_EXPORT_TABLE: &str = "query_result";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Ndjson,
+    Sql,
+    Markdown,
+}
+
+/// What to export: a whole table or the result of a read-only query.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ExportSource {
+    Table { name: String },
+    Query {
+        sql: String,
+        #[serde(default)]
+        params: QueryParams,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportSummary {
+    pub path: String,
+    pub format: ExportFormat,
+    #[serde(rename = "rowCount")]
+    pub row_count: u64,
+    #[serde(rename = "sizeBytes")]
+    pub size_bytes: u64,
+}
+
+/// Streams `source` into `writer` one row at a time. Returns the row count.
+pub fn export_to_writer<W: Write>(
+    db_path: &str,
+    source: &ExportSource,
+    format: ExportFormat,
+    writer: W,
+    timeout: Duration,
+) -> AppResult<u64> {
+    let no_params = QueryParams::default();
+    let (query, params, table) = match source {
+        ExportSource::Table { name } => (format!("SELECT * FROM {}", quote_identifier(name)), &no_params, name.as_str()),
+        ExportSource::Query { sql, params } => (sql.clone(), params, QUERY_EXPORT_TABLE),
+    };
+
+    // Both callbacks write through the same exporter
+    let exporter = RefCell::new(Exporter::new(format, writer, table));
+    let count = database_browser::stream_query(
+        db_path,
+        &query,
+        params,
+        timeout,
+        |columns| exporter.borrow_mut().begin(columns).map_err(write_error),
+        |cells| exporter.borrow_mut().row(cells).map_err(write_error),
+    )?;
+    exporter.into_inner().finish().map_err(write_error)?;
+    Ok(count)
+}
+
+/// Exports to `output_path`. The file is written next to its destination
+/// and only renamed into place once complete, so a failed export never
+/// leaves a truncated file behind.
+pub fn export_to_file(
+    db_path: &str,
+    source: &ExportSource,
+    format: ExportFormat,
+    output_path: &str,
+    timeout: Duration,
+) -> AppResult<ExportSummary> {
+    let target = Path::new(output_path);
+    if !target.is_absolute() {
+        return Err(AppError::invalid_path("outputPath", target, "Export path must be absolute"));
+    }
+    match target.parent() {
+        Some(parent) if parent.is_dir() => {}
+        _ => return Err(AppError::invalid_path("outputPath", target, "Export directory does not exist")),
+    }
+
+    let partial = target.with_file_name(format!(
+        "{}.partial",
+        target.file_name().map(|n| n.to_string_lossy()).unwrap_or_default()
+    ));
+    let file = File::create(&partial)
+        .map_err(|e| AppError::io("Failed to create export file", &partial, e))?;
+
+    let row_count = match export_to_writer(db_path, source, format, BufWriter::new(file), timeout) {
+        Ok(count) => count,
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+    };
+
+    fs::rename(&partial, target)
+        .map_err(|e| AppError::io("Failed to move export into place", target, e))?;
+    let size_bytes = fs::metadata(target)
+        .map_err(|e| AppError::io("Failed to read export file", target, e))?
+        .len();
+
+    Ok(ExportSummary {
+        path: output_path.to_string(),
+        format,
+        row_count,
+        size_bytes,
+    })
+}
+
+fn write_error(e: io::Error) -> AppError {
+    AppError::Io {
+        context: "Failed to write export".to_string(),
+        path: None,
+        source: e,
+    }
+}
+
+struct Exporter<'a, W: Write> {
+    format: ExportFormat,
+    out: W,
+    table: &'a str,
+    columns: Vec<String>,
+    rows: u64,
+}
+
+impl<'a, W: Write> Exporter<'a, W> {
+    fn new(format: ExportFormat, out: W, table: &'a str) -> Self {
+        Exporter {
+            format,
+            out,
+            table,
+            columns: Vec::new(),
+            rows: 0,
+        }
+    }
+
+    fn begin(&mut self, columns: &[ResultColumn]) -> io::Result<()> {
+        self.columns = columns.iter().map(|c| c.name.clone()).collect();
+
+        match self.format {
+            ExportFormat::Csv => {
+                let header: Vec<String> = self.columns.iter().map(|c| csv_field(c)).collect();
+                write!(self.out, "{}\r\n", header.join(","))
+            }
+            ExportFormat::Json => write!(self.out, "["),
+            ExportFormat::Markdown => {
+                let header: Vec<String> = self.columns.iter().map(|c| markdown_text(c)).collect();
+                writeln!(self.out, "| {} |", header.join(" | "))?;
+                writeln!(self.out, "|{}", " --- |".repeat(self.columns.len()))
+            }
+            ExportFormat::Ndjson | ExportFormat::Sql => Ok(()),
+        }
+    }
+
+    fn row(&mut self, cells: &[CellValue]) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Csv => {
+                let fields: Vec<String> = cells.iter().map(|c| csv_field(&plain_text(c))).collect();
+                write!(self.out, "{}\r\n", fields.join(","))?;
+            }
+            ExportFormat::Json => {
+                let separator = if self.rows == 0 { "\n  " } else { ",\n  " };
+                write!(self.out, "{}{}", separator, json_object(&self.columns, cells)?)?;
+            }
+            ExportFormat::Ndjson => writeln!(self.out, "{}", json_object(&self.columns, cells)?)?,
+            ExportFormat::Sql => {
+                let columns: Vec<String> = self.columns.iter().map(|c| quote_identifier(c)).collect();
+                let values: Vec<String> = cells.iter().map(sql_literal).collect();
+                writeln!(
+                    self.out,
+                    "INSERT INTO {} ({}) VALUES ({});",
+                    quote_identifier(self.table),
+                    columns.join(", "),
+                    values.join(", ")
+                )?;
+            }
+            ExportFormat::Markdown => {
+                let fields: Vec<String> = cells.iter().map(markdown_cell).collect();
+                writeln!(self.out, "| {} |", fields.join(" | "))?;
+            }
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.format == ExportFormat::Json {
+            write!(self.out, "{}", if self.rows == 0 { "]\n" } else { "\n]\n" })?;
+        }
+        self.out.flush()
+    }
+}
+
+/// Text form used by CSV: NULL is an empty field, blobs are lowercase hex.
+fn plain_text(cell: &CellValue) -> String {
+    match cell {
+        CellValue::Null => String::new(),
+        CellValue::Integer(v) => v.to_string(),
+        CellValue::Real(v) => v.to_string(),
+        CellValue::Text(v) => v.clone(),
+        CellValue::Blob(bytes) => hex(bytes),
+    }
+}
+
+/// Quotes a field when RFC 4180 requires it, doubling embedded quotes.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Written by hand rather than through a map so duplicate column names survive
+fn json_object(columns: &[String], cells: &[CellValue]) -> io::Result<String> {
+    let mut fields = Vec::with_capacity(cells.len());
+    for (column, cell) in columns.iter().zip(cells) {
+        let value = match cell {
+            CellValue::Null => serde_json::Value::Null,
+            CellValue::Integer(v) => serde_json::Value::from(*v),
+            CellValue::Real(v) => serde_json::Value::from(*v),
+            CellValue::Text(v) => serde_json::Value::from(v.as_str()),
+            CellValue::Blob(bytes) => serde_json::Value::from(BASE64.encode(bytes)),
+        };
+        fields.push(format!("{}:{}", serde_json::to_string(column)?, value));
+    }
+    Ok(format!("{{{}}}", fields.join(",")))
+}
+
+fn markdown_cell(cell: &CellValue) -> String {
+    match cell {
+        CellValue::Null => "NULL".to_string(),
+        CellValue::Blob(bytes) => format!("<blob {} bytes>", bytes.len()),
+        other => markdown_text(&plain_text(other)),
+    }
+}
+
+fn markdown_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\r', '\n'], "<br>")
+}
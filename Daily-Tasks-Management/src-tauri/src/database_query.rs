@@ -0,0 +1,426 @@
+use crate::database_browser::{self, QueryParams, ReadOnlyConnection, ResultColumn, PROGRESS_INTERVAL};
+use crate::database_value::CellValue;
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Rows returned per page unless the caller asks for another size.
+pub const DEFAULT_PAGE_SIZE: usize = 200;
+pub const MAX_PAGE_SIZE: usize = 5000;
+
+/// Rows a query may return in total unless the caller sets its own cap.
+pub const DEFAULT_ROW_LIMIT: u64 = 100_000;
+
+/// Upper bound for a caller-supplied time limit.
+pub const MAX_QUERY_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Open queries beyond this many close the least recently used one.
+pub const MAX_OPEN_QUERIES: usize = 4;
+
+/// An open cursor keeps a read lock on its database file, which blocks
+/// writers, so queries nobody fetches from are closed after this long.
+pub const IDLE_QUERY_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct QueryOptions {
+    #[serde(rename = "pageSize")]
+    pub page_size: Option<usize>,
+    #[serde(rename = "maxRows")]
+    pub max_rows: Option<u64>,
+    /// Execution time allowed across all pages; time spent waiting for the
+    /// next fetch does not count.
+    #[serde(rename = "timeoutMs")]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryStatus {
+    /// More rows may follow; fetch the next page.
+    Running,
+    Done,
+    /// Stopped at the row cap with rows left unread.
+    Truncated,
+    Cancelled,
+    Failed,
+}
+
+impl QueryStatus {
+    fn is_finished(self) -> bool {
+        self != QueryStatus::Running
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueryProgress {
+    pub handle: u64,
+    pub status: QueryStatus,
+    #[serde(rename = "rowsRead")]
+    pub rows_read: u64,
+    /// Wall-clock time since the query started.
+    #[serde(rename = "elapsedMs")]
+    pub elapsed_ms: u64,
+    /// Time spent executing, which is what the time limit applies to.
+    #[serde(rename = "executionMs")]
+    pub execution_ms: u64,
+    /// Approximate number of SQLite VM instructions run so far, which keeps
+    /// growing while a slow statement has not produced a row yet.
+    pub steps: u64,
+}
+
+/// A prepared query, ready for its first fetch.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryStarted {
+    pub handle: u64,
+    pub columns: Vec<ResultColumn>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryPage {
+    pub handle: u64,
+    pub columns: Vec<ResultColumn>,
+    pub rows: Vec<Vec<CellValue>>,
+    /// Position of the first row of this page in the whole result.
+    pub offset: u64,
+    pub progress: QueryProgress,
+}
+
+#[derive(Default)]
+struct ExecutionClock {
+    spent: Duration,
+    running_since: Option<Instant>,
+}
+
+impl ExecutionClock {
+    fn total(&self) -> Duration {
+        self.spent + self.running_since.map_or(Duration::ZERO, |since| since.elapsed())
+    }
+}
+
+/// State shared between a query's worker thread, its progress handler and
+/// the commands that fetch, poll and cancel it.
+struct Progress {
+    started: Instant,
+    timeout: Duration,
+    cancelled: AtomicBool,
+    steps: AtomicU64,
+    rows_read: AtomicU64,
+    clock: Mutex<ExecutionClock>,
+    status: Mutex<QueryStatus>,
+}
+
+impl Progress {
+    fn new(timeout: Duration) -> Self {
+        Progress {
+            started: Instant::now(),
+            timeout,
+            cancelled: AtomicBool::new(false),
+            steps: AtomicU64::new(0),
+            rows_read: AtomicU64::new(0),
+            clock: Mutex::new(ExecutionClock::default()),
+            status: Mutex::new(QueryStatus::Running),
+        }
+    }
+
+    // Called by SQLite every PROGRESS_INTERVAL instructions; true interrupts the statement
+    fn should_stop(&self) -> bool {
+        self.steps.fetch_add(PROGRESS_INTERVAL as u64, Ordering::Relaxed);
+        self.cancelled.load(Ordering::Relaxed) || self.execution_time() > self.timeout
+    }
+
+    fn execution_time(&self) -> Duration {
+        self.clock.lock().map(|clock| clock.total()).unwrap_or_default()
+    }
+
+    fn is_executing(&self) -> bool {
+        self.clock.lock().map(|clock| clock.running_since.is_some()).unwrap_or(false)
+    }
+
+    fn set_executing(&self, executing: bool) {
+        if let Ok(mut clock) = self.clock.lock() {
+            match (executing, clock.running_since.take()) {
+                (true, _) => clock.running_since = Some(Instant::now()),
+                (false, Some(since)) => clock.spent += since.elapsed(),
+                (false, None) => {}
+            }
+        }
+    }
+
+    fn status(&self) -> QueryStatus {
+        self.status.lock().map(|status| *status).unwrap_or(QueryStatus::Failed)
+    }
+
+    fn set_status(&self, status: QueryStatus) {
+        if let Ok(mut current) = self.status.lock() {
+            *current = status;
+        }
+    }
+
+    fn snapshot(&self, handle: u64) -> QueryProgress {
+        QueryProgress {
+            handle,
+            status: self.status(),
+            rows_read: self.rows_read.load(Ordering::Relaxed),
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            execution_ms: self.execution_time().as_millis() as u64,
+            steps: self.steps.load(Ordering::Relaxed),
+        }
+    }
+}
+
+struct PageRequest {
+    size: usize,
+    reply: Sender<AppResult<Vec<Vec<CellValue>>>>,
+}
+
+struct QuerySession {
+    requests: Sender<PageRequest>,
+    progress: Arc<Progress>,
+    columns: Vec<ResultColumn>,
+    page_size: usize,
+    last_used: Instant,
+}
+
+/// Queries whose results are read page by page through a handle. Each one
+/// runs on its own thread that owns the read-only connection and its open
+/// cursor, so rows are only read as pages are requested and a huge result
+/// never has to fit in memory.
+#[derive(Default)]
+pub struct QuerySessions {
+    next_handle: AtomicU64,
+    sessions: Mutex<HashMap<u64, QuerySession>>,
+}
+
+impl QuerySessions {
+    pub fn new() -> Self {
+        QuerySessions::default()
+    }
+
+    /// Prepares a query without reading any rows, so the caller has a
+    /// handle to poll or cancel while the first page is fetched.
+    pub fn start(&self, db_path: &str, sql: &str, params: &QueryParams, options: &QueryOptions) -> AppResult<QueryStarted> {
+        let page_size = page_size(options.page_size)?;
+        let max_rows = options.max_rows.unwrap_or(DEFAULT_ROW_LIMIT);
+        let timeout = match options.timeout_ms {
+            Some(0) => return Err(AppError::invalid_field("timeoutMs", "Time limit must be positive")),
+            Some(ms) => Duration::from_millis(ms).min(MAX_QUERY_TIMEOUT),
+            None => database_browser::QUERY_TIMEOUT,
+        };
+
+        let progress = Arc::new(Progress::new(timeout));
+        let interrupt = Arc::clone(&progress);
+        let conn = ReadOnlyConnection::open_interruptible(db_path, timeout, move || interrupt.should_stop())?;
+
+        let (requests, pages) = mpsc::channel();
+        let (ready_tx, ready) = mpsc::channel();
+        let worker_progress = Arc::clone(&progress);
+        let (sql, params) = (sql.to_string(), params.clone());
+        thread::Builder::new()
+            .name("database-query".to_string())
+            .spawn(move || run_query(conn, &sql, &params, max_rows, &worker_progress, ready_tx, pages))
+            .map_err(|e| AppError::internal(format!("Failed to start query thread: {}", e)))?;
+
+        let columns = ready
+            .recv()
+            .map_err(|_| AppError::internal("Query thread stopped before preparing the query"))??;
+
+        let handle = self.next_handle.fetch_add(1, Ordering::Relaxed) + 1;
+        {
+            let mut sessions = self.sessions.lock()?;
+            close_idle_sessions(&mut sessions);
+            if sessions.len() >= MAX_OPEN_QUERIES {
+                let oldest = sessions.iter().min_by_key(|(_, s)| s.last_used).map(|(id, _)| *id);
+                if let Some(session) = oldest.and_then(|id| sessions.remove(&id)) {
+                    session.progress.cancelled.store(true, Ordering::Relaxed);
+                }
+            }
+            sessions.insert(handle, QuerySession {
+                requests,
+                progress,
+                columns: columns.clone(),
+                page_size,
+                last_used: Instant::now(),
+            });
+        }
+
+        Ok(QueryStarted { handle, columns })
+    }
+
+    /// Reads the next page, of the size the query was started with unless
+    /// `size` is given. A page whose status is no longer `running` is the
+    /// last one, after which the handle is gone.
+    pub fn fetch(&self, handle: u64, size: Option<usize>) -> AppResult<QueryPage> {
+        let (requests, progress, columns, size) = {
+            let mut sessions = self.sessions.lock()?;
+            let session = sessions.get_mut(&handle).ok_or_else(|| AppError::not_found("Query", handle))?;
+            session.last_used = Instant::now();
+            let size = match size {
+                Some(size) => page_size(Some(size))?,
+                None => session.page_size,
+            };
+            (session.requests.clone(), Arc::clone(&session.progress), session.columns.clone(), size)
+        };
+
+        let (reply, page) = mpsc::channel();
+        let rows = match requests.send(PageRequest { size, reply }) {
+            Ok(()) => page.recv().unwrap_or_else(|_| Ok(Vec::new())),
+            // The worker already finished, e.g. after a cancel
+            Err(_) => Ok(Vec::new()),
+        };
+
+        let status = progress.status();
+        if status.is_finished() || rows.is_err() {
+            self.sessions.lock()?.remove(&handle);
+        }
+        let rows = rows?;
+
+        let progress = progress.snapshot(handle);
+        Ok(QueryPage {
+            handle,
+            columns,
+            offset: progress.rows_read - rows.len() as u64,
+            rows,
+            progress,
+        })
+    }
+
+    pub fn progress(&self, handle: u64) -> AppResult<QueryProgress> {
+        let sessions = self.sessions.lock()?;
+        let session = sessions.get(&handle).ok_or_else(|| AppError::not_found("Query", handle))?;
+        Ok(session.progress.snapshot(handle))
+    }
+
+    /// Interrupts the statement if it is running and releases the handle.
+    /// A fetch in progress returns the rows read so far as its last page.
+    pub fn cancel(&self, handle: u64) -> AppResult<QueryProgress> {
+        let session = self.sessions.lock()?
+            .remove(&handle)
+            .ok_or_else(|| AppError::not_found("Query", handle))?;
+        session.progress.cancelled.store(true, Ordering::Relaxed);
+        if !session.progress.status().is_finished() {
+            session.progress.set_status(QueryStatus::Cancelled);
+        }
+        Ok(session.progress.snapshot(handle))
+    }
+
+    /// Closes queries nobody has fetched from for `IDLE_QUERY_TIMEOUT`.
+    pub fn close_idle(&self) {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            close_idle_sessions(&mut sessions);
+        }
+    }
+
+    pub fn open_count(&self) -> usize {
+        self.sessions.lock().map(|sessions| sessions.len()).unwrap_or(0)
+    }
+}
+
+fn page_size(size: Option<usize>) -> AppResult<usize> {
+    match size {
+        Some(size) if size == 0 || size > MAX_PAGE_SIZE => Err(AppError::invalid_field(
+            "pageSize",
+            format!("Page size must be between 1 and {}", MAX_PAGE_SIZE),
+        )),
+        Some(size) => Ok(size),
+        None => Ok(DEFAULT_PAGE_SIZE),
+    }
+}
+
+fn close_idle_sessions(sessions: &mut HashMap<u64, QuerySession>) {
+    // Dropping a session's sender ends its worker thread
+    sessions.retain(|_, session| session.progress.is_executing() || session.last_used.elapsed() < IDLE_QUERY_TIMEOUT);
+}
+
+/// Body of a query's worker thread: prepares the statement, reports its
+/// columns, then reads one page per request until the result or the
+/// requests run out.
+fn run_query(
+    conn: ReadOnlyConnection,
+    sql: &str,
+    params: &QueryParams,
+    max_rows: u64,
+    progress: &Progress,
+    ready: Sender<AppResult<Vec<ResultColumn>>>,
+    requests: Receiver<PageRequest>,
+) {
+    progress.set_executing(true);
+    let prepared = conn.prepare(sql, params, "Failed to execute query");
+    progress.set_executing(false);
+
+    let (mut stmt, columns) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            progress.set_status(QueryStatus::Failed);
+            let _ = ready.send(Err(e));
+            return;
+        }
+    };
+    let column_count = columns.len();
+    if ready.send(Ok(columns)).is_err() {
+        return;
+    }
+
+    let mut rows = stmt.raw_query();
+    for request in requests {
+        progress.set_executing(true);
+        let page = read_page(&conn, &mut rows, column_count, request.size, max_rows, progress);
+        progress.set_executing(false);
+
+        let finished = page.is_err() || progress.status().is_finished();
+        let _ = request.reply.send(page);
+        if finished {
+            break;
+        }
+    }
+}
+
+fn read_page(
+    conn: &ReadOnlyConnection,
+    rows: &mut rusqlite::Rows<'_>,
+    column_count: usize,
+    size: usize,
+    max_rows: u64,
+    progress: &Progress,
+) -> AppResult<Vec<Vec<CellValue>>> {
+    let mut page = Vec::new();
+    while page.len() < size {
+        if progress.cancelled.load(Ordering::Relaxed) {
+            progress.set_status(QueryStatus::Cancelled);
+            break;
+        }
+
+        let row = match rows.next() {
+            Ok(Some(row)) => row,
+            Ok(None) => {
+                progress.set_status(QueryStatus::Done);
+                break;
+            }
+            // Interrupted by a cancel rather than the time limit
+            Err(_) if progress.cancelled.load(Ordering::Relaxed) => {
+                progress.set_status(QueryStatus::Cancelled);
+                break;
+            }
+            Err(e) => {
+                progress.set_status(QueryStatus::Failed);
+                return Err(conn.explain(e, "Failed to execute query"));
+            }
+        };
+
+        if progress.rows_read.load(Ordering::Relaxed) >= max_rows {
+            progress.set_status(QueryStatus::Truncated);
+            break;
+        }
+        let cells = database_browser::read_row(row, column_count).map_err(|e| {
+            progress.set_status(QueryStatus::Failed);
+            conn.explain(e, "Failed to execute query")
+        })?;
+        page.push(cells);
+        progress.rows_read.fetch_add(1, Ordering::Relaxed);
+    }
+    Ok(page)
+}
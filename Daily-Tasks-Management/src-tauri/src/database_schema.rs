@@ -0,0 +1,216 @@
+use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DatabaseSchema {
+    pub tables: Vec<TableSchema>,
+    pub views: Vec<ViewSchema>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TableSchema {
+    pub name: String,
+    /// The `CREATE TABLE` statement as stored in `sqlite_master`.
+    pub sql: Option<String>,
+    pub columns: Vec<ColumnInfo>,
+    pub indexes: Vec<IndexInfo>,
+    #[serde(rename = "foreignKeys")]
+    pub foreign_keys: Vec<ForeignKeyInfo>,
+    pub triggers: Vec<TriggerInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ViewSchema {
+    pub name: String,
+    pub sql: Option<String>,
+    pub columns: Vec<ColumnInfo>,
+    pub triggers: Vec<TriggerInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// Declared type, empty when the column has none.
+    #[serde(rename = "declaredType")]
+    pub declared_type: String,
+    pub nullable: bool,
+    #[serde(rename = "defaultValue")]
+    pub default_value: Option<String>,
+    /// 1-based position in the primary key, 0 when not part of it.
+    #[serde(rename = "primaryKey")]
+    pub primary_key: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IndexInfo {
+    pub name: String,
+    pub unique: bool,
+    /// `c` for CREATE INDEX, `u` for UNIQUE constraints, `pk` for primary keys.
+    pub origin: String,
+    pub partial: bool,
+    pub columns: Vec<String>,
+    /// `None` for indexes SQLite creates automatically.
+    pub sql: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ForeignKeyInfo {
+    pub id: u32,
+    #[serde(rename = "referencedTable")]
+    pub referenced_table: String,
+    pub columns: Vec<String>,
+    /// Referenced columns; `None` entries refer to the primary key.
+    #[serde(rename = "referencedColumns")]
+    pub referenced_columns: Vec<Option<String>>,
+    #[serde(rename = "onUpdate")]
+    pub on_update: String,
+    #[serde(rename = "onDelete")]
+    pub on_delete: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TriggerInfo {
+    pub name: String,
+    pub sql: Option<String>,
+}
+
+/// Reads the full schema of the main database.
+pub fn introspect(conn: &Connection) -> Result<DatabaseSchema> {
+    let mut stmt = conn.prepare(
+        "SELECT type, name, sql FROM sqlite_master
+         WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'
+         ORDER BY name",
+    )?;
+    let objects = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<String>>(2)?)))?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut schema = DatabaseSchema {
+        tables: Vec::new(),
+        views: Vec::new(),
+    };
+    for (kind, name, sql) in objects {
+        if kind == "view" {
+            schema.views.push(ViewSchema {
+                columns: columns(conn, &name)?,
+                triggers: triggers(conn, &name)?,
+                name,
+                sql,
+            });
+        } else {
+            schema.tables.push(TableSchema {
+                columns: columns(conn, &name)?,
+                indexes: indexes(conn, &name)?,
+                foreign_keys: foreign_keys(conn, &name)?,
+                triggers: triggers(conn, &name)?,
+                name,
+                sql,
+            });
+        }
+    }
+
+    Ok(schema)
+}
+
+pub fn columns(conn: &Connection, table: &str) -> Result<Vec<ColumnInfo>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?1) ORDER BY cid",
+    )?;
+    let columns = stmt.query_map([table], |row| {
+        Ok(ColumnInfo {
+            name: row.get(0)?,
+            declared_type: row.get(1)?,
+            nullable: !row.get::<_, bool>(2)?,
+            default_value: row.get(3)?,
+            primary_key: row.get(4)?,
+        })
+    })?;
+    columns.collect()
+}
+
+pub fn indexes(conn: &Connection, table: &str) -> Result<Vec<IndexInfo>> {
+    let mut stmt = conn.prepare(
+        "SELECT il.name, il.\"unique\", il.origin, il.partial, m.sql
+         FROM pragma_index_list(?1) AS il
+         LEFT JOIN sqlite_master AS m ON m.type = 'index' AND m.name = il.name
+         ORDER BY il.name",
+    )?;
+    let listed = stmt
+        .query_map([table], |row| {
+            Ok(IndexInfo {
+                name: row.get(0)?,
+                unique: row.get(1)?,
+                origin: row.get(2)?,
+                partial: row.get(3)?,
+                columns: Vec::new(),
+                sql: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut column_stmt = conn.prepare(
+        "SELECT name FROM pragma_index_info(?1) ORDER BY seqno",
+    )?;
+    let mut indexes = Vec::with_capacity(listed.len());
+    for mut index in listed {
+        // Expression columns have no name
+        index.columns = column_stmt
+            .query_map([&index.name], |row| row.get::<_, Option<String>>(0))?
+            .map(|name| name.map(|n| n.unwrap_or_else(|| "<expression>".to_string())))
+            .collect::<Result<Vec<_>>>()?;
+        indexes.push(index);
+    }
+    Ok(indexes)
+}
+
+pub fn foreign_keys(conn: &Connection, table: &str) -> Result<Vec<ForeignKeyInfo>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, \"table\", \"from\", \"to\", on_update, on_delete
+         FROM pragma_foreign_key_list(?1) ORDER BY id, seq",
+    )?;
+    let rows = stmt
+        .query_map([table], |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    // Composite keys come back as one row per column
+    let mut keys: Vec<ForeignKeyInfo> = Vec::new();
+    for (id, referenced_table, from, to, on_update, on_delete) in rows {
+        match keys.last_mut() {
+            Some(key) if key.id == id => {
+                key.columns.push(from);
+                key.referenced_columns.push(to);
+            }
+            _ => keys.push(ForeignKeyInfo {
+                id,
+                referenced_table,
+                columns: vec![from],
+                referenced_columns: vec![to],
+                on_update,
+                on_delete,
+            }),
+        }
+    }
+    Ok(keys)
+}
+
+pub fn triggers(conn: &Connection, table: &str) -> Result<Vec<TriggerInfo>> {
+    let mut stmt = conn.prepare(
+        "SELECT name, sql FROM sqlite_master WHERE type = 'trigger' AND tbl_name = ?1 ORDER BY name",
+    )?;
+    let triggers = stmt.query_map([table], |row| {
+        Ok(TriggerInfo {
+            name: row.get(0)?,
+            sql: row.get(1)?,
+        })
+    })?;
+    triggers.collect()
+}
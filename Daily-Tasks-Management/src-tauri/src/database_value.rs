@@ -0,0 +1,179 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rusqlite::types::{ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Bytes of a blob shown in its hex preview.
+pub const BLOB_PREVIEW_BYTES: usize = 32;
+
+/// One SQLite value with its storage class.
+///
+/// Serializes as `{ "type": ..., ... }`. Integers travel as decimal strings
+/// because JavaScript numbers lose precision past 2^53, and blobs carry
+/// their full content as base64 next to the size and a hex preview.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "CellRepr", try_from = "CellRepr")]
+pub enum CellValue {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum CellRepr {
+    Null,
+    Integer {
+        value: IntegerRepr,
+    },
+    Real {
+        value: f64,
+    },
+    Text {
+        value: String,
+    },
+    Blob {
+        #[serde(default)]
+        size: usize,
+        #[serde(default)]
+        hex: String,
+        base64: String,
+    },
+}
+
+// Accepts plain numbers from callers that don't need the full range
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum IntegerRepr {
+    Text(String),
+    Number(i64),
+}
+
+impl From<CellValue> for CellRepr {
+    fn from(value: CellValue) -> Self {
+        match value {
+            CellValue::Null => CellRepr::Null,
+            CellValue::Integer(v) => CellRepr::Integer {
+                value: IntegerRepr::Text(v.to_string()),
+            },
+            CellValue::Real(v) => CellRepr::Real { value: v },
+            CellValue::Text(v) => CellRepr::Text { value: v },
+            CellValue::Blob(bytes) => CellRepr::Blob {
+                size: bytes.len(),
+                hex: hex_preview(&bytes),
+                base64: BASE64.encode(&bytes),
+            },
+        }
+    }
+}
+
+impl TryFrom<CellRepr> for CellValue {
+    type Error = String;
+
+    fn try_from(repr: CellRepr) -> Result<Self, Self::Error> {
+        Ok(match repr {
+            CellRepr::Null => CellValue::Null,
+            CellRepr::Integer { value: IntegerRepr::Number(v) } => CellValue::Integer(v),
+            CellRepr::Integer { value: IntegerRepr::Text(v) } => CellValue::Integer(
+                v.parse().map_err(|_| format!("Invalid integer '{}'", v))?,
+            ),
+            CellRepr::Real { value } => CellValue::Real(value),
+            CellRepr::Text { value } => CellValue::Text(value),
+            CellRepr::Blob { base64, .. } => CellValue::Blob(
+                BASE64.decode(base64).map_err(|e| format!("Invalid base64 blob: {}", e))?,
+            ),
+        })
+    }
+}
+
+impl From<ValueRef<'_>> for CellValue {
+    fn from(value: ValueRef<'_>) -> Self {
+        match value {
+            ValueRef::Null => CellValue::Null,
+            ValueRef::Integer(v) => CellValue::Integer(v),
+            ValueRef::Real(v) => CellValue::Real(v),
+            // SQLite does not enforce UTF-8, so invalid text is kept as a lossy copy
+            ValueRef::Text(v) => CellValue::Text(String::from_utf8_lossy(v).to_string()),
+            ValueRef::Blob(v) => CellValue::Blob(v.to_vec()),
+        }
+    }
+}
+
+impl ToSql for CellValue {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(match self {
+            CellValue::Null => ValueRef::Null,
+            CellValue::Integer(v) => ValueRef::Integer(*v),
+            CellValue::Real(v) => ValueRef::Real(*v),
+            CellValue::Text(v) => ValueRef::Text(v.as_bytes()),
+            CellValue::Blob(v) => ValueRef::Blob(v),
+        }))
+    }
+}
+
+/// Lowercase hex of the first `BLOB_PREVIEW_BYTES` bytes.
+pub fn hex_preview(bytes: &[u8]) -> String {
+    bytes.iter()
+        .take(BLOB_PREVIEW_BYTES)
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The value as a SQLite literal, e.g. for generated INSERT statements.
+pub fn sql_literal(cell: &CellValue) -> String {
+    match cell {
+        CellValue::Null => "NULL".to_string(),
+        CellValue::Integer(v) => v.to_string(),
+        // SQLite reads 9e999 as infinity; Debug keeps enough digits to round-trip
+        CellValue::Real(v) if v.is_infinite() => if *v > 0.0 { "9e999" } else { "-9e999" }.to_string(),
+        CellValue::Real(v) => format!("{:?}", v),
+        CellValue::Text(v) => format!("'{}'", v.replace('\'', "''")),
+        CellValue::Blob(bytes) => format!("X'{}'", hex(bytes)),
+    }
+}
+
+/// Orders values the way SQLite's `ORDER BY ... COLLATE BINARY` does: NULL,
+/// then numbers compared by value, then text, then blobs, both bytewise.
+pub fn compare_cells(a: &CellValue, b: &CellValue) -> Ordering {
+    fn rank(cell: &CellValue) -> u8 {
+        match cell {
+            CellValue::Null => 0,
+            CellValue::Integer(_) | CellValue::Real(_) => 1,
+            CellValue::Text(_) => 2,
+            CellValue::Blob(_) => 3,
+        }
+    }
+
+    match (a, b) {
+        (CellValue::Integer(x), CellValue::Integer(y)) => x.cmp(y),
+        (CellValue::Integer(x), CellValue::Real(y)) => compare_integer_real(*x, *y),
+        (CellValue::Real(x), CellValue::Integer(y)) => compare_integer_real(*y, *x).reverse(),
+        (CellValue::Real(x), CellValue::Real(y)) => x.total_cmp(y),
+        (CellValue::Text(x), CellValue::Text(y)) => x.as_bytes().cmp(y.as_bytes()),
+        (CellValue::Blob(x), CellValue::Blob(y)) => x.cmp(y),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn compare_integer_real(integer: i64, real: f64) -> Ordering {
+    if real.is_nan() {
+        return Ordering::Greater;
+    }
+    // Large integers lose precision as floats, so only fall back to the
+    // float comparison once the real is out of integer range
+    if real >= 9.223372036854776e18 {
+        return Ordering::Less;
+    }
+    if real < -9.223372036854776e18 {
+        return Ordering::Greater;
+    }
+    // A fractional part puts the real above its floor, negative or not
+    integer.cmp(&(real.floor() as i64)).then(if real > real.floor() { Ordering::Less } else { Ordering::Equal })
+}
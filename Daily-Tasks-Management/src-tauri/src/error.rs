@@ -0,0 +1,359 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::PoisonError;
+
+pub type AppResult<T> = Result<T, AppError>;
+
+/// Machine-readable error category sent to the frontend as `code`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    Validation,
+    Conflict,
+    Io,
+    Database,
+    Git,
+    Process,
+    Timeout,
+    Internal,
+}
+
+/// Error type shared by every module and returned from every Tauri command.
+///
+/// Serializes as `{ code, message, details, sourceChain }` where `details`
+/// carries whichever of `entity`, `id`, `field` and `path` apply.
+#[derive(Debug)]
+pub enum AppError {
+    NotFound {
+        entity: &'static str,
+        id: String,
+    },
+    Validation {
+        message: String,
+        field: Option<String>,
+        path: Option<PathBuf>,
+    },
+    Conflict {
+        message: String,
+        source: Option<rusqlite::Error>,
+    },
+    Io {
+        context: String,
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    Database {
+        context: String,
+        source: rusqlite::Error,
+    },
+    Postgres {
+        context: String,
+        source: postgres::Error,
+    },
+    Git {
+        context: String,
+        path: Option<PathBuf>,
+        source: Option<git2::Error>,
+    },
+    Process {
+        context: String,
+        source: Option<io::Error>,
+    },
+    Timeout {
+        context: String,
+    },
+    Internal {
+        message: String,
+    },
+}
+
+impl AppError {
+    pub fn not_found(entity: &'static str, id: impl ToString) -> Self {
+        AppError::NotFound {
+            entity,
+            id: id.to_string(),
+        }
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        AppError::Validation {
+            message: message.into(),
+            field: None,
+            path: None,
+        }
+    }
+
+    pub fn invalid_field(field: &str, message: impl Into<String>) -> Self {
+        AppError::Validation {
+            message: message.into(),
+            field: Some(field.to_string()),
+            path: None,
+        }
+    }
+
+    pub fn invalid_path(field: &str, path: impl AsRef<Path>, message: impl Into<String>) -> Self {
+        AppError::Validation {
+            message: message.into(),
+            field: Some(field.to_string()),
+            path: Some(path.as_ref().to_path_buf()),
+        }
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        AppError::Conflict {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn io(context: impl Into<String>, path: impl AsRef<Path>, source: io::Error) -> Self {
+        AppError::Io {
+            context: context.into(),
+            path: Some(path.as_ref().to_path_buf()),
+            source,
+        }
+    }
+
+    pub fn git(context: impl Into<String>, path: impl AsRef<Path>, source: git2::Error) -> Self {
+        AppError::Git {
+            context: context.into(),
+            path: Some(path.as_ref().to_path_buf()),
+            source: Some(source),
+        }
+    }
+
+    /// A repository in a state git operations cannot handle, e.g. a detached HEAD.
+    pub fn repository(context: impl Into<String>, path: impl AsRef<Path>) -> Self {
+        AppError::Git {
+            context: context.into(),
+            path: Some(path.as_ref().to_path_buf()),
+            source: None,
+        }
+    }
+
+    pub fn process(context: impl Into<String>, source: Option<io::Error>) -> Self {
+        AppError::Process {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub fn timeout(context: impl Into<String>) -> Self {
+        AppError::Timeout {
+            context: context.into(),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        AppError::Internal {
+            message: message.into(),
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::NotFound { .. } => ErrorCode::NotFound,
+            AppError::Validation { .. } => ErrorCode::Validation,
+            AppError::Conflict { .. } => ErrorCode::Conflict,
+            AppError::Io { source, .. } if source.kind() == io::ErrorKind::NotFound => ErrorCode::NotFound,
+            AppError::Io { .. } => ErrorCode::Io,
+            AppError::Database { .. } | AppError::Postgres { .. } => ErrorCode::Database,
+            AppError::Git { .. } => ErrorCode::Git,
+            AppError::Process { .. } => ErrorCode::Process,
+            AppError::Timeout { .. } => ErrorCode::Timeout,
+            AppError::Internal { .. } => ErrorCode::Internal,
+        }
+    }
+
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            AppError::Validation { field, .. } => field.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            AppError::Validation { path, .. }
+            | AppError::Io { path, .. }
+            | AppError::Git { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Messages of every underlying error, outermost first.
+    pub fn source_chain(&self) -> Vec<String> {
+        let mut chain = Vec::new();
+        let mut current = self.source();
+        while let Some(err) = current {
+            chain.push(err.to_string());
+            current = err.source();
+        }
+        chain
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NotFound { entity, id } => write!(f, "{} {} not found", entity, id),
+            AppError::Validation { message, .. }
+            | AppError::Conflict { message, .. }
+            | AppError::Internal { message } => write!(f, "{}", message),
+            AppError::Io { context, .. }
+            | AppError::Database { context, .. }
+            | AppError::Postgres { context, .. }
+            | AppError::Git { context, .. }
+            | AppError::Process { context, .. }
+            | AppError::Timeout { context } => write!(f, "{}", context),
+        }
+    }
+}
+
+impl Error for AppError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AppError::Conflict { source, .. } => source.as_ref().map(|e| e as &(dyn Error + 'static)),
+            AppError::Io { source, .. } => Some(source),
+            AppError::Database { source, .. } => Some(source),
+            AppError::Postgres { source, .. } => Some(source),
+            AppError::Git { source, .. } => source.as_ref().map(|e| e as &(dyn Error + 'static)),
+            AppError::Process { source, .. } => source.as_ref().map(|e| e as &(dyn Error + 'static)),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(serde::Serialize)]
+        struct Details<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            entity: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            id: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            field: Option<&'a str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            path: Option<String>,
+        }
+
+        let (entity, id) = match self {
+            AppError::NotFound { entity, id } => (Some(*entity), Some(id.as_str())),
+            _ => (None, None),
+        };
+        let details = Details {
+            entity,
+            id,
+            field: self.field(),
+            path: self.path().map(|p| p.to_string_lossy().to_string()),
+        };
+
+        let mut state = serializer.serialize_struct("AppError", 4)?;
+        state.serialize_field("code", &self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("details", &details)?;
+        state.serialize_field("sourceChain", &self.source_chain())?;
+        state.end()
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(source: rusqlite::Error) -> Self {
+        source.into_app_error("Database error")
+    }
+}
+
+impl<T> From<PoisonError<T>> for AppError {
+    fn from(err: PoisonError<T>) -> Self {
+        AppError::internal(format!("Lock poisoned: {}", err))
+    }
+}
+
+/// Wraps a lower-level error in an `AppError` with a description of what failed.
+pub trait Context<T> {
+    fn context(self, context: impl Into<String>) -> AppResult<T>;
+}
+
+/// Conversion of a lower-level error into an `AppError` with context.
+pub trait IntoAppError {
+    fn into_app_error(self, context: impl Into<String>) -> AppError;
+}
+
+impl IntoAppError for rusqlite::Error {
+    fn into_app_error(self, context: impl Into<String>) -> AppError {
+        // Constraint violations (UNIQUE, FOREIGN KEY, ...) are conflicts with existing data
+        if self.sqlite_error_code() == Some(rusqlite::ErrorCode::ConstraintViolation) {
+            AppError::Conflict {
+                message: context.into(),
+                source: Some(self),
+            }
+        } else {
+            AppError::Database {
+                context: context.into(),
+                source: self,
+            }
+        }
+    }
+}
+
+impl IntoAppError for io::Error {
+    fn into_app_error(self, context: impl Into<String>) -> AppError {
+        AppError::Io {
+            context: context.into(),
+            path: None,
+            source: self,
+        }
+    }
+}
+
+impl IntoAppError for postgres::Error {
+    fn into_app_error(self, context: impl Into<String>) -> AppError {
+        AppError::Postgres {
+            context: context.into(),
+            source: self,
+        }
+    }
+}
+
+impl IntoAppError for git2::Error {
+    fn into_app_error(self, context: impl Into<String>) -> AppError {
+        AppError::Git {
+            context: context.into(),
+            path: None,
+            source: Some(self),
+        }
+    }
+}
+
+/// Re-describes an error that already carries a source; errors that stand on
+/// their own (not found, validation, ...) keep their original message.
+impl IntoAppError for AppError {
+    fn into_app_error(self, context: impl Into<String>) -> AppError {
+        match self {
+            AppError::Database { source, .. } => AppError::Database { context: context.into(), source },
+            AppError::Postgres { source, .. } => AppError::Postgres { context: context.into(), source },
+            AppError::Conflict { source: Some(source), .. } => AppError::Conflict {
+                message: context.into(),
+                source: Some(source),
+            },
+            AppError::Io { path, source, .. } => AppError::Io { context: context.into(), path, source },
+            AppError::Git { path, source: Some(source), .. } => AppError::Git {
+                context: context.into(),
+                path,
+                source: Some(source),
+            },
+            other => other,
+        }
+    }
+}
+
+impl<T, E: IntoAppError> Context<T> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> AppResult<T> {
+        self.map_err(|e| e.into_app_error(context))
+    }
+}
@@ -1,15 +1,16 @@
+use crate::error::{AppError, AppResult};
 use crate::model::GitStatus;
-use git2::{Repository, StatusOptions};
+use git2::{Commit, Repository, StatusOptions};
 use std::path::Path;
 use chrono::Utc;
 
-pub fn get_git_status(repo_path: &str) -> Result<GitStatus, String> {
+pub fn get_git_status(repo_path: &str) -> AppResult<GitStatus> {
     let repo = Repository::open(repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
+        .map_err(|e| AppError::git("Failed to open repository", repo_path, e))?;
 
     // Get current branch
     let head = repo.head()
-        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+        .map_err(|e| AppError::git("Failed to get HEAD", repo_path, e))?;
     let current_branch = head.shorthand().map(|s| s.to_string());
 
     // Get uncommitted changes
@@ -18,7 +19,7 @@ pub fn get_git_status(repo_path: &str) -> Result<GitStatus, String> {
     opts.include_ignored(false);
     
     let statuses = repo.statuses(Some(&mut opts))
-        .map_err(|e| format!("Failed to get status: {}", e))?;
+        .map_err(|e| AppError::git("Failed to get status", repo_path, e))?;
     
     let mut uncommitted_changes = Vec::new();
     for entry in statuses.iter() {
@@ -43,28 +44,28 @@ pub fn get_git_status(repo_path: &str) -> Result<GitStatus, String> {
 }
 
 
-pub fn get_current_branch(repo_path: &str) -> Result<String, String> {
+pub fn get_current_branch(repo_path: &str) -> AppResult<String> {
     let repo = Repository::open(repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
+        .map_err(|e| AppError::git("Failed to open repository", repo_path, e))?;
     
     let head = repo.head()
-        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+        .map_err(|e| AppError::git("Failed to get HEAD", repo_path, e))?;
     
     head.shorthand()
         .map(|s| s.to_string())
-        .ok_or_else(|| "No branch name found".to_string())
+        .ok_or_else(|| AppError::repository("No branch name found", repo_path))
 }
 
-pub fn get_uncommitted_changes(repo_path: &str) -> Result<Vec<String>, String> {
+pub fn get_uncommitted_changes(repo_path: &str) -> AppResult<Vec<String>> {
     let repo = Repository::open(repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
+        .map_err(|e| AppError::git("Failed to open repository", repo_path, e))?;
 
     let mut opts = StatusOptions::new();
     opts.include_untracked(true);
     opts.include_ignored(false);
     
     let statuses = repo.statuses(Some(&mut opts))
-        .map_err(|e| format!("Failed to get status: {}", e))?;
+        .map_err(|e| AppError::git("Failed to get status", repo_path, e))?;
     
     let mut changes = Vec::new();
     for entry in statuses.iter() {
@@ -76,22 +77,23 @@ pub fn get_uncommitted_changes(repo_path: &str) -> Result<Vec<String>, String> {
     Ok(changes)
 }
 
-pub fn get_ahead_behind(repo_path: &str) -> Result<(u32, u32), String> {
+pub fn get_ahead_behind(repo_path: &str) -> AppResult<(u32, u32)> {
     let repo = Repository::open(repo_path)
-        .map_err(|e| format!("Failed to open repository: {}", e))?;
+        .map_err(|e| AppError::git("Failed to open repository", repo_path, e))?;
     get_ahead_behind_from_repo(&repo)
 }
 
-fn get_ahead_behind_from_repo(repo: &Repository) -> Result<(u32, u32), String> {
+fn get_ahead_behind_from_repo(repo: &Repository) -> AppResult<(u32, u32)> {
+    let repo_path = repo.workdir().unwrap_or_else(|| repo.path());
     let head = repo.head()
-        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+        .map_err(|e| AppError::git("Failed to get HEAD", repo_path, e))?;
     
     let head_oid = head.target()
-        .ok_or_else(|| "HEAD has no target".to_string())?;
+        .ok_or_else(|| AppError::repository("HEAD has no target", repo_path))?;
     
     // Try to find upstream branch
     let branch_name = head.shorthand()
-        .ok_or_else(|| "No branch name found".to_string())?;
+        .ok_or_else(|| AppError::repository("No branch name found", repo_path))?;
     
     // Try common upstream names
     let upstream_names = [
@@ -103,7 +105,7 @@ fn get_ahead_behind_from_repo(repo: &Repository) -> Result<(u32, u32), String> {
         if let Ok(upstream_ref) = repo.find_reference(&format!("refs/remotes/{}", upstream_name)) {
             if let Some(upstream_oid) = upstream_ref.target() {
                 let (ahead, behind) = repo.graph_ahead_behind(head_oid, upstream_oid)
-                    .map_err(|e| format!("Failed to compare branches: {}", e))?;
+                    .map_err(|e| AppError::git("Failed to compare branches", repo_path, e))?;
                 return Ok((ahead as u32, behind as u32));
             }
         }
@@ -113,6 +115,83 @@ fn get_ahead_behind_from_repo(repo: &Repository) -> Result<(u32, u32), String> {
     Ok((0, 0))
 }
 
+/// Stages every new, modified and deleted file and commits it on HEAD.
+pub fn commit_all(repo_path: &str, message: &str) -> AppResult<()> {
+    let repo = Repository::open(repo_path)
+        .map_err(|e| AppError::git("Failed to open repository", repo_path, e))?;
+    
+    let mut index = repo.index()
+        .map_err(|e| AppError::git("Failed to get index", repo_path, e))?;
+    
+    // Get status to find all changed files
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true);
+    opts.include_ignored(false);
+    
+    let statuses = repo.statuses(Some(&mut opts))
+        .map_err(|e| AppError::git("Failed to get status", repo_path, e))?;
+    
+    // Stage all modified, deleted, and untracked files
+    for entry in statuses.iter() {
+        if let Some(path) = entry.path() {
+            let status = entry.status();
+            
+            // Add modified, new, or deleted files
+            if status.is_wt_new() || status.is_wt_modified() || status.is_wt_deleted() {
+                if status.is_wt_deleted() {
+                    // Remove deleted files from index
+                    index.remove_path(Path::new(path))
+                        .map_err(|e| AppError::git(format!("Failed to remove {} from index", path), repo_path, e))?;
+                } else {
+                    // Add new or modified files to index
+                    index.add_path(Path::new(path))
+                        .map_err(|e| AppError::git(format!("Failed to add {} to index", path), repo_path, e))?;
+                }
+            }
+        }
+    }
+    
+    // Write the index to disk
+    index.write()
+        .map_err(|e| AppError::git("Failed to write index", repo_path, e))?;
+    
+    // Get the updated index and write tree
+    let mut index = repo.index()
+        .map_err(|e| AppError::git("Failed to get updated index", repo_path, e))?;
+    
+    let tree_id = index.write_tree()
+        .map_err(|e| AppError::git("Failed to write tree", repo_path, e))?;
+    let tree = repo.find_tree(tree_id)
+        .map_err(|e| AppError::git("Failed to find tree", repo_path, e))?;
+    
+    let sig = repo.signature()
+        .map_err(|e| AppError::git("Failed to get signature", repo_path, e))?;
+    
+    // Get parent commit (if exists)
+    let parents = if let Ok(head) = repo.head() {
+        if let Ok(parent) = head.peel_to_commit() {
+            vec![parent]
+        } else {
+            Vec::new()
+        }
+    } else {
+        Vec::new()
+    };
+    
+    // Create commit
+    let parent_refs: Vec<&Commit> = parents.iter().collect();
+    repo.commit(
+        Some("HEAD"),
+        &sig,
+        &sig,
+        message,
+        &tree,
+        &parent_refs,
+    ).map_err(|e| AppError::git("Failed to commit", repo_path, e))?;
+    
+    Ok(())
+}
+
 pub fn is_git_repository(path: &str) -> bool {
     Path::new(path).join(".git").exists() || Repository::open(path).is_ok()
 }
@@ -0,0 +1,742 @@
+use crate::command_line;
+use crate::error::{AppError, AppResult, Context};
+use crate::git;
+use crate::model::*;
+use crate::optimization::optimize_task_schedule;
+use crate::projects::ProjectManager;
+use crate::repository::{
+    ConnectionProfileRepository, GitRepoRepository, ProjectRepository, QueryRepository, ServiceGroupRepository, ServiceRepository,
+    TaskRepository,
+};
+use crate::secrets::{connection_password_key, service_env_key, SecretStore};
+use crate::health;
+use crate::log_parser;
+use crate::service_env::{self, ResolvedEnvVar};
+use crate::service_graph::{self, Dependencies};
+use crate::database_browser::QueryResult;
+use crate::database_connector::ConnectorTarget;
+use crate::database_query::QueryStarted;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Command logic, generic over the repositories so it runs against any store.
+// The Tauri commands in `commands` bind these to the managed SQLite database.
+
+// Task handlers
+pub fn create_task<R: TaskRepository>(repo: &R, form_data: TaskFormData) -> AppResult<Task> {
+    repo.create_task(form_data).context("Failed to create task")
+}
+
+pub fn get_all_tasks<R: TaskRepository>(repo: &R) -> AppResult<Vec<Task>> {
+    repo.get_all_tasks().context("Failed to get tasks")
+}
+
+pub fn get_task<R: TaskRepository>(repo: &R, id: u32) -> AppResult<Option<Task>> {
+    repo.get_task_by_id(id).context("Failed to get task")
+}
+
+pub fn update_task<R: TaskRepository>(repo: &R, task: &Task) -> AppResult<()> {
+    repo.update_task(task).context("Failed to update task")
+}
+
+pub fn delete_task<R: TaskRepository>(repo: &R, id: u32) -> AppResult<()> {
+    repo.delete_task(id).context("Failed to delete task")
+}
+
+pub fn toggle_task_completion<R: TaskRepository>(repo: &R, id: u32, completed: bool) -> AppResult<()> {
+    repo.mark_task_completed(id, completed).context("Failed to toggle task completion")
+}
+
+pub fn optimize_tasks<R: TaskRepository>(repo: &R) -> AppResult<Vec<Task>> {
+    let tasks = repo.get_all_tasks().context("Failed to get tasks")?;
+    let optimized_tasks = optimize_task_schedule(tasks);
+
+    // Update each task with the new scheduled start time
+    for task in &optimized_tasks {
+        repo.update_task(task).context("Failed to update task")?;
+    }
+
+    Ok(optimized_tasks)
+}
+
+// Project handlers
+pub fn add_project<R: ProjectRepository>(repo: &R, name: String, path: String) -> AppResult<Project> {
+    // Validate path
+    ProjectManager::validate_project_path(&path)?;
+
+    // Get project info
+    let (detected_name, project_type) = ProjectManager::get_project_info(&path)
+        .context("Failed to get project info")?;
+
+    let final_name = if name.is_empty() { detected_name } else { name };
+
+    repo.create_project(final_name, path, project_type, None)
+        .context("Failed to create project")
+}
+
+pub fn preview_project_removal<R: ProjectRepository>(repo: &R, id: u32) -> AppResult<ProjectDeletionImpact> {
+    repo.get_project_deletion_impact(id).context("Failed to inspect project")
+}
+
+pub fn remove_project<R: ProjectRepository>(repo: &R, id: u32, mode: ProjectDeleteMode) -> AppResult<ProjectDeletionImpact> {
+    repo.delete_project_with_mode(id, mode).context("Failed to delete project")
+}
+
+pub fn get_all_projects<R: ProjectRepository>(repo: &R) -> AppResult<Vec<Project>> {
+    repo.get_all_projects().context("Failed to get projects")
+}
+
+pub fn get_project_by_id<R: ProjectRepository>(repo: &R, id: u32) -> AppResult<Option<Project>> {
+    repo.get_project_by_id(id).context("Failed to get project")
+}
+
+pub fn update_project<R: ProjectRepository>(repo: &R, project: &Project) -> AppResult<()> {
+    repo.update_project(project).context("Failed to update project")
+}
+
+// Service handlers
+pub fn register_service<R: ServiceRepository>(
+    repo: &R,
+    name: String,
+    command: String,
+    working_dir: Option<String>,
+    project_id: Option<u32>,
+    auto_start: bool,
+    shell: bool,
+) -> AppResult<Service> {
+    validate_service_command(&command, shell)?;
+    repo.create_service(name, command, working_dir, project_id, auto_start, shell)
+        .context("Failed to create service")
+}
+
+pub fn update_service<R: ServiceRepository>(repo: &R, service: &Service) -> AppResult<()> {
+    require_service(repo, service.id)?;
+    validate_service_command(&service.command, service.shell)?;
+    repo.update_service(service).context("Failed to update service")
+}
+
+/// Commands outside shell mode must parse into a program and arguments, so
+/// mistakes show up when the service is saved rather than when it starts.
+fn validate_service_command(command: &str, shell: bool) -> AppResult<()> {
+    if shell {
+        if command.trim().is_empty() {
+            return Err(AppError::invalid_field("command", "Empty command"));
+        }
+        return Ok(());
+    }
+    command_line::parse_command(command).map(|_| ())
+}
+
+pub fn unregister_service<R: ServiceRepository>(repo: &R, id: u32) -> AppResult<()> {
+    repo.delete_service(id).context("Failed to delete service")
+}
+
+pub fn get_all_services<R: ServiceRepository>(repo: &R) -> AppResult<Vec<Service>> {
+    repo.get_all_services().context("Failed to get services")
+}
+
+pub fn get_service_by_id<R: ServiceRepository>(repo: &R, id: u32) -> AppResult<Option<Service>> {
+    repo.get_service_by_id(id).context("Failed to get service")
+}
+
+/// Like `get_service_by_id`, but a missing service is an error.
+pub fn require_service<R: ServiceRepository>(repo: &R, id: u32) -> AppResult<Service> {
+    get_service_by_id(repo, id)?.ok_or_else(|| AppError::not_found("Service", id))
+}
+
+// Service environment handlers
+
+pub fn get_service_environment<R: ServiceRepository>(repo: &R, service_id: u32) -> AppResult<ServiceEnvironment> {
+    require_service(repo, service_id)?;
+    repo.get_service_environment(service_id).context("Failed to get service environment")
+}
+
+fn validate_service_environment(environment: &ServiceEnvironment) -> AppResult<()> {
+    for (i, var) in environment.variables.iter().enumerate() {
+        if !command_line::is_env_name(&var.name) {
+            return Err(AppError::invalid_field("variables", format!("{} is not a valid variable name", var.name)));
+        }
+        if environment.variables[..i].iter().any(|earlier| earlier.name == var.name) {
+            return Err(AppError::invalid_field("variables", format!("{} is set twice", var.name)));
+        }
+        if !var.secret && var.value.is_none() {
+            return Err(AppError::invalid_field("variables", format!("{} needs a value", var.name)));
+        }
+    }
+    if environment.env_files.iter().any(|path| path.trim().is_empty()) {
+        return Err(AppError::invalid_field("envFiles", "Env file paths cannot be empty"));
+    }
+    Ok(())
+}
+
+/// Saves a service's environment, moving secret values into the secret
+/// store. A secret sent without a value keeps the one already stored.
+/// Returns what was saved, secrets without their values.
+pub fn set_service_environment<R: ServiceRepository, S: SecretStore>(
+    repo: &R,
+    secrets: &S,
+    workspace: &str,
+    service_id: u32,
+    environment: &ServiceEnvironment,
+) -> AppResult<ServiceEnvironment> {
+    require_service(repo, service_id)?;
+    validate_service_environment(environment)?;
+    let previous = repo.get_service_environment(service_id).context("Failed to get service environment")?;
+    let key = |name: &str| service_env_key(workspace, service_id, name);
+
+    for var in environment.variables.iter().filter(|v| v.secret && v.value.is_none()) {
+        if secrets.get_secret(&key(&var.name))?.is_none() {
+            return Err(AppError::invalid_field("variables", format!("{} needs a value", var.name)));
+        }
+    }
+    for var in environment.variables.iter().filter(|v| v.secret) {
+        if let Some(value) = &var.value {
+            secrets.set_secret(&key(&var.name), value)?;
+        }
+    }
+
+    let saved = ServiceEnvironment {
+        variables: environment.variables.iter()
+            .map(|var| ServiceEnvVar {
+                value: if var.secret { None } else { var.value.clone() },
+                ..var.clone()
+            })
+            .collect(),
+        env_files: environment.env_files.iter().map(|path| path.trim().to_string()).collect(),
+    };
+    repo.set_service_environment(service_id, &saved).context("Failed to save service environment")?;
+
+    // Secrets that were removed or are no longer secret
+    for old in previous.variables.iter().filter(|v| v.secret) {
+        if !saved.variables.iter().any(|v| v.secret && v.name == old.name) {
+            secrets.delete_secret(&key(&old.name))?;
+        }
+    }
+    Ok(saved)
+}
+
+/// Secret store keys of the services' secret variables, read before the
+/// services are deleted so their values can be removed afterwards.
+pub fn service_secret_keys<R: ServiceRepository>(repo: &R, workspace: &str, service_ids: &[u32]) -> AppResult<Vec<String>> {
+    let mut keys = Vec::new();
+    for &service_id in service_ids {
+        let environment = repo.get_service_environment(service_id).context("Failed to get service environment")?;
+        keys.extend(environment.variables.iter()
+            .filter(|var| var.secret)
+            .map(|var| service_env_key(workspace, service_id, &var.name)));
+    }
+    Ok(keys)
+}
+
+// Env files are found next to the service: its working directory, or its project's
+fn service_base_dir<R: ProjectRepository>(repo: &R, service: &Service) -> AppResult<Option<PathBuf>> {
+    if let Some(working_dir) = &service.working_dir {
+        return Ok(Some(PathBuf::from(working_dir)));
+    }
+    let project = match service.project_id {
+        Some(project_id) => get_project_by_id(repo, project_id)?,
+        None => None,
+    };
+    Ok(project.map(|p| PathBuf::from(p.path)))
+}
+
+/// Everything a service adds to its environment, with secret values filled
+/// in. Only for starting the service; mask it before returning it anywhere.
+pub fn service_launch_environment<R: ProjectRepository + ServiceRepository, S: SecretStore>(
+    repo: &R,
+    secrets: &S,
+    workspace: &str,
+    service: &Service,
+) -> AppResult<Vec<ResolvedEnvVar>> {
+    let environment = repo.get_service_environment(service.id).context("Failed to get service environment")?;
+    let base_dir = service_base_dir(repo, service)?;
+    let files = environment.env_files.iter()
+        .map(|path| service_env::env_file_path(path, base_dir.as_deref()))
+        .collect::<AppResult<Vec<_>>>()?;
+
+    let variables = environment.variables.into_iter()
+        .map(|var| {
+            if !var.secret {
+                return Ok(var);
+            }
+            let value = secrets.get_secret(&service_env_key(workspace, service.id, &var.name))?
+                .ok_or_else(|| AppError::validation(format!("Secret {} has no stored value; set it again", var.name)))?;
+            Ok(ServiceEnvVar { value: Some(value), ..var })
+        })
+        .collect::<AppResult<Vec<_>>>()?;
+
+    service_env::resolve_environment(&files, &variables)
+}
+
+/// The environment a service would start with, secrets masked.
+pub fn preview_service_environment<R: ProjectRepository + ServiceRepository, S: SecretStore>(
+    repo: &R,
+    secrets: &S,
+    workspace: &str,
+    service_id: u32,
+) -> AppResult<Vec<ResolvedEnvVar>> {
+    let service = require_service(repo, service_id)?;
+    let resolved = service_launch_environment(repo, secrets, workspace, &service)?;
+    Ok(resolved.into_iter().map(ResolvedEnvVar::masked).collect())
+}
+
+/// `.env*` files next to a service, to choose its env files from.
+pub fn find_service_env_files<R: ProjectRepository + ServiceRepository>(repo: &R, service_id: u32) -> AppResult<Vec<String>> {
+    let service = require_service(repo, service_id)?;
+    Ok(service_base_dir(repo, &service)?
+        .map(|dir| ProjectManager::find_env_files(&dir.to_string_lossy()))
+        .unwrap_or_default())
+}
+
+// Restart policy handlers
+
+pub fn get_restart_policy<R: ServiceRepository>(repo: &R, service_id: u32) -> AppResult<RestartPolicy> {
+    require_service(repo, service_id)?;
+    repo.get_restart_policy(service_id).context("Failed to get restart policy")
+}
+
+pub fn set_restart_policy<R: ServiceRepository>(repo: &R, service_id: u32, policy: &RestartPolicy) -> AppResult<()> {
+    require_service(repo, service_id)?;
+    if policy.initial_backoff_ms == 0 {
+        return Err(AppError::invalid_field("initialBackoffMs", "The first delay must be at least 1 ms"));
+    }
+    if policy.max_backoff_ms < policy.initial_backoff_ms {
+        return Err(AppError::invalid_field("maxBackoffMs", "The longest delay cannot be shorter than the first"));
+    }
+    repo.set_restart_policy(service_id, policy).context("Failed to save restart policy")
+}
+
+// Health check handlers
+
+pub fn get_health_checks<R: ServiceRepository>(repo: &R, service_id: u32) -> AppResult<HealthChecks> {
+    require_service(repo, service_id)?;
+    repo.get_health_checks(service_id).context("Failed to get health checks")
+}
+
+pub fn set_health_checks<R: ServiceRepository>(repo: &R, service_id: u32, checks: &HealthChecks) -> AppResult<()> {
+    require_service(repo, service_id)?;
+    health::validate_health_checks(checks)?;
+    repo.set_health_checks(service_id, checks).context("Failed to save health checks")
+}
+
+// Log retention handlers
+
+pub fn get_log_retention<R: ServiceRepository>(repo: &R, service_id: u32) -> AppResult<LogRetention> {
+    require_service(repo, service_id)?;
+    repo.get_log_retention(service_id).context("Failed to get log retention")
+}
+
+pub fn set_log_retention<R: ServiceRepository>(repo: &R, service_id: u32, retention: &LogRetention) -> AppResult<()> {
+    require_service(repo, service_id)?;
+    // Zero would delete every line; a limit is turned off with null instead
+    if retention.max_age_days == Some(0) {
+        return Err(AppError::invalid_field("maxAgeDays", "Keep logs for at least 1 day"));
+    }
+    if retention.max_lines == Some(0) {
+        return Err(AppError::invalid_field("maxLines", "Keep at least 1 line"));
+    }
+    if retention.max_bytes == Some(0) {
+        return Err(AppError::invalid_field("maxBytes", "Keep at least 1 byte"));
+    }
+    repo.set_log_retention(service_id, retention).context("Failed to save log retention")
+}
+
+// Log parser handlers
+
+pub fn get_log_parser<R: ServiceRepository>(repo: &R, service_id: u32) -> AppResult<LogParser> {
+    require_service(repo, service_id)?;
+    repo.get_log_parser(service_id).context("Failed to get log parser")
+}
+
+pub fn set_log_parser<R: ServiceRepository>(repo: &R, service_id: u32, parser: &LogParser) -> AppResult<()> {
+    require_service(repo, service_id)?;
+    log_parser::validate_log_parser(parser)?;
+    repo.set_log_parser(service_id, parser).context("Failed to save log parser")
+}
+
+// Service dependency and group handlers
+
+/// Every service's dependencies, for ordering starts and stops.
+pub fn service_dependencies<R: ServiceRepository>(repo: &R) -> AppResult<Dependencies> {
+    let mut dependencies = Dependencies::new();
+    for service in get_all_services(repo)? {
+        let depends_on = repo.get_service_dependencies(service.id).context("Failed to get service dependencies")?;
+        if !depends_on.is_empty() {
+            dependencies.insert(service.id, depends_on);
+        }
+    }
+    Ok(dependencies)
+}
+
+pub fn get_service_dependencies<R: ServiceRepository>(repo: &R, service_id: u32) -> AppResult<Vec<u32>> {
+    require_service(repo, service_id)?;
+    repo.get_service_dependencies(service_id).context("Failed to get service dependencies")
+}
+
+/// Replaces what a service depends on, refusing changes that would make a
+/// service wait on itself.
+pub fn set_service_dependencies<R: ServiceRepository>(repo: &R, service_id: u32, depends_on: &[u32]) -> AppResult<()> {
+    require_service(repo, service_id)?;
+    for &dependency in depends_on {
+        if dependency == service_id {
+            return Err(AppError::invalid_field("dependencies", "A service cannot depend on itself"));
+        }
+        if get_service_by_id(repo, dependency)?.is_none() {
+            return Err(AppError::invalid_field("dependencies", format!("Service {} does not exist", dependency)));
+        }
+    }
+
+    let mut dependencies = service_dependencies(repo)?;
+    dependencies.insert(service_id, depends_on.to_vec());
+    if let Some(cycle) = service_graph::find_cycle(&dependencies) {
+        let names = cycle.iter()
+            .map(|&id| Ok(get_service_by_id(repo, id)?.map(|s| s.name).unwrap_or_else(|| id.to_string())))
+            .collect::<AppResult<Vec<_>>>()?;
+        return Err(AppError::invalid_field("dependencies", format!("This would create a cycle: {}", names.join(" → "))));
+    }
+
+    repo.set_service_dependencies(service_id, depends_on).context("Failed to save service dependencies")
+}
+
+fn validate_service_group<R: ServiceRepository>(repo: &R, name: &str, service_ids: &[u32]) -> AppResult<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::invalid_field("name", "Group name is required"));
+    }
+    for &service_id in service_ids {
+        if get_service_by_id(repo, service_id)?.is_none() {
+            return Err(AppError::invalid_field("serviceIds", format!("Service {} does not exist", service_id)));
+        }
+    }
+    Ok(())
+}
+
+pub fn get_service_groups<R: ServiceGroupRepository>(repo: &R) -> AppResult<Vec<ServiceGroup>> {
+    repo.get_service_groups().context("Failed to get service groups")
+}
+
+pub fn require_service_group<R: ServiceGroupRepository>(repo: &R, id: u32) -> AppResult<ServiceGroup> {
+    repo.get_service_group_by_id(id)
+        .context("Failed to get service group")?
+        .ok_or_else(|| AppError::not_found("Service group", id))
+}
+
+pub fn create_service_group<R: ServiceRepository + ServiceGroupRepository>(
+    repo: &R,
+    name: String,
+    service_ids: &[u32],
+) -> AppResult<ServiceGroup> {
+    validate_service_group(repo, &name, service_ids)?;
+    repo.create_service_group(name.trim().to_string(), service_ids).context("Failed to create service group")
+}
+
+pub fn update_service_group<R: ServiceRepository + ServiceGroupRepository>(repo: &R, group: &ServiceGroup) -> AppResult<()> {
+    require_service_group(repo, group.id)?;
+    validate_service_group(repo, &group.name, &group.service_ids)?;
+    let group = ServiceGroup { name: group.name.trim().to_string(), ..group.clone() };
+    repo.update_service_group(&group).context("Failed to update service group")
+}
+
+pub fn delete_service_group<R: ServiceGroupRepository>(repo: &R, id: u32) -> AppResult<()> {
+    require_service_group(repo, id)?;
+    repo.delete_service_group(id).context("Failed to delete service group")
+}
+
+/// The group's services and everything they depend on, each after its
+/// dependencies.
+pub fn group_start_order<R: ServiceRepository + ServiceGroupRepository>(repo: &R, group_id: u32) -> AppResult<Vec<Service>> {
+    let group = require_service_group(repo, group_id)?;
+    let order = service_graph::start_order(&group.service_ids, &service_dependencies(repo)?)?;
+    order.into_iter().map(|id| require_service(repo, id)).collect()
+}
+
+/// The group's own services, dependents before what they depend on.
+/// Dependencies outside the group keep running.
+pub fn group_stop_order<R: ServiceRepository + ServiceGroupRepository>(repo: &R, group_id: u32) -> AppResult<Vec<Service>> {
+    let group = require_service_group(repo, group_id)?;
+    let mut services = group_start_order(repo, group_id)?;
+    services.retain(|s| group.service_ids.contains(&s.id));
+    services.reverse();
+    Ok(services)
+}
+
+// Git handlers
+
+/// Path of the git repository inside a project's directory.
+pub fn project_repository_path<R: ProjectRepository>(repo: &R, project_id: u32) -> AppResult<String> {
+    let project = get_project_by_id(repo, project_id)?
+        .ok_or_else(|| AppError::not_found("Project", project_id))?;
+
+    ProjectManager::find_git_repository(&project.path)
+        .ok_or_else(|| AppError::not_found("Git repository", &project.path))
+}
+
+pub fn get_git_status_for_project<R: ProjectRepository + GitRepoRepository>(
+    repo: &R,
+    project_id: u32,
+) -> AppResult<Option<GitStatus>> {
+    let repo_path = project_repository_path(repo, project_id)?;
+
+    // Get git status
+    let mut git_status = git::get_git_status(&repo_path)
+        .context("Failed to get git status")?;
+
+    // Update git_status with project info
+    git_status.id = repo.get_git_repo_by_project_id(project_id)
+        .context("Failed to get git repo")?
+        .map(|g| g.id)
+        .unwrap_or(0);
+    git_status.project_id = project_id;
+
+    // Update or create git_repo record
+    if git_status.id == 0 {
+        let _ = repo.create_git_repo(project_id, repo_path.clone());
+        git_status.id = repo.get_git_repo_by_project_id(project_id)
+            .context("Failed to get git repo")?
+            .map(|g| g.id)
+            .unwrap_or(0);
+    } else {
+        repo.update_git_repo(&git_status)
+            .context("Failed to update git repo")?;
+    }
+
+    Ok(Some(git_status))
+}
+
+pub fn get_all_git_statuses<R: ProjectRepository + GitRepoRepository>(repo: &R) -> AppResult<Vec<GitStatus>> {
+    let projects = get_all_projects(repo)?;
+
+    let mut statuses = Vec::new();
+    for project in projects {
+        if let Some(repo_path) = ProjectManager::find_git_repository(&project.path) {
+            if let Ok(mut git_status) = git::get_git_status(&repo_path) {
+                git_status.project_id = project.id;
+                if let Ok(Some(git_repo)) = repo.get_git_repo_by_project_id(project.id) {
+                    git_status.id = git_repo.id;
+                }
+                statuses.push(git_status);
+            }
+        }
+    }
+
+    Ok(statuses)
+}
+
+pub fn git_commit<R: ProjectRepository>(repo: &R, project_id: u32, message: &str) -> AppResult<()> {
+    let repo_path = project_repository_path(repo, project_id)?;
+    git::commit_all(&repo_path, message)
+}
+
+// Query history and saved query handlers
+
+/// Records a browser query in the history, with its error if it failed.
+pub fn record_query<R: QueryRepository>(
+    repo: &R,
+    database_id: String,
+    query: String,
+    duration: Duration,
+    outcome: &AppResult<QueryResult>,
+) -> AppResult<QueryHistoryEntry> {
+    let outcome = outcome.as_ref().map(|result| Some(result.row_count as u64));
+    add_query_history(repo, database_id, query, duration, outcome)
+}
+
+/// Records a paged query when it is prepared. Its rows are read later, so
+/// the entry has no row count.
+pub fn record_query_start<R: QueryRepository>(
+    repo: &R,
+    database_id: String,
+    query: String,
+    duration: Duration,
+    outcome: &AppResult<QueryStarted>,
+) -> AppResult<QueryHistoryEntry> {
+    let outcome = outcome.as_ref().map(|_| None);
+    add_query_history(repo, database_id, query, duration, outcome)
+}
+
+fn add_query_history<R: QueryRepository>(
+    repo: &R,
+    database_id: String,
+    query: String,
+    duration: Duration,
+    outcome: Result<Option<u64>, &AppError>,
+) -> AppResult<QueryHistoryEntry> {
+    let (row_count, error) = match outcome {
+        Ok(row_count) => (row_count, None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+
+    repo.add_query_history(NewQueryHistory {
+        database_id,
+        query,
+        duration_ms: duration.as_millis() as u64,
+        row_count,
+        error,
+    }).context("Failed to record query history")
+}
+
+pub fn get_query_history<R: QueryRepository>(
+    repo: &R,
+    search: Option<&str>,
+    database_id: Option<&str>,
+    limit: u32,
+) -> AppResult<Vec<QueryHistoryEntry>> {
+    let search = search.map(str::trim).filter(|s| !s.is_empty());
+    repo.search_query_history(search, database_id, limit).context("Failed to get query history")
+}
+
+pub fn clear_query_history<R: QueryRepository>(repo: &R) -> AppResult<()> {
+    repo.clear_query_history().context("Failed to clear query history")
+}
+
+fn validate_saved_query(name: &str, query: &str) -> AppResult<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::invalid_field("name", "Saved query name is required"));
+    }
+    if query.trim().is_empty() {
+        return Err(AppError::invalid_field("query", "Saved query text is required"));
+    }
+    Ok(())
+}
+
+pub fn save_query<R: QueryRepository>(
+    repo: &R,
+    name: String,
+    query: String,
+    database_id: Option<String>,
+    description: Option<String>,
+) -> AppResult<SavedQuery> {
+    validate_saved_query(&name, &query)?;
+    repo.create_saved_query(name.trim().to_string(), query, database_id, description)
+        .context("Failed to save query")
+}
+
+pub fn get_saved_queries<R: QueryRepository>(repo: &R, search: Option<&str>) -> AppResult<Vec<SavedQuery>> {
+    let search = search.map(str::trim).filter(|s| !s.is_empty());
+    repo.search_saved_queries(search).context("Failed to get saved queries")
+}
+
+pub fn update_saved_query<R: QueryRepository>(repo: &R, saved_query: &SavedQuery) -> AppResult<()> {
+    validate_saved_query(&saved_query.name, &saved_query.query)?;
+    repo.get_saved_query_by_id(saved_query.id)
+        .context("Failed to get saved query")?
+        .ok_or_else(|| AppError::not_found("Saved query", saved_query.id))?;
+
+    repo.update_saved_query(&SavedQuery {
+        name: saved_query.name.trim().to_string(),
+        ..saved_query.clone()
+    }).context("Failed to update saved query")
+}
+
+pub fn delete_saved_query<R: QueryRepository>(repo: &R, id: u32) -> AppResult<()> {
+    repo.delete_saved_query(id).context("Failed to delete saved query")
+}
+
+fn validate_connection_profile(name: &str, host: &str, port: u16, username: &str) -> AppResult<()> {
+    if name.trim().is_empty() {
+        return Err(AppError::invalid_field("name", "Profile name is required"));
+    }
+    if host.trim().is_empty() {
+        return Err(AppError::invalid_field("host", "Host is required"));
+    }
+    if port == 0 {
+        return Err(AppError::invalid_field("port", "Port must be between 1 and 65535"));
+    }
+    if username.trim().is_empty() {
+        return Err(AppError::invalid_field("username", "Username is required"));
+    }
+    Ok(())
+}
+
+/// Stores a password, or removes it when empty.
+fn store_password<S: SecretStore>(secrets: &S, key: &str, password: &str) -> AppResult<()> {
+    if password.is_empty() {
+        secrets.delete_secret(key)
+    } else {
+        secrets.set_secret(key, password)
+    }
+}
+
+pub fn create_connection_profile<R: ProjectRepository + ConnectionProfileRepository, S: SecretStore>(
+    repo: &R,
+    secrets: &S,
+    workspace: &str,
+    form: ConnectionProfileForm,
+) -> AppResult<ConnectionProfile> {
+    validate_connection_profile(&form.name, &form.host, form.port, &form.username)?;
+    repo.get_project_by_id(form.project_id)
+        .context("Failed to get project")?
+        .ok_or_else(|| AppError::not_found("Project", form.project_id))?;
+
+    let profile = repo.create_connection_profile(&ConnectionProfileForm {
+        name: form.name.trim().to_string(),
+        host: form.host.trim().to_string(),
+        password: None,
+        ..form.clone()
+    }).context("Failed to create connection profile")?;
+
+    let key = connection_password_key(workspace, profile.id);
+    // Never leave a profile behind whose password could not be stored
+    if let Err(e) = store_password(secrets, &key, form.password.as_deref().unwrap_or_default()) {
+        let _ = repo.delete_connection_profile(profile.id);
+        return Err(e);
+    }
+    Ok(profile)
+}
+
+pub fn get_connection_profiles<R: ConnectionProfileRepository>(
+    repo: &R,
+    project_id: Option<u32>,
+) -> AppResult<Vec<ConnectionProfile>> {
+    repo.get_connection_profiles(project_id).context("Failed to get connection profiles")
+}
+
+/// `password` of `None` keeps the stored password; an empty one clears it.
+pub fn update_connection_profile<R: ConnectionProfileRepository, S: SecretStore>(
+    repo: &R,
+    secrets: &S,
+    workspace: &str,
+    profile: &ConnectionProfile,
+    password: Option<String>,
+) -> AppResult<()> {
+    validate_connection_profile(&profile.name, &profile.host, profile.port, &profile.username)?;
+    require_connection_profile(repo, profile.id)?;
+
+    repo.update_connection_profile(&ConnectionProfile {
+        name: profile.name.trim().to_string(),
+        host: profile.host.trim().to_string(),
+        ..profile.clone()
+    }).context("Failed to update connection profile")?;
+
+    match password {
+        Some(password) => store_password(secrets, &connection_password_key(workspace, profile.id), &password),
+        None => Ok(()),
+    }
+}
+
+pub fn delete_connection_profile<R: ConnectionProfileRepository, S: SecretStore>(
+    repo: &R,
+    secrets: &S,
+    workspace: &str,
+    id: u32,
+) -> AppResult<()> {
+    repo.delete_connection_profile(id).context("Failed to delete connection profile")?;
+    secrets.delete_secret(&connection_password_key(workspace, id))
+}
+
+pub fn require_connection_profile<R: ConnectionProfileRepository>(repo: &R, id: u32) -> AppResult<ConnectionProfile> {
+    repo.get_connection_profile_by_id(id)
+        .context("Failed to get connection profile")?
+        .ok_or_else(|| AppError::not_found("Connection profile", id))
+}
+
+/// A profile with its stored password, ready to connect.
+pub fn connection_profile_target<R: ConnectionProfileRepository, S: SecretStore>(
+    repo: &R,
+    secrets: &S,
+    workspace: &str,
+    id: u32,
+) -> AppResult<ConnectorTarget> {
+    let profile = require_connection_profile(repo, id)?;
+    let password = secrets.get_secret(&connection_password_key(workspace, id))?;
+    Ok(ConnectorTarget::Profile { profile, password })
+}
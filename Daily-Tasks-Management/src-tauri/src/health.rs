@@ -0,0 +1,172 @@
+use crate::error::{AppError, AppResult};
+use crate::model::{HealthChecks, LogEntry, Probe, ProbeCheck};
+use regex::Regex;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+// Probes faster than this would mostly measure themselves
+const MIN_INTERVAL_MS: u64 = 100;
+
+/// What a probe of one process may look at besides the network.
+pub struct ProbeContext {
+    pub service_id: u32,
+    pub logs: Arc<Mutex<HashMap<u32, Vec<LogEntry>>>>,
+    /// Lines with a lower id were logged by an earlier process.
+    pub first_log_id: u32,
+    pub working_dir: Option<String>,
+    pub env: Vec<(String, String)>,
+}
+
+/// Host, port and path of an `http://` URL on this machine.
+pub fn parse_local_url(url: &str) -> AppResult<(String, u16, String)> {
+    let rest = url.strip_prefix("http://")
+        .ok_or_else(|| AppError::invalid_field("url", "Only http:// URLs can be probed"))?;
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+
+    // [::1]:8080 keeps its colons inside the brackets
+    let (host, port) = match authority.rfind(':') {
+        Some(i) if !authority[i..].contains(']') => (&authority[..i], Some(&authority[i + 1..])),
+        _ => (authority, None),
+    };
+    let port = match port {
+        Some(port) => port.parse::<u16>()
+            .map_err(|_| AppError::invalid_field("url", format!("{} is not a valid port", port)))?,
+        None => 80,
+    };
+
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if !matches!(host, "localhost" | "127.0.0.1" | "::1") {
+        return Err(AppError::invalid_field("url", "Health checks only reach localhost"));
+    }
+    Ok((host.to_string(), port, path.to_string()))
+}
+
+fn validate_probe(probe: &Probe, liveness: bool) -> AppResult<()> {
+    match &probe.check {
+        ProbeCheck::Http { url, expected_status } => {
+            parse_local_url(url)?;
+            if !(100..=599).contains(expected_status) {
+                return Err(AppError::invalid_field("expectedStatus", "Expected status must be between 100 and 599"));
+            }
+        }
+        ProbeCheck::Tcp { port } => {
+            if *port == 0 {
+                return Err(AppError::invalid_field("port", "Port must be between 1 and 65535"));
+            }
+        }
+        ProbeCheck::Log { pattern } => {
+            // A line that appeared once cannot tell whether the service is still alive
+            if liveness {
+                return Err(AppError::invalid_field("kind", "Log patterns can only check readiness"));
+            }
+            Regex::new(pattern).map_err(|e| AppError::invalid_field("pattern", e.to_string()))?;
+        }
+        ProbeCheck::Command { command } => {
+            if command.trim().is_empty() {
+                return Err(AppError::invalid_field("command", "Command is required"));
+            }
+        }
+    }
+
+    if probe.interval_ms < MIN_INTERVAL_MS {
+        return Err(AppError::invalid_field("intervalMs", format!("Interval must be at least {} ms", MIN_INTERVAL_MS)));
+    }
+    if probe.timeout_ms == 0 {
+        return Err(AppError::invalid_field("timeoutMs", "Timeout must be at least 1 ms"));
+    }
+    if probe.failure_threshold == 0 {
+        return Err(AppError::invalid_field("failureThreshold", "Failure threshold must be at least 1"));
+    }
+    Ok(())
+}
+
+pub fn validate_health_checks(checks: &HealthChecks) -> AppResult<()> {
+    if let Some(readiness) = &checks.readiness {
+        validate_probe(readiness, false)?;
+    }
+    if let Some(liveness) = &checks.liveness {
+        validate_probe(liveness, true)?;
+    }
+    Ok(())
+}
+
+/// Runs a probe once. `Ok` and `Err` both carry a message for the status.
+pub async fn run_probe(probe: &Probe, context: &ProbeContext) -> Result<String, String> {
+    let timeout = Duration::from_millis(probe.timeout_ms);
+    match tokio::time::timeout(timeout, check(&probe.check, context)).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("No answer within {} ms", probe.timeout_ms)),
+    }
+}
+
+async fn check(check: &ProbeCheck, context: &ProbeContext) -> Result<String, String> {
+    match check {
+        ProbeCheck::Http { url, expected_status } => {
+            let status = http_status(url).await?;
+            if status == *expected_status {
+                Ok(format!("{} answered {}", url, status))
+            } else {
+                Err(format!("{} answered {}, expected {}", url, status, expected_status))
+            }
+        }
+        ProbeCheck::Tcp { port } => {
+            TcpStream::connect(("localhost", *port)).await
+                .map(|_| format!("Port {} accepts connections", port))
+                .map_err(|e| format!("Port {}: {}", port, e))
+        }
+        ProbeCheck::Log { pattern } => {
+            let regex = Regex::new(pattern).map_err(|e| e.to_string())?;
+            let logs = context.logs.lock().await;
+            logs.get(&context.service_id)
+                .and_then(|lines| lines.iter().find(|l| l.id >= context.first_log_id && regex.is_match(&l.message)))
+                .map(|line| format!("Logged: {}", line.message))
+                .ok_or_else(|| format!("Nothing logged matches {}", pattern))
+        }
+        ProbeCheck::Command { command } => {
+            let mut cmd = crate::services::shell_command(command);
+            if let Some(working_dir) = &context.working_dir {
+                cmd.current_dir(working_dir);
+            }
+            cmd.envs(context.env.iter().cloned());
+            cmd.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+            // A probe that times out is dropped, and its process with it
+            cmd.kill_on_drop(true);
+
+            let status = cmd.status().await.map_err(|e| format!("Failed to run {}: {}", command, e))?;
+            match status.code() {
+                Some(0) => Ok(format!("{} exited with 0", command)),
+                Some(code) => Err(format!("{} exited with {}", command, code)),
+                None => Err(format!("{} was killed", command)),
+            }
+        }
+    }
+}
+
+// A minimal HTTP/1.1 GET; only the status line is read
+async fn http_status(url: &str) -> Result<u16, String> {
+    let (host, port, path) = parse_local_url(url).map_err(|e| e.to_string())?;
+    let mut stream = TcpStream::connect((host.as_str(), port)).await
+        .map_err(|e| format!("{}: {}", url, e))?;
+
+    let host_header = if host.contains(':') { format!("[{}]:{}", host, port) } else { format!("{}:{}", host, port) };
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nUser-Agent: daily-tasks-health-check\r\n\r\n",
+        path, host_header
+    );
+    stream.write_all(request.as_bytes()).await.map_err(|e| format!("{}: {}", url, e))?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line).await.map_err(|e| format!("{}: {}", url, e))?;
+    status_line.split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| format!("{} did not answer with HTTP", url))
+}
@@ -5,25 +5,183 @@ pub mod database;
 pub mod optimization;
 pub mod projects;
 pub mod services;
+pub mod command_line;
+pub mod service_env;
+pub mod health;
+pub mod log_parser;
+pub mod log_store;
+pub mod service_metrics;
+pub mod service_graph;
+pub mod service_groups;
+pub mod auto_start;
+pub mod settings;
 pub mod git;
-// pub mod database_browser; // Temporarily removed for business logic focus 
+pub mod workspace;
+pub mod backup;
+pub mod error;
+pub mod repository;
+pub mod memory;
+pub mod handlers;
+pub mod database_browser;
+pub mod database_discovery;
+pub mod database_schema;
+pub mod database_value;
+pub mod database_export;
+pub mod database_diff;
+pub mod database_edit;
+pub mod database_query;
+pub mod database_connector;
+pub mod postgres_connector;
+pub mod secrets;
 
-use database::Database;
+use auto_start::{AutoStartEvent, AutoStartLog};
+use backup::{BackupManager, RetentionPolicy};
+use commands::{
+    AutoStartLogState, DbState, LogStoreState, LogWriterState, MetricsState, QuerySessionsState, SecretsState,
+    ServiceMgrState, SettingsState, WorkspaceState,
+};
+use database_edit::WriteModes;
+use database_query::QuerySessions;
+use log_store::{LogStore, LogWriter};
+use secrets::FileSecretStore;
+use service_metrics::{MetricsHistory, ResourceSampler};
 use services::ServiceManager;
-use std::sync::Mutex;
+use settings::SettingsManager;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
+use workspace::WorkspaceManager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Initialize database
-    let db = Database::new("tasks.db").expect("Failed to initialize database");
-    let db_state = Mutex::new(db);
-    
-    // Initialize service manager
-    let service_manager = ServiceManager::new();
+    let args: Vec<String> = std::env::args().collect();
+
+    // Initialize service manager; its output is stored by the log writer task
+    let (log_writer, log_receiver) = LogWriter::channel();
+    let service_manager = ServiceManager::with_log_writer(log_writer.clone());
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .manage(db_state)
+        .setup(move |app| {
+            // Resolve the data directory and open the active workspace's database
+            let data_dir = workspace::resolve_data_dir(
+                &args,
+                std::env::var(workspace::DATA_DIR_ENV).ok(),
+                app.path().app_data_dir()?,
+            );
+            let requested = workspace::requested_workspace(
+                &args,
+                std::env::var(workspace::WORKSPACE_ENV).ok(),
+            );
+            // Secrets are shared by all workspaces and keyed by workspace name
+            let secrets = FileSecretStore::new(data_dir.join(secrets::SECRETS_FILE));
+            let safe_mode = settings::safe_mode_requested(&args, std::env::var(settings::SAFE_MODE_ENV).ok());
+            let settings = SettingsManager::new(data_dir.join(settings::SETTINGS_FILE), safe_mode)?;
+            if let Some(e) = settings.load_error() {
+                eprintln!("{}", e);
+            }
+            let legacy_database = Path::new(workspace::LEGACY_DATABASE_FILE);
+            let workspaces = WorkspaceManager::new(data_dir, requested, Some(legacy_database))?;
+            let backups = BackupManager::new(RetentionPolicy::from_args(&args));
+            if let Some(legacy) = workspaces.imported_from() {
+                eprintln!("Imported {} into the default workspace", legacy.display());
+                backup::snapshot_import(&workspaces.database_path(workspace::DEFAULT_WORKSPACE), &backups)?;
+            }
+
+            let db_path = workspaces.active_database_path()?;
+            let db = backup::open_database(&db_path, &backups)?;
+            let logs = LogStore::new(&workspaces.active_logs_path()?.to_string_lossy())?;
+
+            app.manage(Mutex::new(db));
+            app.manage(Mutex::new(logs));
+            app.manage(log_writer);
+            app.manage(workspaces);
+            app.manage(backups);
+            app.manage(secrets);
+            app.manage(Arc::new(QuerySessions::new()));
+            app.manage(WriteModes::new());
+            app.manage(settings);
+            app.manage(AutoStartLog::new());
+            app.manage(MetricsHistory::new());
+
+            // Store service output in batches
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(log_store::write_batches(log_receiver, move |entries| {
+                let stored = handle.state::<LogStoreState>().lock()
+                    .map_err(error::AppError::from)
+                    .and_then(|logs| logs.insert(entries));
+                if let Err(e) = stored {
+                    eprintln!("Failed to store {} log lines: {}", entries.len(), e);
+                }
+            }));
+
+            // Forward state, readiness and health changes to the frontend
+            let handle = app.handle().clone();
+            let mut statuses = handle.state::<ServiceMgrState>().subscribe();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    match statuses.recv().await {
+                        Ok(status) => {
+                            let _ = handle.emit(services::SERVICE_STATUS_EVENT, status);
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            });
+
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                run_auto_start(&handle).await;
+            });
+
+            // Periodically snapshot whichever workspace is active
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(backup::SCHEDULE_CHECK_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    run_scheduled_backup(&handle);
+                }
+            });
+
+            // Sample the process trees of running services into the metrics history
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut sampler = ResourceSampler::new();
+                let mut interval = tokio::time::interval(service_metrics::SAMPLE_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let running = handle.state::<ServiceMgrState>().running_pids().await;
+                    let samples = sampler.sample(&running);
+                    if let Err(e) = handle.state::<MetricsState>().record(samples) {
+                        eprintln!("Failed to record service metrics: {}", e);
+                    }
+                }
+            });
+
+            // Trim stored logs to each service's retention
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(log_store::RETENTION_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    run_log_retention(&handle);
+                }
+            });
+
+            // Release read locks held by paged queries nobody reads any more
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut interval = tokio::time::interval(database_query::IDLE_QUERY_TIMEOUT / 4);
+                loop {
+                    interval.tick().await;
+                    handle.state::<QuerySessionsState>().close_idle();
+                }
+            });
+
+            Ok(())
+        })
         .manage(service_manager)
         .invoke_handler(tauri::generate_handler![
             commands::greet,
@@ -38,12 +196,14 @@ pub fn run() {
             // Project management commands
             commands::add_project,
             commands::remove_project,
+            commands::preview_project_removal,
             commands::get_all_projects,
             commands::get_project_by_id,
             commands::update_project,
             // Service management commands
             commands::register_service,
             commands::unregister_service,
+            commands::update_service,
             commands::get_all_services,
             commands::get_service_by_id,
             commands::start_service,
@@ -51,15 +211,156 @@ pub fn run() {
             commands::restart_service,
             commands::get_service_status,
             commands::get_service_logs,
+            commands::get_service_state,
+            commands::get_service_crashes,
+            commands::get_service_metrics,
+            commands::query_service_logs,
+            commands::clear_service_logs,
+            commands::get_log_retention,
+            commands::set_log_retention,
+            commands::get_log_parser,
+            commands::set_log_parser,
+            commands::get_restart_policy,
+            commands::set_restart_policy,
+            // Health check commands
+            commands::get_health_checks,
+            commands::set_health_checks,
+            // Service dependency and group commands
+            commands::get_service_dependencies,
+            commands::set_service_dependencies,
+            commands::get_service_groups,
+            commands::create_service_group,
+            commands::update_service_group,
+            commands::delete_service_group,
+            commands::start_service_group,
+            commands::stop_service_group,
+            // Service environment commands
+            commands::get_service_environment,
+            commands::set_service_environment,
+            commands::preview_service_environment,
+            commands::find_service_env_files,
+            // Settings and auto-start commands
+            commands::get_app_settings,
+            commands::update_app_settings,
+            commands::is_safe_mode,
+            commands::get_settings_load_error,
+            commands::get_auto_start_events,
             // Git commands
             commands::get_git_status_for_project,
             commands::get_all_git_statuses,
             commands::git_commit,
             commands::git_push,
             commands::git_pull,
+            // Workspace commands
+            commands::list_workspaces,
+            commands::get_active_workspace,
+            commands::create_workspace,
+            commands::switch_workspace,
+            // Backup commands
+            commands::list_backups,
+            commands::inspect_backup,
+            commands::create_backup,
+            commands::restore_backup,
+            // Database browser commands
+            commands::list_project_databases,
+            commands::list_database_tables,
+            commands::get_database_schema,
+            commands::get_table_data,
+            commands::execute_database_query,
+            commands::start_database_query,
+            commands::fetch_query_page,
+            commands::get_query_progress,
+            commands::cancel_database_query,
+            commands::export_database_data,
+            commands::diff_databases,
+            commands::get_database_write_mode,
+            commands::set_database_write_mode,
+            commands::get_editable_rows,
+            commands::preview_database_changes,
+            commands::apply_database_changes,
+            commands::get_query_parameters,
+            // Query history and saved query commands
+            commands::get_query_history,
+            commands::clear_query_history,
+            commands::save_query,
+            commands::get_saved_queries,
+            commands::update_saved_query,
+            commands::delete_saved_query,
+            // Connection profile commands
+            commands::create_connection_profile,
+            commands::get_connection_profiles,
+            commands::update_connection_profile,
+            commands::delete_connection_profile,
+            commands::test_connection_profile,
             // Utility commands
             commands::open_in_vscode
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Services run in their own process groups, so nothing else ends them
+            if let tauri::RunEvent::Exit = event {
+                tauri::async_runtime::block_on(async {
+                    app.state::<ServiceMgrState>().stop_all_services().await;
+                    app.state::<LogWriterState>().flush().await;
+                });
+            }
+        });
+}
+
+fn run_scheduled_backup(handle: &tauri::AppHandle) {
+    let backups = handle.state::<BackupManager>();
+    let db = handle.state::<DbState>();
+    let Ok(db) = db.lock() else {
+        return;
+    };
+
+    if let Err(e) = backups.snapshot_if_due(&db, backup::SCHEDULED_SNAPSHOT_AGE) {
+        eprintln!("Scheduled backup failed: {}", e);
+    }
+}
+
+fn run_log_retention(handle: &tauri::AppHandle) {
+    let db = handle.state::<DbState>();
+    let logs = handle.state::<LogStoreState>();
+    let (Ok(db), Ok(logs)) = (db.lock(), logs.lock()) else {
+        return;
+    };
+
+    if let Err(e) = log_store::apply_retention_all(&logs, &*db, chrono::Utc::now()) {
+        eprintln!("Log retention failed: {}", e);
+    }
+}
+
+/// Starts the active workspace's `auto_start` services, reporting each step
+/// as an event and in the auto-start log.
+async fn run_auto_start(handle: &tauri::AppHandle) {
+    let log = handle.state::<AutoStartLogState>();
+    let report = |event: AutoStartEvent| {
+        let _ = log.push(event.clone());
+        let _ = handle.emit(auto_start::AUTO_START_EVENT, event);
+    };
+
+    match handle.state::<SettingsState>().auto_start_skip_reason() {
+        Ok(None) => {}
+        Ok(Some(reason)) => return report(AutoStartEvent::Skipped { reason }),
+        Err(e) => return report(AutoStartEvent::Skipped { reason: e.to_string() }),
+    }
+
+    let db = handle.state::<DbState>();
+    let services = db.lock()
+        .map_err(error::AppError::from)
+        .and_then(|db| Ok((handlers::get_all_services(&*db)?, handlers::service_dependencies(&*db)?)));
+    let (services, dependencies) = match services {
+        Ok(services) => services,
+        Err(e) => return report(AutoStartEvent::Skipped { reason: e.to_string() }),
+    };
+
+    let workspaces = handle.state::<WorkspaceState>();
+    let secrets = handle.state::<SecretsState>();
+    let settings = handle.state::<SettingsState>();
+    let launch = |service: &model::Service| {
+        commands::service_launch(&db, &workspaces, &secrets, &settings, service.id).map(|(_, options)| options)
+    };
+    auto_start::auto_start_services(&handle.state::<ServiceMgrState>(), services, &dependencies, launch, report).await;
 }
@@ -0,0 +1,293 @@
+use crate::error::{AppError, AppResult};
+use crate::model::{LogFormat, LogParser};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Levels a log entry can have, least severe first.
+pub const LOG_LEVELS: [&str; 4] = ["debug", "info", "warn", "error"];
+
+// Keys holding what a line is about rather than extra fields
+const LEVEL_KEYS: [&str; 4] = ["level", "lvl", "severity", "levelname"];
+const MESSAGE_KEYS: [&str; 2] = ["msg", "message"];
+const TIME_KEYS: [&str; 3] = ["time", "timestamp", "ts"];
+// A level prefix is only looked for among the first words
+const PREFIX_WORDS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// One line of output, ready to be logged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedLine {
+    pub level: String,
+    pub message: String,
+    pub fields: BTreeMap<String, String>,
+}
+
+impl ParsedLine {
+    pub fn plain(level: &str, message: String) -> Self {
+        ParsedLine { level: level.to_string(), message, fields: BTreeMap::new() }
+    }
+}
+
+pub fn validate_log_parser(parser: &LogParser) -> AppResult<()> {
+    if !LOG_LEVELS.contains(&parser.stderr_level.as_str()) {
+        return Err(AppError::invalid_field(
+            "stderrLevel",
+            format!("Level must be one of {}", LOG_LEVELS.join(", ")),
+        ));
+    }
+    Ok(())
+}
+
+/// Detects the level, message and fields of `line` in the formats `parser`
+/// allows. Lines without a level get the stream's.
+pub fn parse_line(parser: &LogParser, stream: Stream, line: &str) -> ParsedLine {
+    let text = strip_ansi(line);
+    let detected = match parser.format {
+        LogFormat::Auto => parse_json(&text)
+            .or_else(|| parse_logfmt(&text, true))
+            .map(|mut parsed| {
+                // Structured lines without a level may still start with one
+                if parsed.level.is_none() {
+                    parsed.level = prefix_level(&parsed.message);
+                }
+                parsed
+            })
+            .unwrap_or_else(|| Detected::unstructured(&text, prefix_level(&text))),
+        LogFormat::Json => parse_json(&text).unwrap_or_else(|| Detected::unstructured(&text, None)),
+        LogFormat::Logfmt => parse_logfmt(&text, false).unwrap_or_else(|| Detected::unstructured(&text, None)),
+        LogFormat::Prefix => Detected::unstructured(&text, prefix_level(&text)),
+        LogFormat::Plain => Detected::unstructured(&text, None),
+    };
+
+    let level = detected.level.map(str::to_string).unwrap_or_else(|| match stream {
+        Stream::Stdout => "info".to_string(),
+        Stream::Stderr => parser.stderr_level.clone(),
+    });
+    // Colors only survive where the message is the line itself
+    let message = if parser.keep_ansi && detected.message == text {
+        line.to_string()
+    } else {
+        detected.message
+    };
+    ParsedLine { level, message, fields: detected.fields }
+}
+
+struct Detected {
+    level: Option<&'static str>,
+    message: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl Detected {
+    fn unstructured(text: &str, level: Option<&'static str>) -> Self {
+        Detected { level, message: text.to_string(), fields: BTreeMap::new() }
+    }
+}
+
+/// Removes ANSI escape sequences: colors and other CSI sequences, and OSC
+/// sequences such as terminal titles and hyperlinks.
+pub fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters and intermediates up to a final byte in @..~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: ends with BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Two-character sequences such as ESC 7
+            Some(_) | None => {}
+        }
+    }
+    out
+}
+
+/// Maps the level names and numbers of common loggers to `LOG_LEVELS`.
+pub fn normalize_level(level: &str) -> Option<&'static str> {
+    match level.to_ascii_lowercase().as_str() {
+        "trace" | "debug" | "verbose" | "fine" | "finer" | "finest" => Some("debug"),
+        "info" | "information" | "notice" => Some("info"),
+        "warn" | "warning" => Some("warn"),
+        "error" | "err" | "fatal" | "critical" | "crit" | "panic" | "alert" | "emerg" | "severe" => Some("error"),
+        _ => None,
+    }
+}
+
+// pino and bunyan: 10 trace, 20 debug, 30 info, 40 warn, 50 error, 60 fatal
+fn numeric_level(level: f64) -> &'static str {
+    if level < 30.0 {
+        "debug"
+    } else if level < 40.0 {
+        "info"
+    } else if level < 50.0 {
+        "warn"
+    } else {
+        "error"
+    }
+}
+
+fn parse_json(text: &str) -> Option<Detected> {
+    if !text.trim_start().starts_with('{') {
+        return None;
+    }
+    let mut object: Map<String, Value> = serde_json::from_str(text.trim()).ok()?;
+
+    let level = LEVEL_KEYS.iter().find_map(|key| match object.get(*key)? {
+        Value::String(level) => normalize_level(level),
+        Value::Number(level) => level.as_f64().map(numeric_level),
+        _ => None,
+    });
+    // tracing nests the message with the event's fields
+    if let Some(Value::Object(nested)) = object.remove("fields") {
+        for (key, value) in nested {
+            object.entry(key).or_insert(value);
+        }
+    }
+    let message = MESSAGE_KEYS.iter().find_map(|key| match object.get(*key)? {
+        Value::String(message) => Some(message.clone()),
+        _ => None,
+    });
+
+    let fields = object
+        .into_iter()
+        .filter(|(key, _)| !is_reserved(key, message.is_some()))
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(value) => value,
+                value => value.to_string(),
+            };
+            (key, value)
+        })
+        .collect();
+    Some(Detected { level, message: message.unwrap_or_else(|| text.to_string()), fields })
+}
+
+fn is_reserved(key: &str, has_message: bool) -> bool {
+    LEVEL_KEYS.contains(&key)
+        || TIME_KEYS.contains(&key)
+        || (has_message && MESSAGE_KEYS.contains(&key))
+        // bunyan's format version
+        || key == "v"
+}
+
+/// `key=value` pairs separated by spaces, values optionally in double
+/// quotes. In auto mode a line also needs a level or message key, so that
+/// plain output with an `=` in it is left alone.
+fn parse_logfmt(text: &str, require_known_key: bool) -> Option<Detected> {
+    let pairs = logfmt_pairs(text)?;
+    let has_known_key = pairs
+        .iter()
+        .any(|(key, _)| LEVEL_KEYS.contains(&key.as_str()) || MESSAGE_KEYS.contains(&key.as_str()));
+    if require_known_key && !has_known_key {
+        return None;
+    }
+
+    let level = pairs
+        .iter()
+        .find(|(key, _)| LEVEL_KEYS.contains(&key.as_str()))
+        .and_then(|(_, value)| normalize_level(value));
+    let message = pairs
+        .iter()
+        .find(|(key, _)| MESSAGE_KEYS.contains(&key.as_str()))
+        .map(|(_, value)| value.clone());
+    let fields = pairs
+        .into_iter()
+        .filter(|(key, _)| !is_reserved(key, message.is_some()))
+        .collect();
+    Some(Detected { level, message: message.unwrap_or_else(|| text.to_string()), fields })
+}
+
+fn logfmt_pairs(text: &str) -> Option<Vec<(String, String)>> {
+    let mut pairs = Vec::new();
+    let mut rest = text.trim();
+    while !rest.is_empty() {
+        let key_end = rest.find('=')?;
+        let key = &rest[..key_end];
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-' | '/')) {
+            return None;
+        }
+        rest = &rest[key_end + 1..];
+
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let end = loop {
+                match chars.next()? {
+                    (i, '"') => break i,
+                    (_, '\\') => value.push(chars.next()?.1),
+                    (_, c) => value.push(c),
+                }
+            };
+            rest = &quoted[end + 1..];
+            if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+                return None;
+            }
+            value
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let value = rest[..end].to_string();
+            rest = &rest[end..];
+            value
+        };
+        pairs.push((key.to_string(), value));
+        rest = rest.trim_start();
+    }
+    (!pairs.is_empty()).then_some(pairs)
+}
+
+/// A level written before the message: `ERROR: ...` or `warning[E0308]: ...`
+/// at the start, `[warn]` in brackets, or an upper-case `WARN` among the
+/// first words, as in `2024-01-01T00:00:00Z WARN app: ...`.
+fn prefix_level(text: &str) -> Option<&'static str> {
+    let text = text.trim_start();
+
+    let word_end = text.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(text.len());
+    let (word, rest) = text.split_at(word_end);
+    let rest = match rest.strip_prefix('[') {
+        // cargo adds an error code: error[E0308]:
+        Some(code) => code.split_once(']').map_or(rest, |(_, rest)| rest),
+        None => rest,
+    };
+    if rest.starts_with(':') {
+        if let Some(level) = normalize_level(word) {
+            return Some(level);
+        }
+    }
+
+    text.split_whitespace().take(PREFIX_WORDS).find_map(|token| {
+        let word = token.trim_matches(|c: char| matches!(c, '[' | ']' | '(' | ')' | '<' | '>' | ':' | '|'));
+        let bracketed = (token.starts_with('[') && token.trim_end_matches(':').ends_with(']'))
+            || (token.starts_with('<') && token.ends_with('>'));
+        let upper = word.len() >= 3 && word.chars().all(|c| c.is_ascii_uppercase());
+        if bracketed || upper {
+            normalize_level(word)
+        } else {
+            None
+        }
+    })
+}
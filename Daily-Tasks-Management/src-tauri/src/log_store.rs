@@ -0,0 +1,283 @@
+use crate::database::like_pattern;
+use crate::error::{AppError, AppResult, Context};
+use crate::model::{LogEntry, LogPage, LogQuery, LogRetention};
+use crate::repository::ServiceRepository;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+
+/// How often every service's retention is applied.
+pub const RETENTION_INTERVAL: Duration = Duration::from_secs(10 * 60);
+// A batch is written this long after its first line at the latest
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+// Lines written in one transaction at most
+const BATCH_SIZE: usize = 500;
+const DEFAULT_PAGE_SIZE: usize = 200;
+const MAX_PAGE_SIZE: usize = 1_000;
+
+// AUTOINCREMENT so ids, which are the paging cursor, are never reused
+const SERVICE_LOGS_TABLE: &str = "CREATE TABLE IF NOT EXISTS service_logs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    service_id INTEGER NOT NULL,
+    level TEXT NOT NULL,
+    message TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    created_ms INTEGER NOT NULL,
+    fields TEXT
+)";
+
+const SERVICE_LOGS_INDEX: &str = "CREATE INDEX IF NOT EXISTS service_logs_by_service ON service_logs(service_id, id)";
+
+/// Service output of one workspace, in its own SQLite file.
+pub struct LogStore {
+    conn: Connection,
+}
+
+impl LogStore {
+    pub fn new(path: &str) -> AppResult<Self> {
+        let conn = Connection::open(path).context(format!("Failed to open log database {}", path))?;
+        // Readers are not blocked by the writer's batches
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))?;
+        conn.execute(SERVICE_LOGS_TABLE, [])?;
+        conn.execute(SERVICE_LOGS_INDEX, [])?;
+        // Stores created before fields were parsed lack the column
+        let has_fields: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info('service_logs') WHERE name = 'fields')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_fields {
+            conn.execute("ALTER TABLE service_logs ADD COLUMN fields TEXT", [])?;
+        }
+        Ok(LogStore { conn })
+    }
+
+    /// Writes `entries` in one transaction. Their ids are replaced by the
+    /// store's own.
+    pub fn insert(&self, entries: &[LogEntry]) -> AppResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO service_logs (service_id, level, message, timestamp, created_ms, fields)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for entry in entries {
+                let created_ms = DateTime::parse_from_rfc3339(&entry.timestamp)
+                    .map(|t| t.timestamp_millis())
+                    .unwrap_or_else(|_| Utc::now().timestamp_millis());
+                // NULL rather than an empty object for unstructured lines
+                let fields = (!entry.fields.is_empty())
+                    .then(|| serde_json::to_string(&entry.fields))
+                    .transpose()
+                    .map_err(|e| AppError::internal(format!("Failed to encode log fields: {}", e)))?;
+                stmt.execute(params![
+                    entry.service_id, entry.level, entry.message, entry.timestamp, created_ms, fields,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// One page of matching lines, newest first.
+    pub fn query(&self, query: &LogQuery) -> AppResult<LogPage> {
+        let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let levels = (!query.levels.is_empty())
+            .then(|| serde_json::to_string(&query.levels))
+            .transpose()
+            .map_err(|e| AppError::internal(format!("Failed to encode levels: {}", e)))?;
+        let since = query.since.as_deref().map(|t| timestamp_ms("since", t)).transpose()?;
+        let until = query.until.as_deref().map(|t| timestamp_ms("until", t)).transpose()?;
+        let search = query.search.as_deref().filter(|s| !s.is_empty()).map(like_pattern);
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, service_id, level, message, timestamp, fields FROM service_logs
+             WHERE (?1 IS NULL OR service_id = ?1)
+               AND (?2 IS NULL OR level IN (SELECT value FROM json_each(?2)))
+               AND (?3 IS NULL OR created_ms >= ?3)
+               AND (?4 IS NULL OR created_ms < ?4)
+               AND (?5 IS NULL OR message LIKE ?5 ESCAPE '\\')
+               AND (?6 IS NULL OR id < ?6)
+             ORDER BY id DESC LIMIT ?7",
+        )?;
+        // One extra row tells whether another page follows
+        let mut entries = stmt
+            .query_map(
+                params![query.service_id, levels, since, until, search, query.cursor, limit + 1],
+                |row| {
+                    let fields: Option<String> = row.get(5)?;
+                    Ok(LogEntry {
+                        id: row.get(0)?,
+                        service_id: row.get(1)?,
+                        level: row.get(2)?,
+                        message: row.get(3)?,
+                        timestamp: row.get(4)?,
+                        fields: fields
+                            .map(|fields| serde_json::from_str(&fields))
+                            .transpose()
+                            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, e.into()))?
+                            .unwrap_or_default(),
+                    })
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let next_cursor = if entries.len() > limit {
+            entries.truncate(limit);
+            entries.last().map(|entry| entry.id)
+        } else {
+            None
+        };
+        Ok(LogPage { entries, next_cursor })
+    }
+
+    /// Services with at least one stored line.
+    pub fn service_ids(&self) -> AppResult<Vec<u32>> {
+        let mut stmt = self.conn.prepare("SELECT DISTINCT service_id FROM service_logs ORDER BY service_id")?;
+        let ids = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<Vec<u32>>>()?;
+        Ok(ids)
+    }
+
+    /// Deletes what `retention` does not keep. Returns the lines deleted.
+    pub fn apply_retention(&self, service_id: u32, retention: &LogRetention, now: DateTime<Utc>) -> AppResult<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut deleted = 0;
+
+        if let Some(days) = retention.max_age_days {
+            let cutoff = (now - chrono::Duration::days(days.into())).timestamp_millis();
+            deleted += tx.execute(
+                "DELETE FROM service_logs WHERE service_id = ?1 AND created_ms < ?2",
+                params![service_id, cutoff],
+            )?;
+        }
+        if let Some(max_lines) = retention.max_lines {
+            deleted += tx.execute(
+                "DELETE FROM service_logs WHERE service_id = ?1 AND id <= (
+                     SELECT id FROM service_logs WHERE service_id = ?1 ORDER BY id DESC LIMIT 1 OFFSET ?2
+                 )",
+                params![service_id, max_lines],
+            )?;
+        }
+        if let Some(max_bytes) = retention.max_bytes {
+            // The newest line that no longer fits, counting from the newest
+            deleted += tx.execute(
+                "DELETE FROM service_logs WHERE service_id = ?1 AND id <= (
+                     SELECT id FROM (
+                         SELECT id, SUM(length(CAST(message AS BLOB))) OVER (ORDER BY id DESC) AS total
+                         FROM service_logs WHERE service_id = ?1
+                     )
+                     WHERE total > ?2 ORDER BY id DESC LIMIT 1
+                 )",
+                params![service_id, max_bytes],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    pub fn delete_service(&self, service_id: u32) -> AppResult<usize> {
+        Ok(self.conn.execute("DELETE FROM service_logs WHERE service_id = ?1", params![service_id])?)
+    }
+}
+
+fn timestamp_ms(field: &str, timestamp: &str) -> AppResult<i64> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.timestamp_millis())
+        .map_err(|_| AppError::invalid_field(field, format!("{} is not an RFC 3339 timestamp", timestamp)))
+}
+
+/// Applies each service's retention, and deletes the lines of services
+/// that no longer exist. Returns the lines deleted.
+pub fn apply_retention_all<R: ServiceRepository>(logs: &LogStore, repo: &R, now: DateTime<Utc>) -> AppResult<usize> {
+    let mut deleted = 0;
+    for service_id in logs.service_ids()? {
+        deleted += match repo.get_service_by_id(service_id)? {
+            Some(_) => logs.apply_retention(service_id, &repo.get_log_retention(service_id)?, now)?,
+            None => logs.delete_service(service_id)?,
+        };
+    }
+    Ok(deleted)
+}
+
+pub enum LogMessage {
+    Entry(LogEntry),
+    /// Answered once every line sent before it is written.
+    Flush(oneshot::Sender<()>),
+}
+
+/// Hands log lines to the task running `write_batches`.
+#[derive(Clone)]
+pub struct LogWriter {
+    sender: mpsc::UnboundedSender<LogMessage>,
+}
+
+impl LogWriter {
+    pub fn channel() -> (LogWriter, mpsc::UnboundedReceiver<LogMessage>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (LogWriter { sender }, receiver)
+    }
+
+    /// Lines sent after the writer task ended are dropped.
+    pub fn write(&self, entry: LogEntry) {
+        let _ = self.sender.send(LogMessage::Entry(entry));
+    }
+
+    /// Waits until every line written so far is stored.
+    pub async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.sender.send(LogMessage::Flush(done)).is_ok() {
+            let _ = wait.await;
+        }
+    }
+}
+
+/// Collects lines into batches for `write`: up to `BATCH_SIZE` lines, or
+/// whatever arrived within `FLUSH_INTERVAL` of a batch's first line.
+/// Returns once every `LogWriter` is dropped.
+pub async fn write_batches(mut receiver: mpsc::UnboundedReceiver<LogMessage>, mut write: impl FnMut(&[LogEntry])) {
+    let mut batch = Vec::new();
+    let mut deadline = Instant::now();
+    loop {
+        let message = if batch.is_empty() {
+            receiver.recv().await
+        } else {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(message) => message,
+                Err(_) => {
+                    write(&batch);
+                    batch.clear();
+                    continue;
+                }
+            }
+        };
+
+        match message {
+            Some(LogMessage::Entry(entry)) => {
+                if batch.is_empty() {
+                    deadline = Instant::now() + FLUSH_INTERVAL;
+                }
+                batch.push(entry);
+                if batch.len() >= BATCH_SIZE {
+                    write(&batch);
+                    batch.clear();
+                }
+            }
+            Some(LogMessage::Flush(done)) => {
+                if !batch.is_empty() {
+                    write(&batch);
+                    batch.clear();
+                }
+                let _ = done.send(());
+            }
+            None => {
+                if !batch.is_empty() {
+                    write(&batch);
+                }
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,639 @@
+use crate::error::{AppError, AppResult};
+use crate::model::*;
+use crate::repository::{
+    task_from_form, ConnectionProfileRepository, GitRepoRepository, ProjectRepository, QueryRepository, ServiceGroupRepository,
+    ServiceRepository, TaskRepository, MAX_QUERY_HISTORY,
+};
+use chrono::Utc;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+/// Store that keeps everything in memory, for tests and for embedding the
+/// core without a database file. Enforces the same constraints as the
+/// SQLite schema: unique project paths and project foreign keys.
+#[derive(Default)]
+pub struct MemoryStore {
+    data: RefCell<MemoryData>,
+}
+
+#[derive(Default)]
+struct MemoryData {
+    tasks: Table<Task>,
+    projects: Table<Project>,
+    services: Table<Service>,
+    git_repos: Table<GitStatus>,
+    query_history: Table<QueryHistoryEntry>,
+    saved_queries: Table<SavedQuery>,
+    connection_profiles: Table<ConnectionProfile>,
+    // Keyed by service id, like rows of the service_env tables
+    service_environments: BTreeMap<u32, ServiceEnvironment>,
+    restart_policies: BTreeMap<u32, RestartPolicy>,
+    health_checks: BTreeMap<u32, HealthChecks>,
+    log_retention: BTreeMap<u32, LogRetention>,
+    log_parsers: BTreeMap<u32, LogParser>,
+    service_dependencies: BTreeMap<u32, Vec<u32>>,
+    service_groups: Table<ServiceGroup>,
+}
+
+/// Rows keyed by id, plus the table's id sequence.
+struct Table<T> {
+    rows: BTreeMap<u32, T>,
+    last_id: u32,
+}
+
+impl<T> Default for Table<T> {
+    fn default() -> Self {
+        Table {
+            rows: BTreeMap::new(),
+            last_id: 0,
+        }
+    }
+}
+
+impl<T> Table<T> {
+    // Like SQLite AUTOINCREMENT, ids are never reused
+    fn next_id(&mut self) -> u32 {
+        self.last_id += 1;
+        self.last_id
+    }
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+}
+
+impl MemoryData {
+    fn check_project(&self, project_id: Option<u32>) -> AppResult<()> {
+        match project_id {
+            Some(id) if !self.projects.rows.contains_key(&id) => {
+                Err(AppError::conflict(format!("Project {} does not exist", id)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_services(&self, service_ids: &[u32]) -> AppResult<()> {
+        match service_ids.iter().find(|id| !self.services.rows.contains_key(id)) {
+            Some(id) => Err(AppError::conflict(format!("Service {} does not exist", id))),
+            None => Ok(()),
+        }
+    }
+
+    // What the ON DELETE CASCADE references to services remove
+    fn forget_service(&mut self, id: u32) {
+        self.service_environments.remove(&id);
+        self.restart_policies.remove(&id);
+        self.health_checks.remove(&id);
+        self.log_retention.remove(&id);
+        self.log_parsers.remove(&id);
+        self.service_dependencies.remove(&id);
+        for depends_on in self.service_dependencies.values_mut() {
+            depends_on.retain(|&d| d != id);
+        }
+        for group in self.service_groups.rows.values_mut() {
+            group.service_ids.retain(|&s| s != id);
+        }
+    }
+
+    fn check_unique_path(&self, path: &str, except: Option<u32>) -> AppResult<()> {
+        if self.projects.rows.values().any(|p| p.path == path && Some(p.id) != except) {
+            return Err(AppError::conflict(format!("A project already uses path {}", path)));
+        }
+        Ok(())
+    }
+}
+
+// Same rule as the UNIQUE constraint on service_groups.name
+fn check_unique_group_name(groups: &Table<ServiceGroup>, name: &str, except: Option<u32>) -> AppResult<()> {
+    if groups.rows.values().any(|g| g.name == name && Some(g.id) != except) {
+        return Err(AppError::conflict(format!("A service group is already named {}", name)));
+    }
+    Ok(())
+}
+
+// Members are kept like the service_group_members primary key: once, by id
+fn sorted_ids(ids: &[u32]) -> Vec<u32> {
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+// Same rule as the UNIQUE constraint on saved_queries.name
+fn check_unique_query_name(saved_queries: &Table<SavedQuery>, name: &str, except: Option<u32>) -> AppResult<()> {
+    if saved_queries.rows.values().any(|q| q.name == name && Some(q.id) != except) {
+        return Err(AppError::conflict(format!("A saved query is already named {}", name)));
+    }
+    Ok(())
+}
+
+/// Case-insensitive substring match, like LIKE '%search%' on ASCII text.
+fn matches_search(text: &str, search: &str) -> bool {
+    text.to_lowercase().contains(&search.to_lowercase())
+}
+
+/// Newest first, keeping insertion order for equal timestamps.
+fn newest_first<T: Clone>(items: &BTreeMap<u32, T>, created_at: impl Fn(&T) -> &str) -> Vec<T> {
+    let mut items: Vec<T> = items.values().cloned().collect();
+    items.sort_by(|a, b| created_at(b).cmp(created_at(a)));
+    items
+}
+
+impl TaskRepository for MemoryStore {
+    fn create_task(&self, form_data: TaskFormData) -> AppResult<Task> {
+        let mut data = self.data.borrow_mut();
+        let mut task = task_from_form(form_data, Utc::now().to_rfc3339());
+        task.id = data.tasks.next_id();
+        data.tasks.rows.insert(task.id, task.clone());
+        Ok(task)
+    }
+
+    fn get_all_tasks(&self) -> AppResult<Vec<Task>> {
+        Ok(newest_first(&self.data.borrow().tasks.rows, |t| &t.created_at))
+    }
+
+    fn get_task_by_id(&self, id: u32) -> AppResult<Option<Task>> {
+        Ok(self.data.borrow().tasks.rows.get(&id).cloned())
+    }
+
+    fn update_task(&self, task: &Task) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        data.check_project(task.project_id)?;
+        if let Some(existing) = data.tasks.rows.get_mut(&task.id) {
+            // created_at is not editable
+            *existing = Task {
+                created_at: existing.created_at.clone(),
+                ..task.clone()
+            };
+        }
+        Ok(())
+    }
+
+    fn delete_task(&self, id: u32) -> AppResult<()> {
+        self.data.borrow_mut().tasks.rows.remove(&id);
+        Ok(())
+    }
+
+    fn mark_task_completed(&self, id: u32, completed: bool) -> AppResult<()> {
+        if let Some(task) = self.data.borrow_mut().tasks.rows.get_mut(&id) {
+            task.completed = completed;
+        }
+        Ok(())
+    }
+}
+
+impl ProjectRepository for MemoryStore {
+    fn create_project(&self, name: String, path: String, project_type: Option<String>, description: Option<String>) -> AppResult<Project> {
+        let mut data = self.data.borrow_mut();
+        data.check_unique_path(&path, None)?;
+
+        let created_at = Utc::now().to_rfc3339();
+        let project = Project {
+            id: data.projects.next_id(),
+            name,
+            path,
+            project_type,
+            description,
+            last_accessed: Some(created_at.clone()),
+            created_at,
+        };
+        data.projects.rows.insert(project.id, project.clone());
+        Ok(project)
+    }
+
+    fn get_all_projects(&self) -> AppResult<Vec<Project>> {
+        let mut projects = newest_first(&self.data.borrow().projects.rows, |p| &p.created_at);
+        projects.sort_by(|a, b| b.last_accessed.cmp(&a.last_accessed));
+        Ok(projects)
+    }
+
+    fn get_project_by_id(&self, id: u32) -> AppResult<Option<Project>> {
+        Ok(self.data.borrow().projects.rows.get(&id).cloned())
+    }
+
+    fn update_project(&self, project: &Project) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        data.check_unique_path(&project.path, Some(project.id))?;
+        if let Some(existing) = data.projects.rows.get_mut(&project.id) {
+            existing.name = project.name.clone();
+            existing.path = project.path.clone();
+            existing.project_type = project.project_type.clone();
+            existing.description = project.description.clone();
+            existing.last_accessed = Some(Utc::now().to_rfc3339());
+        }
+        Ok(())
+    }
+
+    fn get_project_deletion_impact(&self, project_id: u32) -> AppResult<ProjectDeletionImpact> {
+        let data = self.data.borrow();
+        Ok(ProjectDeletionImpact {
+            project_id,
+            service_ids: data.services.rows.values().filter(|s| s.project_id == Some(project_id)).map(|s| s.id).collect(),
+            task_ids: data.tasks.rows.values().filter(|t| t.project_id == Some(project_id)).map(|t| t.id).collect(),
+            git_repo_ids: data.git_repos.rows.values().filter(|g| g.project_id == project_id).map(|g| g.id).collect(),
+            connection_profile_ids: data.connection_profiles.rows.values()
+                .filter(|c| c.project_id == project_id)
+                .map(|c| c.id)
+                .collect(),
+        })
+    }
+
+    fn delete_project(&self, id: u32) -> AppResult<()> {
+        self.delete_project_with_mode(id, ProjectDeleteMode::Detach)?;
+        Ok(())
+    }
+
+    fn delete_project_with_mode(&self, id: u32, mode: ProjectDeleteMode) -> AppResult<ProjectDeletionImpact> {
+        let impact = self.get_project_deletion_impact(id)?;
+
+        let mut data = self.data.borrow_mut();
+        match mode {
+            ProjectDeleteMode::Cascade => {
+                data.services.rows.retain(|_, s| s.project_id != Some(id));
+                for &service_id in &impact.service_ids {
+                    data.forget_service(service_id);
+                }
+                data.tasks.rows.retain(|_, t| t.project_id != Some(id));
+            }
+            ProjectDeleteMode::Detach => {
+                for service in data.services.rows.values_mut().filter(|s| s.project_id == Some(id)) {
+                    service.project_id = None;
+                }
+                for task in data.tasks.rows.values_mut().filter(|t| t.project_id == Some(id)) {
+                    task.project_id = None;
+                }
+            }
+        }
+        data.git_repos.rows.retain(|_, g| g.project_id != id);
+        data.connection_profiles.rows.retain(|_, c| c.project_id != id);
+        data.projects.rows.remove(&id);
+
+        Ok(impact)
+    }
+}
+
+impl ServiceRepository for MemoryStore {
+    fn create_service(&self, name: String, command: String, working_dir: Option<String>, project_id: Option<u32>, auto_start: bool, shell: bool) -> AppResult<Service> {
+        let mut data = self.data.borrow_mut();
+        data.check_project(project_id)?;
+
+        let service = Service {
+            id: data.services.next_id(),
+            name,
+            command,
+            working_dir,
+            project_id,
+            auto_start,
+            shell,
+            created_at: Utc::now().to_rfc3339(),
+        };
+        data.services.rows.insert(service.id, service.clone());
+        Ok(service)
+    }
+
+    fn get_all_services(&self) -> AppResult<Vec<Service>> {
+        Ok(newest_first(&self.data.borrow().services.rows, |s| &s.created_at))
+    }
+
+    fn get_service_by_id(&self, id: u32) -> AppResult<Option<Service>> {
+        Ok(self.data.borrow().services.rows.get(&id).cloned())
+    }
+
+    fn update_service(&self, service: &Service) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        data.check_project(service.project_id)?;
+        if let Some(existing) = data.services.rows.get_mut(&service.id) {
+            *existing = Service {
+                created_at: existing.created_at.clone(),
+                ..service.clone()
+            };
+        }
+        Ok(())
+    }
+
+    fn delete_service(&self, id: u32) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        data.services.rows.remove(&id);
+        data.forget_service(id);
+        Ok(())
+    }
+
+    fn get_service_environment(&self, service_id: u32) -> AppResult<ServiceEnvironment> {
+        Ok(self.data.borrow().service_environments.get(&service_id).cloned().unwrap_or_default())
+    }
+
+    fn set_service_environment(&self, service_id: u32, environment: &ServiceEnvironment) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        if !data.services.rows.contains_key(&service_id) {
+            return Err(AppError::conflict(format!("Service {} does not exist", service_id)));
+        }
+        if let Some(var) = environment.variables.iter().enumerate().find_map(|(i, v)| {
+            environment.variables[..i].iter().any(|earlier| earlier.name == v.name).then_some(v)
+        }) {
+            return Err(AppError::conflict(format!("Variable {} is set twice", var.name)));
+        }
+
+        let variables = environment.variables.iter()
+            .map(|v| ServiceEnvVar {
+                value: if v.secret { None } else { v.value.clone() },
+                ..v.clone()
+            })
+            .collect();
+        data.service_environments.insert(service_id, ServiceEnvironment {
+            variables,
+            env_files: environment.env_files.clone(),
+        });
+        Ok(())
+    }
+
+    fn get_restart_policy(&self, service_id: u32) -> AppResult<RestartPolicy> {
+        Ok(self.data.borrow().restart_policies.get(&service_id).cloned().unwrap_or_default())
+    }
+
+    fn set_restart_policy(&self, service_id: u32, policy: &RestartPolicy) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        if !data.services.rows.contains_key(&service_id) {
+            return Err(AppError::conflict(format!("Service {} does not exist", service_id)));
+        }
+        data.restart_policies.insert(service_id, policy.clone());
+        Ok(())
+    }
+
+    fn get_health_checks(&self, service_id: u32) -> AppResult<HealthChecks> {
+        Ok(self.data.borrow().health_checks.get(&service_id).cloned().unwrap_or_default())
+    }
+
+    fn set_health_checks(&self, service_id: u32, checks: &HealthChecks) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        data.check_services(&[service_id])?;
+        data.health_checks.insert(service_id, checks.clone());
+        Ok(())
+    }
+
+    fn get_log_retention(&self, service_id: u32) -> AppResult<LogRetention> {
+        Ok(self.data.borrow().log_retention.get(&service_id).cloned().unwrap_or_default())
+    }
+
+    fn set_log_retention(&self, service_id: u32, retention: &LogRetention) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        data.check_services(&[service_id])?;
+        data.log_retention.insert(service_id, retention.clone());
+        Ok(())
+    }
+
+    fn get_log_parser(&self, service_id: u32) -> AppResult<LogParser> {
+        Ok(self.data.borrow().log_parsers.get(&service_id).cloned().unwrap_or_default())
+    }
+
+    fn set_log_parser(&self, service_id: u32, parser: &LogParser) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        data.check_services(&[service_id])?;
+        data.log_parsers.insert(service_id, parser.clone());
+        Ok(())
+    }
+
+    fn get_service_dependencies(&self, service_id: u32) -> AppResult<Vec<u32>> {
+        Ok(self.data.borrow().service_dependencies.get(&service_id).cloned().unwrap_or_default())
+    }
+
+    fn set_service_dependencies(&self, service_id: u32, depends_on: &[u32]) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        data.check_services(&[service_id])?;
+        data.check_services(depends_on)?;
+        if depends_on.is_empty() {
+            data.service_dependencies.remove(&service_id);
+        } else {
+            data.service_dependencies.insert(service_id, sorted_ids(depends_on));
+        }
+        Ok(())
+    }
+}
+
+impl ServiceGroupRepository for MemoryStore {
+    fn create_service_group(&self, name: String, service_ids: &[u32]) -> AppResult<ServiceGroup> {
+        let mut data = self.data.borrow_mut();
+        check_unique_group_name(&data.service_groups, &name, None)?;
+        data.check_services(service_ids)?;
+
+        let group = ServiceGroup {
+            id: data.service_groups.next_id(),
+            name,
+            service_ids: sorted_ids(service_ids),
+            created_at: Utc::now().to_rfc3339(),
+        };
+        data.service_groups.rows.insert(group.id, group.clone());
+        Ok(group)
+    }
+
+    fn get_service_groups(&self) -> AppResult<Vec<ServiceGroup>> {
+        let mut groups: Vec<ServiceGroup> = self.data.borrow().service_groups.rows.values().cloned().collect();
+        groups.sort_by_key(|g| g.name.to_lowercase());
+        Ok(groups)
+    }
+
+    fn get_service_group_by_id(&self, id: u32) -> AppResult<Option<ServiceGroup>> {
+        Ok(self.data.borrow().service_groups.rows.get(&id).cloned())
+    }
+
+    fn update_service_group(&self, group: &ServiceGroup) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        check_unique_group_name(&data.service_groups, &group.name, Some(group.id))?;
+        data.check_services(&group.service_ids)?;
+        if let Some(existing) = data.service_groups.rows.get_mut(&group.id) {
+            existing.name = group.name.clone();
+            existing.service_ids = sorted_ids(&group.service_ids);
+        }
+        Ok(())
+    }
+
+    fn delete_service_group(&self, id: u32) -> AppResult<()> {
+        self.data.borrow_mut().service_groups.rows.remove(&id);
+        Ok(())
+    }
+}
+
+impl GitRepoRepository for MemoryStore {
+    fn create_git_repo(&self, project_id: u32, repo_path: String) -> AppResult<u32> {
+        let mut data = self.data.borrow_mut();
+        data.check_project(Some(project_id))?;
+
+        let id = data.git_repos.next_id();
+        data.git_repos.rows.insert(id, GitStatus {
+            id,
+            project_id,
+            repo_path,
+            current_branch: None,
+            uncommitted_changes: Vec::new(),
+            ahead_count: 0,
+            behind_count: 0,
+            last_checked: Some(Utc::now().to_rfc3339()),
+        });
+        Ok(id)
+    }
+
+    fn get_git_repo_by_project_id(&self, project_id: u32) -> AppResult<Option<GitStatus>> {
+        Ok(self.data.borrow().git_repos.rows.values().find(|g| g.project_id == project_id).cloned())
+    }
+
+    fn update_git_repo(&self, git_status: &GitStatus) -> AppResult<()> {
+        if let Some(existing) = self.data.borrow_mut().git_repos.rows.get_mut(&git_status.id) {
+            existing.current_branch = git_status.current_branch.clone();
+            existing.last_checked = Some(Utc::now().to_rfc3339());
+        }
+        Ok(())
+    }
+
+    fn delete_git_repo(&self, id: u32) -> AppResult<()> {
+        self.data.borrow_mut().git_repos.rows.remove(&id);
+        Ok(())
+    }
+}
+
+impl QueryRepository for MemoryStore {
+    fn add_query_history(&self, entry: NewQueryHistory) -> AppResult<QueryHistoryEntry> {
+        let mut data = self.data.borrow_mut();
+        let history = &mut data.query_history;
+
+        let entry = QueryHistoryEntry {
+            id: history.next_id(),
+            database_id: entry.database_id,
+            query: entry.query,
+            duration_ms: entry.duration_ms,
+            row_count: entry.row_count,
+            error: entry.error,
+            executed_at: Utc::now().to_rfc3339(),
+        };
+        history.rows.insert(entry.id, entry.clone());
+
+        while history.rows.len() > MAX_QUERY_HISTORY as usize {
+            history.rows.pop_first();
+        }
+        Ok(entry)
+    }
+
+    fn search_query_history(&self, search: Option<&str>, database_id: Option<&str>, limit: u32) -> AppResult<Vec<QueryHistoryEntry>> {
+        Ok(self.data.borrow().query_history.rows
+            .values()
+            .rev()
+            .filter(|e| search.is_none_or(|s| matches_search(&e.query, s)))
+            .filter(|e| database_id.is_none_or(|id| e.database_id == id))
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    fn clear_query_history(&self) -> AppResult<()> {
+        self.data.borrow_mut().query_history.rows.clear();
+        Ok(())
+    }
+
+    fn create_saved_query(&self, name: String, query: String, database_id: Option<String>, description: Option<String>) -> AppResult<SavedQuery> {
+        let mut data = self.data.borrow_mut();
+        check_unique_query_name(&data.saved_queries, &name, None)?;
+
+        let created_at = Utc::now().to_rfc3339();
+        let saved_query = SavedQuery {
+            id: data.saved_queries.next_id(),
+            name,
+            query,
+            database_id,
+            description,
+            updated_at: created_at.clone(),
+            created_at,
+        };
+        data.saved_queries.rows.insert(saved_query.id, saved_query.clone());
+        Ok(saved_query)
+    }
+
+    fn search_saved_queries(&self, search: Option<&str>) -> AppResult<Vec<SavedQuery>> {
+        let mut queries: Vec<SavedQuery> = self.data.borrow().saved_queries.rows
+            .values()
+            .filter(|q| {
+                search.is_none_or(|s| {
+                    matches_search(&q.name, s)
+                        || matches_search(&q.query, s)
+                        || q.description.as_deref().is_some_and(|d| matches_search(d, s))
+                })
+            })
+            .cloned()
+            .collect();
+        queries.sort_by_key(|q| q.name.to_lowercase());
+        Ok(queries)
+    }
+
+    fn get_saved_query_by_id(&self, id: u32) -> AppResult<Option<SavedQuery>> {
+        Ok(self.data.borrow().saved_queries.rows.get(&id).cloned())
+    }
+
+    fn update_saved_query(&self, saved_query: &SavedQuery) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        check_unique_query_name(&data.saved_queries, &saved_query.name, Some(saved_query.id))?;
+        if let Some(existing) = data.saved_queries.rows.get_mut(&saved_query.id) {
+            *existing = SavedQuery {
+                created_at: existing.created_at.clone(),
+                updated_at: Utc::now().to_rfc3339(),
+                ..saved_query.clone()
+            };
+        }
+        Ok(())
+    }
+
+    fn delete_saved_query(&self, id: u32) -> AppResult<()> {
+        self.data.borrow_mut().saved_queries.rows.remove(&id);
+        Ok(())
+    }
+}
+
+impl ConnectionProfileRepository for MemoryStore {
+    fn create_connection_profile(&self, form: &ConnectionProfileForm) -> AppResult<ConnectionProfile> {
+        let mut data = self.data.borrow_mut();
+        data.check_project(Some(form.project_id))?;
+
+        let profile = ConnectionProfile {
+            id: data.connection_profiles.next_id(),
+            project_id: form.project_id,
+            name: form.name.clone(),
+            kind: form.kind,
+            host: form.host.clone(),
+            port: form.port,
+            database: form.database.clone(),
+            username: form.username.clone(),
+            created_at: Utc::now().to_rfc3339(),
+        };
+        data.connection_profiles.rows.insert(profile.id, profile.clone());
+        Ok(profile)
+    }
+
+    fn get_connection_profiles(&self, project_id: Option<u32>) -> AppResult<Vec<ConnectionProfile>> {
+        let mut profiles: Vec<ConnectionProfile> = self.data.borrow().connection_profiles.rows
+            .values()
+            .filter(|c| project_id.is_none_or(|id| c.project_id == id))
+            .cloned()
+            .collect();
+        profiles.sort_by_key(|c| c.name.to_lowercase());
+        Ok(profiles)
+    }
+
+    fn get_connection_profile_by_id(&self, id: u32) -> AppResult<Option<ConnectionProfile>> {
+        Ok(self.data.borrow().connection_profiles.rows.get(&id).cloned())
+    }
+
+    fn update_connection_profile(&self, profile: &ConnectionProfile) -> AppResult<()> {
+        let mut data = self.data.borrow_mut();
+        data.check_project(Some(profile.project_id))?;
+        if let Some(existing) = data.connection_profiles.rows.get_mut(&profile.id) {
+            *existing = ConnectionProfile {
+                created_at: existing.created_at.clone(),
+                ..profile.clone()
+            };
+        }
+        Ok(())
+    }
+
+    fn delete_connection_profile(&self, id: u32) -> AppResult<()> {
+        self.data.borrow_mut().connection_profiles.rows.remove(&id);
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Project {
@@ -14,6 +16,30 @@ pub struct Project {
     pub created_at: String,
 }
 
+/// What happens to a project's services and tasks when it is deleted.
+/// Its git_repos rows are always removed.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectDeleteMode {
+    Cascade,
+    #[default]
+    Detach,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProjectDeletionImpact {
+    #[serde(rename = "projectId")]
+    pub project_id: u32,
+    #[serde(rename = "serviceIds")]
+    pub service_ids: Vec<u32>,
+    #[serde(rename = "taskIds")]
+    pub task_ids: Vec<u32>,
+    #[serde(rename = "gitRepoIds")]
+    pub git_repo_ids: Vec<u32>,
+    #[serde(rename = "connectionProfileIds")]
+    pub connection_profile_ids: Vec<u32>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Service {
     pub id: u32,
@@ -25,10 +51,35 @@ pub struct Service {
     pub project_id: Option<u32>,
     #[serde(rename = "autoStart")]
     pub auto_start: bool,
+    /// Run `command` through `/bin/sh -c` instead of splitting it into arguments.
+    #[serde(default)]
+    pub shell: bool,
     #[serde(rename = "createdAt")]
     pub created_at: String,
 }
 
+/// A variable set directly on a service. Secret values are kept in the
+/// secret store, so they are `None` whenever a variable is read back.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ServiceEnvVar {
+    pub name: String,
+    /// When saving, `None` keeps a secret's stored value.
+    pub value: Option<String>,
+    #[serde(default)]
+    pub secret: bool,
+}
+
+/// What a service adds to the environment it inherits from the app: the
+/// env files are loaded in order, then `variables` override them.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ServiceEnvironment {
+    pub variables: Vec<ServiceEnvVar>,
+    /// Relative paths are taken from the service's working directory, or
+    /// its project's when it has none.
+    #[serde(rename = "envFiles")]
+    pub env_files: Vec<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GitStatus {
     pub id: u32,
@@ -48,6 +99,263 @@ pub struct GitStatus {
     pub last_checked: Option<String>,
 }
 
+/// Services started and stopped together, e.g. "full stack". Their
+/// dependencies are started first even when they are not members.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ServiceGroup {
+    pub id: u32,
+    pub name: String,
+    /// Ordered by id; the start order comes from the dependencies.
+    #[serde(rename = "serviceIds")]
+    pub service_ids: Vec<u32>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+/// When the supervisor starts a service again after its process exits.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartMode {
+    #[default]
+    Never,
+    /// After a non-zero exit code or a signal.
+    OnFailure,
+    /// After any exit, including a clean one.
+    Always,
+}
+
+impl RestartMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RestartMode::Never => "never",
+            RestartMode::OnFailure => "on-failure",
+            RestartMode::Always => "always",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "never" => Some(RestartMode::Never),
+            "on-failure" => Some(RestartMode::OnFailure),
+            "always" => Some(RestartMode::Always),
+            _ => None,
+        }
+    }
+}
+
+/// How a service is restarted. The delay doubles with each restart in a
+/// row, from `initial_backoff_ms` up to `max_backoff_ms`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RestartPolicy {
+    pub mode: RestartMode,
+    /// Restarts in a row before giving up; a run that stays up resets the count.
+    #[serde(rename = "maxRetries")]
+    pub max_retries: u32,
+    #[serde(rename = "initialBackoffMs")]
+    pub initial_backoff_ms: u64,
+    #[serde(rename = "maxBackoffMs")]
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            mode: RestartMode::Never,
+            max_retries: 5,
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Delay before the `attempt`th restart in a row, counting from 1.
+    pub fn backoff(&self, attempt: u32) -> std::time::Duration {
+        let factor = 1u64.checked_shl(attempt.saturating_sub(1)).unwrap_or(u64::MAX);
+        let delay = self.initial_backoff_ms.saturating_mul(factor).min(self.max_backoff_ms);
+        std::time::Duration::from_millis(delay)
+    }
+
+    /// Whether a process that exited with `exit` should be started again.
+    pub fn restarts_after(&self, exit: &ExitInfo) -> bool {
+        match self.mode {
+            RestartMode::Never => false,
+            RestartMode::OnFailure => !exit.success(),
+            RestartMode::Always => true,
+        }
+    }
+}
+
+/// How a service's process ended. On Unix a process killed by a signal has
+/// no exit code.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ExitInfo {
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+}
+
+impl ExitInfo {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+impl fmt::Display for ExitInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.code, self.signal) {
+            (Some(code), _) => write!(f, "exit code {}", code),
+            (None, Some(signal)) => write!(f, "signal {}", signal),
+            (None, None) => write!(f, "unknown exit status"),
+        }
+    }
+}
+
+/// What a health probe checks.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ProbeCheck {
+    /// A GET on a localhost URL answers with `expected_status`.
+    Http {
+        url: String,
+        #[serde(rename = "expectedStatus")]
+        expected_status: u16,
+    },
+    /// A local port accepts connections.
+    Tcp { port: u16 },
+    /// A line logged by the current process matches the regex `pattern`.
+    Log { pattern: String },
+    /// `command` exits with 0. Runs through the shell, in the service's directory.
+    Command { command: String },
+}
+
+/// A check run every `interval_ms`, failing when it takes over `timeout_ms`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Probe {
+    pub check: ProbeCheck,
+    #[serde(rename = "intervalMs")]
+    pub interval_ms: u64,
+    #[serde(rename = "timeoutMs")]
+    pub timeout_ms: u64,
+    /// Failures in a row before a liveness probe marks the service unhealthy.
+    #[serde(rename = "failureThreshold")]
+    pub failure_threshold: u32,
+}
+
+/// A service's probes. Readiness is checked after each start until it
+/// passes; liveness from then on, restarting the service when it fails
+/// unless the restart policy is `never`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct HealthChecks {
+    pub readiness: Option<Probe>,
+    pub liveness: Option<Probe>,
+}
+
+/// The latest run of a probe.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ProbeResult {
+    pub ok: bool,
+    pub message: String,
+    pub timestamp: String,
+    #[serde(rename = "consecutiveFailures")]
+    pub consecutive_failures: u32,
+}
+
+/// A service's process exiting while it was meant to be running.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CrashRecord {
+    #[serde(rename = "serviceId")]
+    pub service_id: u32,
+    pub pid: u32,
+    pub exit: ExitInfo,
+    pub timestamp: String,
+    /// How long the process ran.
+    #[serde(rename = "uptimeMs")]
+    pub uptime_ms: u64,
+    /// Output just before the exit, oldest first.
+    #[serde(rename = "lastLogs")]
+    pub last_logs: Vec<String>,
+    /// Whether the supervisor starts it again.
+    #[serde(rename = "willRestart")]
+    pub will_restart: bool,
+    /// Set when the supervisor ended the process, e.g. a failed liveness probe.
+    pub reason: Option<String>,
+}
+
+/// Resource use of a service's process and every process it started.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ResourceSample {
+    pub timestamp: String,
+    /// The service's own process; changes when it restarts.
+    pub pid: u32,
+    /// Processes in the tree, the service's own included.
+    pub processes: u32,
+    /// Summed over the tree; 100 is one core.
+    #[serde(rename = "cpuPercent")]
+    pub cpu_percent: f32,
+    #[serde(rename = "rssBytes")]
+    pub rss_bytes: u64,
+    /// `None` where the platform does not list threads.
+    pub threads: Option<u32>,
+    /// TCP ports the tree listens on, ascending.
+    #[serde(rename = "openPorts")]
+    pub open_ports: Vec<u16>,
+}
+
+/// Where a supervised service is in its lifecycle.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ServiceState {
+    Running,
+    /// Waiting out the backoff before a restart.
+    Restarting,
+    /// Exited and not restarted by its policy.
+    Exited,
+    /// Hit the policy's maximum retries.
+    GaveUp,
+    Stopped,
+}
+
+/// What `ServiceManager` knows about a service, beyond running or not.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceStatus {
+    #[serde(rename = "serviceId")]
+    pub service_id: u32,
+    pub state: ServiceState,
+    /// Process of the current run; `None` between runs.
+    pub pid: Option<u32>,
+    /// Restarts since the service was started by hand.
+    pub restarts: u32,
+    #[serde(rename = "lastExit")]
+    pub last_exit: Option<ExitInfo>,
+    #[serde(rename = "nextRestartAt")]
+    pub next_restart_at: Option<String>,
+    /// The current process passed its readiness probe, or stayed up for a
+    /// moment when it has none.
+    pub ready: bool,
+    /// The liveness verdict; `None` without a liveness probe or before it ran.
+    pub healthy: Option<bool>,
+    pub readiness: Option<ProbeResult>,
+    pub liveness: Option<ProbeResult>,
+}
+
+impl ServiceStatus {
+    /// A service without a supervisor.
+    pub fn stopped(service_id: u32) -> Self {
+        ServiceStatus {
+            service_id,
+            state: ServiceState::Stopped,
+            pid: None,
+            restarts: 0,
+            last_exit: None,
+            next_restart_at: None,
+            ready: false,
+            healthy: None,
+            readiness: None,
+            liveness: None,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LogEntry {
     pub id: u32,
@@ -56,6 +364,126 @@ pub struct LogEntry {
     pub level: String, // "info", "error", "warn", "debug"
     pub message: String,
     pub timestamp: String,
+    /// Key-value pairs of a structured line, besides its level and message.
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+}
+
+/// How much of a service's persisted log is kept. Each limit applies on
+/// its own, oldest lines going first; `None` turns a limit off.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LogRetention {
+    #[serde(rename = "maxAgeDays")]
+    pub max_age_days: Option<u32>,
+    #[serde(rename = "maxLines")]
+    pub max_lines: Option<u64>,
+    /// Counted over message text only.
+    #[serde(rename = "maxBytes")]
+    pub max_bytes: Option<u64>,
+}
+
+impl Default for LogRetention {
+    fn default() -> Self {
+        LogRetention {
+            max_age_days: Some(7),
+            max_lines: Some(100_000),
+            max_bytes: Some(50 * 1024 * 1024),
+        }
+    }
+}
+
+/// Which formats a service's output is recognized in.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogFormat {
+    /// JSON, then logfmt, then a level prefix.
+    #[default]
+    Auto,
+    /// JSON objects as written by tracing, pino or bunyan.
+    Json,
+    Logfmt,
+    /// A level like `[WARN]` or `error:` near the start of the line.
+    Prefix,
+    /// No detection; the level follows the stream.
+    Plain,
+}
+
+impl LogFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogFormat::Auto => "auto",
+            LogFormat::Json => "json",
+            LogFormat::Logfmt => "logfmt",
+            LogFormat::Prefix => "prefix",
+            LogFormat::Plain => "plain",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(LogFormat::Auto),
+            "json" => Some(LogFormat::Json),
+            "logfmt" => Some(LogFormat::Logfmt),
+            "prefix" => Some(LogFormat::Prefix),
+            "plain" => Some(LogFormat::Plain),
+            _ => None,
+        }
+    }
+}
+
+/// How lines of a service's output become log entries.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LogParser {
+    pub format: LogFormat,
+    /// Keeps ANSI color codes for the log viewer to render; they are
+    /// stripped otherwise. Levels are detected without them either way.
+    #[serde(rename = "keepAnsi")]
+    pub keep_ansi: bool,
+    /// Level of stderr lines without a detected level. Such stdout lines
+    /// are always "info".
+    #[serde(rename = "stderrLevel")]
+    pub stderr_level: String,
+}
+
+impl Default for LogParser {
+    fn default() -> Self {
+        // Many tools write progress to stderr, so it is not an error by itself
+        LogParser { format: LogFormat::Auto, keep_ansi: false, stderr_level: "info".to_string() }
+    }
+}
+
+/// Filters for persisted logs, which come newest first. Pass the previous
+/// page's `nextCursor` as `cursor` to continue with older lines.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LogQuery {
+    #[serde(rename = "serviceId")]
+    pub service_id: Option<u32>,
+    /// Any of these levels; all levels when empty.
+    #[serde(default)]
+    pub levels: Vec<String>,
+    /// RFC 3339 bounds, `since` inclusive and `until` exclusive.
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Case-insensitive substring of the message.
+    pub search: Option<String>,
+    pub cursor: Option<u32>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LogPage {
+    pub entries: Vec<LogEntry>,
+    /// `None` on the last page.
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Workspace {
+    pub name: String,
+    #[serde(rename = "databasePath")]
+    pub database_path: String,
+    pub active: bool,
 }
 
 // Legacy LocalService for backward compatibility
@@ -67,7 +495,106 @@ pub struct LocalService {
     pub working_dir: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Server kinds a connection profile can point at.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectorKind {
+    Postgres,
+}
+
+impl ConnectorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectorKind::Postgres => "postgres",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "postgres" => Some(ConnectorKind::Postgres),
+            _ => None,
+        }
+    }
+}
+
+/// How to reach a project's database server. The password is not part of
+/// the profile; it lives in the workspace's secret store.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectionProfile {
+    pub id: u32,
+    #[serde(rename = "projectId")]
+    pub project_id: u32,
+    pub name: String,
+    pub kind: ConnectorKind,
+    pub host: String,
+    pub port: u16,
+    pub database: String,
+    pub username: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectionProfileForm {
+    #[serde(rename = "projectId")]
+    pub project_id: u32,
+    pub name: String,
+    pub kind: ConnectorKind,
+    pub host: String,
+    pub port: u16,
+    pub database: String,
+    pub username: String,
+    /// Stored in the secret store; `None` or empty means no password.
+    pub password: Option<String>,
+}
+
+/// One query run in the database browser, successful or not.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueryHistoryEntry {
+    pub id: u32,
+    #[serde(rename = "databaseId")]
+    pub database_id: String,
+    pub query: String,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+    /// `None` when the query failed or its rows were read in pages.
+    #[serde(rename = "rowCount")]
+    pub row_count: Option<u64>,
+    pub error: Option<String>,
+    #[serde(rename = "executedAt")]
+    pub executed_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewQueryHistory {
+    #[serde(rename = "databaseId")]
+    pub database_id: String,
+    pub query: String,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+    #[serde(rename = "rowCount")]
+    pub row_count: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// A named query kept for reuse. `?` and `:name` placeholders are bound
+/// when it is run.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedQuery {
+    pub id: u32,
+    pub name: String,
+    pub query: String,
+    /// Database the query was written for, if any.
+    #[serde(rename = "databaseId")]
+    pub database_id: Option<String>,
+    pub description: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Task {
     pub id: u32,
     pub title: String,
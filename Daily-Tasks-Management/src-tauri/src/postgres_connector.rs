@@ -0,0 +1,348 @@
+use crate::database_browser::{QueryParams, QueryResult, ResultColumn, TableInfo};
+use crate::database_connector::DatabaseConnector;
+use crate::database_query::DEFAULT_ROW_LIMIT;
+use crate::database_value::CellValue;
+use crate::error::{AppError, AppResult, IntoAppError};
+use crate::model::ConnectionProfile;
+use bytes::BytesMut;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use postgres::error::SqlState;
+use postgres::types::{to_sql_checked, FromSql, IsNull, Kind, ToSql, Type};
+use postgres::{Client, Config, NoTls};
+use std::error::Error;
+use std::time::Duration;
+
+type BoxError = Box<dyn Error + Sync + Send>;
+
+/// PostgreSQL server reached through a connection profile. Sessions default
+/// to read-only transactions with a statement timeout, and every browser
+/// query runs in its own read-only transaction that is rolled back. Results
+/// stop at `DEFAULT_ROW_LIMIT` rows, as they are read in one piece.
+///
+/// Connections are unencrypted, which suits servers on localhost or in
+/// local containers.
+pub struct PostgresConnector {
+    client: Client,
+    timeout: Duration,
+}
+
+impl PostgresConnector {
+    pub fn connect(profile: &ConnectionProfile, password: Option<&str>, timeout: Duration) -> AppResult<Self> {
+        let mut config = Config::new();
+        config
+            .host(&profile.host)
+            .port(profile.port)
+            .dbname(&profile.database)
+            .user(&profile.username);
+        if let Some(password) = password {
+            config.password(password);
+        }
+        PostgresConnector::connect_with(config, timeout)
+    }
+
+    /// Connects with an already built configuration, e.g. parsed from a URL.
+    pub fn connect_with(mut config: Config, timeout: Duration) -> AppResult<Self> {
+        config
+            .application_name("Daily Tasks Management")
+            .connect_timeout(timeout)
+            .options(&format!(
+                "-c default_transaction_read_only=on -c statement_timeout={}",
+                timeout.as_millis()
+            ));
+
+        let client = config
+            .connect(NoTls)
+            .map_err(|e| e.into_app_error("Failed to connect to PostgreSQL"))?;
+        Ok(PostgresConnector { client, timeout })
+    }
+
+    fn query(&mut self, sql: &str, params: &QueryParams, context: &str) -> AppResult<QueryResult> {
+        let timeout = self.timeout;
+        let mut tx = self.client
+            .build_transaction()
+            .read_only(true)
+            .start()
+            .map_err(|e| explain(e, context, timeout))?;
+
+        let stmt = tx.prepare(sql).map_err(|e| explain(e, context, timeout))?;
+        let bound = bind_params(stmt.params(), params)?;
+        let refs: Vec<&(dyn ToSql + Sync)> = bound.iter().map(|p| p as &(dyn ToSql + Sync)).collect();
+        // Read through a portal so rows past the cap never leave the server;
+        // one extra row tells whether any were left unread
+        let portal = tx.bind(&stmt, &refs).map_err(|e| explain(e, context, timeout))?;
+        let fetch = i32::try_from(DEFAULT_ROW_LIMIT + 1).unwrap_or(i32::MAX);
+        let mut rows = tx.query_portal(&portal, fetch).map_err(|e| explain(e, context, timeout))?;
+        tx.rollback().map_err(|e| explain(e, context, timeout))?;
+        let truncated = rows.len() as u64 > DEFAULT_ROW_LIMIT;
+        rows.truncate(DEFAULT_ROW_LIMIT as usize);
+
+        let columns: Vec<ResultColumn> = stmt
+            .columns()
+            .iter()
+            .map(|c| ResultColumn {
+                name: c.name().to_string(),
+                declared_type: Some(c.type_().name().to_string()),
+            })
+            .collect();
+        let rows = rows
+            .iter()
+            .map(|row| (0..columns.len()).map(|i| row.try_get::<_, PgCell>(i).map(|c| c.0)).collect())
+            .collect::<Result<Vec<Vec<CellValue>>, _>>()
+            .map_err(|e| explain(e, context, timeout))?;
+
+        Ok(QueryResult {
+            columns,
+            row_count: rows.len(),
+            rows,
+            truncated,
+        })
+    }
+}
+
+impl DatabaseConnector for PostgresConnector {
+    fn server_version(&mut self) -> AppResult<String> {
+        let row = self.client
+            .query_one("SELECT version()", &[])
+            .map_err(|e| explain(e, "Failed to query server version", self.timeout))?;
+        Ok(row.get(0))
+    }
+
+    /// Tables in every non-system schema. Tables outside `public` are
+    /// listed as `schema.table`. Row counts are the planner's estimates, as
+    /// counting a large table would not finish within the timeout.
+    fn list_tables(&mut self) -> AppResult<Vec<TableInfo>> {
+        let timeout = self.timeout;
+        // reltuples is -1 until the table is first vacuumed or analyzed
+        let rows = self.client
+            .query(
+                "SELECT n.nspname, c.relname,
+                        CASE WHEN c.reltuples >= 0 THEN c.reltuples::bigint ELSE COALESCE(s.n_live_tup, 0) END
+                 FROM pg_class c
+                 JOIN pg_namespace n ON n.oid = c.relnamespace
+                 LEFT JOIN pg_stat_user_tables s ON s.relid = c.oid
+                 WHERE c.relkind IN ('r', 'p')
+                   AND n.nspname NOT IN ('pg_catalog', 'information_schema') AND n.nspname NOT LIKE 'pg_toast%'
+                   AND has_table_privilege(c.oid, 'SELECT')
+                 ORDER BY n.nspname <> 'public', n.nspname, c.relname",
+                &[],
+            )
+            .map_err(|e| explain(e, "Failed to list tables", timeout))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let (schema, table, count): (String, String, i64) = (row.get(0), row.get(1), row.get(2));
+                TableInfo {
+                    name: if schema == "public" { table } else { format!("{}.{}", schema, table) },
+                    row_count: u32::try_from(count).unwrap_or(u32::MAX),
+                }
+            })
+            .collect())
+    }
+
+    fn get_table_data(&mut self, table: &str, limit: u32, offset: u32) -> AppResult<QueryResult> {
+        let (schema, table) = table.split_once('.').unwrap_or(("public", table));
+        let query = format!("SELECT * FROM {} LIMIT $1 OFFSET $2", qualified_name(schema, table));
+        let params = QueryParams {
+            positional: vec![CellValue::Integer(limit.into()), CellValue::Integer(offset.into())],
+            ..QueryParams::default()
+        };
+        self.query(&query, &params, "Failed to read table")
+    }
+
+    /// Runs one statement with `$1`, `$2`, ... bound from `params.positional`.
+    fn execute_query(&mut self, query: &str, params: &QueryParams) -> AppResult<QueryResult> {
+        if !params.named.is_empty() {
+            return Err(AppError::invalid_field(
+                "params",
+                "PostgreSQL queries use $1, $2, ... placeholders instead of named ones",
+            ));
+        }
+        self.query(query, params, "Failed to execute query")
+    }
+}
+
+/// Turns cancellations and read-only violations into errors the user can act on.
+fn explain(err: postgres::Error, context: &str, timeout: Duration) -> AppError {
+    match err.code() {
+        Some(&SqlState::QUERY_CANCELED) => AppError::timeout(format!(
+            "Query exceeded the {} second time limit",
+            timeout.as_secs_f32()
+        )),
+        Some(&SqlState::READ_ONLY_SQL_TRANSACTION) => {
+            let detail = err.as_db_error().map(|e| e.message().to_string()).unwrap_or_default();
+            AppError::invalid_field("query", format!("Not allowed in the read-only browser: {}", detail))
+        }
+        _ => err.into_app_error(context),
+    }
+}
+
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn qualified_name(schema: &str, table: &str) -> String {
+    format!("{}.{}", quote_identifier(schema), quote_identifier(table))
+}
+
+/// Pairs each placeholder with its value, converting once up front so a
+/// value that does not fit is reported against the parameter.
+fn bind_params<'a>(types: &[Type], params: &'a QueryParams) -> AppResult<Vec<PgParam<'a>>> {
+    types
+        .iter()
+        .enumerate()
+        .map(|(i, ty)| {
+            let value = params.positional
+                .get(i)
+                .ok_or_else(|| AppError::invalid_field("params", format!("No value given for parameter ${}", i + 1)))?;
+            PgParam(value)
+                .to_sql(ty, &mut BytesMut::new())
+                .map_err(|e| AppError::invalid_field("params", format!("Parameter ${}: {}", i + 1, e)))?;
+            Ok(PgParam(value))
+        })
+        .collect()
+}
+
+fn is_text_type(ty: &Type) -> bool {
+    matches!(*ty, Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME | Type::UNKNOWN)
+        || matches!(ty.kind(), Kind::Enum(_))
+        || ty.name() == "citext"
+}
+
+/// Any column value as a cell. Types without a natural cell representation
+/// are rendered as text; anything unrecognised is returned as raw bytes.
+struct PgCell(CellValue);
+
+impl<'a> FromSql<'a> for PgCell {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+        let cell = match *ty {
+            Type::BOOL => CellValue::Integer(bool::from_sql(ty, raw)? as i64),
+            Type::INT2 => CellValue::Integer(i16::from_sql(ty, raw)?.into()),
+            Type::INT4 => CellValue::Integer(i32::from_sql(ty, raw)?.into()),
+            Type::INT8 => CellValue::Integer(i64::from_sql(ty, raw)?),
+            Type::OID => CellValue::Integer(u32::from_sql(ty, raw)?.into()),
+            Type::FLOAT4 => CellValue::Real(f32::from_sql(ty, raw)?.into()),
+            Type::FLOAT8 => CellValue::Real(f64::from_sql(ty, raw)?),
+            Type::BYTEA => CellValue::Blob(raw.to_vec()),
+            Type::NUMERIC => CellValue::Text(decode_numeric(raw)?),
+            Type::UUID => CellValue::Text(decode_uuid(raw)?),
+            Type::JSON | Type::JSONB => CellValue::Text(serde_json::Value::from_sql(ty, raw)?.to_string()),
+            Type::TIMESTAMP => CellValue::Text(NaiveDateTime::from_sql(ty, raw)?.to_string()),
+            Type::TIMESTAMPTZ => CellValue::Text(DateTime::<Utc>::from_sql(ty, raw)?.to_rfc3339()),
+            Type::DATE => CellValue::Text(NaiveDate::from_sql(ty, raw)?.to_string()),
+            Type::TIME => CellValue::Text(NaiveTime::from_sql(ty, raw)?.to_string()),
+            _ if is_text_type(ty) => CellValue::Text(String::from_utf8_lossy(raw).to_string()),
+            _ => CellValue::Blob(raw.to_vec()),
+        };
+        Ok(PgCell(cell))
+    }
+
+    fn from_sql_null(_: &Type) -> Result<Self, BoxError> {
+        Ok(PgCell(CellValue::Null))
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+/// Renders the binary NUMERIC format: base-10000 digit groups, the weight
+/// of the first group, a sign word and the display scale.
+fn decode_numeric(raw: &[u8]) -> Result<String, BoxError> {
+    let word = |i: usize| -> Result<u16, BoxError> {
+        raw.get(i..i + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+            .ok_or_else(|| "Truncated NUMERIC value".into())
+    };
+    let group_count = word(0)? as usize;
+    let weight = word(2)? as i16 as i32;
+    let sign = word(4)?;
+    let scale = word(6)? as usize;
+    let groups = (0..group_count).map(|i| word(8 + 2 * i)).collect::<Result<Vec<_>, _>>()?;
+
+    match sign {
+        0xC000 => return Ok("NaN".to_string()),
+        0xD000 => return Ok("Infinity".to_string()),
+        0xF000 => return Ok("-Infinity".to_string()),
+        _ => {}
+    }
+    let group = |i: i32| if i < 0 { 0 } else { groups.get(i as usize).copied().unwrap_or(0) };
+
+    let mut text = String::new();
+    if sign == 0x4000 {
+        text.push('-');
+    }
+    if weight < 0 {
+        text.push('0');
+    } else {
+        text.push_str(&group(0).to_string());
+        for i in 1..=weight {
+            text.push_str(&format!("{:04}", group(i)));
+        }
+    }
+    if scale > 0 {
+        let mut fraction = String::new();
+        let mut i = weight + 1;
+        while fraction.len() < scale {
+            fraction.push_str(&format!("{:04}", group(i)));
+            i += 1;
+        }
+        fraction.truncate(scale);
+        text.push('.');
+        text.push_str(&fraction);
+    }
+    Ok(text)
+}
+
+fn decode_uuid(raw: &[u8]) -> Result<String, BoxError> {
+    if raw.len() != 16 {
+        return Err("Invalid UUID value".into());
+    }
+    let hex: String = raw.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32]))
+}
+
+/// A cell bound to a parameter of whatever type the server inferred.
+/// Values that do not fit the type need an explicit cast in the query.
+#[derive(Debug)]
+struct PgParam<'a>(&'a CellValue);
+
+impl ToSql for PgParam<'_> {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+        match (self.0, ty) {
+            (CellValue::Null, _) => Ok(IsNull::Yes),
+            (CellValue::Integer(v), &Type::BOOL) => (*v != 0).to_sql(ty, out),
+            (CellValue::Integer(v), &Type::INT2) => i16::try_from(*v)?.to_sql(ty, out),
+            (CellValue::Integer(v), &Type::INT4) => i32::try_from(*v)?.to_sql(ty, out),
+            (CellValue::Integer(v), &Type::INT8) => v.to_sql(ty, out),
+            (CellValue::Integer(v), &Type::OID) => u32::try_from(*v)?.to_sql(ty, out),
+            (CellValue::Integer(v), &Type::FLOAT4) => (*v as f32).to_sql(ty, out),
+            (CellValue::Integer(v), &Type::FLOAT8) => (*v as f64).to_sql(ty, out),
+            (CellValue::Real(v), &Type::FLOAT4) => (*v as f32).to_sql(ty, out),
+            (CellValue::Real(v), &Type::FLOAT8) => v.to_sql(ty, out),
+            (CellValue::Text(v), &Type::JSON | &Type::JSONB) => serde_json::from_str::<serde_json::Value>(v)?.to_sql(ty, out),
+            (CellValue::Text(v), _) if is_text_type(ty) => {
+                out.extend_from_slice(v.as_bytes());
+                Ok(IsNull::No)
+            }
+            (CellValue::Blob(v), &Type::BYTEA) => v.to_sql(ty, out),
+            (value, _) => Err(format!(
+                "Cannot bind {} to a parameter of type {}; cast the placeholder, e.g. $1::text",
+                match value {
+                    CellValue::Null => "NULL",
+                    CellValue::Integer(_) => "an integer",
+                    CellValue::Real(_) => "a real",
+                    CellValue::Text(_) => "text",
+                    CellValue::Blob(_) => "a blob",
+                },
+                ty.name()
+            ).into()),
+        }
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
@@ -1,28 +1,29 @@
 use std::path::Path;
+use crate::error::{AppError, AppResult};
 use crate::git;
 
 pub struct ProjectManager;
 
 impl ProjectManager {
-    pub fn validate_project_path(path: &str) -> Result<(), String> {
+    pub fn validate_project_path(path: &str) -> AppResult<()> {
         let path = Path::new(path);
         
         if !path.exists() {
-            return Err("Path does not exist".to_string());
+            return Err(AppError::invalid_path("path", path, "Path does not exist"));
         }
         
         if !path.is_dir() {
-            return Err("Path is not a directory".to_string());
+            return Err(AppError::invalid_path("path", path, "Path is not a directory"));
         }
         
         Ok(())
     }
 
-    pub fn get_project_info(path: &str) -> Result<(String, Option<String>), String> {
+    pub fn get_project_info(path: &str) -> AppResult<(String, Option<String>)> {
         let path = Path::new(path);
         
         if !path.exists() {
-            return Err("Path does not exist".to_string());
+            return Err(AppError::invalid_path("path", path, "Path does not exist"));
         }
         
         let name = path.file_name()
@@ -89,6 +90,8 @@ impl ProjectManager {
             }
         }
         
+        // Directory order is arbitrary
+        env_files.sort();
         env_files
     }
 }
@@ -0,0 +1,154 @@
+use crate::error::AppResult;
+use crate::model::*;
+
+// Storage interfaces used by the command layer. `Database` implements them on
+// SQLite and `MemoryStore` keeps everything in memory.
+
+pub trait TaskRepository {
+    fn create_task(&self, form_data: TaskFormData) -> AppResult<Task>;
+    fn get_all_tasks(&self) -> AppResult<Vec<Task>>;
+    fn get_task_by_id(&self, id: u32) -> AppResult<Option<Task>>;
+    fn update_task(&self, task: &Task) -> AppResult<()>;
+    fn delete_task(&self, id: u32) -> AppResult<()>;
+    fn mark_task_completed(&self, id: u32, completed: bool) -> AppResult<()>;
+}
+
+pub trait ProjectRepository {
+    fn create_project(&self, name: String, path: String, project_type: Option<String>, description: Option<String>) -> AppResult<Project>;
+    fn get_all_projects(&self) -> AppResult<Vec<Project>>;
+    fn get_project_by_id(&self, id: u32) -> AppResult<Option<Project>>;
+    fn update_project(&self, project: &Project) -> AppResult<()>;
+
+    /// Lists the rows that reference a project, i.e. what deleting it affects.
+    fn get_project_deletion_impact(&self, project_id: u32) -> AppResult<ProjectDeletionImpact>;
+
+    /// Deletes a project, detaching its services and tasks and removing its git repos.
+    fn delete_project(&self, id: u32) -> AppResult<()>;
+
+    fn delete_project_with_mode(&self, id: u32, mode: ProjectDeleteMode) -> AppResult<ProjectDeletionImpact>;
+}
+
+pub trait ServiceRepository {
+    fn create_service(&self, name: String, command: String, working_dir: Option<String>, project_id: Option<u32>, auto_start: bool, shell: bool) -> AppResult<Service>;
+    fn get_all_services(&self) -> AppResult<Vec<Service>>;
+    fn get_service_by_id(&self, id: u32) -> AppResult<Option<Service>>;
+    fn update_service(&self, service: &Service) -> AppResult<()>;
+    fn delete_service(&self, id: u32) -> AppResult<()>;
+
+    /// Variables and env files in their saved order; deleted with the service.
+    fn get_service_environment(&self, service_id: u32) -> AppResult<ServiceEnvironment>;
+
+    /// Replaces the whole environment. Values of secret variables are not stored.
+    fn set_service_environment(&self, service_id: u32, environment: &ServiceEnvironment) -> AppResult<()>;
+
+    /// The default policy when none was saved; deleted with the service.
+    fn get_restart_policy(&self, service_id: u32) -> AppResult<RestartPolicy>;
+    fn set_restart_policy(&self, service_id: u32, policy: &RestartPolicy) -> AppResult<()>;
+
+    /// No probes when none were saved; deleted with the service.
+    fn get_health_checks(&self, service_id: u32) -> AppResult<HealthChecks>;
+    fn set_health_checks(&self, service_id: u32, checks: &HealthChecks) -> AppResult<()>;
+
+    /// The default retention when none was saved; deleted with the service.
+    fn get_log_retention(&self, service_id: u32) -> AppResult<LogRetention>;
+    fn set_log_retention(&self, service_id: u32, retention: &LogRetention) -> AppResult<()>;
+
+    /// The default parser when none was saved; deleted with the service.
+    fn get_log_parser(&self, service_id: u32) -> AppResult<LogParser>;
+    fn set_log_parser(&self, service_id: u32, parser: &LogParser) -> AppResult<()>;
+
+    /// Ids of the services that must be running before this one, ordered by
+    /// id. Removed when either service is deleted.
+    fn get_service_dependencies(&self, service_id: u32) -> AppResult<Vec<u32>>;
+    fn set_service_dependencies(&self, service_id: u32, depends_on: &[u32]) -> AppResult<()>;
+}
+
+/// Named groups of services. Deleting a service removes it from its groups.
+pub trait ServiceGroupRepository {
+    fn create_service_group(&self, name: String, service_ids: &[u32]) -> AppResult<ServiceGroup>;
+
+    /// Ordered by name.
+    fn get_service_groups(&self) -> AppResult<Vec<ServiceGroup>>;
+    fn get_service_group_by_id(&self, id: u32) -> AppResult<Option<ServiceGroup>>;
+
+    /// Renames the group and replaces its members.
+    fn update_service_group(&self, group: &ServiceGroup) -> AppResult<()>;
+    fn delete_service_group(&self, id: u32) -> AppResult<()>;
+}
+
+pub trait GitRepoRepository {
+    fn create_git_repo(&self, project_id: u32, repo_path: String) -> AppResult<u32>;
+    fn get_git_repo_by_project_id(&self, project_id: u32) -> AppResult<Option<GitStatus>>;
+    fn update_git_repo(&self, git_status: &GitStatus) -> AppResult<()>;
+    fn delete_git_repo(&self, id: u32) -> AppResult<()>;
+}
+
+/// Connection profiles belong to a project and are deleted with it.
+pub trait ConnectionProfileRepository {
+    fn create_connection_profile(&self, form: &ConnectionProfileForm) -> AppResult<ConnectionProfile>;
+
+    /// Ordered by name, for one project or all of them.
+    fn get_connection_profiles(&self, project_id: Option<u32>) -> AppResult<Vec<ConnectionProfile>>;
+    fn get_connection_profile_by_id(&self, id: u32) -> AppResult<Option<ConnectionProfile>>;
+    fn update_connection_profile(&self, profile: &ConnectionProfile) -> AppResult<()>;
+    fn delete_connection_profile(&self, id: u32) -> AppResult<()>;
+}
+
+/// Oldest history entries are dropped once this many are stored.
+pub const MAX_QUERY_HISTORY: u32 = 1000;
+
+/// Database browser history and saved queries. Searches match
+/// case-insensitively anywhere in the text.
+pub trait QueryRepository {
+    fn add_query_history(&self, entry: NewQueryHistory) -> AppResult<QueryHistoryEntry>;
+
+    /// Newest first, optionally limited to one database.
+    fn search_query_history(&self, search: Option<&str>, database_id: Option<&str>, limit: u32) -> AppResult<Vec<QueryHistoryEntry>>;
+    fn clear_query_history(&self) -> AppResult<()>;
+
+    fn create_saved_query(&self, name: String, query: String, database_id: Option<String>, description: Option<String>) -> AppResult<SavedQuery>;
+
+    /// Ordered by name; `search` matches the name, query and description.
+    fn search_saved_queries(&self, search: Option<&str>) -> AppResult<Vec<SavedQuery>>;
+    fn get_saved_query_by_id(&self, id: u32) -> AppResult<Option<SavedQuery>>;
+    fn update_saved_query(&self, saved_query: &SavedQuery) -> AppResult<()>;
+    fn delete_saved_query(&self, id: u32) -> AppResult<()>;
+}
+
+/// Everything the app persists. Implemented for any type providing all repositories.
+pub trait Store:
+    TaskRepository + ProjectRepository + ServiceRepository + ServiceGroupRepository + GitRepoRepository + QueryRepository
+    + ConnectionProfileRepository
+{
+}
+
+impl<T> Store for T where
+    T: TaskRepository + ProjectRepository + ServiceRepository + ServiceGroupRepository + GitRepoRepository + QueryRepository
+        + ConnectionProfileRepository
+{
+}
+
+/// Builds a new, unsaved task (id 0) from the task form.
+pub(crate) fn task_from_form(form_data: TaskFormData, created_at: String) -> Task {
+    // Convert form data to proper types
+    let estimated_hours: u32 = form_data.estimated_hours.parse().unwrap_or(0);
+    let estimated_minutes: u32 = form_data.estimated_minutes.parse().unwrap_or(0);
+    let priority: u8 = form_data.priority.parse().unwrap_or(3);
+
+    Task {
+        id: 0,
+        title: form_data.title,
+        priority,
+        deadline: form_data.deadline,
+        estimated_time: estimated_hours * 60 + estimated_minutes,
+        start_date: if form_data.start_date.is_empty() { None } else { Some(form_data.start_date) },
+        scheduled_start: None,
+        completed: false,
+        locked: false,
+        category: None,
+        reminder_enabled: false,
+        reminder_minutes: 0,
+        project_id: None,
+        created_at,
+    }
+}
@@ -0,0 +1,140 @@
+use crate::error::{AppError, AppResult};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// File name of the secret store in the data directory.
+pub const SECRETS_FILE: &str = "secrets.json";
+
+/// Credentials kept outside the app database, so they never end up in the
+/// database browser, exports or snapshots.
+pub trait SecretStore {
+    fn get_secret(&self, key: &str) -> AppResult<Option<String>>;
+    fn set_secret(&self, key: &str, value: &str) -> AppResult<()>;
+    fn delete_secret(&self, key: &str) -> AppResult<()>;
+}
+
+/// Key of a connection profile's password. Profile ids are per workspace
+/// database, so the key includes the workspace.
+pub fn connection_password_key(workspace: &str, profile_id: u32) -> String {
+    format!("{}/connection-profile:{}", workspace, profile_id)
+}
+
+/// Key of a secret environment variable of a service.
+pub fn service_env_key(workspace: &str, service_id: u32, name: &str) -> String {
+    format!("{}/service-env:{}:{}", workspace, service_id, name)
+}
+
+/// Secrets in a JSON file readable only by the current user. This keeps
+/// them out of the database but does not encrypt them.
+pub struct FileSecretStore {
+    path: PathBuf,
+    // Serializes read-modify-write cycles within the process
+    lock: Mutex<()>,
+}
+
+impl FileSecretStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileSecretStore {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn read(&self) -> AppResult<BTreeMap<String, String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(|e| {
+                AppError::internal(format!("Secret store {} is corrupt: {}", self.path.display(), e))
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(AppError::io("Failed to read secret store", &self.path, e)),
+        }
+    }
+
+    /// Writes a sibling file first so a crash never leaves a truncated store.
+    fn write(&self, secrets: &BTreeMap<String, String>) -> AppResult<()> {
+        let contents = serde_json::to_string_pretty(secrets)
+            .map_err(|e| AppError::internal(format!("Failed to serialize secrets: {}", e)))?;
+        let partial = self.path.with_extension("json.partial");
+
+        write_private(&partial, &contents)
+            .map_err(|e| AppError::io("Failed to write secret store", &partial, e))?;
+        fs::rename(&partial, &self.path)
+            .map_err(|e| AppError::io("Failed to write secret store", &self.path, e))
+    }
+
+    fn update(&self, change: impl FnOnce(&mut BTreeMap<String, String>)) -> AppResult<()> {
+        let _guard = self.lock.lock()?;
+        let mut secrets = self.read()?;
+        change(&mut secrets);
+        self.write(&secrets)
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(contents.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    fs::write(path, contents)
+}
+
+impl SecretStore for FileSecretStore {
+    fn get_secret(&self, key: &str) -> AppResult<Option<String>> {
+        let _guard = self.lock.lock()?;
+        Ok(self.read()?.remove(key))
+    }
+
+    fn set_secret(&self, key: &str, value: &str) -> AppResult<()> {
+        self.update(|secrets| {
+            secrets.insert(key.to_string(), value.to_string());
+        })
+    }
+
+    fn delete_secret(&self, key: &str) -> AppResult<()> {
+        self.update(|secrets| {
+            secrets.remove(key);
+        })
+    }
+}
+
+/// Secret store for tests and for running without a data directory.
+#[derive(Default)]
+pub struct MemorySecretStore {
+    secrets: Mutex<BTreeMap<String, String>>,
+}
+
+impl MemorySecretStore {
+    pub fn new() -> Self {
+        MemorySecretStore::default()
+    }
+}
+
+impl SecretStore for MemorySecretStore {
+    fn get_secret(&self, key: &str) -> AppResult<Option<String>> {
+        Ok(self.secrets.lock()?.get(key).cloned())
+    }
+
+    fn set_secret(&self, key: &str, value: &str) -> AppResult<()> {
+        self.secrets.lock()?.insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete_secret(&self, key: &str) -> AppResult<()> {
+        self.secrets.lock()?.remove(key);
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+use crate::command_line::is_env_name;
+use crate::error::{AppError, AppResult};
+use crate::model::ServiceEnvVar;
+use serde::Serialize;
+use std::fs;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+
+/// Where a variable of a service's environment gets its value.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum EnvSource {
+    File { path: String },
+    Service,
+}
+
+/// A variable a service starts with, after files and overrides are applied.
+#[derive(Debug, Serialize, Clone)]
+pub struct ResolvedEnvVar {
+    pub name: String,
+    /// `None` once masked.
+    pub value: Option<String>,
+    pub secret: bool,
+    pub source: EnvSource,
+}
+
+impl ResolvedEnvVar {
+    /// Hides the value of secrets, for anything sent to the frontend.
+    pub fn masked(self) -> Self {
+        ResolvedEnvVar {
+            value: if self.secret { None } else { self.value },
+            ..self
+        }
+    }
+}
+
+// Name parts that mark a variable loaded from a file as a credential
+const SECRET_NAME_PARTS: &[&str] = &[
+    "SECRET", "PASSWORD", "PASSWD", "PASS", "TOKEN", "KEY", "APIKEY", "CREDENTIAL", "CREDENTIALS", "PRIVATE", "AUTH",
+];
+
+/// Guesses whether a variable from an env file holds a credential, e.g.
+/// `DB_PASSWORD` or `STRIPE_SECRET_KEY`. Files have no secret flag, so
+/// their values are masked by name.
+pub fn looks_secret(name: &str) -> bool {
+    name.to_ascii_uppercase()
+        .split('_')
+        .any(|part| SECRET_NAME_PARTS.contains(&part))
+}
+
+/// Resolves an env file path; relative ones are taken from `base_dir`.
+pub fn env_file_path(path: &str, base_dir: Option<&Path>) -> AppResult<PathBuf> {
+    let path = Path::new(path);
+    match base_dir {
+        _ if path.is_absolute() => Ok(path.to_path_buf()),
+        Some(base_dir) => Ok(base_dir.join(path)),
+        None => Err(AppError::invalid_field(
+            "envFiles",
+            format!("{} is relative, but the service has no working directory or project", path.display()),
+        )),
+    }
+}
+
+/// Loads `files` in order, later files overriding earlier ones, then
+/// applies `variables`, whose values must be filled in. Files may refer
+/// to the app's own environment and to variables set before them.
+pub fn resolve_environment(files: &[PathBuf], variables: &[ServiceEnvVar]) -> AppResult<Vec<ResolvedEnvVar>> {
+    let mut resolved: Vec<ResolvedEnvVar> = Vec::new();
+    let set = |resolved: &mut Vec<ResolvedEnvVar>, var: ResolvedEnvVar| {
+        match resolved.iter_mut().find(|r| r.name == var.name) {
+            Some(existing) => *existing = var,
+            None => resolved.push(var),
+        }
+    };
+
+    for path in files {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| AppError::io("Failed to read env file", path, e))?;
+        let lookup = |name: &str| {
+            resolved.iter()
+                .find(|r| r.name == name)
+                .and_then(|r| r.value.clone())
+                .or_else(|| std::env::var(name).ok())
+        };
+        let parsed = parse_dotenv(&contents, lookup)
+            .map_err(|e| AppError::validation(format!("{}: {}", path.display(), e)))?;
+
+        for (name, value) in parsed {
+            let secret = looks_secret(&name);
+            set(&mut resolved, ResolvedEnvVar {
+                name,
+                value: Some(value),
+                secret,
+                source: EnvSource::File { path: path.display().to_string() },
+            });
+        }
+    }
+
+    for var in variables {
+        set(&mut resolved, ResolvedEnvVar {
+            name: var.name.clone(),
+            value: var.value.clone(),
+            secret: var.secret,
+            source: EnvSource::Service,
+        });
+    }
+    Ok(resolved)
+}
+
+/// Parses a `.env` file into its assignments, in order of first
+/// appearance; a name assigned twice keeps the last value.
+///
+/// - `#` starts a comment at the beginning of a line or after whitespace
+/// - `export NAME=value` is the same as `NAME=value`
+/// - single quotes keep their content literally
+/// - double quotes understand `\n`, `\t`, `\r`, `\"`, `\\` and `\$`
+/// - quoted values may span lines
+/// - `$NAME`, `${NAME}`, `${NAME:-default}` and `${NAME-default}` expand outside single quotes,
+///   from earlier lines first and then `lookup`; unset names expand to nothing
+pub fn parse_dotenv(contents: &str, lookup: impl Fn(&str) -> Option<String>) -> AppResult<Vec<(String, String)>> {
+    let mut parser = DotenvParser {
+        chars: contents.chars().peekable(),
+        line: 1,
+        vars: Vec::new(),
+    };
+    parser.parse(&lookup)?;
+    Ok(parser.vars)
+}
+
+struct DotenvParser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    vars: Vec<(String, String)>,
+}
+
+impl DotenvParser<'_> {
+    fn error(&self, line: usize, message: impl std::fmt::Display) -> AppError {
+        AppError::validation(format!("line {}: {}", line, message))
+    }
+
+    fn skip_blanks(&mut self) {
+        while self.chars.next_if(|&c| c == ' ' || c == '\t' || c == '\r').is_some() {}
+    }
+
+    // Leaves the newline for the caller
+    fn skip_comment(&mut self) {
+        while self.chars.next_if(|&c| c != '\n').is_some() {}
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.chars.next_if(|&c| c != '=' && !c.is_whitespace()) {
+            word.push(c);
+        }
+        word
+    }
+
+    fn parse(&mut self, lookup: &dyn Fn(&str) -> Option<String>) -> AppResult<()> {
+        loop {
+            self.skip_blanks();
+            match self.chars.peek() {
+                None => return Ok(()),
+                Some('\n') => {
+                    self.advance();
+                    continue;
+                }
+                Some('#') => {
+                    self.skip_comment();
+                    continue;
+                }
+                Some(_) => {}
+            }
+
+            let line = self.line;
+            let mut name = self.word();
+            if name == "export" && self.chars.peek().is_some_and(|&c| c == ' ' || c == '\t') {
+                self.skip_blanks();
+                name = self.word();
+            }
+            self.skip_blanks();
+            if self.chars.next_if_eq(&'=').is_none() {
+                return Err(self.error(line, "expected NAME=value"));
+            }
+            if !is_env_name(&name) {
+                return Err(self.error(line, format!("{} is not a valid variable name", name)));
+            }
+            self.skip_blanks();
+
+            let value = match self.chars.peek() {
+                Some('\'') => self.single_quoted(line)?,
+                Some('"') => self.double_quoted(line, lookup)?,
+                _ => self.unquoted(lookup)?,
+            };
+
+            // Only a comment may follow a value
+            self.skip_blanks();
+            match self.chars.peek() {
+                None | Some('\n') => {}
+                Some('#') => self.skip_comment(),
+                Some(_) => return Err(self.error(self.line, "unexpected text after the closing quote")),
+            }
+
+            match self.vars.iter_mut().find(|(n, _)| *n == name) {
+                Some(existing) => existing.1 = value,
+                None => self.vars.push((name, value)),
+            }
+        }
+    }
+
+    fn single_quoted(&mut self, line: usize) -> AppResult<String> {
+        self.advance();
+        let mut value = String::new();
+        loop {
+            match self.advance() {
+                Some('\'') => return Ok(value),
+                Some(c) => value.push(c),
+                None => return Err(self.error(line, "unterminated single quote")),
+            }
+        }
+    }
+
+    fn double_quoted(&mut self, line: usize, lookup: &dyn Fn(&str) -> Option<String>) -> AppResult<String> {
+        self.advance();
+        let mut value = String::new();
+        loop {
+            match self.advance() {
+                Some('"') => return Ok(value),
+                Some('\\') => match self.advance() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some(c @ ('"' | '\\' | '$')) => value.push(c),
+                    Some(c) => {
+                        value.push('\\');
+                        value.push(c);
+                    }
+                    None => return Err(self.error(line, "unterminated double quote")),
+                },
+                Some('$') => self.expansion(&mut value, lookup)?,
+                Some(c) => value.push(c),
+                None => return Err(self.error(line, "unterminated double quote")),
+            }
+        }
+    }
+
+    fn unquoted(&mut self, lookup: &dyn Fn(&str) -> Option<String>) -> AppResult<String> {
+        let mut value = String::new();
+        while let Some(c) = self.chars.next_if(|&c| c != '\n') {
+            match c {
+                // A comment needs whitespace before it, so `a#b` stays whole
+                '#' if value.ends_with([' ', '\t']) => {
+                    self.skip_comment();
+                    break;
+                }
+                '$' => self.expansion(&mut value, lookup)?,
+                c => value.push(c),
+            }
+        }
+        Ok(value.trim_end().to_string())
+    }
+
+    /// Expands the reference after a `$` into `value`; a `$` not followed
+    /// by a name or `{` is kept.
+    fn expansion(&mut self, value: &mut String, lookup: &dyn Fn(&str) -> Option<String>) -> AppResult<()> {
+        let line = self.line;
+        let resolve = |vars: &[(String, String)], name: &str| {
+            vars.iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v.clone())
+                .or_else(|| lookup(name))
+        };
+
+        if self.chars.next_if_eq(&'{').is_some() {
+            let mut inner = String::new();
+            loop {
+                match self.chars.next() {
+                    Some('}') => break,
+                    Some('\n') | None => return Err(self.error(line, "unterminated ${")),
+                    Some(c) => inner.push(c),
+                }
+            }
+            let (name, default, use_default_if_empty) = match inner.find(['-', ':']) {
+                Some(i) if inner[i..].starts_with(":-") => (&inner[..i], Some(&inner[i + 2..]), true),
+                Some(i) if inner[i..].starts_with('-') => (&inner[..i], Some(&inner[i + 1..]), false),
+                Some(_) => return Err(self.error(line, format!("unsupported expansion ${{{}}}", inner))),
+                None => (inner.as_str(), None, false),
+            };
+            if !is_env_name(name) {
+                return Err(self.error(line, format!("{} is not a valid variable name", name)));
+            }
+
+            let current = resolve(&self.vars, name);
+            let expanded = match (current, default) {
+                (Some(v), Some(default)) if v.is_empty() && use_default_if_empty => default.to_string(),
+                (Some(v), _) => v,
+                (None, Some(default)) => default.to_string(),
+                (None, None) => String::new(),
+            };
+            value.push_str(&expanded);
+        } else if self.chars.peek().is_some_and(|&c| c == '_' || c.is_ascii_alphabetic()) {
+            let mut name = String::new();
+            while let Some(c) = self.chars.next_if(|&c| c == '_' || c.is_ascii_alphanumeric()) {
+                name.push(c);
+            }
+            value.push_str(&resolve(&self.vars, &name).unwrap_or_default());
+        } else {
+            value.push('$');
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use crate::error::{AppError, AppResult};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Service ids mapped to the ids they depend on.
+pub type Dependencies = BTreeMap<u32, Vec<u32>>;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+/// `targets` and everything they depend on, each service after its
+/// dependencies. Ties are broken by id so the order is stable.
+pub fn start_order(targets: &[u32], dependencies: &Dependencies) -> AppResult<Vec<u32>> {
+    let mut order = Vec::new();
+    let mut visits = BTreeMap::new();
+    let mut path = Vec::new();
+    let targets: BTreeSet<u32> = targets.iter().copied().collect();
+
+    for &target in &targets {
+        if let Some(cycle) = visit(target, dependencies, &mut visits, &mut path, &mut order) {
+            return Err(AppError::validation(format!("Dependency cycle: {}", format_ids(&cycle))));
+        }
+    }
+    Ok(order)
+}
+
+/// A chain of dependencies leading back to where it started, such as
+/// `[1, 2, 1]`, if there is one.
+pub fn find_cycle(dependencies: &Dependencies) -> Option<Vec<u32>> {
+    let mut visits = BTreeMap::new();
+    let mut path = Vec::new();
+    let mut order = Vec::new();
+    dependencies.keys().find_map(|&id| visit(id, dependencies, &mut visits, &mut path, &mut order))
+}
+
+// Depth-first, appending each service once its dependencies are in `order`
+fn visit(
+    id: u32,
+    dependencies: &Dependencies,
+    visits: &mut BTreeMap<u32, Visit>,
+    path: &mut Vec<u32>,
+    order: &mut Vec<u32>,
+) -> Option<Vec<u32>> {
+    match visits.get(&id) {
+        Some(Visit::Done) => return None,
+        Some(Visit::InProgress) => {
+            let start = path.iter().position(|&p| p == id).unwrap_or(0);
+            let mut cycle = path[start..].to_vec();
+            cycle.push(id);
+            return Some(cycle);
+        }
+        None => {}
+    }
+
+    visits.insert(id, Visit::InProgress);
+    path.push(id);
+    let mut depends_on: Vec<u32> = dependencies.get(&id).cloned().unwrap_or_default();
+    depends_on.sort_unstable();
+    for dependency in depends_on {
+        if let Some(cycle) = visit(dependency, dependencies, visits, path, order) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    visits.insert(id, Visit::Done);
+    order.push(id);
+    None
+}
+
+fn format_ids(ids: &[u32]) -> String {
+    ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(" → ")
+}
@@ -0,0 +1,84 @@
+use crate::error::{AppError, AppResult};
+use crate::model::{Service, ServiceState};
+use crate::services::{LaunchOptions, ServiceManager};
+use serde::Serialize;
+use std::time::Duration;
+
+/// How long each service of a group may take to become ready.
+pub const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A service of a group that could not be brought up.
+#[derive(Debug, Serialize, Clone)]
+pub struct GroupFailure {
+    #[serde(rename = "serviceId")]
+    pub service_id: u32,
+    pub name: String,
+    pub error: String,
+}
+
+/// What starting a group did, by service id in start order.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct GroupStartReport {
+    pub started: Vec<u32>,
+    #[serde(rename = "alreadyRunning")]
+    pub already_running: Vec<u32>,
+    /// The first service that failed; nothing after it was started.
+    pub failed: Option<GroupFailure>,
+    /// Left stopped because a service before them failed.
+    pub skipped: Vec<u32>,
+}
+
+/// Starts `services` one at a time, in the order given, waiting for each to
+/// be ready before the next. Services that are already running or waiting
+/// to restart are only waited for. Stops at the first failure, leaving what
+/// started running.
+pub async fn start_in_order(
+    manager: &ServiceManager,
+    services: Vec<Service>,
+    launch: impl Fn(&Service) -> AppResult<LaunchOptions>,
+    ready_timeout: Duration,
+) -> GroupStartReport {
+    let mut report = GroupStartReport::default();
+
+    for (i, service) in services.iter().enumerate() {
+        let running = manager.is_supervised(service.id).await;
+        let result = async {
+            if !running {
+                manager.start_service_with(service.id, service, launch(service)?).await?;
+            }
+            manager.wait_until_ready(service.id, ready_timeout).await
+        }.await;
+
+        match result {
+            Ok(()) if running => report.already_running.push(service.id),
+            Ok(()) => report.started.push(service.id),
+            Err(e) => {
+                report.failed = Some(GroupFailure {
+                    service_id: service.id,
+                    name: service.name.clone(),
+                    error: e.to_string(),
+                });
+                report.skipped = services[i + 1..].iter().map(|s| s.id).collect();
+                break;
+            }
+        }
+    }
+    report
+}
+
+/// Stops `services` in the order given, skipping those not running.
+/// Returns the ids that were stopped.
+pub async fn stop_in_order(manager: &ServiceManager, services: Vec<Service>) -> AppResult<Vec<u32>> {
+    let mut stopped = Vec::new();
+    for service in services {
+        // Services that exited keep their supervisor entry until stopped
+        let state = manager.service_status(service.id).await.state;
+        let running = matches!(state, ServiceState::Running | ServiceState::Restarting);
+        match manager.stop_service(service.id).await {
+            Ok(()) if running => stopped.push(service.id),
+            Ok(()) | Err(AppError::NotFound { .. }) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(stopped)
+}
@@ -0,0 +1,203 @@
+use crate::error::AppResult;
+use crate::model::ResourceSample;
+use chrono::Utc;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+use sysinfo::{Pid, ProcessRefreshKind, System};
+
+/// How often running services are sampled.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+/// Samples kept per service, ten minutes at the default interval.
+pub const HISTORY_LEN: usize = 300;
+
+/// Samples the process trees of running services. It keeps one `System`
+/// between samples, which is also what lets sysinfo compute CPU usage.
+pub struct ResourceSampler {
+    system: System,
+}
+
+impl Default for ResourceSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourceSampler {
+    pub fn new() -> Self {
+        ResourceSampler { system: System::new() }
+    }
+
+    /// One sample per `(service_id, pid)` whose process still runs.
+    /// Only those processes and their descendants are refreshed.
+    pub fn sample(&mut self, services: &[(u32, u32)]) -> Vec<(u32, ResourceSample)> {
+        #[cfg(not(target_os = "linux"))]
+        {
+            // Elsewhere children are only found through the parent of every process
+            self.system.refresh_processes_specifics(ProcessRefreshKind::new());
+        }
+
+        let trees: Vec<(u32, u32, Vec<Pid>)> = services
+            .iter()
+            .map(|&(service_id, pid)| (service_id, pid, self.process_tree(Pid::from_u32(pid))))
+            .collect();
+        let all_pids: Vec<Pid> = trees.iter().flat_map(|(_, _, tree)| tree.iter().copied()).collect();
+        self.system.refresh_pids_specifics(&all_pids, ProcessRefreshKind::new().with_cpu().with_memory());
+
+        let listening = listening_sockets();
+        let timestamp = Utc::now().to_rfc3339();
+        trees
+            .into_iter()
+            .filter(|(_, pid, _)| self.system.process(Pid::from_u32(*pid)).is_some())
+            .map(|(service_id, pid, tree)| {
+                let mut sample = ResourceSample {
+                    timestamp: timestamp.clone(),
+                    pid,
+                    processes: 0,
+                    cpu_percent: 0.0,
+                    rss_bytes: 0,
+                    threads: None,
+                    open_ports: Vec::new(),
+                };
+                let mut ports = HashSet::new();
+                for pid in &tree {
+                    let Some(process) = self.system.process(*pid) else { continue };
+                    sample.processes += 1;
+                    sample.cpu_percent += process.cpu_usage();
+                    sample.rss_bytes += process.memory();
+                    if let Some(tasks) = process.tasks() {
+                        *sample.threads.get_or_insert(0) += tasks.len() as u32;
+                    }
+                    ports.extend(open_ports(*pid, &listening));
+                }
+                sample.open_ports = ports.into_iter().collect();
+                sample.open_ports.sort_unstable();
+                (service_id, sample)
+            })
+            .collect()
+    }
+
+    /// `root` and its descendants, parents first.
+    fn process_tree(&self, root: Pid) -> Vec<Pid> {
+        let mut tree = vec![root];
+        let mut next = 0;
+        while next < tree.len() {
+            let children = self.children(tree[next]);
+            // A pid reused while walking must not loop forever
+            tree.extend(children.into_iter().filter(|child| !tree.contains(child)).collect::<Vec<_>>());
+            next += 1;
+        }
+        tree
+    }
+
+    #[cfg(target_os = "linux")]
+    fn children(&self, pid: Pid) -> Vec<Pid> {
+        // Each thread lists the children it started
+        let Ok(tasks) = std::fs::read_dir(format!("/proc/{}/task", pid)) else { return Vec::new() };
+        tasks
+            .flatten()
+            .filter_map(|task| std::fs::read_to_string(task.path().join("children")).ok())
+            .flat_map(|children| {
+                children.split_whitespace().filter_map(|child| child.parse().ok()).collect::<Vec<u32>>()
+            })
+            .map(Pid::from_u32)
+            .collect()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn children(&self, pid: Pid) -> Vec<Pid> {
+        self.system
+            .processes()
+            .values()
+            .filter(|process| process.parent() == Some(pid))
+            .map(|process| process.pid())
+            .collect()
+    }
+}
+
+/// Socket inode to port of every listening TCP socket.
+#[cfg(target_os = "linux")]
+fn listening_sockets() -> HashMap<u64, u16> {
+    let mut sockets = HashMap::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        let Ok(contents) = std::fs::read_to_string(table) else { continue };
+        // sl local_address rem_address st tx:rx tr:when retrnsmt uid timeout inode
+        for line in contents.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 || fields[3] != "0A" {
+                continue;
+            }
+            let port = fields[1].rsplit(':').next().and_then(|port| u16::from_str_radix(port, 16).ok());
+            if let (Some(port), Ok(inode)) = (port, fields[9].parse()) {
+                sockets.insert(inode, port);
+            }
+        }
+    }
+    sockets
+}
+
+#[cfg(not(target_os = "linux"))]
+fn listening_sockets() -> HashMap<u64, u16> {
+    HashMap::new()
+}
+
+/// Ports `pid` listens on, found through its open socket descriptors.
+#[cfg(target_os = "linux")]
+fn open_ports(pid: Pid, listening: &HashMap<u64, u16>) -> Vec<u16> {
+    let Ok(fds) = std::fs::read_dir(format!("/proc/{}/fd", pid)) else { return Vec::new() };
+    fds.flatten()
+        .filter_map(|fd| std::fs::read_link(fd.path()).ok())
+        .filter_map(|target| {
+            let target = target.to_string_lossy().into_owned();
+            target.strip_prefix("socket:[")?.strip_suffix(']')?.parse::<u64>().ok()
+        })
+        .filter_map(|inode| listening.get(&inode).copied())
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_ports(_pid: Pid, _listening: &HashMap<u64, u16>) -> Vec<u16> {
+    Vec::new()
+}
+
+/// The latest `HISTORY_LEN` samples of each service since the app started.
+#[derive(Default)]
+pub struct MetricsHistory {
+    samples: Mutex<HashMap<u32, VecDeque<ResourceSample>>>,
+}
+
+impl MetricsHistory {
+    pub fn new() -> Self {
+        MetricsHistory::default()
+    }
+
+    pub fn record(&self, samples: Vec<(u32, ResourceSample)>) -> AppResult<()> {
+        let mut history = self.samples.lock()?;
+        for (service_id, sample) in samples {
+            let service_samples = history.entry(service_id).or_insert_with(|| VecDeque::with_capacity(HISTORY_LEN));
+            if service_samples.len() == HISTORY_LEN {
+                service_samples.pop_front();
+            }
+            service_samples.push_back(sample);
+        }
+        Ok(())
+    }
+
+    /// Oldest first, limited to the latest `limit` samples.
+    pub fn samples(&self, service_id: u32, limit: Option<usize>) -> AppResult<Vec<ResourceSample>> {
+        let history = self.samples.lock()?;
+        let Some(samples) = history.get(&service_id) else { return Ok(Vec::new()) };
+        let skip = limit.map_or(0, |limit| samples.len().saturating_sub(limit));
+        Ok(samples.iter().skip(skip).cloned().collect())
+    }
+
+    pub fn clear(&self, service_id: u32) -> AppResult<()> {
+        self.samples.lock()?.remove(&service_id);
+        Ok(())
+    }
+
+    pub fn clear_all(&self) -> AppResult<()> {
+        self.samples.lock()?.clear();
+        Ok(())
+    }
+}
@@ -1,202 +1,280 @@
-use crate::model::{LocalService, Service, LogEntry};
+use crate::command_line;
+use crate::error::{AppError, AppResult};
+use crate::health::{self, ProbeContext};
+use crate::log_parser::{self, ParsedLine, Stream};
+use crate::log_store::LogWriter;
+use crate::settings::DEFAULT_STOP_GRACE_PERIOD_MS;
+use crate::model::{
+    CrashRecord, ExitInfo, HealthChecks, LocalService, LogEntry, LogParser, Probe, ProbeResult, RestartMode, RestartPolicy, Service,
+    ServiceState, ServiceStatus,
+};
 use std::process::Stdio;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use sysinfo::{System, Pid};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, oneshot, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::task::JoinHandle;
 use std::collections::HashMap;
 use chrono::Utc;
 
+// Log lines kept per service
+const MAX_LOGS: usize = 1000;
+// Crashes kept per service
+const MAX_CRASHES: usize = 50;
+// Log lines saved with each crash
+const CRASH_LOG_LINES: usize = 20;
+// A run this long is not a crash loop, so the next crash starts the backoff over
+const STABLE_RUN: Duration = Duration::from_secs(30);
+// How long to wait for output written just before an exit
+const LOG_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+// Without a readiness probe, a process that stays up this long counts as ready
+const READY_AFTER: Duration = Duration::from_millis(500);
+// How often to check whether a signalled process group has exited
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(50);
+pub const DEFAULT_STOP_GRACE: Duration = Duration::from_millis(DEFAULT_STOP_GRACE_PERIOD_MS);
+
+/// Tauri event carrying a `ServiceStatus` whenever it changes.
+pub const SERVICE_STATUS_EVENT: &str = "service-status";
+
+/// What a service is started with besides its command.
+#[derive(Debug, Clone)]
+pub struct LaunchOptions {
+    /// Added to the app's environment.
+    pub env: Vec<(String, String)>,
+    pub restart: RestartPolicy,
+    pub health: HealthChecks,
+    pub log_parser: LogParser,
+    /// How long the process group has to exit before it is killed.
+    pub stop_grace: Duration,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        LaunchOptions {
+            env: Vec::new(),
+            restart: RestartPolicy::default(),
+            health: HealthChecks::default(),
+            log_parser: LogParser::default(),
+            stop_grace: DEFAULT_STOP_GRACE,
+        }
+    }
+}
+
+type Logs = Arc<Mutex<HashMap<u32, Vec<LogEntry>>>>;
+type Crashes = Arc<Mutex<HashMap<u32, Vec<CrashRecord>>>>;
+
 pub struct ServiceManager {
-    processes: Arc<Mutex<HashMap<u32, tokio::process::Child>>>,
-    logs: Arc<Mutex<HashMap<u32, Vec<LogEntry>>>>,
+    supervisors: Arc<Mutex<HashMap<u32, Supervisor>>>,
+    logs: Logs,
     log_id_counter: Arc<Mutex<u32>>,
+    crashes: Crashes,
+    events: broadcast::Sender<ServiceStatus>,
+    log_writer: Option<LogWriter>,
+}
+
+/// The task that owns a service's process and restarts it per its policy.
+/// It stays in the map after giving up, so its status can still be read.
+struct Supervisor {
+    status: Arc<Mutex<ServiceStatus>>,
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<()>,
 }
 
 impl ServiceManager {
     pub fn new() -> Self {
         ServiceManager {
-            processes: Arc::new(Mutex::new(HashMap::new())),
+            supervisors: Arc::new(Mutex::new(HashMap::new())),
             logs: Arc::new(Mutex::new(HashMap::new())),
             log_id_counter: Arc::new(Mutex::new(1)),
+            crashes: Arc::new(Mutex::new(HashMap::new())),
+            events: broadcast::channel(256).0,
+            log_writer: None,
         }
     }
 
-    pub async fn start_service_by_id(&self, service_id: u32, service: &Service) -> Result<u32, String> {
+    /// A manager that also hands every log line to `writer` for storage.
+    pub fn with_log_writer(writer: LogWriter) -> Self {
+        ServiceManager { log_writer: Some(writer), ..ServiceManager::new() }
+    }
+
+    /// Every change of a service's state, readiness or health.
+    pub fn subscribe(&self) -> broadcast::Receiver<ServiceStatus> {
+        self.events.subscribe()
+    }
+
+    pub async fn start_service_by_id(&self, service_id: u32, service: &Service) -> AppResult<u32> {
+        self.start_service_with_env(service_id, service, Vec::new()).await
+    }
+
+    /// Starts a service with `env` added to the app's environment. Variables
+    /// set in front of the command itself still take precedence.
+    pub async fn start_service_with_env(&self, service_id: u32, service: &Service, env: Vec<(String, String)>) -> AppResult<u32> {
+        self.start_service_with(service_id, service, LaunchOptions { env, ..LaunchOptions::default() }).await
+    }
+
+    /// Starts a service under a supervisor that applies `options.restart`
+    /// whenever its process exits. Returns the first process's PID.
+    pub async fn start_service_with(&self, service_id: u32, service: &Service, options: LaunchOptions) -> AppResult<u32> {
         let local_service = LocalService {
             id: service_id,
             name: service.name.clone(),
             command: service.command.clone(),
             working_dir: service.working_dir.clone(),
         };
-        self.start_service(&local_service).await
+        self.supervise(local_service, service.shell, options).await
     }
 
-    pub async fn start_service(&self, service: &LocalService) -> Result<u32, String> {
-        let mut parts = service.command.split_whitespace();
-        let program = parts.next().ok_or("Empty command")?;
-        let args: Vec<&str> = parts.collect();
+    /// Starts a service whose command is split into a program and arguments.
+    pub async fn start_service(&self, service: &LocalService) -> AppResult<u32> {
+        self.supervise(service.clone(), false, LaunchOptions::default()).await
+    }
 
-        let mut cmd = tokio::process::Command::new(program);
-        cmd.args(&args);
-        
-        if let Some(ref working_dir) = service.working_dir {
-            cmd.current_dir(working_dir);
+    async fn supervise(&self, service: LocalService, shell: bool, options: LaunchOptions) -> AppResult<u32> {
+        let mut supervisors = self.supervisors.lock().await;
+        if supervisors.get(&service.id).is_some_and(|s| !s.task.is_finished()) {
+            return Err(AppError::conflict(format!("Service '{}' is already running", service.name)));
         }
 
-        cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::piped());
+        let launcher = Launcher {
+            service,
+            shell,
+            env: options.env,
+            health: options.health,
+            log_parser: options.log_parser,
+            stop_grace: options.stop_grace,
+            logs: self.logs.clone(),
+            log_id_counter: self.log_id_counter.clone(),
+            log_writer: self.log_writer.clone(),
+        };
+        let run = launcher.spawn().await?;
+        let pid = run.pid;
+        let service_id = launcher.service.id;
 
-        let mut child = cmd.spawn()
-            .map_err(|e| format!("Failed to start service: {}", e))?;
+        let status = StatusHandle {
+            status: Arc::new(Mutex::new(ServiceStatus {
+                state: ServiceState::Running,
+                pid: Some(pid),
+                ..ServiceStatus::stopped(service_id)
+            })),
+            events: self.events.clone(),
+        };
+        let (stop, stop_rx) = oneshot::channel();
+        let shared_status = status.status.clone();
+        let task = tokio::spawn(supervise(
+            launcher,
+            run,
+            options.restart,
+            status,
+            self.crashes.clone(),
+            stop_rx,
+        ));
 
-        // Get the PID
-        let pid = child.id().unwrap_or(0);
+        supervisors.insert(service_id, Supervisor { status: shared_status, stop, task });
+        Ok(pid)
+    }
 
-        // Capture stdout and stderr
-        let stdout = child.stdout.take();
-        let stderr = child.stderr.take();
-        
-        let service_id = service.id;
-        let logs = self.logs.clone();
-        let log_id_counter = self.log_id_counter.clone();
+    /// Stops a service and its supervisor, so it is not restarted.
+    pub async fn stop_service(&self, service_id: u32) -> AppResult<()> {
+        let supervisor = self.supervisors.lock().await.remove(&service_id)
+            .ok_or_else(|| AppError::not_found("Service", service_id))?;
 
-        // Spawn task to read stdout
-        if let Some(stdout) = stdout {
-            let logs_clone = logs.clone();
-            let log_id_counter_clone = log_id_counter.clone();
-            tokio::spawn(async move {
-                let reader = BufReader::new(stdout);
-                let mut lines = reader.lines();
-                
-                while let Ok(Some(line)) = lines.next_line().await {
-                    let mut counter = log_id_counter_clone.lock().await;
-                    let id = *counter;
-                    *counter += 1;
-                    drop(counter);
-                    
-                    let log_entry = LogEntry {
-                        id,
-                        service_id,
-                        level: "info".to_string(),
-                        message: line,
-                        timestamp: Utc::now().to_rfc3339(),
-                    };
-                    
-                    let mut logs_map = logs_clone.lock().await;
-                    logs_map.entry(service_id)
-                        .or_insert_with(Vec::new)
-                        .push(log_entry);
-                    
-                    // Keep only last 1000 logs per service
-                    if let Some(service_logs) = logs_map.get_mut(&service_id) {
-                        if service_logs.len() > 1000 {
-                            service_logs.remove(0);
-                        }
-                    }
-                }
-            });
-        }
+        let _ = supervisor.stop.send(());
+        let _ = supervisor.task.await;
+        let _ = self.events.send(ServiceStatus::stopped(service_id));
+        Ok(())
+    }
 
-        // Spawn task to read stderr
-        if let Some(stderr) = stderr {
-            let logs_clone = logs.clone();
-            let log_id_counter_clone = log_id_counter.clone();
-            tokio::spawn(async move {
-                let reader = BufReader::new(stderr);
-                let mut lines = reader.lines();
-                
-                while let Ok(Some(line)) = lines.next_line().await {
-                    let mut counter = log_id_counter_clone.lock().await;
-                    let id = *counter;
-                    *counter += 1;
-                    drop(counter);
-                    
-                    let log_entry = LogEntry {
-                        id,
-                        service_id,
-                        level: "error".to_string(),
-                        message: line,
-                        timestamp: Utc::now().to_rfc3339(),
-                    };
-                    
-                    let mut logs_map = logs_clone.lock().await;
-                    logs_map.entry(service_id)
-                        .or_insert_with(Vec::new)
-                        .push(log_entry);
-                    
-                    // Keep only last 1000 logs per service
-                    if let Some(service_logs) = logs_map.get_mut(&service_id) {
-                        if service_logs.len() > 1000 {
-                            service_logs.remove(0);
-                        }
-                    }
-                }
-            });
+    /// Stops every service at once, so their grace periods run side by
+    /// side. Used when the app exits.
+    pub async fn stop_all_services(&self) {
+        let supervisors: Vec<(u32, Supervisor)> = self.supervisors.lock().await.drain().collect();
+        let mut tasks = Vec::new();
+        for (service_id, supervisor) in supervisors {
+            let _ = supervisor.stop.send(());
+            tasks.push((service_id, supervisor.task));
+        }
+        for (service_id, task) in tasks {
+            let _ = task.await;
+            let _ = self.events.send(ServiceStatus::stopped(service_id));
         }
+    }
 
-        let mut processes = self.processes.lock().await;
-        processes.insert(service.id, child);
+    /// Whether a supervisor still looks after the service, i.e. it is
+    /// running or waiting to be restarted.
+    pub async fn is_supervised(&self, service_id: u32) -> bool {
+        self.supervisors.lock().await.get(&service_id).is_some_and(|s| !s.task.is_finished())
+    }
 
-        Ok(pid)
+    pub async fn is_service_running(&self, service_id: u32) -> bool {
+        self.service_status(service_id).await.state == ServiceState::Running
+    }
+
+    /// Where the service is in its lifecycle; stopped when it was never
+    /// started or was stopped by hand.
+    pub async fn service_status(&self, service_id: u32) -> ServiceStatus {
+        let status = match self.supervisors.lock().await.get(&service_id) {
+            Some(supervisor) => supervisor.status.clone(),
+            None => return ServiceStatus::stopped(service_id),
+        };
+        let status = status.lock().await.clone();
+        status
     }
 
-    pub async fn stop_service(&self, service_id: u32) -> Result<(), String> {
-        let mut processes = self.processes.lock().await;
-        
-        if let Some(mut child) = processes.remove(&service_id) {
-            #[cfg(unix)]
-            {
-                use nix::sys::signal::{kill, Signal};
-                use nix::unistd::Pid;
-                
-                if let Some(pid) = child.id() {
-                    let _ = kill(Pid::from_raw(pid as i32), Some(Signal::SIGTERM));
+    /// Waits until the service is ready for its dependents. Fails when it
+    /// exits for good or is stopped first, or `timeout` passes.
+    pub async fn wait_until_ready(&self, service_id: u32, timeout: Duration) -> AppResult<()> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let status = self.service_status(service_id).await;
+            match status.state {
+                ServiceState::Running if status.ready => return Ok(()),
+                ServiceState::Running | ServiceState::Restarting => {}
+                ServiceState::Stopped => {
+                    return Err(AppError::process(format!("Service {} was stopped before it was ready", service_id), None));
+                }
+                ServiceState::Exited | ServiceState::GaveUp => {
+                    let exit = status.last_exit.map(|exit| format!(" with {}", exit)).unwrap_or_default();
+                    return Err(AppError::process(format!("Service {} exited{} before it was ready", service_id, exit), None));
                 }
             }
-            
-            #[cfg(windows)]
-            {
-                let _ = child.kill().await;
+
+            if Instant::now() >= deadline {
+                let reason = status.readiness.map(|r| format!(": {}", r.message)).unwrap_or_default();
+                return Err(AppError::timeout(format!(
+                    "Service {} was not ready after {} s{}", service_id, timeout.as_secs(), reason,
+                )));
             }
-            
-            let _ = child.wait().await;
-            Ok(())
-        } else {
-            Err("Service not found".to_string())
+            tokio::time::sleep(Duration::from_millis(50)).await;
         }
     }
 
-    pub async fn is_service_running(&self, service_id: u32) -> bool {
-        let mut processes = self.processes.lock().await;
-        if let Some(child) = processes.get_mut(&service_id) {
-            match child.try_wait() {
-                Ok(Some(_)) => false, // Process has exited
-                Ok(None) => true,     // Process is still running
-                Err(_) => false,
+    /// Crashes since the app started, newest first.
+    pub async fn get_service_crashes(&self, service_id: u32) -> Vec<CrashRecord> {
+        let crashes = self.crashes.lock().await;
+        crashes.get(&service_id)
+            .map(|crashes| crashes.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// `(service_id, pid)` of every service whose process is up, for sampling.
+    pub async fn running_pids(&self) -> Vec<(u32, u32)> {
+        let statuses: Vec<_> = self.supervisors.lock().await
+            .values()
+            .map(|supervisor| supervisor.status.clone())
+            .collect();
+        let mut running = Vec::new();
+        for status in statuses {
+            let status = status.lock().await;
+            if let (ServiceState::Running, Some(pid)) = (status.state, status.pid) {
+                running.push((status.service_id, pid));
             }
-        } else {
-            false
-        }
-    }
-
-    pub fn get_process_info(pid: u32) -> Option<(String, String)> {
-        let mut system = System::new_all();
-        system.refresh_all();
-        
-        if let Some(process) = system.process(Pid::from(pid as usize)) {
-            Some((
-                process.name().to_string(),
-                format!("CPU: {:.1}%, Memory: {:.1}MB", 
-                    process.cpu_usage(),
-                    process.memory() as f64 / 1024.0 / 1024.0
-                )
-            ))
-        } else {
-            None
         }
+        running
     }
 
+    /// The latest lines kept in memory, newest first. Older output is in
+    /// the log store.
     pub async fn get_service_logs(&self, service_id: u32, limit: Option<usize>) -> Vec<LogEntry> {
         let logs = self.logs.lock().await;
         if let Some(service_logs) = logs.get(&service_id) {
@@ -216,4 +294,520 @@ impl ServiceManager {
         let mut logs = self.logs.lock().await;
         logs.remove(&service_id);
     }
+
+    /// Forgets the logs and crashes of every service, whose ids mean other
+    /// services once another workspace is active.
+    pub async fn clear_all(&self) {
+        self.logs.lock().await.clear();
+        self.crashes.lock().await.clear();
+    }
+}
+
+/// Everything needed to start a service's process again.
+struct Launcher {
+    service: LocalService,
+    shell: bool,
+    env: Vec<(String, String)>,
+    health: HealthChecks,
+    log_parser: LogParser,
+    stop_grace: Duration,
+    logs: Logs,
+    log_id_counter: Arc<Mutex<u32>>,
+    log_writer: Option<LogWriter>,
+}
+
+/// One process of a service, with the tasks reading its output.
+struct Run {
+    child: tokio::process::Child,
+    pid: u32,
+    readers: Vec<JoinHandle<()>>,
+    started: Instant,
+    /// Id of the first line this process can log.
+    first_log_id: u32,
+}
+
+impl Launcher {
+    async fn spawn(&self) -> AppResult<Run> {
+        let service = &self.service;
+        let mut cmd = if self.shell {
+            let mut cmd = shell_command(&service.command);
+            cmd.envs(self.env.iter().cloned());
+            cmd
+        } else {
+            let parsed = command_line::parse_command(&service.command)?;
+            let mut cmd = tokio::process::Command::new(&parsed.program);
+            cmd.args(&parsed.args);
+            cmd.envs(self.env.iter().cloned());
+            cmd.envs(parsed.env);
+            cmd
+        };
+
+        if let Some(ref working_dir) = service.working_dir {
+            cmd.current_dir(working_dir);
+        }
+
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+
+        // Its own process group, so stopping it reaches whatever it started
+        #[cfg(unix)]
+        cmd.process_group(0);
+        #[cfg(windows)]
+        {
+            const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+            cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+        }
+
+        let first_log_id = *self.log_id_counter.lock().await;
+        let mut child = cmd.spawn()
+            .map_err(|e| AppError::process(format!("Failed to start service '{}'", service.name), Some(e)))?;
+
+        let pid = child.id().unwrap_or(0);
+
+        // Capture stdout and stderr
+        let mut readers = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(self.read_logs(stdout, Stream::Stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(self.read_logs(stderr, Stream::Stderr));
+        }
+
+        Ok(Run { child, pid, readers, started: Instant::now(), first_log_id })
+    }
+
+    fn read_logs(&self, output: impl AsyncRead + Unpin + Send + 'static, stream: Stream) -> JoinHandle<()> {
+        let parser = self.log_parser.clone();
+        let logs = self.logs.clone();
+        let log_id_counter = self.log_id_counter.clone();
+        let log_writer = self.log_writer.clone();
+        let service_id = self.service.id;
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(output).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let parsed = log_parser::parse_line(&parser, stream, &line);
+                push_log(&logs, &log_id_counter, log_writer.as_ref(), service_id, parsed).await;
+            }
+        })
+    }
+
+    fn probe_context(&self, run: &Run) -> ProbeContext {
+        ProbeContext {
+            service_id: self.service.id,
+            logs: self.logs.clone(),
+            first_log_id: run.first_log_id,
+            working_dir: self.service.working_dir.clone(),
+            env: self.env.clone(),
+        }
+    }
+
+    async fn log(&self, level: &str, message: String) {
+        let line = ParsedLine::plain(level, message);
+        push_log(&self.logs, &self.log_id_counter, self.log_writer.as_ref(), self.service.id, line).await;
+    }
+
+    /// Asks the process group of `run` to exit and kills it once the grace
+    /// period passes. Also used after the process exited on its own, so
+    /// children it left behind do not keep holding ports. Only a group that
+    /// is already gone skips the wait; a signal that fails for any other
+    /// reason still ends in the kill, so stopping never hangs on it.
+    async fn terminate(&self, run: &mut Run) {
+        if !process_group::signal_stop(run.pid) {
+            let _ = run.child.wait().await;
+            return;
+        }
+
+        let deadline = Instant::now() + self.stop_grace;
+        let _ = tokio::time::timeout(self.stop_grace, run.child.wait()).await;
+        // The leader may be gone while the rest of its group is still up
+        while process_group::is_alive(run.pid) && Instant::now() < deadline {
+            tokio::time::sleep(STOP_POLL_INTERVAL).await;
+        }
+
+        if process_group::is_alive(run.pid) {
+            self.log("warn", format!(
+                "Processes still running {} ms after the stop signal; killing them", self.stop_grace.as_millis(),
+            )).await;
+            process_group::kill(run.pid);
+        }
+        let _ = run.child.wait().await;
+    }
+}
+
+async fn push_log(
+    logs: &Logs,
+    log_id_counter: &Mutex<u32>,
+    log_writer: Option<&LogWriter>,
+    service_id: u32,
+    line: ParsedLine,
+) {
+    let mut counter = log_id_counter.lock().await;
+    let id = *counter;
+    *counter += 1;
+    drop(counter);
+
+    let log_entry = LogEntry {
+        id,
+        service_id,
+        level: line.level,
+        message: line.message,
+        timestamp: Utc::now().to_rfc3339(),
+        fields: line.fields,
+    };
+    if let Some(log_writer) = log_writer {
+        log_writer.write(log_entry.clone());
+    }
+
+    let mut logs_map = logs.lock().await;
+    let service_logs = logs_map.entry(service_id).or_insert_with(Vec::new);
+    service_logs.push(log_entry);
+
+    // Keep only the last MAX_LOGS lines per service
+    if service_logs.len() > MAX_LOGS {
+        service_logs.remove(0);
+    }
+}
+
+/// A service's status, announcing each change to the manager's subscribers.
+#[derive(Clone)]
+struct StatusHandle {
+    status: Arc<Mutex<ServiceStatus>>,
+    events: broadcast::Sender<ServiceStatus>,
+}
+
+impl StatusHandle {
+    async fn update(&self, change: impl FnOnce(&mut ServiceStatus)) {
+        let mut status = self.status.lock().await;
+        let before = Self::summary(&status);
+        change(&mut status);
+        // Probe timestamps alone are not worth an event
+        if Self::summary(&status) != before {
+            let _ = self.events.send(status.clone());
+        }
+    }
+
+    fn summary(status: &ServiceStatus) -> StatusSummary {
+        StatusSummary {
+            state: status.state,
+            pid: status.pid,
+            restarts: status.restarts,
+            ready: status.ready,
+            healthy: status.healthy,
+            readiness_ok: status.readiness.as_ref().map(|r| r.ok),
+            liveness_ok: status.liveness.as_ref().map(|r| r.ok),
+        }
+    }
+}
+
+/// The parts of a status whose change is announced.
+#[derive(PartialEq)]
+struct StatusSummary {
+    state: ServiceState,
+    pid: Option<u32>,
+    restarts: u32,
+    ready: bool,
+    healthy: Option<bool>,
+    readiness_ok: Option<bool>,
+    liveness_ok: Option<bool>,
+}
+
+/// Waits on each process of a service, restarting it per `policy` until it
+/// is stopped, exits for good, or runs out of retries.
+async fn supervise(
+    launcher: Launcher,
+    first_run: Run,
+    policy: RestartPolicy,
+    status: StatusHandle,
+    crashes: Crashes,
+    mut stop: oneshot::Receiver<()>,
+) {
+    let service_id = launcher.service.id;
+    let mut next_run = Some(first_run);
+    // Restarts in a row, reset by a run that stays up
+    let mut attempt = 0;
+
+    loop {
+        let run = match next_run.take() {
+            Some(run) => Ok(run),
+            None => launcher.spawn().await,
+        };
+
+        let (exit, reason, pid, uptime, readers) = match run {
+            Ok(mut run) => {
+                status.update(|status| {
+                    status.state = ServiceState::Running;
+                    status.pid = Some(run.pid);
+                    status.next_restart_at = None;
+                    status.ready = false;
+                    status.healthy = None;
+                    status.readiness = None;
+                    status.liveness = None;
+                }).await;
+
+                // A failing liveness probe only ends the process when a restart can follow
+                let (unhealthy, mut unhealthy_rx) = oneshot::channel();
+                let unhealthy = (policy.mode != RestartMode::Never).then_some(unhealthy);
+                let monitor = tokio::spawn(monitor(launcher.probe_context(&run), launcher.health.clone(), status.clone(), unhealthy));
+
+                let (result, reason) = tokio::select! {
+                    result = run.child.wait() => {
+                        launcher.terminate(&mut run).await;
+                        (result, None)
+                    }
+                    Ok(reason) = &mut unhealthy_rx => {
+                        launcher.log("warn", format!("{}; stopping the process", reason)).await;
+                        launcher.terminate(&mut run).await;
+                        (run.child.wait().await, Some(reason))
+                    }
+                    // Also taken when the manager is dropped
+                    _ = &mut stop => {
+                        monitor.abort();
+                        launcher.terminate(&mut run).await;
+                        status.update(|status| {
+                            status.state = ServiceState::Stopped;
+                            status.pid = None;
+                            status.ready = false;
+                            status.healthy = None;
+                        }).await;
+                        return;
+                    }
+                };
+                monitor.abort();
+                let exit = match result {
+                    Ok(exit_status) => exit_info(exit_status),
+                    Err(_) => ExitInfo { code: None, signal: None },
+                };
+                (exit, reason, run.pid, run.started.elapsed(), run.readers)
+            }
+            Err(e) => {
+                launcher.log("error", e.to_string()).await;
+                (ExitInfo { code: None, signal: None }, None, 0, Duration::ZERO, Vec::new())
+            }
+        };
+
+        if uptime >= STABLE_RUN {
+            attempt = 0;
+        }
+        // An unhealthy process counts as failed whatever it exited with
+        let wants_restart = reason.is_some() || policy.restarts_after(&exit);
+        let restart = wants_restart && attempt < policy.max_retries;
+        let delay = policy.backoff(attempt + 1);
+        status.update(|status| {
+            status.pid = None;
+            status.ready = false;
+            status.last_exit = Some(exit.clone());
+            if restart {
+                status.state = ServiceState::Restarting;
+                status.restarts += 1;
+                status.next_restart_at = chrono::Duration::from_std(delay)
+                    .ok()
+                    .map(|delay| (Utc::now() + delay).to_rfc3339());
+            } else {
+                status.state = if wants_restart { ServiceState::GaveUp } else { ServiceState::Exited };
+            }
+        }).await;
+
+        // Let the readers pick up what the process wrote before exiting;
+        // a child it left behind may hold the pipes open, hence the timeout
+        for reader in readers {
+            let _ = tokio::time::timeout(LOG_DRAIN_TIMEOUT, reader).await;
+        }
+
+        if !exit.success() || reason.is_some() {
+            let last_logs = {
+                let logs = launcher.logs.lock().await;
+                let service_logs = logs.get(&service_id).map(Vec::as_slice).unwrap_or_default();
+                service_logs[service_logs.len().saturating_sub(CRASH_LOG_LINES)..]
+                    .iter()
+                    .map(|log| log.message.clone())
+                    .collect()
+            };
+            let mut crashes = crashes.lock().await;
+            let service_crashes = crashes.entry(service_id).or_insert_with(Vec::new);
+            service_crashes.push(CrashRecord {
+                service_id,
+                pid,
+                exit: exit.clone(),
+                reason,
+                timestamp: Utc::now().to_rfc3339(),
+                uptime_ms: uptime.as_millis() as u64,
+                last_logs,
+                will_restart: restart,
+            });
+            if service_crashes.len() > MAX_CRASHES {
+                service_crashes.remove(0);
+            }
+        }
+
+        if !restart {
+            if wants_restart {
+                launcher.log("error", format!("Process exited with {}; gave up after {} restarts", exit, attempt)).await;
+            }
+            return;
+        }
+
+        attempt += 1;
+        launcher.log("warn", format!(
+            "Process exited with {}; restarting in {} ms (attempt {} of {})",
+            exit, delay.as_millis(), attempt, policy.max_retries,
+        )).await;
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = &mut stop => {
+                status.update(|status| {
+                    status.state = ServiceState::Stopped;
+                    status.next_restart_at = None;
+                }).await;
+                return;
+            }
+        }
+    }
+}
+
+/// Probes one process: readiness until it first passes, then liveness for
+/// as long as the process runs. Sends on `unhealthy` once liveness fails
+/// `failure_threshold` times in a row.
+async fn monitor(
+    context: ProbeContext,
+    checks: HealthChecks,
+    status: StatusHandle,
+    unhealthy: Option<oneshot::Sender<String>>,
+) {
+    match &checks.readiness {
+        Some(probe) => {
+            let mut failures = 0;
+            loop {
+                let (ok, result) = probe_once(probe, &context, &mut failures).await;
+                status.update(|status| {
+                    status.ready = ok;
+                    status.readiness = Some(result);
+                }).await;
+                if ok {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(probe.interval_ms)).await;
+            }
+        }
+        None => {
+            tokio::time::sleep(READY_AFTER).await;
+            status.update(|status| status.ready = true).await;
+        }
+    }
+
+    let Some(probe) = &checks.liveness else { return };
+    let mut failures = 0;
+    loop {
+        tokio::time::sleep(Duration::from_millis(probe.interval_ms)).await;
+        let (_, result) = probe_once(probe, &context, &mut failures).await;
+        let failed = failures >= probe.failure_threshold;
+        let message = result.message.clone();
+        status.update(|status| {
+            status.healthy = Some(!failed);
+            status.liveness = Some(result);
+        }).await;
+        if failed {
+            if let Some(unhealthy) = unhealthy {
+                let _ = unhealthy.send(format!("Liveness probe failed: {}", message));
+            }
+            return;
+        }
+    }
+}
+
+async fn probe_once(probe: &Probe, context: &ProbeContext, failures: &mut u32) -> (bool, ProbeResult) {
+    let (ok, message) = match health::run_probe(probe, context).await {
+        Ok(message) => (true, message),
+        Err(message) => (false, message),
+    };
+    *failures = if ok { 0 } else { *failures + 1 };
+    let result = ProbeResult {
+        ok,
+        message,
+        timestamp: Utc::now().to_rfc3339(),
+        consecutive_failures: *failures,
+    };
+    (ok, result)
+}
+
+/// Signals to the process group a service was started in. Its id is the
+/// PID of the service's own process.
+#[cfg(unix)]
+mod process_group {
+    use nix::errno::Errno;
+    use nix::sys::signal::{killpg, Signal};
+    use nix::unistd::Pid;
+
+    /// Sends SIGTERM to the group; false only when no process of it is left.
+    pub fn signal_stop(pid: u32) -> bool {
+        !matches!(killpg(Pid::from_raw(pid as i32), Signal::SIGTERM), Err(Errno::ESRCH))
+    }
+
+    pub fn is_alive(pid: u32) -> bool {
+        !matches!(killpg(Pid::from_raw(pid as i32), None), Err(Errno::ESRCH))
+    }
+
+    pub fn kill(pid: u32) {
+        let _ = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL);
+    }
+}
+
+/// Windows has no group signals, so the process tree is ended through
+/// `taskkill`.
+#[cfg(windows)]
+mod process_group {
+    use std::process::{Command, Stdio};
+
+    fn taskkill(pid: u32, force: bool) -> bool {
+        let mut cmd = Command::new("taskkill");
+        cmd.args(["/T", "/PID", &pid.to_string()]);
+        if force {
+            cmd.arg("/F");
+        }
+        cmd.stdout(Stdio::null()).stderr(Stdio::null());
+        cmd.status().is_ok_and(|status| status.success())
+    }
+
+    /// Asks the tree to close; false only when no process of it is left.
+    /// Windowless console processes such as node or cargo refuse a polite
+    /// `taskkill`, so they are left to the forced one after the grace period.
+    pub fn signal_stop(pid: u32) -> bool {
+        taskkill(pid, false) || is_alive(pid)
+    }
+
+    pub fn is_alive(pid: u32) -> bool {
+        let output = Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .output();
+        output.is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+    }
+
+    pub fn kill(pid: u32) {
+        taskkill(pid, true);
+    }
+}
+
+#[cfg(unix)]
+fn exit_info(status: std::process::ExitStatus) -> ExitInfo {
+    use std::os::unix::process::ExitStatusExt;
+    ExitInfo { code: status.code(), signal: status.signal() }
+}
+
+#[cfg(windows)]
+fn exit_info(status: std::process::ExitStatus) -> ExitInfo {
+    ExitInfo { code: status.code(), signal: None }
+}
+
+#[cfg(unix)]
+pub(crate) fn shell_command(command: &str) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("/bin/sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+pub(crate) fn shell_command(command: &str) -> tokio::process::Command {
+    let mut cmd = tokio::process::Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
 }
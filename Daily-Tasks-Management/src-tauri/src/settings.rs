@@ -0,0 +1,138 @@
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// File name of the app settings in the data directory.
+pub const SETTINGS_FILE: &str = "settings.json";
+pub const SAFE_MODE_FLAG: &str = "--safe-mode";
+pub const SAFE_MODE_ENV: &str = "DAILY_TASKS_SAFE_MODE";
+pub const DEFAULT_STOP_GRACE_PERIOD_MS: u64 = 5_000;
+// Longer than this and quitting the app would seem to hang
+const MAX_STOP_GRACE_PERIOD_MS: u64 = 120_000;
+
+/// Settings shared by all workspaces.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct AppSettings {
+    /// Leave `auto_start` services stopped when the app launches.
+    #[serde(rename = "skipAutoStart", default)]
+    pub skip_auto_start: bool,
+    /// How long a stopped service's process group has to exit after
+    /// SIGTERM before it is killed. Applies from the next start.
+    #[serde(rename = "stopGracePeriodMs", default = "default_stop_grace_period_ms")]
+    pub stop_grace_period_ms: u64,
+}
+
+fn default_stop_grace_period_ms() -> u64 {
+    DEFAULT_STOP_GRACE_PERIOD_MS
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        AppSettings {
+            skip_auto_start: false,
+            stop_grace_period_ms: DEFAULT_STOP_GRACE_PERIOD_MS,
+        }
+    }
+}
+
+impl AppSettings {
+    pub fn stop_grace_period(&self) -> Duration {
+        Duration::from_millis(self.stop_grace_period_ms)
+    }
+}
+
+/// True when the app was launched with `--safe-mode` or with the
+/// environment variable set to `1`, `true` or `yes`.
+pub fn safe_mode_requested(args: &[String], env_value: Option<String>) -> bool {
+    args.iter().any(|arg| arg == SAFE_MODE_FLAG)
+        || env_value.is_some_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+}
+
+/// Loads and saves `AppSettings`. Safe mode only lasts for this launch and
+/// is never saved.
+pub struct SettingsManager {
+    path: PathBuf,
+    settings: Mutex<AppSettings>,
+    safe_mode: bool,
+    load_error: Option<String>,
+}
+
+impl SettingsManager {
+    /// A missing file means defaults. So does a corrupt one, which is moved
+    /// aside rather than overwritten and reported by `load_error`, so the
+    /// app still launches.
+    pub fn new(path: impl Into<PathBuf>, safe_mode: bool) -> AppResult<Self> {
+        let path = path.into();
+        let mut load_error = None;
+        let settings = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                let kept = path.with_extension("json.corrupt");
+                let kept_note = match fs::rename(&path, &kept) {
+                    Ok(()) => format!("; it was kept as {}", kept.display()),
+                    Err(_) => String::new(),
+                };
+                load_error = Some(format!(
+                    "Settings file {} is corrupt, so defaults are used{}: {}", path.display(), kept_note, e,
+                ));
+                AppSettings::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => AppSettings::default(),
+            Err(e) => return Err(AppError::io("Failed to read settings", &path, e)),
+        };
+
+        Ok(SettingsManager {
+            path,
+            settings: Mutex::new(settings),
+            safe_mode,
+            load_error,
+        })
+    }
+
+    /// Why the saved settings could not be used in this launch.
+    pub fn load_error(&self) -> Option<&str> {
+        self.load_error.as_deref()
+    }
+
+    pub fn get(&self) -> AppResult<AppSettings> {
+        Ok(self.settings.lock()?.clone())
+    }
+
+    pub fn update(&self, settings: AppSettings) -> AppResult<()> {
+        if settings.stop_grace_period_ms > MAX_STOP_GRACE_PERIOD_MS {
+            return Err(AppError::invalid_field(
+                "stopGracePeriodMs",
+                format!("Grace period must be at most {} ms", MAX_STOP_GRACE_PERIOD_MS),
+            ));
+        }
+        let mut current = self.settings.lock()?;
+        let contents = serde_json::to_string_pretty(&settings)
+            .map_err(|e| AppError::internal(format!("Failed to serialize settings: {}", e)))?;
+        // A torn write must not leave a corrupt file behind
+        let partial = self.path.with_extension("json.partial");
+        fs::write(&partial, contents)
+            .map_err(|e| AppError::io("Failed to write settings", &partial, e))?;
+        fs::rename(&partial, &self.path)
+            .map_err(|e| AppError::io("Failed to replace settings", &self.path, e))?;
+        *current = settings;
+        Ok(())
+    }
+
+    pub fn safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    /// Why auto-start is skipped for this launch, if it is.
+    pub fn auto_start_skip_reason(&self) -> AppResult<Option<String>> {
+        if self.safe_mode {
+            return Ok(Some("Launched in safe mode".to_string()));
+        }
+        if self.get()?.skip_auto_start {
+            return Ok(Some("Auto-start is turned off in settings".to_string()));
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,214 @@
+use crate::database::Database;
+use crate::error::{AppError, AppResult, Context};
+use crate::model::Workspace;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const DATA_DIR_ENV: &str = "DAILY_TASKS_DATA_DIR";
+pub const WORKSPACE_ENV: &str = "DAILY_TASKS_WORKSPACE";
+pub const DATA_DIR_FLAG: &str = "--data-dir";
+pub const WORKSPACE_FLAG: &str = "--workspace";
+pub const DEFAULT_WORKSPACE: &str = "default";
+/// Where releases before workspaces kept the database, relative to the
+/// working directory.
+pub const LEGACY_DATABASE_FILE: &str = "tasks.db";
+
+const WORKSPACES_DIR: &str = "workspaces";
+const DATABASE_FILE: &str = "tasks.db";
+// Kept apart from tasks.db so log writes never wait on the main database
+const LOGS_FILE: &str = "logs.db";
+const ACTIVE_WORKSPACE_FILE: &str = "active_workspace";
+
+/// Picks the data directory: CLI flag first, then the environment variable,
+/// then the platform app-data directory.
+pub fn resolve_data_dir(args: &[String], env_value: Option<String>, default_dir: PathBuf) -> PathBuf {
+    flag_value(args, DATA_DIR_FLAG)
+        .or(env_value.filter(|v| !v.trim().is_empty()))
+        .map(PathBuf::from)
+        .unwrap_or(default_dir)
+}
+
+/// Workspace requested at launch through the CLI flag or environment variable, if any.
+pub fn requested_workspace(args: &[String], env_value: Option<String>) -> Option<String> {
+    flag_value(args, WORKSPACE_FLAG).or(env_value.filter(|v| !v.trim().is_empty()))
+}
+
+/// Reads `--flag value` or `--flag=value` from the command line.
+pub fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    let prefix = format!("{}=", flag);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            return iter.next().cloned();
+        }
+        if let Some(value) = arg.strip_prefix(&prefix) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+pub fn validate_workspace_name(name: &str) -> AppResult<()> {
+    if name.is_empty() || name.len() > 64 {
+        return Err(AppError::invalid_field("name", "Workspace name must be between 1 and 64 characters"));
+    }
+
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(AppError::invalid_field("name", "Workspace name may only contain letters, digits, '-' and '_'"));
+    }
+
+    Ok(())
+}
+
+/// Owns the data directory layout. Each workspace lives in
+/// `<data_dir>/workspaces/<name>/` with its own `tasks.db` and `logs.db`.
+pub struct WorkspaceManager {
+    data_dir: PathBuf,
+    active: Mutex<String>,
+    imported_from: Option<PathBuf>,
+}
+
+impl WorkspaceManager {
+    /// Opens the data directory. On first run, a database at `legacy_database`
+    /// is copied into the default workspace so existing tasks carry over.
+    pub fn new(data_dir: PathBuf, requested: Option<String>, legacy_database: Option<&Path>) -> AppResult<Self> {
+        fs::create_dir_all(data_dir.join(WORKSPACES_DIR))
+            .map_err(|e| AppError::io("Failed to create data directory", &data_dir, e))?;
+
+        // Fall back to the workspace that was active when the app last closed
+        let active = match requested {
+            Some(name) => name,
+            None => fs::read_to_string(data_dir.join(ACTIVE_WORKSPACE_FILE))
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| validate_workspace_name(s).is_ok())
+                .unwrap_or_else(|| DEFAULT_WORKSPACE.to_string()),
+        };
+        validate_workspace_name(&active)?;
+
+        let mut manager = WorkspaceManager {
+            data_dir,
+            active: Mutex::new(active.clone()),
+            imported_from: None,
+        };
+        manager.ensure_workspace_dir(&active)?;
+        manager.persist_active(&active)?;
+
+        let default_path = manager.database_path(DEFAULT_WORKSPACE);
+        if let Some(legacy) = legacy_database.filter(|legacy| legacy.is_file() && !default_path.exists()) {
+            manager.ensure_workspace_dir(DEFAULT_WORKSPACE)?;
+            // Left unmigrated; the copy is migrated when the workspace opens
+            Database::open(&legacy.to_string_lossy())?
+                .backup_to(&default_path)
+                .context(format!("Failed to import {}", legacy.display()))?;
+            manager.imported_from = Some(legacy.to_path_buf());
+        }
+        Ok(manager)
+    }
+
+    /// The legacy database copied into the default workspace at startup, if any.
+    pub fn imported_from(&self) -> Option<&Path> {
+        self.imported_from.as_deref()
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    pub fn active_workspace(&self) -> AppResult<String> {
+        Ok(self.active.lock()?.clone())
+    }
+
+    pub fn workspace_dir(&self, name: &str) -> PathBuf {
+        self.data_dir.join(WORKSPACES_DIR).join(name)
+    }
+
+    pub fn database_path(&self, name: &str) -> PathBuf {
+        self.workspace_dir(name).join(DATABASE_FILE)
+    }
+
+    pub fn active_database_path(&self) -> AppResult<PathBuf> {
+        Ok(self.database_path(&self.active_workspace()?))
+    }
+
+    pub fn logs_path(&self, name: &str) -> PathBuf {
+        self.workspace_dir(name).join(LOGS_FILE)
+    }
+
+    pub fn active_logs_path(&self) -> AppResult<PathBuf> {
+        Ok(self.logs_path(&self.active_workspace()?))
+    }
+
+    pub fn list_workspaces(&self) -> AppResult<Vec<Workspace>> {
+        let active = self.active_workspace()?;
+        let dir = self.data_dir.join(WORKSPACES_DIR);
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| AppError::io("Failed to read workspaces", &dir, e))?;
+
+        let mut workspaces = Vec::new();
+        for entry in entries.flatten() {
+            if !entry.path().is_dir() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                if validate_workspace_name(name).is_ok() {
+                    workspaces.push(self.describe(name, &active));
+                }
+            }
+        }
+
+        workspaces.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(workspaces)
+    }
+
+    pub fn get_active(&self) -> AppResult<Workspace> {
+        let active = self.active_workspace()?;
+        Ok(self.describe(&active, &active))
+    }
+
+    pub fn create_workspace(&self, name: &str) -> AppResult<Workspace> {
+        validate_workspace_name(name)?;
+        if self.workspace_dir(name).exists() {
+            return Err(AppError::conflict(format!("Workspace '{}' already exists", name)));
+        }
+
+        self.ensure_workspace_dir(name)?;
+        Ok(self.describe(name, &self.active_workspace()?))
+    }
+
+    /// Marks `name` as the active workspace and returns the path of its database.
+    /// The caller is responsible for swapping the open `Database`.
+    pub fn set_active(&self, name: &str) -> AppResult<PathBuf> {
+        validate_workspace_name(name)?;
+        if !self.workspace_dir(name).is_dir() {
+            return Err(AppError::not_found("Workspace", name));
+        }
+
+        let mut active = self.active.lock()?;
+        self.persist_active(name)?;
+        *active = name.to_string();
+
+        Ok(self.database_path(name))
+    }
+
+    fn ensure_workspace_dir(&self, name: &str) -> AppResult<()> {
+        let dir = self.workspace_dir(name);
+        fs::create_dir_all(&dir)
+            .map_err(|e| AppError::io("Failed to create workspace directory", &dir, e))
+    }
+
+    fn persist_active(&self, name: &str) -> AppResult<()> {
+        let path = self.data_dir.join(ACTIVE_WORKSPACE_FILE);
+        fs::write(&path, name)
+            .map_err(|e| AppError::io("Failed to save active workspace", &path, e))
+    }
+
+    fn describe(&self, name: &str, active: &str) -> Workspace {
+        Workspace {
+            name: name.to_string(),
+            database_path: self.database_path(name).to_string_lossy().to_string(),
+            active: name == active,
+        }
+    }
+}
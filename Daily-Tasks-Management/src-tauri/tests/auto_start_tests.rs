@@ -0,0 +1,148 @@
+use daily_tasks_management_lib::auto_start::{auto_start_order, auto_start_services, AutoStartEvent};
+use daily_tasks_management_lib::error::AppError;
+use daily_tasks_management_lib::model::Service;
+use daily_tasks_management_lib::service_graph::Dependencies;
+use daily_tasks_management_lib::services::{LaunchOptions, ServiceManager};
+use daily_tasks_management_lib::settings::{
+    safe_mode_requested, AppSettings, SettingsManager, DEFAULT_STOP_GRACE_PERIOD_MS,
+};
+use std::fs;
+use tempfile::TempDir;
+
+fn service(id: u32, command: &str, auto_start: bool) -> Service {
+    Service {
+        id,
+        name: format!("service-{}", id),
+        command: command.to_string(),
+        working_dir: None,
+        project_id: None,
+        auto_start,
+        shell: false,
+        created_at: "2030-01-01 00:00:00".to_string(),
+    }
+}
+
+#[test]
+fn test_auto_start_order_is_by_id() {
+    let services = vec![service(3, "sleep 1", true), service(1, "sleep 1", true), service(2, "sleep 1", false)];
+    let ids: Vec<u32> = auto_start_order(services.clone(), &Dependencies::new()).into_iter().map(|s| s.id).collect();
+    assert_eq!(ids, vec![1, 3]);
+
+    // Dependencies come first; one that does not auto-start stays stopped
+    let dependencies = Dependencies::from([(1, vec![3, 2])]);
+    let ids: Vec<u32> = auto_start_order(services, &dependencies).into_iter().map(|s| s.id).collect();
+    assert_eq!(ids, vec![3, 1]);
+}
+
+#[tokio::test]
+async fn test_auto_start_reports_failures_and_continues() {
+    let manager = ServiceManager::new();
+    let services = vec![
+        service(2, "sleep 2", true),
+        service(1, "definitely-not-a-real-binary-xyz", true),
+        service(3, "sleep 2", true),
+        service(4, "sleep 2", false),
+    ];
+
+    let mut events = Vec::new();
+    auto_start_services(
+        &manager,
+        services,
+        &Dependencies::new(),
+        |s| if s.id == 3 { Err(AppError::validation("env file is broken")) } else { Ok(LaunchOptions::default()) },
+        |event| events.push(event),
+    ).await;
+
+    let kinds: Vec<(&str, u32)> = events.iter().filter_map(|e| match e {
+        AutoStartEvent::Starting { service_id, .. } => Some(("starting", *service_id)),
+        AutoStartEvent::Started { service_id, .. } => Some(("started", *service_id)),
+        AutoStartEvent::Failed { service_id, .. } => Some(("failed", *service_id)),
+        _ => None,
+    }).collect();
+    assert_eq!(kinds, vec![
+        ("starting", 1), ("failed", 1),
+        ("starting", 2), ("started", 2),
+        ("starting", 3), ("failed", 3),
+    ]);
+    assert_eq!(events.last(), Some(&AutoStartEvent::Finished { started: 1, failed: 2 }));
+
+    assert!(manager.is_service_running(2).await);
+    assert!(!manager.is_service_running(4).await);
+    manager.stop_all_services().await;
+}
+
+#[tokio::test]
+async fn test_auto_start_waits_for_dependencies() {
+    let manager = ServiceManager::new();
+    // The api depends on a database that exits before it is ready
+    let services = vec![service(1, "sleep 2", true), service(2, "false", true)];
+    let dependencies = Dependencies::from([(1, vec![2])]);
+
+    let mut events = Vec::new();
+    auto_start_services(&manager, services, &dependencies, |_| Ok(LaunchOptions::default()), |event| events.push(event)).await;
+
+    let failed = events.iter().find_map(|e| match e {
+        AutoStartEvent::Failed { service_id, error, .. } => Some((*service_id, error.clone())),
+        _ => None,
+    });
+    let (service_id, error) = failed.expect("The api should not start");
+    assert_eq!(service_id, 1);
+    assert!(error.contains("exited"), "{}", error);
+    assert!(!manager.is_service_running(1).await);
+    manager.stop_all_services().await;
+}
+
+#[test]
+fn test_safe_mode_requested() {
+    assert!(safe_mode_requested(&["app".to_string(), "--safe-mode".to_string()], None));
+    assert!(safe_mode_requested(&[], Some(" True ".to_string())));
+    assert!(!safe_mode_requested(&[], Some("0".to_string())));
+    assert!(!safe_mode_requested(&["app".to_string()], None));
+}
+
+#[test]
+fn test_settings_persist_and_skip_auto_start() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let path = temp_dir.path().join("settings.json");
+
+    let settings = SettingsManager::new(&path, false).expect("Failed to load settings");
+    assert_eq!(settings.get().unwrap(), AppSettings::default());
+    assert_eq!(settings.auto_start_skip_reason().unwrap(), None);
+
+    settings.update(AppSettings { skip_auto_start: true, ..AppSettings::default() }).expect("Failed to save settings");
+    let reloaded = SettingsManager::new(&path, false).expect("Failed to reload settings");
+    assert!(reloaded.get().unwrap().skip_auto_start);
+    assert!(reloaded.auto_start_skip_reason().unwrap().is_some());
+
+    // Safe mode skips auto-start even when the setting allows it
+    reloaded.update(AppSettings::default()).unwrap();
+    let safe = SettingsManager::new(&path, true).expect("Failed to reload settings");
+    assert_eq!(safe.auto_start_skip_reason().unwrap().as_deref(), Some("Launched in safe mode"));
+
+    assert!(!path.with_extension("json.partial").exists());
+    assert_eq!(safe.load_error(), None);
+
+    // A corrupt file still launches, with defaults, and is kept aside
+    fs::write(&path, "{ not json").unwrap();
+    let recovered = SettingsManager::new(&path, false).expect("Corrupt settings failed the launch");
+    assert_eq!(recovered.get().unwrap(), AppSettings::default());
+    assert!(recovered.load_error().is_some_and(|e| e.contains("corrupt")));
+    assert_eq!(fs::read_to_string(path.with_extension("json.corrupt")).unwrap(), "{ not json");
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_stop_grace_period_setting() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let path = temp_dir.path().join("settings.json");
+
+    // Files saved before the setting existed get the default
+    fs::write(&path, r#"{ "skipAutoStart": true }"#).unwrap();
+    let settings = SettingsManager::new(&path, false).expect("Failed to load settings");
+    assert_eq!(settings.get().unwrap().stop_grace_period_ms, DEFAULT_STOP_GRACE_PERIOD_MS);
+
+    let err = settings.update(AppSettings { stop_grace_period_ms: 600_000, ..AppSettings::default() }).unwrap_err();
+    assert_eq!(err.field(), Some("stopGracePeriodMs"));
+    settings.update(AppSettings { stop_grace_period_ms: 1_500, ..AppSettings::default() }).unwrap();
+    assert_eq!(settings.get().unwrap().stop_grace_period().as_millis(), 1_500);
+}
@@ -0,0 +1,186 @@
+use daily_tasks_management_lib::backup::{self, BackupManager, BackupReason, RetentionPolicy};
+use daily_tasks_management_lib::database::Database;
+use daily_tasks_management_lib::repository::ProjectRepository;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn setup_test_db() -> (Database, PathBuf, TempDir) {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("tasks.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .expect("Failed to create test database");
+    (db, db_path, temp_dir)
+}
+
+fn write_fake_snapshot(dir: &Path, id: &str) {
+    fs::create_dir_all(dir).expect("Failed to create backup dir");
+    fs::write(dir.join(id), b"").expect("Failed to write snapshot");
+}
+
+#[test]
+fn test_create_and_list_snapshots() {
+    let (db, db_path, _temp_dir) = setup_test_db();
+    let backups = BackupManager::new(RetentionPolicy::default());
+
+    let snapshot = backups.create_snapshot(&db, BackupReason::Manual)
+        .expect("Failed to create snapshot");
+    assert_eq!(snapshot.reason, BackupReason::Manual);
+    assert!(snapshot.size_bytes > 0);
+
+    let snapshots = backups.list_snapshots(&db_path).expect("Failed to list snapshots");
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].id, snapshot.id);
+}
+
+#[test]
+fn test_inspect_snapshot() {
+    let (db, db_path, _temp_dir) = setup_test_db();
+    let backups = BackupManager::new(RetentionPolicy::default());
+
+    db.create_project("Backed Up".to_string(), "/tmp/backed_up".to_string(), None, None)
+        .expect("Failed to create project");
+    let snapshot = backups.create_snapshot(&db, BackupReason::Manual)
+        .expect("Failed to create snapshot");
+
+    let details = backups.inspect_snapshot(&db_path, &snapshot.id)
+        .expect("Failed to inspect snapshot");
+    assert_eq!(details.integrity, "ok");
+    assert_eq!(details.table_row_counts.get("projects"), Some(&1));
+
+    assert!(backups.inspect_snapshot(&db_path, "../tasks.db").is_err());
+}
+
+#[test]
+fn test_restore_snapshot() {
+    let (mut db, db_path, _temp_dir) = setup_test_db();
+    let backups = BackupManager::new(RetentionPolicy::default());
+
+    db.create_project("Keep".to_string(), "/tmp/keep".to_string(), None, None)
+        .expect("Failed to create project");
+    let snapshot = backups.create_snapshot(&db, BackupReason::Manual)
+        .expect("Failed to create snapshot");
+
+    db.create_project("Later".to_string(), "/tmp/later".to_string(), None, None)
+        .expect("Failed to create project");
+    assert_eq!(db.get_all_projects().unwrap().len(), 2);
+
+    let safety = backups.restore_snapshot(&mut db, &snapshot.id)
+        .expect("Failed to restore snapshot");
+    assert_eq!(safety.reason, BackupReason::Restore);
+
+    let projects = db.get_all_projects().expect("Failed to get projects");
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].name, "Keep");
+
+    // The pre-restore state is still available
+    let details = backups.inspect_snapshot(&db_path, &safety.id)
+        .expect("Failed to inspect safety snapshot");
+    assert_eq!(details.table_row_counts.get("projects"), Some(&2));
+}
+
+#[test]
+fn test_snapshot_if_due() {
+    let (db, db_path, _temp_dir) = setup_test_db();
+    let backups = BackupManager::new(RetentionPolicy::default());
+
+    let first = backups.snapshot_if_due(&db, Duration::from_secs(3600))
+        .expect("Failed to run scheduled backup");
+    assert!(first.is_some());
+
+    let second = backups.snapshot_if_due(&db, Duration::from_secs(3600))
+        .expect("Failed to run scheduled backup");
+    assert!(second.is_none());
+
+    assert_eq!(backups.list_snapshots(&db_path).unwrap().len(), 1);
+}
+
+#[test]
+fn test_retention_keeps_daily_and_weekly() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let dir = temp_dir.path().join("backups");
+    let backups = BackupManager::new(RetentionPolicy { keep_daily: 2, keep_weekly: 2 });
+
+    // Two snapshots on the newest day, one the day before and two older weeks
+    write_fake_snapshot(&dir, "20240619T180000000Z-scheduled.db");
+    write_fake_snapshot(&dir, "20240619T090000000Z-optimize.db");
+    write_fake_snapshot(&dir, "20240618T090000000Z-scheduled.db");
+    write_fake_snapshot(&dir, "20240612T090000000Z-scheduled.db");
+    write_fake_snapshot(&dir, "20240605T090000000Z-scheduled.db");
+
+    let mut removed = backups.prune(&dir).expect("Failed to prune");
+    removed.sort();
+
+    assert_eq!(removed, vec![
+        "20240605T090000000Z-scheduled.db".to_string(),
+        "20240619T090000000Z-optimize.db".to_string(),
+    ]);
+}
+
+#[test]
+fn test_migration_snapshot_taken() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("tasks.db");
+
+    {
+        let conn = rusqlite::Connection::open(&db_path).expect("Failed to open legacy db");
+        conn.execute_batch(
+            "CREATE TABLE projects (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL,
+                 path TEXT NOT NULL UNIQUE, project_type TEXT, description TEXT,
+                 last_accessed TEXT, created_at TEXT NOT NULL);
+             CREATE TABLE services (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL,
+                 command TEXT NOT NULL, working_dir TEXT, project_id INTEGER,
+                 auto_start BOOLEAN DEFAULT 0, created_at TEXT NOT NULL,
+                 FOREIGN KEY(project_id) REFERENCES projects(id));"
+        ).expect("Failed to create legacy schema");
+    }
+
+    let backups = BackupManager::new(RetentionPolicy::default());
+    let db = backup::open_database(&db_path, &backups).expect("Failed to open database");
+    assert!(!db.needs_migration().unwrap());
+
+    let snapshots = backups.list_snapshots(&db_path).expect("Failed to list snapshots");
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].reason, BackupReason::Migration);
+
+    // Already migrated, so reopening takes no further snapshot
+    drop(db);
+    backup::open_database(&db_path, &backups).expect("Failed to reopen database");
+    assert_eq!(backups.list_snapshots(&db_path).unwrap().len(), 1);
+}
+
+#[test]
+fn test_added_column_takes_migration_snapshot() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("tasks.db");
+
+    // Current foreign keys, but from before services had a shell column
+    drop(Database::new(db_path.to_str().unwrap()).expect("Failed to create database"));
+    rusqlite::Connection::open(&db_path).unwrap()
+        .execute_batch("ALTER TABLE services DROP COLUMN shell")
+        .expect("Failed to drop column");
+
+    let backups = BackupManager::new(RetentionPolicy::default());
+    let db = backup::open_database(&db_path, &backups).expect("Failed to open database");
+    assert!(!db.needs_migration().unwrap());
+
+    let snapshots = backups.list_snapshots(&db_path).expect("Failed to list snapshots");
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].reason, BackupReason::Migration);
+}
+
+#[test]
+fn test_import_snapshot_keeps_the_imported_data() {
+    let (db, db_path, _temp_dir) = setup_test_db();
+    db.create_project("Imported".to_string(), "/tmp/imported".to_string(), None, None)
+        .expect("Failed to create project");
+    drop(db);
+
+    let backups = BackupManager::new(RetentionPolicy::default());
+    let snapshot = backup::snapshot_import(&db_path, &backups).expect("Failed to snapshot import");
+    assert_eq!(snapshot.reason, BackupReason::Import);
+
+    let details = backups.inspect_snapshot(&db_path, &snapshot.id).expect("Failed to inspect snapshot");
+    assert_eq!(details.table_row_counts.get("projects"), Some(&1));
+}
@@ -0,0 +1,55 @@
+use daily_tasks_management_lib::command_line::parse_command;
+
+fn args(command: &str) -> Vec<String> {
+    let parsed = parse_command(command).expect("Failed to parse command");
+    std::iter::once(parsed.program).chain(parsed.args).collect()
+}
+
+#[test]
+fn test_parse_command_quotes_and_escapes() {
+    assert_eq!(args("npm run dev"), vec!["npm", "run", "dev"]);
+    assert_eq!(args("  cargo   watch  "), vec!["cargo", "watch"]);
+    assert_eq!(
+        args(r#"python -c 'print("hi there")' "two words" it\'s"#),
+        vec!["python", "-c", r#"print("hi there")"#, "two words", "it's"],
+    );
+    assert_eq!(args(r#"echo "say \"hi\" \n" a\ b """#), vec!["echo", r#"say "hi" \n"#, "a b", ""]);
+    // A lone `$` and a `#` inside a word are plain characters
+    assert_eq!(args("echo $ a#b # comment"), vec!["echo", "$", "a#b"]);
+    assert_eq!(args("serve \\\n  --port 3000"), vec!["serve", "--port", "3000"]);
+}
+
+#[test]
+fn test_parse_command_env_prefix() {
+    let parsed = parse_command("PORT=3000 NODE_ENV='dev mode' node server.js FLAG=1").expect("Failed to parse command");
+    assert_eq!(parsed.env, vec![
+        ("PORT".to_string(), "3000".to_string()),
+        ("NODE_ENV".to_string(), "dev mode".to_string()),
+    ]);
+    assert_eq!(parsed.program, "node");
+    assert_eq!(parsed.args, vec!["server.js", "FLAG=1"]);
+
+    // A quoted or invalid name is the program, not an assignment
+    assert_eq!(parse_command("'A=1' x").unwrap().program, "A=1");
+    assert_eq!(parse_command("1A=2 x").unwrap().program, "1A=2");
+}
+
+#[test]
+fn test_parse_command_rejects_shell_syntax() {
+    for command in ["npm start | tee log", "a && b", "a; b", "a > out", "echo $HOME", "echo \"${PATH}\"", "echo `date`", "echo $(date)"] {
+        let err = parse_command(command).unwrap_err();
+        assert_eq!(err.field(), Some("command"));
+        assert!(err.to_string().contains("shell mode"), "{}: {}", command, err);
+    }
+    // Quoted operators are just text
+    assert_eq!(args("grep 'a|b' \"x > y\""), vec!["grep", "a|b", "x > y"]);
+}
+
+#[test]
+fn test_parse_command_errors() {
+    assert_eq!(parse_command("   ").unwrap_err().to_string(), "Empty command");
+    assert_eq!(parse_command("PORT=1").unwrap_err().to_string(), "Empty command");
+    assert_eq!(parse_command("echo 'open").unwrap_err().to_string(), "Unterminated single quote");
+    assert_eq!(parse_command("echo \"open").unwrap_err().to_string(), "Unterminated double quote");
+    assert_eq!(parse_command("echo \\").unwrap_err().to_string(), "Command ends with a backslash");
+}
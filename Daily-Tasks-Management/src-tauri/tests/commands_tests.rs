@@ -1,7 +1,14 @@
 use daily_tasks_management_lib::database::Database;
+use daily_tasks_management_lib::database_browser::{self, QUERY_TIMEOUT};
+use daily_tasks_management_lib::error::ErrorCode;
+use daily_tasks_management_lib::handlers;
+use daily_tasks_management_lib::memory::MemoryStore;
+use daily_tasks_management_lib::model::ProjectDeleteMode;
 use daily_tasks_management_lib::projects::ProjectManager;
+use daily_tasks_management_lib::repository::{ProjectRepository, ServiceRepository};
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 use tempfile::TempDir;
 
 fn setup_test_db() -> (Database, TempDir) {
@@ -88,6 +95,7 @@ fn test_register_service_logic() {
         None,
         None,
         false,
+        false,
     ).expect("Failed to create service");
     
     assert_eq!(service.name, "Test Service");
@@ -105,6 +113,7 @@ fn test_get_all_services_logic() {
         None,
         None,
         false,
+        false,
     ).expect("Failed to create service 1");
     
     let _ = db.create_service(
@@ -113,6 +122,7 @@ fn test_get_all_services_logic() {
         None,
         None,
         true,
+        false,
     ).expect("Failed to create service 2");
     
     let services = db.get_all_services().expect("Failed to get services");
@@ -182,8 +192,65 @@ fn test_service_with_project_logic() {
         Some(project_path.to_string()),
         Some(project.id),
         false,
+        false,
     ).expect("Failed to create service");
     
     assert_eq!(service.project_id, Some(project.id));
     assert_eq!(service.working_dir, Some(project_path.to_string()));
 }
+
+// The same command logic runs against the in-memory store, no file needed
+
+#[test]
+fn test_handlers_with_memory_store() {
+    let store = MemoryStore::new();
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let project_path = temp_dir.path().to_str().unwrap();
+    fs::write(Path::new(project_path).join("package.json"), "{}").expect("Failed to write package.json");
+
+    let project = handlers::add_project(&store, String::new(), project_path.to_string())
+        .expect("Failed to add project");
+    assert_eq!(project.project_type, Some("Node.js / Typescript / React".to_string()));
+
+    let service = handlers::register_service(&store, "web".to_string(), "npm start".to_string(), None, Some(project.id), false, false)
+        .expect("Failed to register service");
+    assert_eq!(handlers::require_service(&store, service.id).unwrap().name, "web");
+
+    let err = handlers::require_service(&store, 999).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NotFound);
+
+    // The project directory is not a git repository
+    let err = handlers::git_commit(&store, project.id, "message").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NotFound);
+
+    let impact = handlers::remove_project(&store, project.id, ProjectDeleteMode::Cascade)
+        .expect("Failed to remove project");
+    assert_eq!(impact.service_ids, vec![service.id]);
+    assert!(handlers::get_all_services(&store).unwrap().is_empty());
+}
+
+#[test]
+fn test_query_history_records_failures() {
+    let store = MemoryStore::new();
+    let (db, temp_dir) = setup_test_db();
+    drop(db);
+    let db_path = temp_dir.path().join("test.db");
+
+    let ok = database_browser::execute_query(db_path.to_str().unwrap(), "SELECT 1", QUERY_TIMEOUT);
+    handlers::record_query(&store, "workspace".to_string(), "SELECT 1".to_string(), Duration::from_millis(3), &ok)
+        .expect("Failed to record query");
+
+    let failed = database_browser::execute_query(db_path.to_str().unwrap(), "DELETE FROM tasks", QUERY_TIMEOUT);
+    handlers::record_query(&store, "workspace".to_string(), "DELETE FROM tasks".to_string(), Duration::ZERO, &failed)
+        .expect("Failed to record query");
+
+    let history = handlers::get_query_history(&store, Some("  "), None, 10).expect("Failed to get history");
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].row_count, None);
+    assert!(history[0].error.as_deref().unwrap().contains("read-only"));
+    assert_eq!(history[1].row_count, Some(1));
+    assert_eq!(history[1].duration_ms, 3);
+
+    let err = handlers::save_query(&store, " ".to_string(), "SELECT 1".to_string(), None, None).unwrap_err();
+    assert_eq!(err.field(), Some("name"));
+}
@@ -0,0 +1,235 @@
+use daily_tasks_management_lib::database::Database;
+use daily_tasks_management_lib::database_browser::{self, QueryParameter, QueryParams, QUERY_TIMEOUT};
+use daily_tasks_management_lib::database_value::CellValue;
+use daily_tasks_management_lib::error::ErrorCode;
+use daily_tasks_management_lib::repository::ProjectRepository;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn setup_test_db() -> (String, TempDir) {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("test.db");
+    let db = Database::new(db_path.to_str().unwrap())
+        .expect("Failed to create test database");
+    for i in 0..3 {
+        db.create_project(format!("Project {}", i), format!("/tmp/project{}", i), None, None)
+            .expect("Failed to create project");
+    }
+    (db_path.to_str().unwrap().to_string(), temp_dir)
+}
+
+#[test]
+fn test_list_tables_and_read_data() {
+    let (db_path, _temp_dir) = setup_test_db();
+
+    let tables = database_browser::list_tables(&db_path, QUERY_TIMEOUT).expect("Failed to list tables");
+    let projects = tables.iter().find(|t| t.name == "projects").expect("projects table missing");
+    assert_eq!(projects.row_count, 3);
+
+    let page = database_browser::get_table_data(&db_path, "projects", 2, 2, QUERY_TIMEOUT)
+        .expect("Failed to read table");
+    assert_eq!(page.row_count, 1);
+    assert_eq!(page.columns[0].name, "id");
+    assert_eq!(page.columns[0].declared_type.as_deref(), Some("INTEGER"));
+    // Integers come back typed rather than as NULL
+    assert_eq!(page.rows[0][0], CellValue::Integer(3));
+}
+
+#[test]
+fn test_select_with_keyword_in_column_name() {
+    let (db_path, _temp_dir) = setup_test_db();
+
+    // created_at contains "CREATE", which the old keyword blacklist rejected
+    let result = database_browser::execute_query(
+        &db_path,
+        "SELECT name, created_at FROM projects ORDER BY id",
+        QUERY_TIMEOUT,
+    ).expect("Failed to execute query");
+    assert_eq!(result.row_count, 3);
+    let columns: Vec<_> = result.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(columns, vec!["name", "created_at"]);
+
+    database_browser::execute_query(&db_path, "PRAGMA table_info(projects)", QUERY_TIMEOUT)
+        .expect("Introspection pragma should be allowed");
+}
+
+#[test]
+fn test_writes_and_escapes_are_rejected() {
+    let (db_path, temp_dir) = setup_test_db();
+    let other = temp_dir.path().join("other.db");
+
+    let statements = [
+        "DELETE FROM projects".to_string(),
+        "UPDATE projects SET name = 'x'".to_string(),
+        "CREATE TABLE evil (id INTEGER)".to_string(),
+        "PRAGMA writable_schema = 1".to_string(),
+        "PRAGMA user_version = 7".to_string(),
+        format!("ATTACH DATABASE '{}' AS other", other.display()),
+    ];
+    for sql in &statements {
+        let err = database_browser::execute_query(&db_path, sql, QUERY_TIMEOUT).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Validation, "{} was not rejected", sql);
+        assert_eq!(err.field(), Some("query"));
+    }
+
+    // Nothing was changed and no file was created
+    let result = database_browser::execute_query(&db_path, "SELECT COUNT(*) FROM projects", QUERY_TIMEOUT)
+        .expect("Failed to count projects");
+    assert_eq!(result.rows[0][0], CellValue::Integer(3));
+    assert!(!other.exists());
+}
+
+#[test]
+fn test_query_timeout() {
+    let (db_path, _temp_dir) = setup_test_db();
+
+    let err = database_browser::execute_query(
+        &db_path,
+        "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT COUNT(*) FROM n",
+        Duration::from_millis(100),
+    ).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Timeout);
+}
+
+#[test]
+fn test_missing_database_is_not_created() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let missing = temp_dir.path().join("missing.db");
+
+    let err = database_browser::list_tables(missing.to_str().unwrap(), QUERY_TIMEOUT).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NotFound);
+    assert!(!missing.exists());
+}
+
+#[test]
+fn test_schema_introspection() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("schema.db");
+    let conn = rusqlite::Connection::open(&db_path).expect("Failed to open database");
+    conn.execute_batch(
+        "CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE);
+         CREATE TABLE books (
+             id INTEGER PRIMARY KEY,
+             author_id INTEGER NOT NULL REFERENCES authors(id) ON DELETE CASCADE,
+             title TEXT NOT NULL,
+             status TEXT DEFAULT 'draft'
+         );
+         CREATE INDEX books_by_title ON books (title);
+         CREATE TRIGGER books_touch AFTER UPDATE ON books BEGIN SELECT 1; END;
+         CREATE VIEW drafts AS SELECT id, title FROM books WHERE status = 'draft';",
+    ).expect("Failed to create schema");
+    drop(conn);
+
+    let schema = database_browser::get_schema(db_path.to_str().unwrap(), QUERY_TIMEOUT)
+        .expect("Failed to read schema");
+
+    let names: Vec<_> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["authors", "books"]);
+
+    let books = &schema.tables[1];
+    assert!(books.sql.as_deref().unwrap().starts_with("CREATE TABLE books"));
+
+    let id = &books.columns[0];
+    assert_eq!((id.name.as_str(), id.declared_type.as_str(), id.primary_key), ("id", "INTEGER", 1));
+    let status = &books.columns[3];
+    assert!(status.nullable);
+    assert_eq!(status.default_value.as_deref(), Some("'draft'"));
+    assert!(!books.columns[2].nullable);
+
+    assert_eq!(books.indexes.len(), 1);
+    assert_eq!(books.indexes[0].columns, vec!["title"]);
+    assert!(!books.indexes[0].unique);
+
+    // UNIQUE constraint shows up as an automatic index without SQL
+    let authors_index = &schema.tables[0].indexes[0];
+    assert!(authors_index.unique);
+    assert_eq!(authors_index.origin, "u");
+    assert!(authors_index.sql.is_none());
+
+    assert_eq!(books.foreign_keys.len(), 1);
+    let fk = &books.foreign_keys[0];
+    assert_eq!(fk.referenced_table, "authors");
+    assert_eq!(fk.columns, vec!["author_id"]);
+    assert_eq!(fk.referenced_columns, vec![Some("id".to_string())]);
+    assert_eq!(fk.on_delete, "CASCADE");
+
+    assert_eq!(books.triggers.len(), 1);
+    assert_eq!(books.triggers[0].name, "books_touch");
+
+    assert_eq!(schema.views.len(), 1);
+    let view_columns: Vec<_> = schema.views[0].columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(view_columns, vec!["id", "title"]);
+}
+
+#[test]
+fn test_typed_cells() {
+    let (db_path, _temp_dir) = setup_test_db();
+
+    let result = database_browser::execute_query(
+        &db_path,
+        "SELECT NULL, 9007199254740993, 1.5, 'text', x'00ff10', id + 1 FROM projects LIMIT 1",
+        QUERY_TIMEOUT,
+    ).expect("Failed to execute query");
+
+    assert_eq!(result.rows[0], vec![
+        CellValue::Null,
+        CellValue::Integer(9_007_199_254_740_993),
+        CellValue::Real(1.5),
+        CellValue::Text("text".to_string()),
+        CellValue::Blob(vec![0x00, 0xff, 0x10]),
+        CellValue::Integer(2),
+    ]);
+    // Expressions have no declared type
+    assert!(result.columns.iter().all(|c| c.declared_type.is_none()));
+}
+
+#[test]
+fn test_cell_serialization_round_trip() {
+    let cells = vec![
+        CellValue::Null,
+        CellValue::Integer(i64::MAX),
+        CellValue::Real(-0.25),
+        CellValue::Text("héllo".to_string()),
+        CellValue::Blob((0..=255).collect()),
+    ];
+
+    let json = serde_json::to_value(&cells).expect("Failed to serialize cells");
+    assert_eq!(json[0], serde_json::json!({ "type": "null" }));
+    // Large integers are strings so JavaScript keeps every digit
+    assert_eq!(json[1], serde_json::json!({ "type": "integer", "value": "9223372036854775807" }));
+    assert_eq!(json[4]["size"], 256);
+    assert_eq!(json[4]["hex"].as_str().unwrap().len(), 64);
+
+    let back: Vec<CellValue> = serde_json::from_value(json).expect("Failed to deserialize cells");
+    assert_eq!(back, cells);
+
+    let number: CellValue = serde_json::from_str(r#"{ "type": "integer", "value": 42 }"#).unwrap();
+    assert_eq!(number, CellValue::Integer(42));
+    assert!(serde_json::from_str::<CellValue>(r#"{ "type": "integer", "value": "4x" }"#).is_err());
+}
+
+#[test]
+fn test_query_parameters() {
+    let (db_path, _temp_dir) = setup_test_db();
+    let query = "SELECT name FROM projects WHERE id > ? AND name != :skip AND id <= ?";
+
+    let parameters = database_browser::query_parameters(&db_path, query, QUERY_TIMEOUT)
+        .expect("Failed to list parameters");
+    assert_eq!(parameters, vec![
+        QueryParameter { index: 1, name: None },
+        QueryParameter { index: 2, name: Some("skip".to_string()) },
+        QueryParameter { index: 3, name: None },
+    ]);
+
+    let params = QueryParams {
+        positional: vec![CellValue::Integer(0), CellValue::Null, CellValue::Integer(3)],
+        named: [("skip".to_string(), CellValue::Text("Project 1".to_string()))].into_iter().collect(),
+    };
+    let result = database_browser::execute_query_with_params(&db_path, query, &params, QUERY_TIMEOUT)
+        .expect("Failed to execute query");
+    assert_eq!(result.row_count, 2);
+
+    let err = database_browser::execute_query_with_params(&db_path, query, &QueryParams::default(), QUERY_TIMEOUT)
+        .unwrap_err();
+    assert_eq!(err.field(), Some("params"));
+}
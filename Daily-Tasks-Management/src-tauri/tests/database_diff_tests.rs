@@ -0,0 +1,179 @@
+use daily_tasks_management_lib::database_diff::{diff_databases, ChangeKind, DiffOptions, MigrationMode, DIFF_TIMEOUT};
+use daily_tasks_management_lib::database_value::CellValue;
+use rusqlite::Connection;
+use std::fs;
+use tempfile::TempDir;
+
+fn create_db(dir: &TempDir, name: &str, sql: &str) -> String {
+    let path = dir.path().join(name);
+    let conn = Connection::open(&path).expect("Failed to create test database");
+    conn.execute_batch(sql).expect("Failed to seed test database");
+    path.to_str().unwrap().to_string()
+}
+
+const FROM_SQL: &str = "
+    CREATE TABLE tasks (id INTEGER PRIMARY KEY, title TEXT NOT NULL, done INTEGER);
+    CREATE INDEX idx_tasks_done ON tasks(done);
+    CREATE TABLE notes (body TEXT);
+    CREATE TABLE legacy (id INTEGER PRIMARY KEY);
+    CREATE VIEW open_tasks AS SELECT * FROM tasks WHERE done = 0;
+    INSERT INTO tasks VALUES (1, 'Write', 0), (2, 'Review', 1), (3, 'Ship', 0);
+    INSERT INTO notes VALUES ('first'), ('second');
+";
+
+const TO_SQL: &str = "
+    CREATE TABLE tasks (id INTEGER PRIMARY KEY, title TEXT NOT NULL, done INTEGER, due TEXT);
+    CREATE INDEX idx_tasks_due ON tasks(due);
+    CREATE TABLE notes (body TEXT);
+    CREATE TABLE tags (name TEXT PRIMARY KEY) WITHOUT ROWID;
+    CREATE TRIGGER tasks_done AFTER UPDATE OF done ON tasks BEGIN SELECT 1; END;
+    CREATE VIEW open_tasks AS SELECT id, title FROM tasks WHERE done = 0;
+    INSERT INTO tasks VALUES (1, 'Write', 1, NULL), (3, 'Ship it', 0, '2024-05-01'), (4, 'Test', 0, NULL);
+    INSERT INTO notes VALUES ('first'), ('second'), ('third');
+    INSERT INTO tags VALUES ('urgent');
+";
+
+fn dump(path: &str) -> Vec<String> {
+    let conn = Connection::open(path).expect("Failed to open database");
+    let mut stmt = conn
+        .prepare("SELECT type || ' ' || name || ': ' || ifnull(sql, '') FROM sqlite_master ORDER BY type, name")
+        .expect("Failed to prepare dump");
+    let mut lines: Vec<String> = stmt.query_map([], |row| row.get(0)).unwrap().map(Result::unwrap).collect();
+    for query in ["SELECT * FROM tasks ORDER BY id", "SELECT rowid, * FROM notes ORDER BY rowid", "SELECT * FROM tags ORDER BY name"] {
+        let mut stmt = conn.prepare(query).expect("Failed to prepare dump");
+        let count = stmt.column_count();
+        let rows = stmt.query_map([], |row| {
+            (0..count).map(|i| row.get::<_, rusqlite::types::Value>(i).map(|v| format!("{:?}", v))).collect::<Result<Vec<_>, _>>()
+        }).unwrap();
+        lines.extend(rows.map(|r| format!("{}: {}", query, r.unwrap().join(", "))));
+    }
+    lines
+}
+
+#[test]
+fn test_schema_and_row_differences() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let from = create_db(&dir, "from.db", FROM_SQL);
+    let to = create_db(&dir, "to.db", TO_SQL);
+
+    let diff = diff_databases(&from, &to, &DiffOptions::default(), DIFF_TIMEOUT).expect("Failed to diff databases");
+    let names: Vec<(&str, Option<ChangeKind>)> = diff.tables.iter().map(|t| (t.name.as_str(), t.change)).collect();
+    assert_eq!(names, vec![
+        ("legacy", Some(ChangeKind::Removed)),
+        ("notes", None),
+        ("tags", Some(ChangeKind::Added)),
+        ("tasks", Some(ChangeKind::Changed)),
+    ]);
+
+    let tasks = &diff.tables[3];
+    assert_eq!(tasks.columns.len(), 1);
+    assert_eq!(tasks.columns[0].name, "due");
+    assert_eq!(tasks.columns[0].change, ChangeKind::Added);
+    let indexes: Vec<(&str, ChangeKind)> = tasks.indexes.iter().map(|i| (i.name.as_str(), i.change)).collect();
+    assert_eq!(indexes, vec![("idx_tasks_done", ChangeKind::Removed), ("idx_tasks_due", ChangeKind::Added)]);
+    assert_eq!(tasks.triggers[0].change, ChangeKind::Added);
+
+    // Rows are matched on the primary key and compared over shared columns
+    let data = tasks.data.as_ref().expect("Rows were not compared");
+    assert_eq!(data.key, vec!["id"]);
+    assert_eq!(data.columns, vec!["id", "title", "done"]);
+    assert_eq!((data.added, data.removed, data.changed), (1, 1, 2));
+    assert_eq!(data.rows[0].key, vec![CellValue::Integer(1)]);
+    assert_eq!(data.rows[0].changed_columns, vec!["done"]);
+    assert_eq!(data.rows[1].change, ChangeKind::Removed);
+    assert_eq!(data.rows[2].changed_columns, vec!["title"]);
+    assert_eq!(data.rows[3].change, ChangeKind::Added);
+
+    // Tables without a primary key fall back to the rowid
+    let notes = diff.tables[1].data.as_ref().expect("Rows were not compared");
+    assert_eq!(notes.key, vec!["rowid"]);
+    assert_eq!(notes.added, 1);
+
+    assert_eq!(diff.views.len(), 1);
+    assert_eq!(diff.views[0].change, ChangeKind::Changed);
+    assert!(diff.migration.is_none());
+
+    let limited = DiffOptions { max_rows: Some(1), ..DiffOptions::default() };
+    let diff = diff_databases(&from, &to, &limited, DIFF_TIMEOUT).expect("Failed to diff databases");
+    let data = diff.tables[3].data.as_ref().unwrap();
+    assert_eq!(data.rows.len(), 1);
+    assert!(data.truncated);
+    assert_eq!(data.changed, 2);
+}
+
+#[test]
+fn test_changed_primary_key_skips_rows() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let from = create_db(&dir, "from.db", "CREATE TABLE t (a INTEGER PRIMARY KEY, b TEXT); INSERT INTO t VALUES (1, 'x');");
+    let to = create_db(&dir, "to.db", "CREATE TABLE t (a INTEGER, b TEXT PRIMARY KEY); INSERT INTO t VALUES (1, 'x');");
+
+    let diff = diff_databases(&from, &to, &DiffOptions::default(), DIFF_TIMEOUT).expect("Failed to diff databases");
+    assert!(diff.tables[0].data.is_none());
+    assert_eq!(diff.tables[0].data_skipped.as_deref(), Some("Primary key changed from (a) to (b)"));
+
+    let same = diff_databases(&from, &from, &DiffOptions::default(), DIFF_TIMEOUT).expect("Failed to diff databases");
+    assert!(same.tables.is_empty() && same.views.is_empty());
+}
+
+#[test]
+fn test_negative_real_keys_next_to_integers() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    // Without a declared type, keys keep whether they are integers or reals
+    let from = create_db(&dir, "from.db", "CREATE TABLE m (k PRIMARY KEY, v TEXT);
+        INSERT INTO m VALUES (-2, 'a'), (-1.5, 'b'), (-1, 'c'), (0.5, 'd');");
+    let to = create_db(&dir, "to.db", "CREATE TABLE m (k PRIMARY KEY, v TEXT);
+        INSERT INTO m VALUES (-2, 'a'), (-1.5, 'B'), (-1, 'c'), (0.5, 'd');");
+
+    let options = DiffOptions { migration: Some(MigrationMode::Full), ..DiffOptions::default() };
+    let diff = diff_databases(&from, &to, &options, DIFF_TIMEOUT).expect("Failed to diff databases");
+    let data = diff.tables[0].data.as_ref().expect("Rows were not compared");
+    assert_eq!((data.added, data.removed, data.changed), (0, 0, 1));
+    assert_eq!(data.rows[0].key, vec![CellValue::Real(-1.5)]);
+
+    let conn = Connection::open(&from).unwrap();
+    conn.execute_batch(&diff.migration.expect("No migration script")).expect("Failed to apply migration");
+    let values: Vec<String> = conn
+        .prepare("SELECT v FROM m ORDER BY k").unwrap()
+        .query_map([], |row| row.get(0)).unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(values, vec!["a", "B", "c", "d"]);
+}
+
+#[test]
+fn test_migration_scripts_apply() {
+    let dir = TempDir::new().expect("Failed to create temp dir");
+    let from = create_db(&dir, "from.db", FROM_SQL);
+    let to = create_db(&dir, "to.db", TO_SQL);
+
+    // A full migration makes schema and rows identical
+    let options = DiffOptions { migration: Some(MigrationMode::Full), ..DiffOptions::default() };
+    let diff = diff_databases(&from, &to, &options, DIFF_TIMEOUT).expect("Failed to diff databases");
+    let script = diff.migration.expect("No migration script");
+    let copy = dir.path().join("full.db");
+    fs::copy(&from, &copy).expect("Failed to copy database");
+    Connection::open(&copy).unwrap().execute_batch(&script).expect("Failed to apply migration");
+    let copy = copy.to_str().unwrap().to_string();
+    assert_eq!(dump(&copy), dump(&to));
+    let after = diff_databases(&copy, &to, &DiffOptions::default(), DIFF_TIMEOUT).expect("Failed to diff databases");
+    assert!(after.tables.is_empty() && after.views.is_empty(), "{:?}", after);
+
+    // A schema migration keeps the rows the old columns had
+    let options = DiffOptions { migration: Some(MigrationMode::Schema), include_data: false, ..DiffOptions::default() };
+    let diff = diff_databases(&from, &to, &options, DIFF_TIMEOUT).expect("Failed to diff databases");
+    assert!(diff.tables.iter().all(|t| t.data.is_none()));
+    let copy = dir.path().join("schema.db");
+    fs::copy(&from, &copy).expect("Failed to copy database");
+    let conn = Connection::open(&copy).unwrap();
+    conn.execute_batch(&diff.migration.unwrap()).expect("Failed to apply migration");
+    let titles: Vec<String> = conn
+        .prepare("SELECT title FROM tasks ORDER BY id").unwrap()
+        .query_map([], |row| row.get(0)).unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(titles, vec!["Write", "Review", "Ship"]);
+
+    let after = diff_databases(copy.to_str().unwrap(), &to, &DiffOptions { include_data: false, ..DiffOptions::default() }, DIFF_TIMEOUT)
+        .expect("Failed to diff databases");
+    assert!(after.tables.is_empty() && after.views.is_empty(), "{:?}", after);
+}
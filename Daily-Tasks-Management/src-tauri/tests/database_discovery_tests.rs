@@ -0,0 +1,84 @@
+use daily_tasks_management_lib::database::Database;
+use daily_tasks_management_lib::database_discovery::{self, WORKSPACE_DATABASE_ID};
+use daily_tasks_management_lib::error::ErrorCode;
+use daily_tasks_management_lib::memory::MemoryStore;
+use daily_tasks_management_lib::repository::ProjectRepository;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_sqlite_file(path: &Path) {
+    fs::create_dir_all(path.parent().unwrap()).expect("Failed to create dir");
+    Database::new(path.to_str().unwrap()).expect("Failed to create database");
+}
+
+fn setup_project() -> (MemoryStore, u32, TempDir) {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+
+    create_sqlite_file(&root.join("dev.db"));
+    create_sqlite_file(&root.join("data/cache.sqlite3"));
+    // Skipped: wrong header, dependency directory, hidden directory
+    fs::write(root.join("notes.db"), "not a database").expect("Failed to write file");
+    create_sqlite_file(&root.join("node_modules/pkg/test.db"));
+    create_sqlite_file(&root.join(".cache/state.db"));
+
+    let store = MemoryStore::new();
+    let project = store.create_project("App".to_string(), root.to_str().unwrap().to_string(), None, None)
+        .expect("Failed to create project");
+    (store, project.id, temp_dir)
+}
+
+#[test]
+fn test_discover_databases_in_project() {
+    let (store, project_id, _temp_dir) = setup_project();
+
+    let found = database_discovery::discover_databases(&store, None).expect("Failed to discover databases");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].project_id, project_id);
+
+    let mut relative: Vec<_> = found[0].databases.iter().map(|d| d.relative_path.replace('\\', "/")).collect();
+    relative.sort();
+    assert_eq!(relative, vec!["data/cache.sqlite3", "dev.db"]);
+    assert!(found[0].databases.iter().all(|d| d.size_bytes > 0));
+
+    assert!(database_discovery::discover_databases(&store, Some(999)).is_err());
+}
+
+#[test]
+fn test_resolve_database_by_id() {
+    let (store, project_id, temp_dir) = setup_project();
+    let workspace_db = temp_dir.path().join("tasks.db");
+
+    let found = database_discovery::discover_databases(&store, Some(project_id)).unwrap();
+    for database in &found[0].databases {
+        let path = database_discovery::resolve_database(&store, &workspace_db, &database.id)
+            .expect("Failed to resolve database");
+        assert_eq!(path, Path::new(&database.path));
+    }
+
+    let path = database_discovery::resolve_database(&store, &workspace_db, WORKSPACE_DATABASE_ID).unwrap();
+    assert_eq!(path, workspace_db);
+}
+
+#[test]
+fn test_resolve_rejects_paths_outside_discovery() {
+    let (store, project_id, _temp_dir) = setup_project();
+    let workspace_db = Path::new("/tmp/tasks.db");
+
+    let outside = TempDir::new().expect("Failed to create temp dir");
+    create_sqlite_file(&outside.path().join("other.db"));
+
+    let escape = format!("{}:../{}/other.db", project_id, outside.path().file_name().unwrap().to_str().unwrap());
+    let err = database_discovery::resolve_database(&store, workspace_db, &escape).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Validation);
+
+    let absolute = format!("{}:{}", project_id, outside.path().join("other.db").display());
+    assert!(database_discovery::resolve_database(&store, workspace_db, &absolute).is_err());
+
+    let err = database_discovery::resolve_database(&store, workspace_db, &format!("{}:notes.db", project_id)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NotFound);
+
+    let err = database_discovery::resolve_database(&store, workspace_db, "not-an-id").unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Validation);
+}
@@ -0,0 +1,168 @@
+use daily_tasks_management_lib::database_edit::{
+    apply_changes, get_editable_rows, is_app_database, preview_changes, RowEdit, WriteModes, WRITE_TIMEOUT,
+};
+use daily_tasks_management_lib::database_browser::QUERY_TIMEOUT;
+use daily_tasks_management_lib::database_value::CellValue;
+use daily_tasks_management_lib::error::ErrorCode;
+use rusqlite::Connection;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn setup_test_db() -> (String, TempDir) {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("dev.db");
+    let conn = Connection::open(&db_path).expect("Failed to create test database");
+    conn.execute_batch(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE, email TEXT);
+         CREATE TABLE notes (body TEXT);
+         INSERT INTO users VALUES (1, 'ada', 'ada@example.com'), (2, 'bob', NULL);
+         INSERT INTO notes VALUES ('first');",
+    ).expect("Failed to seed test database");
+    (db_path.to_str().unwrap().to_string(), temp_dir)
+}
+
+fn map(entries: &[(&str, CellValue)]) -> BTreeMap<String, CellValue> {
+    entries.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+}
+
+fn text(value: &str) -> CellValue {
+    CellValue::Text(value.to_string())
+}
+
+fn names(db_path: &str) -> Vec<String> {
+    let conn = Connection::open(db_path).expect("Failed to open database");
+    let mut stmt = conn.prepare("SELECT name FROM users ORDER BY id").unwrap();
+    stmt.query_map([], |row| row.get(0)).unwrap().map(Result::unwrap).collect()
+}
+
+#[test]
+fn test_preview_and_apply_changeset() {
+    let (db_path, _temp_dir) = setup_test_db();
+    let changes = vec![
+        RowEdit::Update {
+            table: "users".to_string(),
+            key: map(&[("id", CellValue::Integer(2))]),
+            values: map(&[("name", text("bo'b")), ("email", CellValue::Null)]),
+        },
+        RowEdit::Insert {
+            table: "users".to_string(),
+            values: map(&[("name", text("cy")), ("id", CellValue::Integer(3))]),
+        },
+        RowEdit::Delete {
+            table: "notes".to_string(),
+            key: map(&[("rowid", CellValue::Integer(1))]),
+        },
+    ];
+
+    let preview = preview_changes(&db_path, &changes).expect("Failed to preview changes");
+    assert_eq!(preview, vec![
+        r#"UPDATE "users" SET "name" = 'bo''b', "email" = NULL WHERE "id" IS 2;"#,
+        r#"INSERT INTO "users" ("id", "name") VALUES (3, 'cy');"#,
+        r#"DELETE FROM "notes" WHERE rowid IS 1;"#,
+    ]);
+    // Previews leave the database alone
+    assert_eq!(names(&db_path), vec!["ada", "bob"]);
+
+    let summary = apply_changes(&db_path, &changes, WRITE_TIMEOUT).expect("Failed to apply changes");
+    assert_eq!(summary.statements, 3);
+    assert_eq!(summary.rows_affected, 3);
+    assert_eq!(names(&db_path), vec!["ada", "bo'b", "cy"]);
+}
+
+#[test]
+fn test_failed_change_rolls_back_changeset() {
+    let (db_path, _temp_dir) = setup_test_db();
+
+    // The second change breaks the UNIQUE constraint
+    let changes = vec![
+        RowEdit::Update {
+            table: "users".to_string(),
+            key: map(&[("id", CellValue::Integer(1))]),
+            values: map(&[("name", text("ada lovelace"))]),
+        },
+        RowEdit::Insert {
+            table: "users".to_string(),
+            values: map(&[("name", text("bob"))]),
+        },
+    ];
+    let err = apply_changes(&db_path, &changes, WRITE_TIMEOUT).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Conflict);
+    assert!(err.to_string().starts_with("Change 2 failed"));
+    assert_eq!(names(&db_path), vec!["ada", "bob"]);
+
+    // A row that is gone counts as a failure too
+    let changes = vec![
+        RowEdit::Delete {
+            table: "users".to_string(),
+            key: map(&[("id", CellValue::Integer(1))]),
+        },
+        RowEdit::Delete {
+            table: "users".to_string(),
+            key: map(&[("id", CellValue::Integer(99))]),
+        },
+    ];
+    let err = apply_changes(&db_path, &changes, WRITE_TIMEOUT).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Conflict);
+    assert_eq!(names(&db_path), vec!["ada", "bob"]);
+}
+
+#[test]
+fn test_editable_rows_carry_their_keys() {
+    let (db_path, _temp_dir) = setup_test_db();
+
+    let users = get_editable_rows(&db_path, "users", 10, 1, QUERY_TIMEOUT).expect("Failed to read rows");
+    assert_eq!(users.key, vec!["id"]);
+    assert_eq!(users.keys, vec![vec![CellValue::Integer(2)]]);
+    assert_eq!(users.data.columns.len(), 3);
+
+    // Tables without a primary key are keyed by rowid, which is not shown as a column
+    let notes = get_editable_rows(&db_path, "notes", 10, 0, QUERY_TIMEOUT).expect("Failed to read rows");
+    assert_eq!(notes.key, vec!["rowid"]);
+    assert_eq!(notes.keys, vec![vec![CellValue::Integer(1)]]);
+    assert_eq!(notes.data.columns[0].name, "body");
+    assert_eq!(notes.data.rows, vec![vec![text("first")]]);
+}
+
+#[test]
+fn test_changes_are_validated() {
+    let (db_path, _temp_dir) = setup_test_db();
+    let validate = |edit: RowEdit| preview_changes(&db_path, &[edit]).unwrap_err();
+
+    let err = validate(RowEdit::Delete { table: "users".to_string(), key: map(&[("name", text("ada"))]) });
+    assert_eq!(err.field(), Some("key"));
+    assert_eq!(err.to_string(), "Rows of users are identified by id");
+
+    let err = validate(RowEdit::Insert { table: "users".to_string(), values: map(&[("age", CellValue::Integer(3))]) });
+    assert_eq!(err.field(), Some("values"));
+
+    let err = validate(RowEdit::Delete { table: "missing".to_string(), key: map(&[("rowid", CellValue::Integer(1))]) });
+    assert_eq!(err.code(), ErrorCode::NotFound);
+
+    let err = preview_changes(&db_path, &[]).unwrap_err();
+    assert_eq!(err.field(), Some("changes"));
+}
+
+#[test]
+fn test_write_modes_and_app_databases() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let data_dir = temp_dir.path().join("data");
+    fs::create_dir_all(data_dir.join("workspaces/default")).expect("Failed to create data dir");
+    let tasks_db = data_dir.join("workspaces/default/tasks.db");
+    let other_db = temp_dir.path().join("dev.db");
+    fs::write(&tasks_db, b"").unwrap();
+    fs::write(&other_db, b"").unwrap();
+
+    assert!(is_app_database(&tasks_db, &data_dir));
+    assert!(!is_app_database(&other_db, &data_dir));
+    assert!(!is_app_database(Path::new("/nonexistent/tasks.db"), &data_dir));
+
+    let modes = WriteModes::new();
+    assert!(!modes.is_enabled(&other_db).unwrap());
+    modes.set(&other_db, true).expect("Failed to enable write mode");
+    assert!(modes.is_enabled(&other_db).unwrap());
+    assert!(!modes.is_enabled(&tasks_db).unwrap());
+    modes.set(&other_db, false).expect("Failed to disable write mode");
+    assert!(!modes.is_enabled(&other_db).unwrap());
+}
@@ -0,0 +1,143 @@
+use daily_tasks_management_lib::database_browser::QueryParams;
+use daily_tasks_management_lib::database_export::{self, ExportFormat, ExportSource, EXPORT_TIMEOUT};
+use daily_tasks_management_lib::database_value::CellValue;
+use daily_tasks_management_lib::error::ErrorCode;
+use rusqlite::Connection;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_test_db() -> (String, TempDir) {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("export.db");
+    let conn = Connection::open(&db_path).expect("Failed to create test database");
+    conn.execute_batch(
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, price REAL, data BLOB);
+         INSERT INTO items VALUES (1, 'plain', 1.5, NULL);
+         INSERT INTO items VALUES (2, 'has \"quotes\", commas
+and | pipes', NULL, x'00ff');
+         INSERT INTO items VALUES (9007199254740993, 'it''s', -2.0, x'');",
+    ).expect("Failed to seed test database");
+    (db_path.to_str().unwrap().to_string(), temp_dir)
+}
+
+fn export(db_path: &str, source: &ExportSource, format: ExportFormat) -> String {
+    let mut out = Vec::new();
+    database_export::export_to_writer(db_path, source, format, &mut out, EXPORT_TIMEOUT)
+        .expect("Failed to export");
+    String::from_utf8(out).expect("Export is not UTF-8")
+}
+
+fn items() -> ExportSource {
+    ExportSource::Table { name: "items".to_string() }
+}
+
+#[test]
+fn test_csv_export_quotes_per_rfc_4180() {
+    let (db_path, _temp_dir) = setup_test_db();
+
+    let csv = export(&db_path, &items(), ExportFormat::Csv);
+    assert_eq!(
+        csv,
+        "id,name,price,data\r\n\
+         1,plain,1.5,\r\n\
+         2,\"has \"\"quotes\"\", commas\nand | pipes\",,00ff\r\n\
+         9007199254740993,it's,-2,\r\n"
+    );
+}
+
+#[test]
+fn test_json_and_ndjson_export() {
+    let (db_path, _temp_dir) = setup_test_db();
+
+    let json = export(&db_path, &items(), ExportFormat::Json);
+    let parsed: serde_json::Value = serde_json::from_str(&json).expect("Export is not valid JSON");
+    assert_eq!(parsed.as_array().unwrap().len(), 3);
+    assert_eq!(parsed[1]["data"], "AP8=");
+    assert!(parsed[1]["price"].is_null());
+    // Large integers are written with every digit
+    assert!(json.contains("\"id\":9007199254740993"));
+
+    let ndjson = export(&db_path, &items(), ExportFormat::Ndjson);
+    let lines: Vec<serde_json::Value> = ndjson
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("Line is not valid JSON"))
+        .collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[2]["name"], "it's");
+
+    let empty = ExportSource::Query { sql: "SELECT * FROM items WHERE id < 0".to_string(), params: QueryParams::default() };
+    assert_eq!(export(&db_path, &empty, ExportFormat::Json), "[]\n");
+}
+
+#[test]
+fn test_sql_export_round_trips() {
+    let (db_path, temp_dir) = setup_test_db();
+
+    let sql = export(&db_path, &items(), ExportFormat::Sql);
+    assert!(sql.starts_with("INSERT INTO \"items\" (\"id\", \"name\", \"price\", \"data\") VALUES (1, 'plain', 1.5, NULL);"));
+
+    // Replaying the statements into an empty copy reproduces the table
+    let copy = Connection::open(temp_dir.path().join("copy.db")).expect("Failed to open copy");
+    copy.execute_batch("CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT, price REAL, data BLOB);")
+        .expect("Failed to create table");
+    copy.execute_batch(&sql).expect("Failed to replay export");
+
+    let original = export(&db_path, &items(), ExportFormat::Ndjson);
+    let replayed = export(temp_dir.path().join("copy.db").to_str().unwrap(), &items(), ExportFormat::Ndjson);
+    assert_eq!(original, replayed);
+}
+
+#[test]
+fn test_markdown_export_of_query() {
+    let (db_path, _temp_dir) = setup_test_db();
+
+    let source = ExportSource::Query {
+        sql: "SELECT name, data FROM items WHERE id < :max ORDER BY id".to_string(),
+        params: QueryParams {
+            named: [("max".to_string(), CellValue::Integer(3))].into_iter().collect(),
+            ..QueryParams::default()
+        },
+    };
+    let markdown = export(&db_path, &source, ExportFormat::Markdown);
+    assert_eq!(
+        markdown,
+        "| name | data |\n\
+         | --- | --- |\n\
+         | plain | NULL |\n\
+         | has \"quotes\", commas<br>and \\| pipes | <blob 2 bytes> |\n"
+    );
+}
+
+#[test]
+fn test_export_to_file() {
+    let (db_path, temp_dir) = setup_test_db();
+    let output = temp_dir.path().join("items.csv");
+
+    let summary = database_export::export_to_file(
+        &db_path,
+        &items(),
+        ExportFormat::Csv,
+        output.to_str().unwrap(),
+        EXPORT_TIMEOUT,
+    ).expect("Failed to export to file");
+    assert_eq!(summary.row_count, 3);
+    assert_eq!(summary.size_bytes, fs::metadata(&output).unwrap().len());
+    assert!(!temp_dir.path().join("items.csv.partial").exists());
+
+    // Writes are rejected by the read-only connection and leave no file behind
+    let failed = temp_dir.path().join("failed.csv");
+    let err = database_export::export_to_file(
+        &db_path,
+        &ExportSource::Query { sql: "DELETE FROM items".to_string(), params: QueryParams::default() },
+        ExportFormat::Csv,
+        failed.to_str().unwrap(),
+        EXPORT_TIMEOUT,
+    ).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Validation);
+    assert!(!failed.exists());
+    assert!(!temp_dir.path().join("failed.csv.partial").exists());
+
+    let err = database_export::export_to_file(&db_path, &items(), ExportFormat::Csv, "items.csv", EXPORT_TIMEOUT)
+        .unwrap_err();
+    assert_eq!(err.field(), Some("outputPath"));
+}
@@ -0,0 +1,140 @@
+use daily_tasks_management_lib::database_browser::QueryParams;
+use daily_tasks_management_lib::database_query::{QueryOptions, QueryPage, QuerySessions, QueryStatus};
+use daily_tasks_management_lib::database_value::CellValue;
+use daily_tasks_management_lib::error::{AppResult, ErrorCode};
+use rusqlite::Connection;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn setup_test_db(rows: u32) -> (String, TempDir) {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("logs.db");
+    let conn = Connection::open(&db_path).expect("Failed to create test database");
+    conn.execute_batch(&format!(
+        "CREATE TABLE logs (id INTEGER PRIMARY KEY, line TEXT);
+         WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < {})
+         INSERT INTO logs SELECT x, 'line ' || x FROM n;",
+        rows
+    )).expect("Failed to seed test database");
+    (db_path.to_str().unwrap().to_string(), temp_dir)
+}
+
+/// Starts a query and reads its first page.
+fn start(sessions: &QuerySessions, db_path: &str, sql: &str, options: &QueryOptions) -> AppResult<QueryPage> {
+    let started = sessions.start(db_path, sql, &QueryParams::default(), options)?;
+    sessions.fetch(started.handle, None)
+}
+
+fn options(page_size: usize, max_rows: Option<u64>, timeout_ms: Option<u64>) -> QueryOptions {
+    QueryOptions {
+        page_size: Some(page_size),
+        max_rows,
+        timeout_ms,
+    }
+}
+
+#[test]
+fn test_pages_through_large_result() {
+    let (db_path, _temp_dir) = setup_test_db(1000);
+    let sessions = QuerySessions::new();
+
+    let first = start(&sessions, &db_path, "SELECT id FROM logs ORDER BY id", &options(300, None, None))
+        .expect("Failed to start query");
+    assert_eq!(first.columns[0].name, "id");
+    assert_eq!(first.rows.len(), 300);
+    assert_eq!(first.progress.status, QueryStatus::Running);
+
+    let mut offsets = vec![first.offset];
+    let mut last = first;
+    while last.progress.status == QueryStatus::Running {
+        last = sessions.fetch(last.handle, None).expect("Failed to fetch page");
+        offsets.push(last.offset);
+    }
+    assert_eq!(offsets, vec![0, 300, 600, 900]);
+    assert_eq!(last.rows.len(), 100);
+    assert_eq!(last.rows[99][0], CellValue::Integer(1000));
+    assert_eq!(last.progress.status, QueryStatus::Done);
+    assert_eq!(last.progress.rows_read, 1000);
+
+    // Finished queries release their handle
+    let err = sessions.fetch(last.handle, None).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NotFound);
+    assert_eq!(sessions.open_count(), 0);
+}
+
+#[test]
+fn test_row_cap_and_validation() {
+    let (db_path, _temp_dir) = setup_test_db(100);
+    let sessions = QuerySessions::new();
+
+    let page = start(&sessions, &db_path, "SELECT * FROM logs", &options(40, Some(30), None))
+        .expect("Failed to start query");
+    assert_eq!(page.rows.len(), 30);
+    assert_eq!(page.progress.status, QueryStatus::Truncated);
+
+    // A cap that matches the result exactly is not a truncation
+    let page = start(&sessions, &db_path, "SELECT * FROM logs", &options(500, Some(100), None))
+        .expect("Failed to start query");
+    assert_eq!(page.progress.status, QueryStatus::Done);
+
+    let err = sessions.start(&db_path, "SELECT * FROM logs", &QueryParams::default(), &options(0, None, None))
+        .unwrap_err();
+    assert_eq!(err.field(), Some("pageSize"));
+    let err = sessions.start(&db_path, "DELETE FROM logs", &QueryParams::default(), &QueryOptions::default())
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Validation);
+    assert_eq!(sessions.open_count(), 0);
+}
+
+#[test]
+fn test_time_limit() {
+    let (db_path, _temp_dir) = setup_test_db(1);
+    let sessions = QuerySessions::new();
+
+    let started = Instant::now();
+    let err = start(
+        &sessions,
+        &db_path,
+        "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT COUNT(*) FROM n",
+        &options(10, None, Some(200)),
+    ).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Timeout);
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(sessions.open_count(), 0);
+}
+
+#[test]
+fn test_cancel_interrupts_running_fetch() {
+    let (db_path, _temp_dir) = setup_test_db(1);
+    let sessions = Arc::new(QuerySessions::new());
+
+    // The first row comes back at once, the second would take hours
+    let first = start(
+        &sessions,
+        &db_path,
+        "SELECT x FROM (WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) SELECT x FROM n)
+         WHERE x = 1 OR x > 1000000000000",
+        &options(1, None, Some(60_000)),
+    ).expect("Failed to start query");
+    assert_eq!(first.rows, vec![vec![CellValue::Integer(1)]]);
+
+    let fetching = Arc::clone(&sessions);
+    let handle = first.handle;
+    let fetch = thread::spawn(move || fetching.fetch(handle, None));
+
+    thread::sleep(Duration::from_millis(200));
+    let progress = sessions.progress(handle).expect("Failed to get progress");
+    assert_eq!(progress.status, QueryStatus::Running);
+    assert!(progress.steps > 0);
+
+    let cancelled = sessions.cancel(handle).expect("Failed to cancel query");
+    assert_eq!(cancelled.status, QueryStatus::Cancelled);
+
+    let last = fetch.join().unwrap().expect("Cancelled fetch failed");
+    assert!(last.rows.is_empty());
+    assert_eq!(last.progress.status, QueryStatus::Cancelled);
+    assert!(last.progress.execution_ms < 10_000);
+    assert_eq!(sessions.cancel(handle).unwrap_err().code(), ErrorCode::NotFound);
+}
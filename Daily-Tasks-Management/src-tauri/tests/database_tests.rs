@@ -1,5 +1,6 @@
 use daily_tasks_management_lib::database::Database;
-use daily_tasks_management_lib::model::TaskFormData;
+use daily_tasks_management_lib::model::{ProjectDeleteMode, TaskFormData};
+use daily_tasks_management_lib::repository::{GitRepoRepository, ProjectRepository, ServiceRepository, TaskRepository};
 use std::fs;
 use std::path::Path;
 use tempfile::TempDir;
@@ -134,6 +135,7 @@ fn test_create_and_get_service() {
         Some("/tmp/service_project".to_string()),
         Some(project.id),
         false,
+        false,
     ).expect("Failed to create service");
     
     assert_eq!(service.name, "Test Service");
@@ -160,6 +162,7 @@ fn test_get_all_services() {
         None,
         None,
         false,
+        false,
     ).expect("Failed to create service 1");
     
     let service2 = db.create_service(
@@ -168,6 +171,7 @@ fn test_get_all_services() {
         None,
         None,
         true,
+        false,
     ).expect("Failed to create service 2");
     
     let services = db.get_all_services().expect("Failed to get services");
@@ -185,6 +189,7 @@ fn test_update_service() {
         None,
         None,
         false,
+        false,
     ).expect("Failed to create service");
     
     service.name = "Updated Service".to_string();
@@ -213,6 +218,7 @@ fn test_delete_service() {
         None,
         None,
         false,
+        false,
     ).expect("Failed to create service");
     
     let service_id = service.id;
@@ -290,3 +296,132 @@ fn test_git_repo_operations() {
     assert_eq!(git_repo.repo_path, "/tmp/git_project");
     // TempDir will clean up automatically
 }
+
+fn create_project_with_children(db: &Database) -> (u32, u32, u32) {
+    let project = db.create_project(
+        "Parent".to_string(),
+        "/tmp/parent".to_string(),
+        None,
+        None,
+    ).expect("Failed to create project");
+
+    let service = db.create_service(
+        "Child Service".to_string(),
+        "npm start".to_string(),
+        None,
+        Some(project.id),
+        false,
+        false,
+    ).expect("Failed to create service");
+
+    let form_data = TaskFormData {
+        title: "Child Task".to_string(),
+        priority: "3".to_string(),
+        deadline: "2024-12-31".to_string(),
+        estimated_hours: "1".to_string(),
+        estimated_minutes: "0".to_string(),
+        start_date: "".to_string(),
+    };
+    let mut task = db.create_task(form_data).expect("Failed to create task");
+    task.project_id = Some(project.id);
+    db.update_task(&task).expect("Failed to update task");
+
+    db.create_git_repo(project.id, "/tmp/parent".to_string())
+        .expect("Failed to create git repo");
+
+    (project.id, service.id, task.id)
+}
+
+#[test]
+fn test_project_deletion_impact() {
+    let (db, _temp_dir) = setup_test_db();
+    let (project_id, service_id, task_id) = create_project_with_children(&db);
+
+    let impact = db.get_project_deletion_impact(project_id)
+        .expect("Failed to get deletion impact");
+
+    assert_eq!(impact.service_ids, vec![service_id]);
+    assert_eq!(impact.task_ids, vec![task_id]);
+    assert_eq!(impact.git_repo_ids.len(), 1);
+}
+
+#[test]
+fn test_delete_project_detaches_children() {
+    let (db, _temp_dir) = setup_test_db();
+    let (project_id, service_id, task_id) = create_project_with_children(&db);
+
+    db.delete_project_with_mode(project_id, ProjectDeleteMode::Detach)
+        .expect("Failed to delete project");
+
+    let service = db.get_service_by_id(service_id).unwrap().expect("Service was deleted");
+    assert_eq!(service.project_id, None);
+
+    let task = db.get_task_by_id(task_id).unwrap().expect("Task was deleted");
+    assert_eq!(task.project_id, None);
+
+    assert!(db.get_git_repo_by_project_id(project_id).unwrap().is_none());
+}
+
+#[test]
+fn test_delete_project_cascades_to_children() {
+    let (db, _temp_dir) = setup_test_db();
+    let (project_id, service_id, task_id) = create_project_with_children(&db);
+
+    let impact = db.delete_project_with_mode(project_id, ProjectDeleteMode::Cascade)
+        .expect("Failed to delete project");
+    assert_eq!(impact.service_ids, vec![service_id]);
+
+    assert!(db.get_service_by_id(service_id).unwrap().is_none());
+    assert!(db.get_task_by_id(task_id).unwrap().is_none());
+    assert!(db.get_git_repo_by_project_id(project_id).unwrap().is_none());
+}
+
+#[test]
+fn test_foreign_keys_enforced() {
+    let (db, _temp_dir) = setup_test_db();
+
+    let result = db.create_service(
+        "Orphan".to_string(),
+        "npm start".to_string(),
+        None,
+        Some(999),
+        false,
+        false,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_legacy_schema_migrated() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let db_path = temp_dir.path().join("legacy.db");
+
+    // Schema as created before ON DELETE rules existed, with an orphaned service
+    {
+        let conn = rusqlite::Connection::open(&db_path).expect("Failed to open legacy db");
+        conn.execute_batch(
+            "PRAGMA foreign_keys = OFF;
+             CREATE TABLE projects (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL,
+                 path TEXT NOT NULL UNIQUE, project_type TEXT, description TEXT,
+                 last_accessed TEXT, created_at TEXT NOT NULL);
+             CREATE TABLE services (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL,
+                 command TEXT NOT NULL, working_dir TEXT, project_id INTEGER,
+                 auto_start BOOLEAN DEFAULT 0, created_at TEXT NOT NULL,
+                 FOREIGN KEY(project_id) REFERENCES projects(id));
+             INSERT INTO services (name, command, project_id, created_at)
+                 VALUES ('orphan', 'npm start', 42, '2024-01-01T00:00:00Z');"
+        ).expect("Failed to create legacy schema");
+    }
+
+    let db = Database::new(db_path.to_str().unwrap()).expect("Failed to migrate database");
+
+    let services = db.get_all_services().expect("Failed to get services");
+    assert_eq!(services.len(), 1);
+    assert_eq!(services[0].project_id, None);
+
+    let (project_id, service_id, _) = create_project_with_children(&db);
+    db.delete_project(project_id).expect("Failed to delete project");
+
+    let service = db.get_service_by_id(service_id).unwrap().expect("Service was deleted");
+    assert_eq!(service.project_id, None);
+}
@@ -0,0 +1,59 @@
+use daily_tasks_management_lib::database::Database;
+use daily_tasks_management_lib::error::{AppError, ErrorCode};
+use daily_tasks_management_lib::git;
+use daily_tasks_management_lib::projects::ProjectManager;
+use daily_tasks_management_lib::repository::ServiceRepository;
+use serde_json::json;
+use tempfile::TempDir;
+
+#[test]
+fn test_not_found_serialization() {
+    let err = AppError::not_found("Project", 42);
+    assert_eq!(err.code(), ErrorCode::NotFound);
+
+    let value = serde_json::to_value(&err).expect("Failed to serialize error");
+    assert_eq!(value["code"], "not_found");
+    assert_eq!(value["message"], "Project 42 not found");
+    assert_eq!(value["details"], json!({ "entity": "Project", "id": "42" }));
+    assert_eq!(value["sourceChain"], json!([]));
+}
+
+#[test]
+fn test_validation_carries_field_and_path() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let missing = temp_dir.path().join("missing");
+
+    let err = ProjectManager::validate_project_path(missing.to_str().unwrap()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Validation);
+    assert_eq!(err.field(), Some("path"));
+    assert_eq!(err.path(), Some(missing.as_path()));
+
+    let value = serde_json::to_value(&err).expect("Failed to serialize error");
+    assert_eq!(value["details"]["field"], "path");
+    assert_eq!(value["details"]["path"], missing.to_str().unwrap());
+}
+
+#[test]
+fn test_git_error_keeps_source_chain() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+
+    let err = git::get_git_status(temp_dir.path().to_str().unwrap()).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Git);
+    assert_eq!(err.path(), Some(temp_dir.path()));
+    assert_eq!(err.source_chain().len(), 1);
+
+    let value = serde_json::to_value(&err).expect("Failed to serialize error");
+    assert_eq!(value["code"], "git");
+    assert!(!value["sourceChain"][0].as_str().unwrap().is_empty());
+}
+
+#[test]
+fn test_constraint_violation_is_conflict() {
+    let db = Database::new(":memory:").expect("Failed to create database");
+
+    // Services must reference an existing project
+    let err = db.create_service("api".to_string(), "cargo run".to_string(), None, Some(999), false, false)
+        .unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Conflict);
+    assert!(!err.source_chain().is_empty());
+}
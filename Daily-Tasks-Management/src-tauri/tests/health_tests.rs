@@ -0,0 +1,177 @@
+use daily_tasks_management_lib::health::{self, ProbeContext};
+use daily_tasks_management_lib::model::{
+    HealthChecks, Probe, ProbeCheck, RestartMode, RestartPolicy, Service, ServiceState,
+};
+use daily_tasks_management_lib::services::{LaunchOptions, ServiceManager};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+
+fn probe(check: ProbeCheck) -> Probe {
+    Probe { check, interval_ms: 100, timeout_ms: 500, failure_threshold: 2 }
+}
+
+fn context() -> ProbeContext {
+    ProbeContext {
+        service_id: 1,
+        logs: Arc::new(Mutex::new(HashMap::new())),
+        first_log_id: 0,
+        working_dir: None,
+        env: Vec::new(),
+    }
+}
+
+fn service(command: &str) -> Service {
+    Service {
+        id: 3,
+        name: "Probed Service".to_string(),
+        command: command.to_string(),
+        working_dir: None,
+        project_id: None,
+        auto_start: false,
+        shell: true,
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+    }
+}
+
+#[test]
+fn test_probe_urls_must_be_local() {
+    assert_eq!(
+        health::parse_local_url("http://localhost:3000/health").unwrap(),
+        ("localhost".to_string(), 3000, "/health".to_string()),
+    );
+    assert_eq!(health::parse_local_url("http://[::1]:8080").unwrap(), ("::1".to_string(), 8080, "/".to_string()));
+    assert_eq!(health::parse_local_url("http://127.0.0.1/").unwrap().1, 80);
+
+    for url in ["https://localhost/", "http://example.com/", "http://localhost:99999/"] {
+        assert_eq!(health::parse_local_url(url).unwrap_err().field(), Some("url"), "{}", url);
+    }
+}
+
+#[test]
+fn test_health_checks_are_validated() {
+    let log = probe(ProbeCheck::Log { pattern: "listening on".to_string() });
+    let valid = HealthChecks { readiness: Some(log.clone()), liveness: Some(probe(ProbeCheck::Tcp { port: 5432 })) };
+    health::validate_health_checks(&valid).expect("Valid checks were refused");
+
+    // Log lines only prove readiness
+    let err = health::validate_health_checks(&HealthChecks { readiness: None, liveness: Some(log) }).unwrap_err();
+    assert_eq!(err.field(), Some("kind"));
+
+    let cases = [
+        (ProbeCheck::Log { pattern: "(unclosed".to_string() }, "pattern"),
+        (ProbeCheck::Tcp { port: 0 }, "port"),
+        (ProbeCheck::Http { url: "http://localhost/".to_string(), expected_status: 42 }, "expectedStatus"),
+        (ProbeCheck::Command { command: "  ".to_string() }, "command"),
+    ];
+    for (check, field) in cases {
+        let checks = HealthChecks { readiness: Some(probe(check)), liveness: None };
+        assert_eq!(health::validate_health_checks(&checks).unwrap_err().field(), Some(field));
+    }
+
+    let too_fast = Probe { interval_ms: 10, ..probe(ProbeCheck::Tcp { port: 80 }) };
+    let err = health::validate_health_checks(&HealthChecks { readiness: Some(too_fast), liveness: None }).unwrap_err();
+    assert_eq!(err.field(), Some("intervalMs"));
+}
+
+#[tokio::test]
+async fn test_tcp_and_http_probes() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("Failed to bind");
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await;
+            let _ = stream.write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n").await;
+        }
+    });
+
+    let context = context();
+    health::run_probe(&probe(ProbeCheck::Tcp { port }), &context).await.expect("Port should be open");
+
+    let url = format!("http://127.0.0.1:{}/health", port);
+    let http = probe(ProbeCheck::Http { url: url.clone(), expected_status: 204 });
+    assert_eq!(health::run_probe(&http, &context).await.unwrap(), format!("{} answered 204", url));
+    let wrong = probe(ProbeCheck::Http { url, expected_status: 200 });
+    assert!(health::run_probe(&wrong, &context).await.unwrap_err().contains("expected 200"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_command_probes_need_exit_zero_in_time() {
+    let context = context();
+    health::run_probe(&probe(ProbeCheck::Command { command: "true".to_string() }), &context).await
+        .expect("true should pass");
+
+    let failing = probe(ProbeCheck::Command { command: "exit 4".to_string() });
+    assert_eq!(health::run_probe(&failing, &context).await.unwrap_err(), "exit 4 exited with 4");
+
+    let slow = Probe { timeout_ms: 50, ..probe(ProbeCheck::Command { command: "sleep 5".to_string() }) };
+    assert_eq!(health::run_probe(&slow, &context).await.unwrap_err(), "No answer within 50 ms");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_readiness_waits_for_a_log_line() {
+    let manager = ServiceManager::new();
+    let service = service("sleep 0.3; echo 'Server listening on 4000'; sleep 5");
+    let options = LaunchOptions {
+        health: HealthChecks {
+            readiness: Some(probe(ProbeCheck::Log { pattern: r"listening on \d+".to_string() })),
+            liveness: None,
+        },
+        ..LaunchOptions::default()
+    };
+    let mut events = manager.subscribe();
+
+    manager.start_service_with(service.id, &service, options).await.expect("Failed to start service");
+    let status = manager.service_status(service.id).await;
+    assert!(!status.ready);
+
+    manager.wait_until_ready(service.id, Duration::from_secs(5)).await.expect("Service never became ready");
+    let readiness = manager.service_status(service.id).await.readiness.expect("Readiness was not probed");
+    assert!(readiness.ok);
+    assert_eq!(readiness.message, "Logged: Server listening on 4000");
+
+    // Becoming ready was announced
+    let mut announced = false;
+    while let Ok(status) = events.try_recv() {
+        announced |= status.ready;
+    }
+    assert!(announced);
+    let _ = manager.stop_service(service.id).await;
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_failing_liveness_restarts_the_service() {
+    let manager = ServiceManager::new();
+    let service = service("sleep 30");
+    let options = LaunchOptions {
+        restart: RestartPolicy { mode: RestartMode::OnFailure, max_retries: 1, initial_backoff_ms: 20, max_backoff_ms: 20 },
+        health: HealthChecks {
+            readiness: None,
+            liveness: Some(probe(ProbeCheck::Command { command: "exit 1".to_string() })),
+        },
+        ..LaunchOptions::default()
+    };
+
+    manager.start_service_with(service.id, &service, options).await.expect("Failed to start service");
+
+    // The process never exits on its own; the probe ends it twice and then the retries run out
+    for _ in 0..100 {
+        if manager.service_status(service.id).await.state == ServiceState::GaveUp {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(manager.service_status(service.id).await.state, ServiceState::GaveUp);
+
+    let crashes = manager.get_service_crashes(service.id).await;
+    assert_eq!(crashes.len(), 2);
+    assert_eq!(crashes[0].reason.as_deref(), Some("Liveness probe failed: exit 1 exited with 1"));
+    assert_eq!(crashes[0].exit.signal, Some(15));
+}
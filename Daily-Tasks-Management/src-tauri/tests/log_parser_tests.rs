@@ -0,0 +1,97 @@
+use daily_tasks_management_lib::log_parser::{parse_line, strip_ansi, ParsedLine, Stream};
+use daily_tasks_management_lib::model::{LogFormat, LogParser};
+
+fn parse(line: &str) -> ParsedLine {
+    parse_line(&LogParser::default(), Stream::Stdout, line)
+}
+
+fn field<'a>(parsed: &'a ParsedLine, key: &str) -> Option<&'a str> {
+    parsed.fields.get(key).map(String::as_str)
+}
+
+#[test]
+fn test_json_loggers() {
+    // tracing-subscriber's JSON format nests the message in `fields`
+    let tracing = parse(r#"{"timestamp":"2024-01-01T00:00:00Z","level":"WARN","fields":{"message":"slow request","ms":1200},"target":"api::http"}"#);
+    assert_eq!(tracing.level, "warn");
+    assert_eq!(tracing.message, "slow request");
+    assert_eq!(field(&tracing, "ms"), Some("1200"));
+    assert_eq!(field(&tracing, "target"), Some("api::http"));
+    assert_eq!(field(&tracing, "timestamp"), None);
+
+    let pino = parse(r#"{"level":50,"time":1704067200000,"pid":42,"hostname":"dev","msg":"connection lost"}"#);
+    assert_eq!(pino.level, "error");
+    assert_eq!(pino.message, "connection lost");
+    assert_eq!(field(&pino, "pid"), Some("42"));
+    assert_eq!(field(&pino, "level"), None);
+
+    let bunyan = parse(r#"{"name":"worker","hostname":"dev","pid":7,"level":20,"msg":"polling","time":"2024-01-01T00:00:00.000Z","v":0}"#);
+    assert_eq!(bunyan.level, "debug");
+    assert_eq!(field(&bunyan, "name"), Some("worker"));
+    assert_eq!(field(&bunyan, "v"), None);
+}
+
+#[test]
+fn test_logfmt() {
+    let parsed = parse(r#"time=2024-01-01T00:00:00Z level=error msg="query failed: \"users\" missing" table=users"#);
+    assert_eq!(parsed.level, "error");
+    assert_eq!(parsed.message, r#"query failed: "users" missing"#);
+    assert_eq!(field(&parsed, "table"), Some("users"));
+    assert_eq!(field(&parsed, "time"), None);
+
+    // Without a level or message key the line is not taken for logfmt
+    let plain = parse("PATH=/usr/bin HOME=/root");
+    assert!(plain.fields.is_empty());
+    assert_eq!(plain.message, "PATH=/usr/bin HOME=/root");
+}
+
+#[test]
+fn test_level_prefixes() {
+    let levels = [
+        ("[WARN] Disk almost full", "warn"),
+        ("2024-01-01 12:00:00 [error] Worker died", "error"),
+        ("[2024-01-01T00:00:00Z INFO  app] Started", "info"),
+        ("2024-01-01T00:00:00.123Z DEBUG hyper::proto: flushed", "debug"),
+        ("ERROR: relation \"users\" does not exist", "error"),
+        ("warning: unused variable `x`", "warn"),
+        ("error[E0425]: cannot find value `y`", "error"),
+        ("Error: Cannot find module 'express'", "error"),
+    ];
+    for (line, level) in levels {
+        assert_eq!(parse_line(&LogParser::default(), Stream::Stderr, line).level, level, "{}", line);
+    }
+
+    // Level words later in a line are not prefixes
+    let message = parse_line(&LogParser::default(), Stream::Stderr, "Compiled with 0 errors and 2 WARN notes");
+    assert_eq!(message.level, "info");
+}
+
+#[test]
+fn test_undetected_lines_follow_the_stream() {
+    let parser = LogParser { stderr_level: "error".to_string(), ..LogParser::default() };
+    assert_eq!(parse_line(&parser, Stream::Stdout, "Compiling app v0.1.0").level, "info");
+    assert_eq!(parse_line(&parser, Stream::Stderr, "Compiling app v0.1.0").level, "error");
+
+    // Detection is limited to the chosen format
+    let plain = LogParser { format: LogFormat::Plain, ..parser.clone() };
+    assert_eq!(parse_line(&plain, Stream::Stderr, "[WARN] Disk almost full").level, "error");
+    let json = LogParser { format: LogFormat::Json, ..parser };
+    assert_eq!(parse_line(&json, Stream::Stdout, "level=warn msg=hi").level, "info");
+    assert_eq!(parse_line(&json, Stream::Stdout, r#"{"level":"warn","msg":"hi"}"#).level, "warn");
+}
+
+#[test]
+fn test_ansi_codes() {
+    let colored = "\x1b[1m\x1b[31merror\x1b[0m: build failed \x1b]8;;https://example.com\x07link\x1b]8;;\x07";
+    assert_eq!(strip_ansi(colored), "error: build failed link");
+
+    let stripped = parse(colored);
+    assert_eq!(stripped.level, "error");
+    assert_eq!(stripped.message, "error: build failed link");
+
+    // Kept for the viewer, but the level is still detected
+    let parser = LogParser { keep_ansi: true, ..LogParser::default() };
+    let kept = parse_line(&parser, Stream::Stdout, colored);
+    assert_eq!(kept.level, "error");
+    assert_eq!(kept.message, colored);
+}
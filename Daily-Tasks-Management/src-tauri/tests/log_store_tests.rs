@@ -0,0 +1,170 @@
+use chrono::{DateTime, Duration, Utc};
+use daily_tasks_management_lib::database::Database;
+use daily_tasks_management_lib::log_store::{self, LogStore, LogWriter};
+use daily_tasks_management_lib::model::{LogEntry, LogQuery, LogRetention};
+use daily_tasks_management_lib::repository::ServiceRepository;
+use std::sync::{Arc, Mutex};
+
+fn entry(service_id: u32, level: &str, message: &str, timestamp: DateTime<Utc>) -> LogEntry {
+    LogEntry {
+        id: 0,
+        service_id,
+        level: level.to_string(),
+        message: message.to_string(),
+        timestamp: timestamp.to_rfc3339(),
+        fields: Default::default(),
+    }
+}
+
+fn messages(store: &LogStore, query: &LogQuery) -> Vec<String> {
+    store.query(query).unwrap().entries.into_iter().map(|e| e.message).collect()
+}
+
+fn no_limits() -> LogRetention {
+    LogRetention { max_age_days: None, max_lines: None, max_bytes: None }
+}
+
+#[test]
+fn test_query_filters() {
+    let store = LogStore::new(":memory:").expect("Failed to open log store");
+    let now = Utc::now();
+    store.insert(&[
+        entry(1, "info", "Listening on port 3000", now - Duration::hours(2)),
+        entry(1, "error", "Connection refused", now - Duration::hours(1)),
+        entry(2, "info", "Compiled 100% of modules", now),
+        entry(1, "warn", "Slow query on port 5432", now),
+    ]).expect("Failed to insert logs");
+
+    let all = messages(&store, &LogQuery::default());
+    assert_eq!(all.len(), 4);
+    assert_eq!(all[0], "Slow query on port 5432", "Newest lines come first");
+
+    let service = LogQuery { service_id: Some(1), ..LogQuery::default() };
+    assert_eq!(messages(&store, &service).len(), 3);
+
+    let levels = LogQuery { levels: vec!["error".to_string(), "warn".to_string()], ..service.clone() };
+    assert_eq!(messages(&store, &levels), vec!["Slow query on port 5432", "Connection refused"]);
+
+    let window = LogQuery {
+        since: Some((now - Duration::minutes(90)).to_rfc3339()),
+        until: Some((now - Duration::minutes(30)).to_rfc3339()),
+        ..service.clone()
+    };
+    assert_eq!(messages(&store, &window), vec!["Connection refused"]);
+
+    let search = LogQuery { search: Some("PORT".to_string()), ..service.clone() };
+    assert_eq!(messages(&store, &search).len(), 2);
+    // Wildcards are matched literally
+    let percent = LogQuery { search: Some("100%".to_string()), ..LogQuery::default() };
+    assert_eq!(messages(&store, &percent), vec!["Compiled 100% of modules"]);
+
+    let err = store.query(&LogQuery { since: Some("yesterday".to_string()), ..LogQuery::default() }).unwrap_err();
+    assert_eq!(err.field(), Some("since"));
+}
+
+#[test]
+fn test_query_pages_with_a_cursor() {
+    let store = LogStore::new(":memory:").expect("Failed to open log store");
+    let now = Utc::now();
+    let entries: Vec<LogEntry> = (0..5).map(|i| entry(1, "info", &format!("line {}", i), now)).collect();
+    store.insert(&entries).expect("Failed to insert logs");
+
+    let first = store.query(&LogQuery { limit: Some(2), ..LogQuery::default() }).unwrap();
+    assert_eq!(first.entries.iter().map(|e| e.message.as_str()).collect::<Vec<_>>(), vec!["line 4", "line 3"]);
+
+    // Lines stored after the first page do not shift the next ones
+    store.insert(&[entry(1, "info", "line 5", now)]).unwrap();
+    let second = store.query(&LogQuery { limit: Some(2), cursor: first.next_cursor, ..LogQuery::default() }).unwrap();
+    assert_eq!(second.entries.iter().map(|e| e.message.as_str()).collect::<Vec<_>>(), vec!["line 2", "line 1"]);
+
+    let last = store.query(&LogQuery { limit: Some(2), cursor: second.next_cursor, ..LogQuery::default() }).unwrap();
+    assert_eq!(last.entries.len(), 1);
+    assert_eq!(last.next_cursor, None);
+}
+
+#[test]
+fn test_fields_are_stored() {
+    let store = LogStore::new(":memory:").expect("Failed to open log store");
+    let mut structured = entry(1, "info", "request done", Utc::now());
+    structured.fields.insert("status".to_string(), "200".to_string());
+    store.insert(&[structured.clone(), entry(1, "info", "plain", Utc::now())]).expect("Failed to insert logs");
+
+    let entries = store.query(&LogQuery::default()).unwrap().entries;
+    assert!(entries[0].fields.is_empty());
+    assert_eq!(entries[1].fields, structured.fields);
+}
+
+#[test]
+fn test_retention_limits() {
+    let store = LogStore::new(":memory:").expect("Failed to open log store");
+    let now = Utc::now();
+    store.insert(&[
+        entry(1, "info", "old", now - Duration::days(10)),
+        entry(1, "info", "aaaa", now),
+        entry(1, "info", "bbbb", now),
+        entry(1, "info", "cccc", now),
+        entry(2, "info", "old", now - Duration::days(10)),
+    ]).expect("Failed to insert logs");
+
+    let by_age = LogRetention { max_age_days: Some(7), ..no_limits() };
+    assert_eq!(store.apply_retention(1, &by_age, now).unwrap(), 1);
+
+    let by_size = LogRetention { max_bytes: Some(9), ..no_limits() };
+    assert_eq!(store.apply_retention(1, &by_size, now).unwrap(), 1);
+    let service = LogQuery { service_id: Some(1), ..LogQuery::default() };
+    assert_eq!(messages(&store, &service), vec!["cccc", "bbbb"]);
+
+    let by_lines = LogRetention { max_lines: Some(1), ..no_limits() };
+    assert_eq!(store.apply_retention(1, &by_lines, now).unwrap(), 1);
+    assert_eq!(messages(&store, &service), vec!["cccc"]);
+
+    // Other services keep their lines
+    assert_eq!(messages(&store, &LogQuery { service_id: Some(2), ..LogQuery::default() }), vec!["old"]);
+}
+
+#[test]
+fn test_retention_removes_deleted_services() {
+    let db = Database::new(":memory:").expect("Failed to create database");
+    let kept = db.create_service("api".to_string(), "cargo run".to_string(), None, None, false, false).unwrap();
+    let removed = db.create_service("web".to_string(), "npm run dev".to_string(), None, None, false, false).unwrap();
+    db.set_log_retention(kept.id, &LogRetention { max_lines: Some(1), ..no_limits() }).unwrap();
+
+    let store = LogStore::new(":memory:").expect("Failed to open log store");
+    let now = Utc::now();
+    store.insert(&[
+        entry(kept.id, "info", "first", now),
+        entry(kept.id, "info", "second", now),
+        entry(removed.id, "info", "gone", now),
+    ]).expect("Failed to insert logs");
+    db.delete_service(removed.id).unwrap();
+
+    assert_eq!(log_store::apply_retention_all(&store, &db, now).unwrap(), 2);
+    assert_eq!(store.service_ids().unwrap(), vec![kept.id]);
+    assert_eq!(messages(&store, &LogQuery::default()), vec!["second"]);
+}
+
+#[tokio::test]
+async fn test_writer_batches_lines() {
+    let (writer, receiver) = LogWriter::channel();
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let recorded = batches.clone();
+    let task = tokio::spawn(log_store::write_batches(receiver, move |entries| {
+        recorded.lock().unwrap().push(entries.len());
+    }));
+
+    for i in 0..3 {
+        writer.write(entry(1, "info", &format!("line {}", i), Utc::now()));
+    }
+    writer.flush().await;
+    assert_eq!(*batches.lock().unwrap(), vec![3]);
+
+    // Without a flush, a batch is written shortly after its first line
+    writer.write(entry(1, "info", "late", Utc::now()));
+    tokio::time::sleep(std::time::Duration::from_millis(600)).await;
+    assert_eq!(*batches.lock().unwrap(), vec![3, 1]);
+
+    writer.write(entry(1, "info", "last", Utc::now()));
+    drop(writer);
+    task.await.unwrap();
+    assert_eq!(*batches.lock().unwrap(), vec![3, 1, 1]);
+}
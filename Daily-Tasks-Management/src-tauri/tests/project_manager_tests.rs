@@ -16,7 +16,7 @@ fn test_validate_project_path_valid() {
 fn test_validate_project_path_invalid() {
     let result = ProjectManager::validate_project_path("/nonexistent/path/12345");
     assert!(result.is_err());
-    assert!(result.unwrap_err().contains("does not exist"));
+    assert!(result.unwrap_err().to_string().contains("does not exist"));
 }
 
 #[test]
@@ -0,0 +1,404 @@
+use daily_tasks_management_lib::database::Database;
+use daily_tasks_management_lib::handlers;
+use daily_tasks_management_lib::error::ErrorCode;
+use daily_tasks_management_lib::memory::MemoryStore;
+use daily_tasks_management_lib::model::{
+    ConnectionProfileForm, ConnectorKind, HealthChecks, LogFormat, LogParser, LogRetention, NewQueryHistory, Probe, ProbeCheck, ProjectDeleteMode,
+    RestartMode, RestartPolicy, TaskFormData,
+};
+use daily_tasks_management_lib::repository::{Store, MAX_QUERY_HISTORY};
+
+// Each scenario runs against both backends so they stay interchangeable
+
+fn task_form(title: &str) -> TaskFormData {
+    TaskFormData {
+        title: title.to_string(),
+        priority: "4".to_string(),
+        deadline: "2030-01-01".to_string(),
+        estimated_hours: "1".to_string(),
+        estimated_minutes: "30".to_string(),
+        start_date: String::new(),
+    }
+}
+
+fn for_each_store(scenario: fn(&dyn Store)) {
+    let db = Database::new(":memory:").expect("Failed to create database");
+    scenario(&db);
+
+    let memory = MemoryStore::new();
+    scenario(&memory);
+}
+
+#[test]
+fn test_task_round_trip() {
+    for_each_store(|store| {
+        let task = store.create_task(task_form("Write report")).expect("Failed to create task");
+        assert_eq!(task.priority, 4);
+        assert_eq!(task.estimated_time, 90);
+
+        store.mark_task_completed(task.id, true).expect("Failed to complete task");
+        let mut saved = store.get_task_by_id(task.id)
+            .expect("Failed to get task")
+            .expect("Task not found");
+        assert!(saved.completed);
+
+        saved.title = "Write final report".to_string();
+        store.update_task(&saved).expect("Failed to update task");
+        assert_eq!(store.get_all_tasks().unwrap()[0].title, "Write final report");
+
+        store.delete_task(task.id).expect("Failed to delete task");
+        assert!(store.get_task_by_id(task.id).unwrap().is_none());
+    });
+}
+
+#[test]
+fn test_project_path_is_unique() {
+    for_each_store(|store| {
+        store.create_project("One".to_string(), "/tmp/same".to_string(), None, None)
+            .expect("Failed to create project");
+        let err = store.create_project("Two".to_string(), "/tmp/same".to_string(), None, None)
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Conflict);
+    });
+}
+
+#[test]
+fn test_service_requires_existing_project() {
+    for_each_store(|store| {
+        let err = store.create_service("api".to_string(), "cargo run".to_string(), None, Some(42), false, false)
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Conflict);
+        assert!(store.create_git_repo(42, "/tmp/repo".to_string()).is_err());
+    });
+}
+
+#[test]
+fn test_delete_project_modes() {
+    for_each_store(|store| {
+        for mode in [ProjectDeleteMode::Detach, ProjectDeleteMode::Cascade] {
+            let path = format!("/tmp/{:?}", mode);
+            let project = store.create_project("Project".to_string(), path.clone(), None, None)
+                .expect("Failed to create project");
+            let service = store.create_service("api".to_string(), "npm start".to_string(), None, Some(project.id), false, false)
+                .expect("Failed to create service");
+            let repo_id = store.create_git_repo(project.id, path)
+                .expect("Failed to create git repo");
+
+            let impact = store.delete_project_with_mode(project.id, mode)
+                .expect("Failed to delete project");
+            assert_eq!(impact.service_ids, vec![service.id]);
+            assert_eq!(impact.git_repo_ids, vec![repo_id]);
+
+            assert!(store.get_project_by_id(project.id).unwrap().is_none());
+            assert!(store.get_git_repo_by_project_id(project.id).unwrap().is_none());
+
+            let remaining = store.get_service_by_id(service.id).unwrap();
+            match mode {
+                ProjectDeleteMode::Detach => assert_eq!(remaining.unwrap().project_id, None),
+                ProjectDeleteMode::Cascade => assert!(remaining.is_none()),
+            }
+        }
+    });
+}
+
+#[test]
+fn test_ids_are_not_reused() {
+    for_each_store(|store| {
+        let first = store.create_service("a".to_string(), "a".to_string(), None, None, false, false).unwrap();
+        store.delete_service(first.id).expect("Failed to delete service");
+        let second = store.create_service("b".to_string(), "b".to_string(), None, None, false, false).unwrap();
+        assert!(second.id > first.id);
+    });
+}
+
+#[test]
+fn test_duplicate_dependencies_are_saved_once() {
+    for_each_store(|store| {
+        let db = store.create_service("db".to_string(), "postgres".to_string(), None, None, false, false).unwrap();
+        let api = store.create_service("api".to_string(), "cargo run".to_string(), None, None, false, false).unwrap();
+        store.set_service_dependencies(api.id, &[db.id, db.id]).expect("Failed to save dependencies");
+        assert_eq!(store.get_service_dependencies(api.id).unwrap(), vec![db.id]);
+    });
+}
+
+fn shell_mode_scenario<S: Store>(store: &S) {
+    let err = handlers::register_service(store, "web".to_string(), "npm start | tee log".to_string(), None, None, false, false)
+        .unwrap_err();
+    assert_eq!(err.field(), Some("command"));
+
+    let mut service = handlers::register_service(store, "web".to_string(), "npm start | tee log".to_string(), None, None, false, true)
+        .expect("Failed to register shell service");
+    assert!(store.get_service_by_id(service.id).unwrap().unwrap().shell);
+
+    // Leaving shell mode requires a command that parses on its own
+    service.shell = false;
+    let err = handlers::update_service(store, &service).unwrap_err();
+    assert_eq!(err.field(), Some("command"));
+
+    service.command = "npm start".to_string();
+    handlers::update_service(store, &service).expect("Failed to update service");
+    let saved = store.get_service_by_id(service.id).unwrap().unwrap();
+    assert!(!saved.shell);
+    assert_eq!(saved.command, "npm start");
+}
+
+#[test]
+fn test_service_shell_mode_and_command_validation() {
+    // The handlers are generic over the store, so each backend is checked directly
+    shell_mode_scenario(&Database::new(":memory:").expect("Failed to create database"));
+    shell_mode_scenario(&MemoryStore::new());
+}
+
+fn restart_policy_scenario<S: Store>(store: &S) {
+    let service = store.create_service("api".to_string(), "cargo run".to_string(), None, None, false, false)
+        .expect("Failed to create service");
+    assert_eq!(handlers::get_restart_policy(store, service.id).unwrap(), RestartPolicy::default());
+
+    let policy = RestartPolicy { mode: RestartMode::OnFailure, max_retries: 3, initial_backoff_ms: 500, max_backoff_ms: 8_000 };
+    handlers::set_restart_policy(store, service.id, &policy).expect("Failed to save restart policy");
+    let updated = RestartPolicy { mode: RestartMode::Always, ..policy };
+    handlers::set_restart_policy(store, service.id, &updated).expect("Failed to update restart policy");
+    assert_eq!(handlers::get_restart_policy(store, service.id).unwrap(), updated);
+
+    let err = handlers::set_restart_policy(store, service.id, &RestartPolicy { max_backoff_ms: 100, ..updated.clone() })
+        .unwrap_err();
+    assert_eq!(err.field(), Some("maxBackoffMs"));
+    assert_eq!(handlers::get_restart_policy(store, 999).unwrap_err().code(), ErrorCode::NotFound);
+
+    store.delete_service(service.id).expect("Failed to delete service");
+    assert_eq!(store.get_restart_policy(service.id).unwrap(), RestartPolicy::default());
+    assert_eq!(store.set_restart_policy(service.id, &updated).unwrap_err().code(), ErrorCode::Conflict);
+}
+
+#[test]
+fn test_restart_policy_round_trip() {
+    restart_policy_scenario(&Database::new(":memory:").expect("Failed to create database"));
+    restart_policy_scenario(&MemoryStore::new());
+}
+
+fn health_checks_scenario<S: Store>(store: &S) {
+    let service = store.create_service("web".to_string(), "npm run dev".to_string(), None, None, false, false)
+        .expect("Failed to create service");
+    assert_eq!(handlers::get_health_checks(store, service.id).unwrap(), HealthChecks::default());
+
+    let checks = HealthChecks {
+        readiness: Some(Probe {
+            check: ProbeCheck::Http { url: "http://localhost:5173/".to_string(), expected_status: 200 },
+            interval_ms: 500,
+            timeout_ms: 2_000,
+            failure_threshold: 1,
+        }),
+        liveness: Some(Probe {
+            check: ProbeCheck::Tcp { port: 5173 },
+            interval_ms: 5_000,
+            timeout_ms: 1_000,
+            failure_threshold: 3,
+        }),
+    };
+    handlers::set_health_checks(store, service.id, &checks).expect("Failed to save health checks");
+    assert_eq!(handlers::get_health_checks(store, service.id).unwrap(), checks);
+
+    // Saving replaces both probes
+    let readiness_only = HealthChecks { liveness: None, ..checks.clone() };
+    handlers::set_health_checks(store, service.id, &readiness_only).expect("Failed to update health checks");
+    assert_eq!(handlers::get_health_checks(store, service.id).unwrap(), readiness_only);
+
+    let remote = Probe {
+        check: ProbeCheck::Http { url: "http://example.com/".to_string(), expected_status: 200 },
+        ..checks.readiness.clone().unwrap()
+    };
+    let err = handlers::set_health_checks(store, service.id, &HealthChecks { readiness: Some(remote), liveness: None })
+        .unwrap_err();
+    assert_eq!(err.field(), Some("url"));
+    assert_eq!(handlers::get_health_checks(store, 999).unwrap_err().code(), ErrorCode::NotFound);
+
+    store.delete_service(service.id).expect("Failed to delete service");
+    assert_eq!(store.get_health_checks(service.id).unwrap(), HealthChecks::default());
+}
+
+#[test]
+fn test_health_checks_round_trip() {
+    health_checks_scenario(&Database::new(":memory:").expect("Failed to create database"));
+    health_checks_scenario(&MemoryStore::new());
+}
+
+fn log_retention_scenario<S: Store>(store: &S) {
+    let service = store.create_service("worker".to_string(), "cargo run".to_string(), None, None, false, false)
+        .expect("Failed to create service");
+    assert_eq!(handlers::get_log_retention(store, service.id).unwrap(), LogRetention::default());
+
+    let retention = LogRetention { max_age_days: Some(30), max_lines: None, max_bytes: Some(1024 * 1024) };
+    handlers::set_log_retention(store, service.id, &retention).expect("Failed to save log retention");
+    let updated = LogRetention { max_lines: Some(500), ..retention };
+    handlers::set_log_retention(store, service.id, &updated).expect("Failed to update log retention");
+    assert_eq!(handlers::get_log_retention(store, service.id).unwrap(), updated);
+
+    let err = handlers::set_log_retention(store, service.id, &LogRetention { max_lines: Some(0), ..updated.clone() })
+        .unwrap_err();
+    assert_eq!(err.field(), Some("maxLines"));
+    assert_eq!(handlers::get_log_retention(store, 999).unwrap_err().code(), ErrorCode::NotFound);
+
+    store.delete_service(service.id).expect("Failed to delete service");
+    assert_eq!(store.get_log_retention(service.id).unwrap(), LogRetention::default());
+}
+
+#[test]
+fn test_log_retention_round_trip() {
+    log_retention_scenario(&Database::new(":memory:").expect("Failed to create database"));
+    log_retention_scenario(&MemoryStore::new());
+}
+
+fn log_parser_scenario<S: Store>(store: &S) {
+    let service = store.create_service("bundler".to_string(), "npm run build".to_string(), None, None, false, false)
+        .expect("Failed to create service");
+    assert_eq!(handlers::get_log_parser(store, service.id).unwrap(), LogParser::default());
+
+    let parser = LogParser { format: LogFormat::Logfmt, keep_ansi: true, stderr_level: "warn".to_string() };
+    handlers::set_log_parser(store, service.id, &parser).expect("Failed to save log parser");
+    let updated = LogParser { format: LogFormat::Plain, ..parser };
+    handlers::set_log_parser(store, service.id, &updated).expect("Failed to update log parser");
+    assert_eq!(handlers::get_log_parser(store, service.id).unwrap(), updated);
+
+    let err = handlers::set_log_parser(store, service.id, &LogParser { stderr_level: "loud".to_string(), ..updated.clone() })
+        .unwrap_err();
+    assert_eq!(err.field(), Some("stderrLevel"));
+    assert_eq!(handlers::get_log_parser(store, 999).unwrap_err().code(), ErrorCode::NotFound);
+
+    store.delete_service(service.id).expect("Failed to delete service");
+    assert_eq!(store.get_log_parser(service.id).unwrap(), LogParser::default());
+}
+
+#[test]
+fn test_log_parser_round_trip() {
+    log_parser_scenario(&Database::new(":memory:").expect("Failed to create database"));
+    log_parser_scenario(&MemoryStore::new());
+}
+
+fn history_entry(database_id: &str, query: &str) -> NewQueryHistory {
+    NewQueryHistory {
+        database_id: database_id.to_string(),
+        query: query.to_string(),
+        duration_ms: 5,
+        row_count: Some(1),
+        error: None,
+    }
+}
+
+#[test]
+fn test_query_history_search_and_retention() {
+    for_each_store(|store| {
+        store.add_query_history(history_entry("workspace", "SELECT * FROM tasks")).unwrap();
+        store.add_query_history(history_entry("1:app.db", "SELECT * FROM users")).unwrap();
+        store.add_query_history(history_entry("workspace", "SELECT 100% FROM projects")).unwrap();
+
+        let all = store.search_query_history(None, None, 10).expect("Failed to get history");
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].query, "SELECT 100% FROM projects");
+
+        let workspace = store.search_query_history(Some("select"), Some("workspace"), 10).unwrap();
+        assert_eq!(workspace.len(), 2);
+        // Wildcards in the search are matched literally
+        assert_eq!(store.search_query_history(Some("%"), None, 10).unwrap().len(), 1);
+        assert_eq!(store.search_query_history(None, None, 1).unwrap().len(), 1);
+
+        for i in 0..MAX_QUERY_HISTORY {
+            store.add_query_history(history_entry("workspace", &format!("SELECT {}", i))).unwrap();
+        }
+        let kept = store.search_query_history(None, None, MAX_QUERY_HISTORY + 10).unwrap();
+        assert_eq!(kept.len(), MAX_QUERY_HISTORY as usize);
+        assert!(store.search_query_history(Some("users"), None, 10).unwrap().is_empty());
+
+        store.clear_query_history().expect("Failed to clear history");
+        assert!(store.search_query_history(None, None, 10).unwrap().is_empty());
+    });
+}
+
+#[test]
+fn test_saved_query_round_trip() {
+    for_each_store(|store| {
+        let overdue = store.create_saved_query(
+            "Overdue tasks".to_string(),
+            "SELECT * FROM tasks WHERE deadline < :today".to_string(),
+            Some("workspace".to_string()),
+            None,
+        ).expect("Failed to save query");
+        store.create_saved_query(
+            "active users".to_string(),
+            "SELECT * FROM users".to_string(),
+            None,
+            Some("Signed in this week".to_string()),
+        ).expect("Failed to save query");
+
+        let names: Vec<String> = store.search_saved_queries(None).unwrap().into_iter().map(|q| q.name).collect();
+        assert_eq!(names, vec!["active users", "Overdue tasks"]);
+        assert_eq!(store.search_saved_queries(Some("WEEK")).unwrap()[0].name, "active users");
+        assert_eq!(store.search_saved_queries(Some("deadline")).unwrap()[0].id, overdue.id);
+
+        let err = store.create_saved_query("Overdue tasks".to_string(), "SELECT 1".to_string(), None, None)
+            .unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Conflict);
+
+        let mut renamed = overdue.clone();
+        renamed.name = "Late tasks".to_string();
+        store.update_saved_query(&renamed).expect("Failed to update saved query");
+        let saved = store.get_saved_query_by_id(overdue.id).unwrap().expect("Saved query not found");
+        assert_eq!(saved.name, "Late tasks");
+        assert_eq!(saved.created_at, overdue.created_at);
+
+        store.delete_saved_query(overdue.id).expect("Failed to delete saved query");
+        assert!(store.get_saved_query_by_id(overdue.id).unwrap().is_none());
+    });
+}
+
+fn profile_form(project_id: u32, name: &str) -> ConnectionProfileForm {
+    ConnectionProfileForm {
+        project_id,
+        name: name.to_string(),
+        kind: ConnectorKind::Postgres,
+        host: "localhost".to_string(),
+        port: 5432,
+        database: "app".to_string(),
+        username: "app".to_string(),
+        password: Some("ignored by the store".to_string()),
+    }
+}
+
+#[test]
+fn test_connection_profiles_follow_their_project() {
+    for_each_store(|store| {
+        let err = store.create_connection_profile(&profile_form(42, "Missing")).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Conflict);
+
+        let project = store.create_project("Api".to_string(), "/tmp/api".to_string(), None, None)
+            .expect("Failed to create project");
+        let other = store.create_project("Web".to_string(), "/tmp/web".to_string(), None, None)
+            .expect("Failed to create project");
+        let staging = store.create_connection_profile(&profile_form(project.id, "staging"))
+            .expect("Failed to create profile");
+        store.create_connection_profile(&profile_form(project.id, "Local"))
+            .expect("Failed to create profile");
+        store.create_connection_profile(&profile_form(other.id, "Web"))
+            .expect("Failed to create profile");
+
+        let names: Vec<String> = store.get_connection_profiles(Some(project.id)).unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, vec!["Local", "staging"]);
+        assert_eq!(store.get_connection_profiles(None).unwrap().len(), 3);
+
+        let mut moved = staging.clone();
+        moved.port = 6543;
+        store.update_connection_profile(&moved).expect("Failed to update profile");
+        let saved = store.get_connection_profile_by_id(staging.id).unwrap().expect("Profile not found");
+        assert_eq!(saved.port, 6543);
+        assert_eq!(saved.kind, ConnectorKind::Postgres);
+
+        // Profiles are removed with their project even when detaching
+        let impact = store.delete_project_with_mode(project.id, ProjectDeleteMode::Detach)
+            .expect("Failed to delete project");
+        assert_eq!(impact.connection_profile_ids.len(), 2);
+        assert!(store.get_connection_profile_by_id(staging.id).unwrap().is_none());
+        assert_eq!(store.get_connection_profiles(None).unwrap().len(), 1);
+    });
+}
@@ -0,0 +1,100 @@
+use daily_tasks_management_lib::database_connector::ConnectorTarget;
+use daily_tasks_management_lib::error::ErrorCode;
+use daily_tasks_management_lib::handlers;
+use daily_tasks_management_lib::memory::MemoryStore;
+use daily_tasks_management_lib::model::{ConnectionProfileForm, ConnectorKind};
+use daily_tasks_management_lib::repository::ProjectRepository;
+use daily_tasks_management_lib::secrets::{connection_password_key, FileSecretStore, MemorySecretStore, SecretStore};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_file_secret_store_round_trip() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let path = temp_dir.path().join("secrets.json");
+    let store = FileSecretStore::new(&path);
+
+    assert_eq!(store.get_secret("missing").expect("Failed to read secret"), None);
+    store.set_secret("default/connection-profile:1", "hunter2").expect("Failed to set secret");
+    store.set_secret("default/connection-profile:2", "other").expect("Failed to set secret");
+    store.delete_secret("default/connection-profile:2").expect("Failed to delete secret");
+
+    // A fresh instance reads what the first one wrote
+    let reopened = FileSecretStore::new(&path);
+    assert_eq!(reopened.get_secret("default/connection-profile:1").unwrap().as_deref(), Some("hunter2"));
+    assert_eq!(reopened.get_secret("default/connection-profile:2").unwrap(), None);
+    assert!(!temp_dir.path().join("secrets.json.partial").exists());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    fs::write(&path, "not json").unwrap();
+    assert!(store.get_secret("default/connection-profile:1").is_err());
+}
+
+fn profile_form(project_id: u32, password: Option<&str>) -> ConnectionProfileForm {
+    ConnectionProfileForm {
+        project_id,
+        name: " Local ".to_string(),
+        kind: ConnectorKind::Postgres,
+        host: "localhost".to_string(),
+        port: 5432,
+        database: "app".to_string(),
+        username: "app".to_string(),
+        password: password.map(str::to_string),
+    }
+}
+
+fn stored_password(target: ConnectorTarget) -> Option<String> {
+    match target {
+        ConnectorTarget::Profile { password, .. } => password,
+        ConnectorTarget::Sqlite(_) => panic!("Expected a profile target"),
+    }
+}
+
+#[test]
+fn test_connection_profile_passwords_stay_in_secret_store() {
+    let store = MemoryStore::new();
+    let secrets = MemorySecretStore::new();
+    let project = store.create_project("Api".to_string(), "/tmp/api".to_string(), None, None)
+        .expect("Failed to create project");
+
+    let mut invalid = profile_form(project.id, None);
+    invalid.port = 0;
+    let err = handlers::create_connection_profile(&store, &secrets, "default", invalid).unwrap_err();
+    assert_eq!(err.field(), Some("port"));
+    let err = handlers::create_connection_profile(&store, &secrets, "default", profile_form(99, None)).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NotFound);
+
+    let profile = handlers::create_connection_profile(&store, &secrets, "default", profile_form(project.id, Some("s3cret")))
+        .expect("Failed to create profile");
+    assert_eq!(profile.name, "Local");
+    let key = connection_password_key("default", profile.id);
+    assert_eq!(secrets.get_secret(&key).unwrap().as_deref(), Some("s3cret"));
+    // Profile ids restart in every workspace, so other workspaces see nothing
+    let other = handlers::connection_profile_target(&store, &secrets, "other", profile.id).unwrap();
+    assert_eq!(stored_password(other), None);
+
+    // No password keeps the stored one, an empty password clears it
+    handlers::update_connection_profile(&store, &secrets, "default", &profile, None)
+        .expect("Failed to update profile");
+    let target = handlers::connection_profile_target(&store, &secrets, "default", profile.id).unwrap();
+    assert_eq!(stored_password(target).as_deref(), Some("s3cret"));
+    handlers::update_connection_profile(&store, &secrets, "default", &profile, Some(String::new()))
+        .expect("Failed to update profile");
+    assert_eq!(secrets.get_secret(&key).unwrap(), None);
+
+    handlers::update_connection_profile(&store, &secrets, "default", &profile, Some("new".to_string()))
+        .expect("Failed to update profile");
+    handlers::delete_connection_profile(&store, &secrets, "default", profile.id)
+        .expect("Failed to delete profile");
+    assert_eq!(secrets.get_secret(&key).unwrap(), None);
+    let err = handlers::connection_profile_target(&store, &secrets, "default", profile.id)
+        .err()
+        .expect("Deleted profile still resolves");
+    assert_eq!(err.code(), ErrorCode::NotFound);
+}
@@ -0,0 +1,187 @@
+use daily_tasks_management_lib::database::Database;
+use daily_tasks_management_lib::error::ErrorCode;
+use daily_tasks_management_lib::handlers;
+use daily_tasks_management_lib::memory::MemoryStore;
+use daily_tasks_management_lib::model::{ServiceEnvVar, ServiceEnvironment};
+use daily_tasks_management_lib::repository::Store;
+use daily_tasks_management_lib::secrets::{service_env_key, MemorySecretStore, SecretStore};
+use daily_tasks_management_lib::service_env::{looks_secret, parse_dotenv, resolve_environment, EnvSource};
+use std::fs;
+use tempfile::TempDir;
+
+fn parse(contents: &str) -> Vec<(String, String)> {
+    let lookup = |name: &str| (name == "APP_HOME").then(|| "/opt/app".to_string());
+    parse_dotenv(contents, lookup).expect("Failed to parse env file")
+}
+
+fn pairs(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+    entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_parse_dotenv_syntax() {
+    let contents = r#"
+# Database settings
+export DB_HOST=localhost
+DB_PORT = 5432   # default port
+GREETING="hello\n\"world\""
+LITERAL='no $DB_HOST \n here'
+URL=postgres://${DB_HOST}:$DB_PORT/app
+COLOR=#fff
+EMPTY=
+MULTI="first
+second"
+LOGS=${APP_HOME}/logs
+FALLBACK=${MISSING:-default}
+BLANK_FALLBACK=${EMPTY:-used}-${EMPTY-unused}
+PRICE=$5 \$
+DB_PORT=6543
+"#;
+    assert_eq!(parse(contents), pairs(&[
+        ("DB_HOST", "localhost"),
+        ("DB_PORT", "6543"),
+        ("GREETING", "hello\n\"world\""),
+        ("LITERAL", "no $DB_HOST \\n here"),
+        ("URL", "postgres://localhost:5432/app"),
+        ("COLOR", "#fff"),
+        ("EMPTY", ""),
+        ("MULTI", "first\nsecond"),
+        ("LOGS", "/opt/app/logs"),
+        ("FALLBACK", "default"),
+        ("BLANK_FALLBACK", "used-"),
+        ("PRICE", "$5 \\$"),
+    ]));
+
+    // Windows line endings are accepted
+    assert_eq!(parse("A=1\r\nB='2'\r\n"), pairs(&[("A", "1"), ("B", "2")]));
+}
+
+#[test]
+fn test_parse_dotenv_errors_name_the_line() {
+    let error = |contents: &str| parse_dotenv(contents, |_| None).unwrap_err();
+
+    let err = error("A=1\nnot an assignment\n");
+    assert_eq!(err.code(), ErrorCode::Validation);
+    assert_eq!(err.to_string(), "line 2: expected NAME=value");
+    assert_eq!(error("1A=x").to_string(), "line 1: 1A is not a valid variable name");
+    assert_eq!(error("A=1\nB=\"open\n\n").to_string(), "line 2: unterminated double quote");
+    assert_eq!(error("A='x' y").to_string(), "line 1: unexpected text after the closing quote");
+    assert_eq!(error("A=${B").to_string(), "line 1: unterminated ${");
+}
+
+#[test]
+fn test_resolve_environment_layers_files_and_variables() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let base = temp_dir.path().join(".env");
+    let local = temp_dir.path().join(".env.local");
+    fs::write(&base, "PORT=3000\nAPI_TOKEN=abc\nHOST=localhost\n").unwrap();
+    fs::write(&local, "PORT=3001\nURL=http://$HOST:$PORT\n").unwrap();
+
+    let variables = vec![
+        ServiceEnvVar { name: "HOST".to_string(), value: Some("0.0.0.0".to_string()), secret: false },
+        ServiceEnvVar { name: "SESSION".to_string(), value: Some("s3cret".to_string()), secret: true },
+    ];
+    let resolved = resolve_environment(&[base.clone(), local.clone()], &variables)
+        .expect("Failed to resolve environment");
+
+    let get = |name: &str| resolved.iter().find(|v| v.name == name).expect("Variable missing").clone();
+    assert_eq!(get("PORT").value.as_deref(), Some("3001"));
+    assert_eq!(get("PORT").source, EnvSource::File { path: local.display().to_string() });
+    // Files expand against what was loaded before the overrides
+    assert_eq!(get("URL").value.as_deref(), Some("http://localhost:3001"));
+    assert_eq!(get("HOST").value.as_deref(), Some("0.0.0.0"));
+    assert_eq!(get("HOST").source, EnvSource::Service);
+
+    // Secrets keep their value until masked
+    assert!(get("API_TOKEN").secret);
+    assert_eq!(get("API_TOKEN").masked().value, None);
+    assert_eq!(get("SESSION").masked().value, None);
+    assert_eq!(get("PORT").masked().value.as_deref(), Some("3001"));
+
+    let err = resolve_environment(&[temp_dir.path().join("missing.env")], &[]).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::NotFound);
+
+    assert!(looks_secret("STRIPE_SECRET_KEY"));
+    assert!(looks_secret("db_password"));
+    assert!(!looks_secret("KEYBOARD_LAYOUT"));
+}
+
+fn secret_scenario<S: Store>(store: &S) {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join(".env"), "PORT=3000\nDB_PASSWORD=from-file\n").unwrap();
+    fs::write(temp_dir.path().join(".env.local"), "PORT=3001\n").unwrap();
+
+    let secrets = MemorySecretStore::new();
+    let working_dir = temp_dir.path().to_str().unwrap().to_string();
+    let service = handlers::register_service(store, "api".to_string(), "node server.js".to_string(), Some(working_dir), None, false, false)
+        .expect("Failed to register service");
+
+    assert_eq!(
+        handlers::find_service_env_files(store, service.id).expect("Failed to find env files").len(),
+        2,
+    );
+
+    let environment = ServiceEnvironment {
+        variables: vec![
+            ServiceEnvVar { name: "NODE_ENV".to_string(), value: Some("development".to_string()), secret: false },
+            ServiceEnvVar { name: "API_KEY".to_string(), value: Some("k-123".to_string()), secret: true },
+        ],
+        env_files: vec![".env".to_string(), " .env.local ".to_string()],
+    };
+    let saved = handlers::set_service_environment(store, &secrets, "default", service.id, &environment)
+        .expect("Failed to save environment");
+    assert_eq!(saved.variables[1].value, None);
+    assert_eq!(saved.env_files, vec![".env", ".env.local"]);
+
+    // The secret only lives in the secret store
+    let stored = handlers::get_service_environment(store, service.id).expect("Failed to get environment");
+    assert_eq!(stored, saved);
+    let key = service_env_key("default", service.id, "API_KEY");
+    assert_eq!(secrets.get_secret(&key).unwrap().as_deref(), Some("k-123"));
+
+    let launch = handlers::service_launch_environment(store, &secrets, "default", &service)
+        .expect("Failed to resolve environment");
+    let value = |name: &str| launch.iter().find(|v| v.name == name).and_then(|v| v.value.clone());
+    assert_eq!(value("API_KEY").as_deref(), Some("k-123"));
+    assert_eq!(value("PORT").as_deref(), Some("3001"));
+
+    let preview = handlers::preview_service_environment(store, &secrets, "default", service.id)
+        .expect("Failed to preview environment");
+    let masked = |name: &str| preview.iter().find(|v| v.name == name).expect("Variable missing").value.clone();
+    assert_eq!(masked("API_KEY"), None);
+    assert_eq!(masked("DB_PASSWORD"), None);
+    assert_eq!(masked("NODE_ENV").as_deref(), Some("development"));
+
+    // Saving again without the secret's value keeps it; dropping it deletes it
+    handlers::set_service_environment(store, &secrets, "default", service.id, &saved)
+        .expect("Failed to save environment");
+    assert_eq!(secrets.get_secret(&key).unwrap().as_deref(), Some("k-123"));
+    handlers::set_service_environment(store, &secrets, "default", service.id, &ServiceEnvironment::default())
+        .expect("Failed to clear environment");
+    assert_eq!(secrets.get_secret(&key).unwrap(), None);
+
+    let invalid = |variables: Vec<ServiceEnvVar>| {
+        let environment = ServiceEnvironment { variables, env_files: Vec::new() };
+        handlers::set_service_environment(store, &secrets, "default", service.id, &environment).unwrap_err()
+    };
+    let var = |name: &str, value: Option<&str>, secret: bool| ServiceEnvVar {
+        name: name.to_string(),
+        value: value.map(str::to_string),
+        secret,
+    };
+    assert_eq!(invalid(vec![var("BAD-NAME", Some("1"), false)]).field(), Some("variables"));
+    assert_eq!(invalid(vec![var("A", Some("1"), false), var("A", Some("2"), false)]).to_string(), "A is set twice");
+    assert_eq!(invalid(vec![var("TOKEN", None, true)]).to_string(), "TOKEN needs a value");
+
+    // Environments go with their service
+    handlers::unregister_service(store, service.id).expect("Failed to delete service");
+    assert!(handlers::get_service_environment(store, service.id).is_err());
+    let orphan = ServiceEnvironment { variables: Vec::new(), env_files: vec![".env".to_string()] };
+    assert_eq!(store.set_service_environment(service.id, &orphan).unwrap_err().code(), ErrorCode::Conflict);
+}
+
+#[test]
+fn test_service_environment_secrets() {
+    secret_scenario(&Database::new(":memory:").expect("Failed to create database"));
+    secret_scenario(&MemoryStore::new());
+}
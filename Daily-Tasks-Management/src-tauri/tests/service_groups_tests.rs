@@ -0,0 +1,155 @@
+use daily_tasks_management_lib::database::Database;
+use daily_tasks_management_lib::error::ErrorCode;
+use daily_tasks_management_lib::handlers;
+use daily_tasks_management_lib::memory::MemoryStore;
+use daily_tasks_management_lib::model::{RestartMode, RestartPolicy, Service, ServiceState};
+use daily_tasks_management_lib::repository::Store;
+use daily_tasks_management_lib::service_graph::{find_cycle, start_order, Dependencies};
+use daily_tasks_management_lib::service_groups::{start_in_order, stop_in_order};
+use daily_tasks_management_lib::services::{LaunchOptions, ServiceManager};
+use std::time::Duration;
+
+fn dependencies(edges: &[(u32, &[u32])]) -> Dependencies {
+    edges.iter().map(|(id, depends_on)| (*id, depends_on.to_vec())).collect()
+}
+
+#[test]
+fn test_start_order_puts_dependencies_first() {
+    // api needs db and queue, the worker needs the queue
+    let deps = dependencies(&[(4, &[2, 1]), (3, &[2]), (2, &[1])]);
+
+    assert_eq!(start_order(&[4], &deps).unwrap(), vec![1, 2, 4]);
+    assert_eq!(start_order(&[4, 3, 5], &deps).unwrap(), vec![1, 2, 3, 4, 5]);
+    assert_eq!(start_order(&[], &deps).unwrap(), Vec::<u32>::new());
+    assert_eq!(find_cycle(&deps), None);
+
+    let cyclic = dependencies(&[(1, &[2]), (2, &[3]), (3, &[1])]);
+    assert_eq!(find_cycle(&cyclic), Some(vec![1, 2, 3, 1]));
+    assert_eq!(start_order(&[1], &cyclic).unwrap_err().code(), ErrorCode::Validation);
+}
+
+fn groups_scenario<S: Store>(store: &S) {
+    let create = |name: &str| {
+        handlers::register_service(store, name.to_string(), "sleep 1".to_string(), None, None, false, false)
+            .expect("Failed to register service")
+    };
+    let db = create("db");
+    let queue = create("queue");
+    let api = create("api");
+    let web = create("web");
+
+    handlers::set_service_dependencies(store, queue.id, &[db.id]).expect("Failed to set dependencies");
+    handlers::set_service_dependencies(store, api.id, &[queue.id, db.id]).expect("Failed to set dependencies");
+    assert_eq!(handlers::get_service_dependencies(store, api.id).unwrap(), vec![db.id, queue.id]);
+
+    let err = handlers::set_service_dependencies(store, db.id, &[api.id]).unwrap_err();
+    assert_eq!(err.field(), Some("dependencies"));
+    assert!(err.to_string().contains("db → api"), "{}", err);
+    let err = handlers::set_service_dependencies(store, db.id, &[db.id]).unwrap_err();
+    assert_eq!(err.field(), Some("dependencies"));
+    assert_eq!(handlers::set_service_dependencies(store, db.id, &[999]).unwrap_err().field(), Some("dependencies"));
+
+    let group = handlers::create_service_group(store, "Full stack".to_string(), &[web.id, api.id])
+        .expect("Failed to create group");
+    assert_eq!(group.service_ids, vec![api.id, web.id]);
+    let err = handlers::create_service_group(store, "Full stack".to_string(), &[]).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::Conflict);
+    assert_eq!(handlers::create_service_group(store, " ".to_string(), &[]).unwrap_err().field(), Some("name"));
+
+    let names = |services: Vec<Service>| services.into_iter().map(|s| s.name).collect::<Vec<_>>();
+    assert_eq!(names(handlers::group_start_order(store, group.id).unwrap()), vec!["db", "queue", "api", "web"]);
+    // Dependencies outside the group are left running
+    assert_eq!(names(handlers::group_stop_order(store, group.id).unwrap()), vec!["web", "api"]);
+
+    let mut renamed = group.clone();
+    renamed.name = "Backend".to_string();
+    renamed.service_ids = vec![queue.id, api.id];
+    handlers::update_service_group(store, &renamed).expect("Failed to update group");
+    assert_eq!(handlers::get_service_groups(store).unwrap()[0].name, "Backend");
+
+    // Deleting a service drops it from groups and from what depends on it
+    store.delete_service(queue.id).expect("Failed to delete service");
+    assert_eq!(handlers::get_service_dependencies(store, api.id).unwrap(), vec![db.id]);
+    assert_eq!(handlers::require_service_group(store, group.id).unwrap().service_ids, vec![api.id]);
+
+    handlers::delete_service_group(store, group.id).expect("Failed to delete group");
+    assert!(handlers::get_service_groups(store).unwrap().is_empty());
+    assert_eq!(handlers::delete_service_group(store, group.id).unwrap_err().code(), ErrorCode::NotFound);
+}
+
+#[test]
+fn test_service_dependencies_and_groups() {
+    groups_scenario(&Database::new(":memory:").expect("Failed to create database"));
+    groups_scenario(&MemoryStore::new());
+}
+
+fn service(id: u32, name: &str, command: &str) -> Service {
+    Service {
+        id,
+        name: name.to_string(),
+        command: command.to_string(),
+        working_dir: None,
+        project_id: None,
+        auto_start: false,
+        shell: true,
+        created_at: "2030-01-01 00:00:00".to_string(),
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_group_starts_in_order_and_stops_at_the_first_failure() {
+    let manager = ServiceManager::new();
+    let order = vec![service(1, "db", "sleep 5"), service(2, "queue", "sleep 5"), service(3, "api", "sleep 5")];
+
+    // The queue is already up, so it is only waited for
+    manager.start_service_with(2, &order[1], LaunchOptions::default()).await.expect("Failed to start queue");
+    let report = start_in_order(&manager, order.clone(), |_| Ok(LaunchOptions::default()), Duration::from_secs(5)).await;
+    assert_eq!(report.started, vec![1, 3]);
+    assert_eq!(report.already_running, vec![2]);
+    assert!(report.failed.is_none());
+
+    let stopped = stop_in_order(&manager, order.into_iter().rev().collect()).await.expect("Failed to stop group");
+    assert_eq!(stopped, vec![3, 2, 1]);
+    assert!(!manager.is_service_running(1).await);
+
+    // A dependency that exits before it is ready keeps its dependents stopped
+    let order = vec![service(4, "db", "exit 2"), service(5, "api", "sleep 5")];
+    let report = start_in_order(&manager, order, |_| Ok(LaunchOptions::default()), Duration::from_secs(5)).await;
+    let failed = report.failed.expect("The broken dependency should fail");
+    assert_eq!(failed.service_id, 4);
+    assert!(failed.error.contains("exit code 2"), "{}", failed.error);
+    assert_eq!(report.skipped, vec![5]);
+    assert!(!manager.is_service_running(5).await);
+
+    // The exited dependency was not running, so stopping it reports nothing
+    let stopped = stop_in_order(&manager, vec![service(4, "db", "exit 2")]).await.expect("Failed to stop group");
+    assert!(stopped.is_empty());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_group_waits_for_a_restarting_service() {
+    let manager = ServiceManager::new();
+    let flaky = service(1, "db", "sleep 1");
+    let options = LaunchOptions {
+        restart: RestartPolicy { mode: RestartMode::Always, max_retries: 5, initial_backoff_ms: 300, max_backoff_ms: 300 },
+        ..LaunchOptions::default()
+    };
+    manager.start_service_with(flaky.id, &flaky, options).await.expect("Failed to start service");
+    for _ in 0..100 {
+        if manager.service_status(flaky.id).await.state == ServiceState::Restarting {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(manager.service_status(flaky.id).await.state, ServiceState::Restarting);
+
+    // Its supervisor brings it back, so the group only waits for it
+    let report = start_in_order(&manager, vec![flaky.clone()], |_| Ok(LaunchOptions::default()), Duration::from_secs(5)).await;
+    assert!(report.failed.is_none(), "{:?}", report.failed);
+    assert_eq!(report.already_running, vec![flaky.id]);
+
+    let stopped = stop_in_order(&manager, vec![flaky]).await.expect("Failed to stop group");
+    assert_eq!(stopped, vec![1]);
+}
@@ -0,0 +1,84 @@
+use daily_tasks_management_lib::model::ResourceSample;
+use daily_tasks_management_lib::service_metrics::{MetricsHistory, ResourceSampler, HISTORY_LEN};
+use std::process::Command;
+use std::time::Duration;
+
+fn sample_at(pid: u32) -> ResourceSample {
+    ResourceSample {
+        timestamp: "2024-01-01T00:00:00Z".to_string(),
+        pid,
+        processes: 1,
+        cpu_percent: 0.0,
+        rss_bytes: 1024,
+        threads: None,
+        open_ports: Vec::new(),
+    }
+}
+
+#[test]
+fn test_history_keeps_the_latest_samples() {
+    let history = MetricsHistory::new();
+    for pid in 0..(HISTORY_LEN as u32 + 5) {
+        history.record(vec![(1, sample_at(pid))]).unwrap();
+    }
+
+    let samples = history.samples(1, None).unwrap();
+    assert_eq!(samples.len(), HISTORY_LEN);
+    assert_eq!(samples[0].pid, 5);
+
+    // A limit keeps the newest ones, still oldest first
+    let latest: Vec<u32> = history.samples(1, Some(2)).unwrap().iter().map(|s| s.pid).collect();
+    assert_eq!(latest, vec![HISTORY_LEN as u32 + 3, HISTORY_LEN as u32 + 4]);
+
+    assert!(history.samples(2, None).unwrap().is_empty());
+    history.clear(1).unwrap();
+    assert!(history.samples(1, None).unwrap().is_empty());
+
+    history.record(vec![(1, sample_at(1)), (2, sample_at(2))]).unwrap();
+    history.clear_all().unwrap();
+    assert!(history.samples(1, None).unwrap().is_empty() && history.samples(2, None).unwrap().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sample_covers_the_process_tree() {
+    use nix::sys::signal::{killpg, Signal};
+    use nix::unistd::Pid;
+    use std::os::unix::process::CommandExt;
+
+    // The shell stays the parent of two sleeping children, all in a group
+    // of their own so they can be killed together
+    let mut child = Command::new("/bin/sh")
+        .arg("-c")
+        .arg("sleep 5 & sleep 5 & wait")
+        .process_group(0)
+        .spawn()
+        .expect("Failed to spawn shell");
+    std::thread::sleep(Duration::from_millis(200));
+
+    let mut sampler = ResourceSampler::new();
+    let samples = sampler.sample(&[(4, child.id()), (5, u32::MAX - 1)]);
+    let _ = killpg(Pid::from_raw(child.id() as i32), Signal::SIGKILL);
+    let _ = child.wait();
+
+    // Processes that are gone are left out
+    assert_eq!(samples.len(), 1);
+    let (service_id, sample) = &samples[0];
+    assert_eq!(*service_id, 4);
+    assert_eq!(sample.pid, child.id());
+    assert!(sample.processes >= 3, "Only {} processes in the tree", sample.processes);
+    assert!(sample.rss_bytes > 0);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_sample_lists_listening_ports() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind");
+    let port = listener.local_addr().unwrap().port();
+
+    let mut sampler = ResourceSampler::new();
+    let samples = sampler.sample(&[(1, std::process::id())]);
+    let sample = &samples[0].1;
+    assert!(sample.open_ports.contains(&port), "{} not in {:?}", port, sample.open_ports);
+    assert!(sample.threads.is_some_and(|threads| threads >= 1));
+}
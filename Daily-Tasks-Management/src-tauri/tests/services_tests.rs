@@ -1,5 +1,7 @@
-use daily_tasks_management_lib::services::ServiceManager;
-use daily_tasks_management_lib::model::{Service, LocalService};
+use daily_tasks_management_lib::services::{LaunchOptions, ServiceManager};
+use daily_tasks_management_lib::model::{
+    ExitInfo, LocalService, RestartMode, RestartPolicy, Service, ServiceState, ServiceStatus,
+};
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -92,7 +94,7 @@ async fn test_service_manager_stop_nonexistent() {
     let result = manager.stop_service(999).await;
     
     assert!(result.is_err());
-    assert!(result.unwrap_err().contains("not found"));
+    assert!(result.unwrap_err().to_string().contains("not found"));
 }
 
 #[tokio::test]
@@ -106,6 +108,7 @@ async fn test_service_manager_start_service_by_id() {
         working_dir: None,
         project_id: None,
         auto_start: false,
+        shell: false,
         created_at: "2024-01-01T00:00:00Z".to_string(),
     };
     
@@ -117,3 +120,283 @@ async fn test_service_manager_start_service_by_id() {
     // Clean up
     let _ = manager.stop_service(service.id).await;
 }
+
+fn shell_service(command: &str, shell: bool) -> Service {
+    Service {
+        id: 7,
+        name: "Shell Service".to_string(),
+        command: command.to_string(),
+        working_dir: None,
+        project_id: None,
+        auto_start: false,
+        shell,
+        created_at: "2024-01-01T00:00:00Z".to_string(),
+    }
+}
+
+async fn wait_for_logs(manager: &ServiceManager, service_id: u32, count: usize) -> Vec<String> {
+    for _ in 0..50 {
+        let logs = manager.get_service_logs(service_id, None).await;
+        if logs.len() >= count {
+            // Logs come newest first
+            return logs.into_iter().rev().map(|log| log.message).collect();
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+    panic!("Service {} did not log {} lines", service_id, count);
+}
+
+#[tokio::test]
+async fn test_service_arguments_keep_their_quoting() {
+    let manager = ServiceManager::new();
+    let service = shell_service(r#"GREETING='hello world' sh -c 'echo "$GREETING"; echo "$0"' "two words""#, false);
+
+    manager.start_service_by_id(service.id, &service).await
+        .expect("Failed to start service");
+
+    assert_eq!(wait_for_logs(&manager, service.id, 2).await, vec!["hello world", "two words"]);
+    let _ = manager.stop_service(service.id).await;
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_shell_mode_runs_through_shell() {
+    let manager = ServiceManager::new();
+
+    // Without shell mode the pipe is refused before anything runs
+    let err = manager.start_service_by_id(7, &shell_service("echo one | tr a-z A-Z", false)).await.unwrap_err();
+    assert!(err.to_string().contains("shell mode"));
+    assert!(!manager.is_service_running(7).await);
+
+    let service = shell_service("echo one | tr a-z A-Z", true);
+    manager.start_service_by_id(service.id, &service).await
+        .expect("Failed to start service");
+
+    assert_eq!(wait_for_logs(&manager, service.id, 1).await, vec!["ONE"]);
+    let _ = manager.stop_service(service.id).await;
+}
+
+#[tokio::test]
+async fn test_service_environment_is_applied() {
+    let manager = ServiceManager::new();
+    let service = shell_service(r#"GREETING=prefix sh -c 'echo "$GREETING $TARGET"'"#, false);
+    let env = vec![
+        ("GREETING".to_string(), "hello".to_string()),
+        ("TARGET".to_string(), "world".to_string()),
+    ];
+
+    manager.start_service_with_env(service.id, &service, env).await
+        .expect("Failed to start service");
+
+    // Assignments in front of the command win over the service environment
+    assert_eq!(wait_for_logs(&manager, service.id, 1).await, vec!["prefix world"]);
+    let _ = manager.stop_service(service.id).await;
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stderr_levels_are_detected() {
+    let manager = ServiceManager::new();
+    let service = shell_service(r#"printf 'Compiling app\nerror[E0308]: mismatched types\n\033[33mwarning\033[0m: unused\n' >&2"#, true);
+
+    manager.start_service_by_id(service.id, &service).await
+        .expect("Failed to start service");
+    wait_for_logs(&manager, service.id, 3).await;
+
+    // Progress on stderr is not an error, and colors are stripped
+    let logs: Vec<(String, String)> = manager.get_service_logs(service.id, None).await
+        .into_iter()
+        .rev()
+        .map(|log| (log.level, log.message))
+        .collect();
+    assert_eq!(logs, vec![
+        ("info".to_string(), "Compiling app".to_string()),
+        ("error".to_string(), "error[E0308]: mismatched types".to_string()),
+        ("warn".to_string(), "warning: unused".to_string()),
+    ]);
+    let _ = manager.stop_service(service.id).await;
+}
+
+fn restart_policy(mode: RestartMode, max_retries: u32) -> LaunchOptions {
+    LaunchOptions {
+        env: Vec::new(),
+        restart: RestartPolicy { mode, max_retries, initial_backoff_ms: 20, max_backoff_ms: 50 },
+        ..LaunchOptions::default()
+    }
+}
+
+async fn wait_for_state(manager: &ServiceManager, service_id: u32, state: ServiceState) -> ServiceStatus {
+    for _ in 0..100 {
+        let status = manager.service_status(service_id).await;
+        if status.state == state {
+            return status;
+        }
+        sleep(Duration::from_millis(20)).await;
+    }
+    panic!("Service {} never reached {:?}", service_id, state);
+}
+
+#[test]
+fn test_restart_backoff_doubles_up_to_the_limit() {
+    let policy = RestartPolicy { mode: RestartMode::Always, max_retries: 10, initial_backoff_ms: 100, max_backoff_ms: 1_000 };
+    let delays: Vec<u128> = (1..=6).map(|attempt| policy.backoff(attempt).as_millis()).collect();
+    assert_eq!(delays, vec![100, 200, 400, 800, 1_000, 1_000]);
+    assert_eq!(policy.backoff(200).as_millis(), 1_000);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_crashing_service_is_restarted_until_it_gives_up() {
+    let manager = ServiceManager::new();
+    let service = shell_service("echo booting; echo 'port in use' >&2; exit 3", true);
+
+    manager.start_service_with(service.id, &service, restart_policy(RestartMode::OnFailure, 2)).await
+        .expect("Failed to start service");
+
+    let status = wait_for_state(&manager, service.id, ServiceState::GaveUp).await;
+    assert_eq!(status.restarts, 2);
+    assert_eq!(status.last_exit, Some(ExitInfo { code: Some(3), signal: None }));
+    assert!(!manager.is_service_running(service.id).await);
+
+    // Newest first; only the last one is not followed by a restart
+    let crashes = manager.get_service_crashes(service.id).await;
+    assert_eq!(crashes.len(), 3);
+    assert_eq!(crashes.iter().map(|c| c.will_restart).collect::<Vec<_>>(), vec![false, true, true]);
+    assert_eq!(crashes[0].exit.code, Some(3));
+    // stdout and stderr are read separately, so their lines may interleave either way
+    let last_logs = &crashes[0].last_logs;
+    assert!(last_logs.contains(&"booting".to_string()) && last_logs.contains(&"port in use".to_string()));
+
+    // A supervisor that gave up can be started again
+    manager.start_service_with(service.id, &service, restart_policy(RestartMode::Never, 0)).await
+        .expect("Failed to start service again");
+    let _ = manager.stop_service(service.id).await;
+}
+
+#[tokio::test]
+async fn test_clear_all_forgets_logs_and_crashes() {
+    let manager = ServiceManager::new();
+    let service = shell_service("echo booting; exit 3", true);
+
+    manager.start_service_with(service.id, &service, restart_policy(RestartMode::Never, 0)).await
+        .expect("Failed to start service");
+    // The crash is recorded once the exit's output is read
+    for _ in 0..50 {
+        if !manager.get_service_crashes(service.id).await.is_empty() {
+            break;
+        }
+        sleep(Duration::from_millis(50)).await;
+    }
+    assert!(!manager.get_service_crashes(service.id).await.is_empty());
+
+    // Another workspace's service with the same id starts with a clean slate
+    manager.clear_all().await;
+    assert!(manager.get_service_logs(service.id, None).await.is_empty());
+    assert!(manager.get_service_crashes(service.id).await.is_empty());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_signals_and_clean_exits_follow_the_policy() {
+    let manager = ServiceManager::new();
+
+    // A clean exit is not a failure, so on-failure leaves it exited
+    let clean = shell_service("exit 0", true);
+    manager.start_service_with(clean.id, &clean, restart_policy(RestartMode::OnFailure, 3)).await
+        .expect("Failed to start service");
+    let status = wait_for_state(&manager, clean.id, ServiceState::Exited).await;
+    assert_eq!(status.restarts, 0);
+    assert!(manager.get_service_crashes(clean.id).await.is_empty());
+
+    let mut killed = shell_service("kill -9 $$", true);
+    killed.id = 8;
+    manager.start_service_with(killed.id, &killed, restart_policy(RestartMode::Never, 3)).await
+        .expect("Failed to start service");
+    let status = wait_for_state(&manager, killed.id, ServiceState::Exited).await;
+    assert_eq!(status.last_exit, Some(ExitInfo { code: None, signal: Some(9) }));
+    assert_eq!(manager.get_service_crashes(killed.id).await[0].exit.to_string(), "signal 9");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stopping_during_backoff_cancels_the_restart() {
+    let manager = ServiceManager::new();
+    let service = shell_service("exit 0", true);
+    let options = LaunchOptions {
+        env: Vec::new(),
+        restart: RestartPolicy { mode: RestartMode::Always, max_retries: 5, initial_backoff_ms: 60_000, max_backoff_ms: 60_000 },
+        ..LaunchOptions::default()
+    };
+
+    manager.start_service_with(service.id, &service, options).await
+        .expect("Failed to start service");
+    let status = wait_for_state(&manager, service.id, ServiceState::Restarting).await;
+    assert!(status.next_restart_at.is_some());
+
+    // Only one supervisor per service
+    let err = manager.start_service_by_id(service.id, &service).await.unwrap_err();
+    assert!(err.to_string().contains("already running"));
+
+    manager.stop_service(service.id).await.expect("Failed to stop service");
+    assert_eq!(manager.service_status(service.id).await.state, ServiceState::Stopped);
+}
+
+#[cfg(unix)]
+fn process_exists(pid: i32) -> bool {
+    nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), None).is_ok()
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stop_reaches_grandchildren() {
+    let manager = ServiceManager::new();
+    // Like `npm run dev`, the service's own process only waits on a child
+    let service = shell_service("sleep 30 & echo $!; wait", true);
+
+    manager.start_service_by_id(service.id, &service).await
+        .expect("Failed to start service");
+    let grandchild: i32 = wait_for_logs(&manager, service.id, 1).await[0].parse().expect("Not a PID");
+    assert!(process_exists(grandchild));
+
+    manager.stop_service(service.id).await.expect("Failed to stop service");
+    assert!(!process_exists(grandchild), "Grandchild {} outlived its service", grandchild);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stop_escalates_after_the_grace_period() {
+    let manager = ServiceManager::new();
+    let service = shell_service("trap '' TERM; echo ready; sleep 30", true);
+    let options = LaunchOptions { stop_grace: Duration::from_millis(200), ..LaunchOptions::default() };
+
+    let pid = manager.start_service_with(service.id, &service, options).await
+        .expect("Failed to start service");
+    wait_for_logs(&manager, service.id, 1).await;
+
+    let started = std::time::Instant::now();
+    manager.stop_service(service.id).await.expect("Failed to stop service");
+    assert!(started.elapsed() >= Duration::from_millis(200));
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(!process_exists(pid as i32));
+
+    let logs = wait_for_logs(&manager, service.id, 2).await;
+    assert!(logs[1].contains("killing them"), "{:?}", logs);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stop_all_services_ends_every_group() {
+    let manager = ServiceManager::new();
+    let mut first = shell_service("sleep 30 & wait", true);
+    first.id = 11;
+    let mut second = shell_service("sleep 30 & wait", true);
+    second.id = 12;
+
+    let first_pid = manager.start_service_by_id(first.id, &first).await.expect("Failed to start service");
+    let second_pid = manager.start_service_by_id(second.id, &second).await.expect("Failed to start service");
+
+    manager.stop_all_services().await;
+    assert!(!process_exists(first_pid as i32) && !process_exists(second_pid as i32));
+    assert_eq!(manager.service_status(first.id).await.state, ServiceState::Stopped);
+    assert_eq!(manager.service_status(second.id).await.state, ServiceState::Stopped);
+}
@@ -0,0 +1,129 @@
+use daily_tasks_management_lib::database::Database;
+use daily_tasks_management_lib::workspace::{self, WorkspaceManager, DEFAULT_WORKSPACE};
+use daily_tasks_management_lib::repository::ProjectRepository;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_resolve_data_dir_precedence() {
+    let default_dir = PathBuf::from("/default");
+
+    // CLI flag wins over the environment variable
+    let dir = workspace::resolve_data_dir(
+        &args(&["app", "--data-dir", "/from/flag"]),
+        Some("/from/env".to_string()),
+        default_dir.clone(),
+    );
+    assert_eq!(dir, PathBuf::from("/from/flag"));
+
+    let dir = workspace::resolve_data_dir(
+        &args(&["app", "--data-dir=/from/flag"]),
+        None,
+        default_dir.clone(),
+    );
+    assert_eq!(dir, PathBuf::from("/from/flag"));
+
+    let dir = workspace::resolve_data_dir(&args(&["app"]), Some("/from/env".to_string()), default_dir.clone());
+    assert_eq!(dir, PathBuf::from("/from/env"));
+
+    let dir = workspace::resolve_data_dir(&args(&["app"]), None, default_dir.clone());
+    assert_eq!(dir, default_dir);
+}
+
+#[test]
+fn test_validate_workspace_name() {
+    assert!(workspace::validate_workspace_name("work").is_ok());
+    assert!(workspace::validate_workspace_name("side_project-2").is_ok());
+    assert!(workspace::validate_workspace_name("").is_err());
+    assert!(workspace::validate_workspace_name("../escape").is_err());
+    assert!(workspace::validate_workspace_name("has space").is_err());
+}
+
+#[test]
+fn test_default_workspace_created() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let manager = WorkspaceManager::new(temp_dir.path().to_path_buf(), None, None)
+        .expect("Failed to create workspace manager");
+
+    assert_eq!(manager.active_workspace().unwrap(), DEFAULT_WORKSPACE);
+
+    let workspaces = manager.list_workspaces().expect("Failed to list workspaces");
+    assert_eq!(workspaces.len(), 1);
+    assert!(workspaces[0].active);
+
+    let db_path = manager.active_database_path().unwrap();
+    assert!(db_path.starts_with(temp_dir.path()));
+    Database::new(db_path.to_str().unwrap()).expect("Failed to open workspace database");
+}
+
+#[test]
+fn test_create_and_switch_workspace() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let manager = WorkspaceManager::new(temp_dir.path().to_path_buf(), Some("work".to_string()), None)
+        .expect("Failed to create workspace manager");
+
+    let personal = manager.create_workspace("personal").expect("Failed to create workspace");
+    assert!(!personal.active);
+    assert!(manager.create_workspace("personal").is_err());
+
+    let work_db = Database::new(manager.active_database_path().unwrap().to_str().unwrap())
+        .expect("Failed to open work database");
+    work_db.create_project("Work".to_string(), "/tmp/work".to_string(), None, None)
+        .expect("Failed to create project");
+
+    let personal_path = manager.set_active("personal").expect("Failed to switch workspace");
+    let personal_db = Database::new(personal_path.to_str().unwrap())
+        .expect("Failed to open personal database");
+
+    // Each workspace has its own database
+    assert!(personal_db.get_all_projects().unwrap().is_empty());
+    assert_eq!(work_db.get_all_projects().unwrap().len(), 1);
+
+    assert!(manager.set_active("missing").is_err());
+}
+
+#[test]
+fn test_active_workspace_persists() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    {
+        let manager = WorkspaceManager::new(temp_dir.path().to_path_buf(), None, None)
+            .expect("Failed to create workspace manager");
+        manager.create_workspace("personal").expect("Failed to create workspace");
+        manager.set_active("personal").expect("Failed to switch workspace");
+    }
+
+    let manager = WorkspaceManager::new(temp_dir.path().to_path_buf(), None, None)
+        .expect("Failed to reopen workspace manager");
+    assert_eq!(manager.active_workspace().unwrap(), "personal");
+}
+
+#[test]
+fn test_legacy_database_imported_on_first_run() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let legacy_path = temp_dir.path().join("tasks.db");
+    Database::new(legacy_path.to_str().unwrap())
+        .expect("Failed to create legacy database")
+        .create_project("Legacy".to_string(), "/tmp/legacy".to_string(), None, None)
+        .expect("Failed to create project");
+
+    let data_dir = temp_dir.path().join("data");
+    let manager = WorkspaceManager::new(data_dir.clone(), None, Some(&legacy_path))
+        .expect("Failed to create workspace manager");
+    assert_eq!(manager.imported_from(), Some(legacy_path.as_path()));
+
+    let db = Database::new(manager.active_database_path().unwrap().to_str().unwrap())
+        .expect("Failed to open workspace database");
+    assert_eq!(db.get_all_projects().unwrap().len(), 1);
+    db.create_project("New".to_string(), "/tmp/new".to_string(), None, None)
+        .expect("Failed to create project");
+
+    // Later launches keep the workspace's own database
+    let manager = WorkspaceManager::new(data_dir, None, Some(&legacy_path))
+        .expect("Failed to reopen workspace manager");
+    assert_eq!(manager.imported_from(), None);
+    assert_eq!(db.get_all_projects().unwrap().len(), 2);
+}
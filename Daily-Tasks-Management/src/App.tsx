@@ -3,6 +3,7 @@ import { invoke } from "@tauri-apps/api/core";
 import "./App.css";
 import "./styles/components.css";
 import { Task, TaskFormData } from './types/Tasks';
+import { errorMessage } from './types/Errors';
 import { TaskForm } from './components/TaskForm';
 import { TaskList } from './components/TaskList';
 import { StatCard } from './components/StatCard';
@@ -13,6 +14,7 @@ import { ProjectsPanel } from './components/ProjectsPanel';
 import { ServicesPanel } from './components/ServicesPanel';
 import { GitStatusPanel } from './components/GitStatusPanel';
 import { LogViewer } from './components/LogViewer';
+import { DatabaseBrowser } from './components/DatabaseBrowser';
 
 function App() {
   const [currentView, setCurrentView] = useState<ViewType>(() => {
@@ -53,7 +55,7 @@ function App() {
       const tasksData = await invoke<Task[]>("get_all_tasks");
       setTasks(tasksData);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error("Failed to load tasks:", err);
     } finally {
       setLoading(false);
@@ -70,7 +72,7 @@ function App() {
       setError(null);
     } catch (err) {
       console.error("Failed to create task - Full error:", err);
-      setError(`Failed to create task: ${err}`);
+      setError(`Failed to create task: ${errorMessage(err)}`);
       // Don't close the form on error so user can try again
     }
   };
@@ -83,7 +85,7 @@ function App() {
       setTasks(prev => prev.filter(task => task.id !== taskId));
       setError(null);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error("Failed to delete task:", err);
     }
   };
@@ -103,7 +105,7 @@ function App() {
       ));
       setError(null);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error("Failed to toggle task completion:", err);
     }
   };
@@ -132,7 +134,7 @@ function App() {
       setEditingTask(null);
       setError(null);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error("Failed to update task:", err);
     }
   };
@@ -151,6 +153,8 @@ function App() {
         return renderTasksView();
       case 'logs':
         return <LogViewer isDarkMode={isDarkMode} />;
+      case 'database':
+        return <DatabaseBrowser isDarkMode={isDarkMode} />;
       default:
         return <Dashboard isDarkMode={isDarkMode} />;
     }
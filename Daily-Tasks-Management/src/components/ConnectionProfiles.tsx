@@ -0,0 +1,241 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { ConnectionProfile, ConnectionProfileForm } from '../types/Database';
+import { Project } from '../types/Projects';
+import { errorMessage } from '../types/Errors';
+
+interface ConnectionProfilesProps {
+  profiles: ConnectionProfile[];
+  // Called after any change so the browser can reload its database list
+  onChanged: () => void;
+  onBrowse: (profile: ConnectionProfile) => void;
+  onError: (message: string) => void;
+  isDarkMode: boolean;
+}
+
+const emptyForm = (projectId: number): ConnectionProfileForm => ({
+  projectId,
+  name: '',
+  kind: 'postgres',
+  host: 'localhost',
+  port: 5432,
+  database: 'postgres',
+  username: 'postgres',
+  password: '',
+});
+
+export function ConnectionProfiles({ profiles, onChanged, onBrowse, onError, isDarkMode }: ConnectionProfilesProps) {
+  const [projects, setProjects] = useState<Project[]>([]);
+  const [form, setForm] = useState<ConnectionProfileForm | null>(null);
+  // Set while editing; the password field then only replaces the stored one when filled in
+  const [editing, setEditing] = useState<ConnectionProfile | null>(null);
+  const [testResults, setTestResults] = useState<Record<number, string>>({});
+  const theme = isDarkMode ? 'dark' : 'light';
+
+  useEffect(() => {
+    loadProjects();
+  }, []);
+
+  const loadProjects = async () => {
+    try {
+      setProjects(await invoke<Project[]>('get_all_projects'));
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const startCreate = () => {
+    if (projects.length === 0) {
+      onError('Add a project before creating a connection profile');
+      return;
+    }
+    setEditing(null);
+    setForm(emptyForm(projects[0].id));
+  };
+
+  const startEdit = (profile: ConnectionProfile) => {
+    setEditing(profile);
+    setForm({ ...profile, password: '' });
+  };
+
+  const handleSubmit = async () => {
+    if (!form) return;
+    try {
+      if (editing) {
+        const { password, ...fields } = form;
+        await invoke('update_connection_profile', {
+          profile: { ...editing, ...fields },
+          password: password ? password : undefined,
+        });
+      } else {
+        await invoke<ConnectionProfile>('create_connection_profile', { form });
+      }
+      setForm(null);
+      setEditing(null);
+      onChanged();
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const handleClearPassword = async (profile: ConnectionProfile) => {
+    try {
+      await invoke('update_connection_profile', { profile, password: '' });
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const handleDelete = async (id: number) => {
+    try {
+      await invoke('delete_connection_profile', { id });
+      onChanged();
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const handleTest = async (id: number) => {
+    try {
+      const version = await invoke<string>('test_connection_profile', { id });
+      setTestResults({ ...testResults, [id]: version });
+    } catch (err) {
+      setTestResults({ ...testResults, [id]: errorMessage(err) });
+    }
+  };
+
+  const projectName = (id: number) => projects.find((p) => p.id === id)?.name ?? `Project ${id}`;
+
+  return (
+    <div className={`connection-profiles ${theme}`}>
+      <div className="profiles-header">
+        <h3 className={`section-title ${theme}`}>Connections</h3>
+        <button onClick={startCreate} className={`btn-secondary ${theme}`}>
+          New connection
+        </button>
+      </div>
+
+      <ul className="library-list">
+        {profiles.map((profile) => (
+          <li key={profile.id} className={`library-item ${theme}`}>
+            <span className="library-name">{profile.name}</span>
+            <span className="library-meta">
+              {projectName(profile.projectId)} · {profile.kind} · {profile.username}@{profile.host}:{profile.port}/
+              {profile.database}
+            </span>
+            {testResults[profile.id] && <span className="library-meta">{testResults[profile.id]}</span>}
+            <div className="profile-actions">
+              <button onClick={() => onBrowse(profile)} className={`btn-primary ${theme}`}>
+                Browse
+              </button>
+              <button onClick={() => handleTest(profile.id)} className={`btn-secondary ${theme}`}>
+                Test
+              </button>
+              <button onClick={() => startEdit(profile)} className={`btn-secondary ${theme}`}>
+                Edit
+              </button>
+              <button onClick={() => handleDelete(profile.id)} className={`btn-secondary ${theme}`}>
+                Delete
+              </button>
+            </div>
+          </li>
+        ))}
+        {profiles.length === 0 && <li className={`empty-state ${theme}`}>No connection profiles</li>}
+      </ul>
+
+      {form && (
+        <div className="profile-form">
+          <label className={`form-label ${theme}`}>
+            Project
+            <select
+              value={form.projectId}
+              onChange={(e) => setForm({ ...form, projectId: Number(e.target.value) })}
+              className={`form-input ${theme}`}
+              disabled={editing !== null}
+            >
+              {projects.map((project) => (
+                <option key={project.id} value={project.id}>
+                  {project.name}
+                </option>
+              ))}
+            </select>
+          </label>
+          <label className={`form-label ${theme}`}>
+            Name
+            <input
+              type="text"
+              value={form.name}
+              onChange={(e) => setForm({ ...form, name: e.target.value })}
+              className={`form-input ${theme}`}
+            />
+          </label>
+          <label className={`form-label ${theme}`}>
+            Host
+            <input
+              type="text"
+              value={form.host}
+              onChange={(e) => setForm({ ...form, host: e.target.value })}
+              className={`form-input ${theme}`}
+            />
+          </label>
+          <label className={`form-label ${theme}`}>
+            Port
+            <input
+              type="number"
+              value={form.port}
+              onChange={(e) => setForm({ ...form, port: Number(e.target.value) })}
+              className={`form-input ${theme}`}
+            />
+          </label>
+          <label className={`form-label ${theme}`}>
+            Database
+            <input
+              type="text"
+              value={form.database}
+              onChange={(e) => setForm({ ...form, database: e.target.value })}
+              className={`form-input ${theme}`}
+            />
+          </label>
+          <label className={`form-label ${theme}`}>
+            Username
+            <input
+              type="text"
+              value={form.username}
+              onChange={(e) => setForm({ ...form, username: e.target.value })}
+              className={`form-input ${theme}`}
+            />
+          </label>
+          <label className={`form-label ${theme}`}>
+            Password
+            <input
+              type="password"
+              value={form.password ?? ''}
+              onChange={(e) => setForm({ ...form, password: e.target.value })}
+              placeholder={editing ? 'Leave empty to keep the saved password' : ''}
+              className={`form-input ${theme}`}
+            />
+          </label>
+          <div className="profile-actions">
+            <button onClick={handleSubmit} className={`btn-primary ${theme}`}>
+              {editing ? 'Save' : 'Create'}
+            </button>
+            {editing && (
+              <button onClick={() => handleClearPassword(editing)} className={`btn-secondary ${theme}`}>
+                Forget password
+              </button>
+            )}
+            <button
+              onClick={() => {
+                setForm(null);
+                setEditing(null);
+              }}
+              className={`btn-secondary ${theme}`}
+            >
+              Cancel
+            </button>
+          </div>
+        </div>
+      )}
+    </div>
+  );
+}
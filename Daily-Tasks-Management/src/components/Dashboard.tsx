@@ -4,6 +4,7 @@ import { Project } from '../types/Projects';
 import { Service } from '../types/Services';
 import { GitStatus } from '../types/Git';
 import { Task } from '../types/Tasks';
+import { errorMessage } from '../types/Errors';
 import '../styles/dashboard.css';
 
 interface DashboardProps {
@@ -39,7 +40,7 @@ export function Dashboard({ isDarkMode }: DashboardProps) {
       setGitStatuses(gitData);
       setTasks(tasksData);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to load dashboard data:', err);
     } finally {
       setLoading(false);
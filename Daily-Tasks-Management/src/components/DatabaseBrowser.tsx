@@ -1,6 +1,34 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { TableInfo, QueryResult } from '../types/Database';
+import {
+  TableInfo,
+  QueryResult,
+  ProjectDatabases,
+  DatabaseSchema,
+  ExportFormat,
+  ExportSource,
+  ExportSummary,
+  QueryParameter,
+  QueryParams,
+  QueryOptions,
+  QueryPage,
+  QueryProgress,
+  QueryStarted,
+  ConnectionProfile,
+  WriteMode,
+  WORKSPACE_DATABASE_ID,
+  buildQueryParams,
+  dollarParameters,
+  isProfileDatabase,
+  profileDatabaseId,
+  formatCell,
+} from '../types/Database';
+import { TableSchemaView } from './TableSchemaView';
+import { QueryLibrary } from './QueryLibrary';
+import { ConnectionProfiles } from './ConnectionProfiles';
+import { DatabaseDiffView } from './DatabaseDiffView';
+import { TableEditor } from './TableEditor';
+import { errorMessage } from '../types/Errors';
 import '../styles/database-browser.css';
 
 interface DatabaseBrowserProps {
@@ -8,8 +36,11 @@ interface DatabaseBrowserProps {
 }
 
 export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
-  const [dbPath, setDbPath] = useState('tasks.db');
+  const [databaseId, setDatabaseId] = useState(WORKSPACE_DATABASE_ID);
+  const [projectDatabases, setProjectDatabases] = useState<ProjectDatabases[]>([]);
+  const [profiles, setProfiles] = useState<ConnectionProfile[]>([]);
   const [tables, setTables] = useState<TableInfo[]>([]);
+  const [schema, setSchema] = useState<DatabaseSchema | null>(null);
   const [selectedTable, setSelectedTable] = useState<string | null>(null);
   const [tableData, setTableData] = useState<QueryResult | null>(null);
   const [loading, setLoading] = useState(false);
@@ -17,36 +48,119 @@ export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
   const [currentPage, setCurrentPage] = useState(0);
   const [query, setQuery] = useState('');
   const [queryResult, setQueryResult] = useState<QueryResult | null>(null);
+  const [queryParameters, setQueryParameters] = useState<QueryParameter[]>([]);
+  const [paramValues, setParamValues] = useState<Record<number, string>>({});
+  const [lastParams, setLastParams] = useState<QueryParams | undefined>(undefined);
+  // Open paged query; null once it has been read to the end or cancelled
+  const [queryHandle, setQueryHandle] = useState<number | null>(null);
+  const [queryProgress, setQueryProgress] = useState<QueryProgress | null>(null);
+  const [fetching, setFetching] = useState(false);
+  const [rowLimit, setRowLimit] = useState('');
+  const [timeLimit, setTimeLimit] = useState('');
+  const [historyVersion, setHistoryVersion] = useState(0);
+  const [exportFormat, setExportFormat] = useState<ExportFormat>('csv');
+  const [exportPath, setExportPath] = useState('');
+  const [exportSummary, setExportSummary] = useState<ExportSummary | null>(null);
+  const [writeMode, setWriteMode] = useState<WriteMode | null>(null);
   const rowsPerPage = 50;
 
   useEffect(() => {
-    if (dbPath) {
-      loadTables();
-    }
-  }, [dbPath]);
+    loadDatabases();
+  }, []);
+
+  useEffect(() => {
+    loadTables();
+    loadWriteMode();
+  }, [databaseId]);
 
   useEffect(() => {
-    if (selectedTable && dbPath) {
+    if (selectedTable) {
       loadTableData();
     }
-  }, [selectedTable, currentPage, dbPath]);
+  }, [selectedTable, currentPage, databaseId]);
+
+  const loadDatabases = async () => {
+    try {
+      const [found, connections] = await Promise.all([
+        invoke<ProjectDatabases[]>('list_project_databases'),
+        invoke<ConnectionProfile[]>('get_connection_profiles'),
+      ]);
+      setProjectDatabases(found);
+      setProfiles(connections);
+    } catch (err) {
+      setError(errorMessage(err));
+      console.error('Failed to discover databases:', err);
+    }
+  };
 
   const loadTables = async () => {
+    // A query left open on the previous database would keep it read-locked
+    if (queryHandle !== null) {
+      invoke('cancel_database_query', { handle: queryHandle }).catch(() => undefined);
+      setQueryHandle(null);
+    }
     try {
       setLoading(true);
       setError(null);
-      const tablesData = await invoke<TableInfo[]>('list_database_tables', { dbPath });
+      // Schema introspection is only available for SQLite databases
+      const [tablesData, schemaData] = await Promise.all([
+        invoke<TableInfo[]>('list_database_tables', { databaseId }),
+        isProfileDatabase(databaseId)
+          ? Promise.resolve(null)
+          : invoke<DatabaseSchema>('get_database_schema', { databaseId }),
+      ]);
       setTables(tablesData);
+      setSchema(schemaData);
       setSelectedTable(null);
       setTableData(null);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to load tables:', err);
     } finally {
       setLoading(false);
     }
   };
 
+  // Keeps the selection, unlike loadTables
+  const refreshTableCounts = async () => {
+    try {
+      setTables(await invoke<TableInfo[]>('list_database_tables', { databaseId }));
+    } catch (err) {
+      setError(errorMessage(err));
+    }
+  };
+
+  const loadWriteMode = async () => {
+    if (isProfileDatabase(databaseId)) {
+      setWriteMode(null);
+      return;
+    }
+    try {
+      setWriteMode(await invoke<WriteMode>('get_database_write_mode', { databaseId }));
+    } catch (err) {
+      setError(errorMessage(err));
+    }
+  };
+
+  const handleToggleWriteMode = async () => {
+    if (!writeMode) return;
+    const enabled = !writeMode.enabled;
+    // The app's own database needs a deliberate override
+    if (enabled && writeMode.appDatabase
+      && !window.confirm('This is the task manager\'s own database. Allow writes to it anyway?')) {
+      return;
+    }
+    try {
+      setWriteMode(await invoke<WriteMode>('set_database_write_mode', {
+        databaseId,
+        enabled,
+        allowAppDatabase: enabled && writeMode.appDatabase,
+      }));
+    } catch (err) {
+      setError(errorMessage(err));
+    }
+  };
+
   const loadTableData = async () => {
     if (!selectedTable) return;
     
@@ -54,14 +168,14 @@ export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
       setLoading(true);
       setError(null);
       const data = await invoke<QueryResult>('get_table_data', {
-        dbPath,
+        databaseId,
         table: selectedTable,
         limit: rowsPerPage,
         offset: currentPage * rowsPerPage,
       });
       setTableData(data);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to load table data:', err);
     } finally {
       setLoading(false);
@@ -77,17 +191,132 @@ export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
     try {
       setLoading(true);
       setError(null);
-      const result = await invoke<QueryResult>('execute_database_query', {
-        dbPath,
-        query: query.trim(),
-      });
-      setQueryResult(result);
+
+      // Placeholders get input fields first; the next click runs the query
+      const parameters = isProfileDatabase(databaseId)
+        ? dollarParameters(query)
+        : await invoke<QueryParameter[]>('get_query_parameters', {
+            databaseId,
+            query: query.trim(),
+          });
+      if (JSON.stringify(parameters) !== JSON.stringify(queryParameters)) {
+        setQueryParameters(parameters);
+        setParamValues({});
+        if (parameters.length > 0) return;
+      }
+
+      const params = parameters.length > 0 ? buildQueryParams(parameters, paramValues) : undefined;
+      setLastParams(params);
       setSelectedTable(null);
       setTableData(null);
+
+      if (isProfileDatabase(databaseId)) {
+        const result = await invoke<QueryResult>('execute_database_query', {
+          databaseId,
+          query: query.trim(),
+          params,
+        });
+        setQueryResult(result);
+        setQueryProgress(null);
+        return;
+      }
+
+      if (queryHandle !== null) {
+        await invoke('cancel_database_query', { handle: queryHandle }).catch(() => undefined);
+      }
+      const started = await invoke<QueryStarted>('start_database_query', {
+        databaseId,
+        query: query.trim(),
+        params,
+        options: queryOptions(),
+      });
+      setQueryHandle(started.handle);
+      setQueryResult({ columns: started.columns, rows: [], rowCount: 0, truncated: false });
+      setLoading(false);
+      await fetchPage(started.handle, false);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to execute query:', err);
       setQueryResult(null);
+    } finally {
+      setLoading(false);
+      setHistoryVersion(version => version + 1);
+    }
+  };
+
+  const queryOptions = (): QueryOptions => ({
+    pageSize: rowsPerPage,
+    maxRows: rowLimit.trim() ? Number(rowLimit) : undefined,
+    timeoutMs: timeLimit.trim() ? Number(timeLimit) * 1000 : undefined,
+  });
+
+  const fetchPage = async (handle: number, append: boolean) => {
+    setFetching(true);
+    // Poll while the page is read so a slow statement still shows activity
+    const poll = window.setInterval(async () => {
+      try {
+        setQueryProgress(await invoke<QueryProgress>('get_query_progress', { handle }));
+      } catch {
+        // The query finished or was cancelled in the meantime
+      }
+    }, 500);
+
+    try {
+      const page = await invoke<QueryPage>('fetch_query_page', { handle });
+      setQueryResult((previous) => {
+        const rows = append && previous ? [...previous.rows, ...page.rows] : page.rows;
+        return { columns: page.columns, rows, rowCount: rows.length, truncated: page.progress.status === 'truncated' };
+      });
+      setQueryProgress(page.progress);
+      setQueryHandle(page.progress.status === 'running' ? handle : null);
+    } catch (err) {
+      setError(errorMessage(err));
+      setQueryHandle(null);
+    } finally {
+      window.clearInterval(poll);
+      setFetching(false);
+    }
+  };
+
+  const handleLoadMore = async () => {
+    if (queryHandle !== null) {
+      await fetchPage(queryHandle, true);
+    }
+  };
+
+  const handleCancelQuery = async () => {
+    if (queryHandle === null) return;
+    try {
+      setQueryProgress(await invoke<QueryProgress>('cancel_database_query', { handle: queryHandle }));
+    } catch (err) {
+      setError(errorMessage(err));
+    } finally {
+      setQueryHandle(null);
+    }
+  };
+
+  const handleExport = async () => {
+    const source: ExportSource | null = queryResult
+      ? { kind: 'query', sql: query.trim(), params: lastParams }
+      : selectedTable
+        ? { kind: 'table', name: selectedTable }
+        : null;
+    if (!source || !exportPath.trim()) return;
+
+    try {
+      setLoading(true);
+      setError(null);
+      const summary = await invoke<ExportSummary>('export_database_data', {
+        databaseId,
+        source,
+        format: exportFormat,
+        outputPath: exportPath.trim(),
+      });
+      setExportSummary(summary);
+    } catch (err) {
+      setError(errorMessage(err));
+      console.error('Failed to export data:', err);
+      setExportSummary(null);
     } finally {
       setLoading(false);
     }
@@ -100,6 +329,11 @@ export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
     return Math.ceil(table.rowCount / rowsPerPage);
   };
 
+  const selectedSchema = schema
+    ? schema.tables.find(t => t.name === selectedTable) ?? schema.views.find(v => v.name === selectedTable)
+    : undefined;
+  const editing = !queryResult && writeMode?.enabled && schema?.tables.some(t => t.name === selectedTable);
+
   return (
     <div className={`database-browser ${isDarkMode ? 'dark' : 'light'}`}>
       <div className="panel-header">
@@ -108,23 +342,66 @@ export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
 
       <div className="db-controls">
         <div className="db-path-input">
-          <label className={`form-label ${isDarkMode ? 'dark' : 'light'}`}>Database Path</label>
-          <input
-            type="text"
-            value={dbPath}
-            onChange={(e) => setDbPath(e.target.value)}
-            placeholder="tasks.db or /path/to/database.db"
+          <label className={`form-label ${isDarkMode ? 'dark' : 'light'}`}>Database</label>
+          <select
+            value={databaseId}
+            onChange={(e) => setDatabaseId(e.target.value)}
             className={`form-input ${isDarkMode ? 'dark' : 'light'}`}
-          />
+          >
+            <option value={WORKSPACE_DATABASE_ID}>Task Manager (active workspace)</option>
+            {projectDatabases
+              .filter((project) => project.databases.length > 0)
+              .map((project) => (
+                <optgroup key={project.projectId} label={project.projectName}>
+                  {project.databases.map((database) => (
+                    <option key={database.id} value={database.id}>
+                      {database.relativePath}
+                    </option>
+                  ))}
+                </optgroup>
+              ))}
+            {profiles.length > 0 && (
+              <optgroup label="Connections">
+                {profiles.map((profile) => (
+                  <option key={profile.id} value={profileDatabaseId(profile.id)}>
+                    {profile.name} ({profile.kind})
+                  </option>
+                ))}
+              </optgroup>
+            )}
+          </select>
           <button
-            onClick={loadTables}
+            onClick={loadDatabases}
             className={`btn-primary ${isDarkMode ? 'dark' : 'light'}`}
           >
-            Load
+            Rescan
           </button>
+          {writeMode && (
+            <button
+              onClick={handleToggleWriteMode}
+              className={`btn-secondary ${writeMode.enabled ? 'write-mode-on' : ''} ${isDarkMode ? 'dark' : 'light'}`}
+            >
+              {writeMode.enabled ? 'Write mode on' : 'Read only'}
+            </button>
+          )}
         </div>
       </div>
 
+      <ConnectionProfiles
+        profiles={profiles}
+        onChanged={loadDatabases}
+        onBrowse={(profile) => setDatabaseId(profileDatabaseId(profile.id))}
+        onError={setError}
+        isDarkMode={isDarkMode}
+      />
+
+      <DatabaseDiffView
+        projectDatabases={projectDatabases}
+        databaseId={isProfileDatabase(databaseId) ? WORKSPACE_DATABASE_ID : databaseId}
+        onError={setError}
+        isDarkMode={isDarkMode}
+      />
+
       {error && (
         <div className="error-message">
           <div className="error-content">
@@ -163,6 +440,27 @@ export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
               ))}
             </div>
           )}
+
+          {schema && schema.views.length > 0 && (
+            <>
+              <h3 className={`sidebar-title ${isDarkMode ? 'dark' : 'light'}`}>Views</h3>
+              <div className="tables-list">
+                {schema.views.map((view) => (
+                  <button
+                    key={view.name}
+                    onClick={() => {
+                      setSelectedTable(view.name);
+                      setCurrentPage(0);
+                      setQueryResult(null);
+                    }}
+                    className={`table-item ${selectedTable === view.name ? 'active' : ''} ${isDarkMode ? 'dark' : 'light'}`}
+                  >
+                    <span className="table-name">{view.name}</span>
+                  </button>
+                ))}
+              </div>
+            </>
+          )}
         </div>
 
         <div className="db-main">
@@ -176,14 +474,66 @@ export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
                 className={`query-input ${isDarkMode ? 'dark' : 'light'}`}
                 rows={3}
               />
+              {queryParameters.length > 0 && (
+                <div className="query-parameters">
+                  {queryParameters.map((parameter) => (
+                    <label key={parameter.index} className={`form-label ${isDarkMode ? 'dark' : 'light'}`}>
+                      {parameter.name
+                        ? `:${parameter.name}`
+                        : `${isProfileDatabase(databaseId) ? '$' : '?'}${parameter.index}`}
+                      <input
+                        type="text"
+                        value={paramValues[parameter.index] ?? ''}
+                        onChange={(e) => setParamValues({ ...paramValues, [parameter.index]: e.target.value })}
+                        placeholder="NULL, number or text"
+                        className={`form-input ${isDarkMode ? 'dark' : 'light'}`}
+                      />
+                    </label>
+                  ))}
+                </div>
+              )}
+              {!isProfileDatabase(databaseId) && (
+                <div className="query-limits">
+                  <label className={`form-label ${isDarkMode ? 'dark' : 'light'}`}>
+                    Row limit
+                    <input
+                      type="number"
+                      min={1}
+                      value={rowLimit}
+                      onChange={(e) => setRowLimit(e.target.value)}
+                      placeholder="100000"
+                      className={`form-input ${isDarkMode ? 'dark' : 'light'}`}
+                    />
+                  </label>
+                  <label className={`form-label ${isDarkMode ? 'dark' : 'light'}`}>
+                    Time limit (s)
+                    <input
+                      type="number"
+                      min={1}
+                      value={timeLimit}
+                      onChange={(e) => setTimeLimit(e.target.value)}
+                      placeholder="10"
+                      className={`form-input ${isDarkMode ? 'dark' : 'light'}`}
+                    />
+                  </label>
+                </div>
+              )}
               <button
                 onClick={handleExecuteQuery}
                 className={`btn-primary ${isDarkMode ? 'dark' : 'light'}`}
-                disabled={!query.trim()}
+                disabled={!query.trim() || fetching}
               >
                 Execute Query
               </button>
             </div>
+            <QueryLibrary
+              databaseId={databaseId}
+              currentQuery={query}
+              historyVersion={historyVersion}
+              onSelect={setQuery}
+              onError={setError}
+              isDarkMode={isDarkMode}
+            />
           </div>
 
           {(tableData || queryResult) && (
@@ -196,10 +546,69 @@ export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
                 <div className="query-info">
                   <span className={`info-text ${isDarkMode ? 'dark' : 'light'}`}>
                     {queryResult.rowCount} row(s) returned
+                    {!queryProgress && queryResult.truncated && ' · stopped at the row limit'}
+                    {queryProgress && (
+                      <>
+                        {' '}· {queryProgress.executionMs} ms
+                        {fetching && ` · running for ${Math.round(queryProgress.elapsedMs / 1000)} s, ${queryProgress.steps} steps`}
+                        {queryProgress.status === 'running' && !fetching && ' · more rows available'}
+                        {queryProgress.status === 'truncated' && ' · stopped at the row limit'}
+                        {queryProgress.status === 'cancelled' && ' · cancelled'}
+                      </>
+                    )}
                   </span>
+                  {queryHandle !== null && !fetching && (
+                    <button onClick={handleLoadMore} className={`btn-secondary ${isDarkMode ? 'dark' : 'light'}`}>
+                      Load more
+                    </button>
+                  )}
+                  {queryHandle !== null && (
+                    <button onClick={handleCancelQuery} className={`btn-secondary ${isDarkMode ? 'dark' : 'light'}`}>
+                      {fetching ? 'Cancel' : 'Close'}
+                    </button>
+                  )}
                 </div>
               )}
 
+              {!isProfileDatabase(databaseId) && (
+                <div className="export-controls">
+                  <select
+                    value={exportFormat}
+                    onChange={(e) => setExportFormat(e.target.value as ExportFormat)}
+                    className={`form-input ${isDarkMode ? 'dark' : 'light'}`}
+                  >
+                    <option value="csv">CSV</option>
+                    <option value="json">JSON</option>
+                    <option value="ndjson">NDJSON</option>
+                    <option value="sql">SQL inserts</option>
+                    <option value="markdown">Markdown</option>
+                  </select>
+                  <input
+                    type="text"
+                    value={exportPath}
+                    onChange={(e) => setExportPath(e.target.value)}
+                    placeholder="/absolute/path/to/export.csv"
+                    className={`form-input ${isDarkMode ? 'dark' : 'light'}`}
+                  />
+                  <button
+                    onClick={handleExport}
+                    className={`btn-secondary ${isDarkMode ? 'dark' : 'light'}`}
+                    disabled={!exportPath.trim() || loading}
+                  >
+                    Export
+                  </button>
+                  {exportSummary && (
+                    <span className={`info-text ${isDarkMode ? 'dark' : 'light'}`}>
+                      Exported {exportSummary.rowCount} row(s) to {exportSummary.path}
+                    </span>
+                  )}
+                </div>
+              )}
+
+              {!queryResult && selectedSchema && (
+                <TableSchemaView schema={selectedSchema} isDarkMode={isDarkMode} />
+              )}
+
               {!queryResult && selectedTable && (
                 <div className="pagination">
                   <button
@@ -222,7 +631,17 @@ export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
                 </div>
               )}
 
-              {loading ? (
+              {editing && selectedTable ? (
+                <TableEditor
+                  databaseId={databaseId}
+                  table={selectedTable}
+                  limit={rowsPerPage}
+                  offset={currentPage * rowsPerPage}
+                  onCommitted={refreshTableCounts}
+                  onError={setError}
+                  isDarkMode={isDarkMode}
+                />
+              ) : loading ? (
                 <div className="loading-container">
                   <div className={`loading-text ${isDarkMode ? 'dark' : 'light'}`}>Loading data...</div>
                 </div>
@@ -232,8 +651,9 @@ export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
                     <thead>
                       <tr>
                         {(queryResult || tableData)?.columns.map((col) => (
-                          <th key={col} className={`table-header ${isDarkMode ? 'dark' : 'light'}`}>
-                            {col}
+                          <th key={col.name} className={`table-header ${isDarkMode ? 'dark' : 'light'}`}>
+                            {col.name}
+                            {col.declaredType && <span className="column-type">{col.declaredType}</span>}
                           </th>
                         ))}
                       </tr>
@@ -242,8 +662,11 @@ export function DatabaseBrowser({ isDarkMode }: DatabaseBrowserProps) {
                       {(queryResult || tableData)?.rows.map((row, idx) => (
                         <tr key={idx} className={`table-row ${isDarkMode ? 'dark' : 'light'}`}>
                           {row.map((cell, cellIdx) => (
-                            <td key={cellIdx} className={`table-cell ${isDarkMode ? 'dark' : 'light'}`}>
-                              {cell}
+                            <td
+                              key={cellIdx}
+                              className={`table-cell cell-${cell.type} ${isDarkMode ? 'dark' : 'light'}`}
+                            >
+                              {formatCell(cell)}
                             </td>
                           ))}
                         </tr>
@@ -0,0 +1,158 @@
+import { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import {
+  DatabaseDiff,
+  DiffOptions,
+  MigrationMode,
+  ProjectDatabases,
+  SchemaChange,
+  TableDiff,
+  WORKSPACE_DATABASE_ID,
+  formatCell,
+} from '../types/Database';
+import { errorMessage } from '../types/Errors';
+
+interface DatabaseDiffViewProps {
+  projectDatabases: ProjectDatabases[];
+  // Preselected as the database being changed
+  databaseId: string;
+  onError: (message: string) => void;
+  isDarkMode: boolean;
+}
+
+function describeChanges<T>(label: string, changes: SchemaChange<T>[]): string[] {
+  return changes.map((c) => `${c.change} ${label} ${c.name}`);
+}
+
+function describeTable(table: TableDiff): string[] {
+  const lines = [
+    ...describeChanges('column', table.columns),
+    ...describeChanges('index', table.indexes),
+    ...describeChanges('trigger', table.triggers),
+  ];
+  if (table.foreignKeysChanged) lines.push('foreign keys changed');
+  if (table.dataSkipped) lines.push(`rows not compared: ${table.dataSkipped}`);
+  return lines;
+}
+
+export function DatabaseDiffView({ projectDatabases, databaseId, onError, isDarkMode }: DatabaseDiffViewProps) {
+  const [fromId, setFromId] = useState(databaseId);
+  const [toId, setToId] = useState(WORKSPACE_DATABASE_ID);
+  const [includeData, setIncludeData] = useState(true);
+  const [migration, setMigration] = useState<MigrationMode | ''>('');
+  const [diff, setDiff] = useState<DatabaseDiff | null>(null);
+  const [running, setRunning] = useState(false);
+  const theme = isDarkMode ? 'dark' : 'light';
+
+  const handleCompare = async () => {
+    const options: DiffOptions = { includeData, migration: migration || undefined };
+    try {
+      setRunning(true);
+      setDiff(await invoke<DatabaseDiff>('diff_databases', {
+        fromDatabaseId: fromId,
+        toDatabaseId: toId,
+        options,
+      }));
+    } catch (err) {
+      onError(errorMessage(err));
+      setDiff(null);
+    } finally {
+      setRunning(false);
+    }
+  };
+
+  const databaseSelect = (value: string, onChange: (id: string) => void) => (
+    <select value={value} onChange={(e) => onChange(e.target.value)} className={`form-input ${theme}`}>
+      <option value={WORKSPACE_DATABASE_ID}>Task Manager (active workspace)</option>
+      {projectDatabases
+        .filter((project) => project.databases.length > 0)
+        .map((project) => (
+          <optgroup key={project.projectId} label={project.projectName}>
+            {project.databases.map((database) => (
+              <option key={database.id} value={database.id}>
+                {database.relativePath}
+              </option>
+            ))}
+          </optgroup>
+        ))}
+    </select>
+  );
+
+  return (
+    <div className={`database-diff ${theme}`}>
+      <h3 className={`section-title ${theme}`}>Compare Databases</h3>
+      <div className="diff-controls">
+        <label className={`form-label ${theme}`}>
+          From
+          {databaseSelect(fromId, setFromId)}
+        </label>
+        <label className={`form-label ${theme}`}>
+          To
+          {databaseSelect(toId, setToId)}
+        </label>
+        <label className={`form-label ${theme}`}>
+          Migration script
+          <select
+            value={migration}
+            onChange={(e) => setMigration(e.target.value as MigrationMode | '')}
+            className={`form-input ${theme}`}
+          >
+            <option value="">None</option>
+            <option value="schema">Schema only</option>
+            <option value="full">Schema and data</option>
+          </select>
+        </label>
+        <label className={`form-label ${theme}`}>
+          <input type="checkbox" checked={includeData} onChange={(e) => setIncludeData(e.target.checked)} />
+          Compare rows
+        </label>
+        <button onClick={handleCompare} className={`btn-primary ${theme}`} disabled={running || fromId === toId}>
+          {running ? 'Comparing...' : 'Compare'}
+        </button>
+      </div>
+
+      {diff && (
+        <ul className="library-list">
+          {diff.tables.length === 0 && diff.views.length === 0 && (
+            <li className={`empty-state ${theme}`}>The databases are identical</li>
+          )}
+          {diff.tables.map((table) => (
+            <li key={table.name} className={`library-item ${theme}`}>
+              <span className="library-name">
+                {table.name}
+                {table.change && <span className={`diff-change ${table.change}`}>{table.change}</span>}
+              </span>
+              {describeTable(table).map((line) => (
+                <span key={line} className="library-meta">{line}</span>
+              ))}
+              {table.data && (
+                <>
+                  <span className="library-meta">
+                    rows by {table.data.key.join(', ')}: +{table.data.added} −{table.data.removed} ~{table.data.changed}
+                    {table.data.truncated && ` (first ${table.data.rows.length} listed)`}
+                  </span>
+                  {table.data.rows.map((row) => (
+                    <span key={`${row.change}:${row.key.map(formatCell).join(',')}`} className={`diff-row ${row.change}`}>
+                      {row.change} {row.key.map(formatCell).join(', ')}
+                      {row.changedColumns.length > 0 && `: ${row.changedColumns.join(', ')}`}
+                    </span>
+                  ))}
+                </>
+              )}
+            </li>
+          ))}
+          {diff.views.map((view) => (
+            <li key={view.name} className={`library-item ${theme}`}>
+              <span className="library-name">
+                {view.name}
+                <span className={`diff-change ${view.change}`}>view {view.change}</span>
+              </span>
+            </li>
+          ))}
+        </ul>
+      )}
+
+      {diff?.migration && <pre className={`schema-sql-text ${theme}`}>{diff.migration}</pre>}
+    </div>
+  );
+}
@@ -2,6 +2,7 @@ import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { GitStatus } from '../types/Git';
 import { Project } from '../types/Projects';
+import { errorMessage } from '../types/Errors';
 import '../styles/git-panel.css';
 
 interface GitStatusPanelProps {
@@ -29,7 +30,7 @@ export function GitStatusPanel({ isDarkMode }: GitStatusPanelProps) {
       setGitStatuses(statusesData);
       setProjects(projectsData);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to load git statuses:', err);
     } finally {
       setLoading(false);
@@ -47,7 +48,7 @@ export function GitStatusPanel({ isDarkMode }: GitStatusPanelProps) {
       await invoke('git_commit', { projectId, message });
       await loadGitStatuses();
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to commit:', err);
     }
   };
@@ -58,7 +59,7 @@ export function GitStatusPanel({ isDarkMode }: GitStatusPanelProps) {
       await invoke('git_push', { projectId });
       await loadGitStatuses(); // Refresh status
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to push:', err);
     }
   };
@@ -69,7 +70,7 @@ export function GitStatusPanel({ isDarkMode }: GitStatusPanelProps) {
       await invoke('git_pull', { projectId });
       await loadGitStatuses(); // Refresh status
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to pull:', err);
     }
   };
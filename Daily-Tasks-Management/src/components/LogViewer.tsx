@@ -1,13 +1,16 @@
 import { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { Service } from '../types/Services';
-import { LogEntry } from '../types/Logs';
+import { LogEntry, LogPage, LogQuery, LogRetention, ansiSegments } from '../types/Logs';
+import { errorMessage } from '../types/Errors';
 import '../styles/log-viewer.css';
 
 interface LogViewerProps {
   isDarkMode: boolean;
 }
 
+const PAGE_SIZE = 500;
+
 export function LogViewer({ isDarkMode }: LogViewerProps) {
   const [services, setServices] = useState<Service[]>([]);
   const [selectedServiceId, setSelectedServiceId] = useState<number | null>(null);
@@ -17,6 +20,9 @@ export function LogViewer({ isDarkMode }: LogViewerProps) {
   const [autoScroll, setAutoScroll] = useState(true);
   const [filter, setFilter] = useState<string>('');
   const [logLevel, setLogLevel] = useState<string>('all');
+  // Continues with lines older than the oldest one shown
+  const [olderCursor, setOlderCursor] = useState<number | null>(null);
+  const [retention, setRetention] = useState<LogRetention | null>(null);
 
   useEffect(() => {
     loadServices();
@@ -24,51 +30,116 @@ export function LogViewer({ isDarkMode }: LogViewerProps) {
 
   useEffect(() => {
     if (selectedServiceId) {
-      loadLogs();
+      setLogs([]);
+      setOlderCursor(null);
+      loadLogs(true);
       // Set up polling for real-time logs (every 2 seconds)
       const interval = setInterval(() => {
         if (autoScroll) {
-          loadLogs();
+          loadLogs(false);
         }
       }, 2000);
       return () => clearInterval(interval);
     }
-  }, [selectedServiceId, autoScroll]);
+  }, [selectedServiceId, autoScroll, filter, logLevel]);
+
+  useEffect(() => {
+    if (!selectedServiceId) return;
+    invoke<LogRetention>('get_log_retention', { id: selectedServiceId })
+      .then(setRetention)
+      .catch(err => console.error('Failed to load log retention:', err));
+  }, [selectedServiceId]);
 
   const loadServices = async () => {
     try {
       const servicesData = await invoke<Service[]>('get_all_services');
       setServices(servicesData);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to load services:', err);
     }
   };
 
-  const loadLogs = async () => {
+  const queryLogs = (cursor?: number) => {
+    const query: LogQuery = {
+      serviceId: selectedServiceId ?? undefined,
+      levels: logLevel === 'all' ? [] : [logLevel],
+      search: filter || undefined,
+      cursor,
+      limit: PAGE_SIZE,
+    };
+    return invoke<LogPage>('query_service_logs', { query });
+  };
+
+  // Fetches the newest page; a reset replaces what is shown instead of
+  // appending the lines that are new since the last poll.
+  const loadLogs = async (reset: boolean) => {
     if (!selectedServiceId) return;
 
     try {
       setLoading(true);
       setError(null);
-      const logsData = await invoke<LogEntry[]>('get_service_logs', { 
-        serviceId: selectedServiceId,
-        limit: 1000 
-      });
-      setLogs(logsData);
+      const page = await queryLogs();
+      const newest = [...page.entries].reverse();
+      if (reset) {
+        setLogs(newest);
+        setOlderCursor(page.nextCursor);
+      } else {
+        setLogs(current => {
+          const lastId = current.length > 0 ? current[current.length - 1].id : 0;
+          return [...current, ...newest.filter(log => log.id > lastId)];
+        });
+      }
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to load logs:', err);
     } finally {
       setLoading(false);
     }
   };
 
-  const filteredLogs = logs.filter(log => {
-    const matchesFilter = !filter || log.message.toLowerCase().includes(filter.toLowerCase());
-    const matchesLevel = logLevel === 'all' || log.level === logLevel;
-    return matchesFilter && matchesLevel;
-  });
+  const loadOlder = async () => {
+    if (olderCursor == null) return;
+
+    try {
+      setAutoScroll(false);
+      const page = await queryLogs(olderCursor);
+      setLogs(current => [...[...page.entries].reverse(), ...current]);
+      setOlderCursor(page.nextCursor);
+    } catch (err) {
+      setError(errorMessage(err));
+      console.error('Failed to load older logs:', err);
+    }
+  };
+
+  const clearLogs = async () => {
+    if (!selectedServiceId) return;
+
+    try {
+      await invoke('clear_service_logs', { id: selectedServiceId });
+      setLogs([]);
+      setOlderCursor(null);
+    } catch (err) {
+      setError(errorMessage(err));
+      console.error('Failed to clear logs:', err);
+    }
+  };
+
+  const saveRetention = async (updated: LogRetention) => {
+    if (!selectedServiceId) return;
+
+    try {
+      await invoke('set_log_retention', { id: selectedServiceId, retention: updated });
+      setRetention(updated);
+    } catch (err) {
+      setError(errorMessage(err));
+      console.error('Failed to save log retention:', err);
+    }
+  };
+
+  // Empty clears a limit
+  const limitValue = (value: string) => (value === '' ? null : Number(value));
+
 
   const logContainerRef = useRef<HTMLDivElement>(null);
 
@@ -76,7 +147,7 @@ export function LogViewer({ isDarkMode }: LogViewerProps) {
     if (autoScroll && logContainerRef.current) {
       logContainerRef.current.scrollTop = logContainerRef.current.scrollHeight;
     }
-  }, [filteredLogs, autoScroll]);
+  }, [logs, autoScroll]);
 
   return (
     <div className={`log-viewer ${isDarkMode ? 'dark' : 'light'}`}>
@@ -84,12 +155,19 @@ export function LogViewer({ isDarkMode }: LogViewerProps) {
         <h2 className={`panel-title ${isDarkMode ? 'dark' : 'light'}`}>Log Viewer</h2>
         <div className="header-actions">
           <button
-            onClick={loadLogs}
+            onClick={() => loadLogs(true)}
             className={`btn-secondary ${isDarkMode ? 'dark' : 'light'}`}
             disabled={!selectedServiceId}
           >
             Refresh
           </button>
+          <button
+            onClick={clearLogs}
+            className={`btn-secondary ${isDarkMode ? 'dark' : 'light'}`}
+            disabled={!selectedServiceId}
+          >
+            Clear
+          </button>
         </div>
       </div>
 
@@ -116,10 +194,7 @@ export function LogViewer({ isDarkMode }: LogViewerProps) {
               {services.map((service) => (
                 <button
                   key={service.id}
-                  onClick={() => {
-                    setSelectedServiceId(service.id);
-                    setLogs([]);
-                  }}
+                  onClick={() => setSelectedServiceId(service.id)}
                   className={`service-item ${selectedServiceId === service.id ? 'active' : ''} ${isDarkMode ? 'dark' : 'light'}`}
                 >
                   <span className="service-name">{service.name}</span>
@@ -178,27 +253,51 @@ export function LogViewer({ isDarkMode }: LogViewerProps) {
                       Loading logs...
                     </div>
                   </div>
-                ) : filteredLogs.length === 0 ? (
+                ) : logs.length === 0 ? (
                   <div className={`empty-state ${isDarkMode ? 'dark' : 'light'}`}>
                     <p>No logs available</p>
                     <p className="empty-hint">
-                      {logs.length === 0 
+                      {filter === '' && logLevel === 'all'
                         ? "Start the service to see logs here. Logs will appear in real-time."
                         : "No logs match your current filter. Try adjusting the search or log level."}
                     </p>
                   </div>
                 ) : (
                   <div className="log-entries">
-                    {filteredLogs.map((log, idx) => (
+                    {olderCursor != null && (
+                      <button
+                        onClick={loadOlder}
+                        className={`btn-secondary log-load-older ${isDarkMode ? 'dark' : 'light'}`}
+                      >
+                        Load older
+                      </button>
+                    )}
+                    {logs.map((log) => (
                       <div
-                        key={idx}
+                        key={log.id}
                         className={`log-entry log-${log.level} ${isDarkMode ? 'dark' : 'light'}`}
                       >
                         <span className="log-timestamp">{log.timestamp}</span>
                         <span className={`log-level log-level-${log.level}`}>
                           {log.level.toUpperCase()}
                         </span>
-                        <span className="log-message">{log.message}</span>
+                        <span className="log-message">
+                          {log.message.includes('\x1b')
+                            ? ansiSegments(log.message).map((segment, i) => (
+                                <span
+                                  key={i}
+                                  className={`${segment.color != null ? `ansi-${segment.color}` : ''} ${segment.bold ? 'ansi-bold' : ''}`}
+                                >
+                                  {segment.text}
+                                </span>
+                              ))
+                            : log.message}
+                          {Object.entries(log.fields ?? {}).map(([key, value]) => (
+                            <span key={key} className="log-field">
+                              {key}={value}
+                            </span>
+                          ))}
+                        </span>
                       </div>
                     ))}
                   </div>
@@ -207,9 +306,41 @@ export function LogViewer({ isDarkMode }: LogViewerProps) {
 
               <div className="log-footer">
                 <span className={`log-count ${isDarkMode ? 'dark' : 'light'}`}>
-                  {filteredLogs.length} log{filteredLogs.length !== 1 ? 's' : ''}
-                  {filter !== '' && ` (filtered from ${logs.length})`}
+                  {logs.length} log{logs.length !== 1 ? 's' : ''}
+                  {olderCursor != null && ' (more stored)'}
                 </span>
+                {retention && (
+                  <div className={`log-retention ${isDarkMode ? 'dark' : 'light'}`}>
+                    Keep
+                    <input
+                      type="number"
+                      min={1}
+                      value={retention.maxAgeDays ?? ''}
+                      onChange={(e) => saveRetention({ ...retention, maxAgeDays: limitValue(e.target.value) })}
+                      className={`filter-input ${isDarkMode ? 'dark' : 'light'}`}
+                    />
+                    days,
+                    <input
+                      type="number"
+                      min={1}
+                      value={retention.maxLines ?? ''}
+                      onChange={(e) => saveRetention({ ...retention, maxLines: limitValue(e.target.value) })}
+                      className={`filter-input ${isDarkMode ? 'dark' : 'light'}`}
+                    />
+                    lines,
+                    <input
+                      type="number"
+                      min={1}
+                      value={retention.maxBytes != null ? retention.maxBytes / 1024 / 1024 : ''}
+                      onChange={(e) => {
+                        const mb = limitValue(e.target.value);
+                        saveRetention({ ...retention, maxBytes: mb == null ? null : Math.round(mb * 1024 * 1024) });
+                      }}
+                      className={`filter-input ${isDarkMode ? 'dark' : 'light'}`}
+                    />
+                    MB at most
+                  </div>
+                )}
               </div>
             </>
           )}
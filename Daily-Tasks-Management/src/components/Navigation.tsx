@@ -1,6 +1,6 @@
 import '../styles/navigation.css';
 
-export type ViewType = 'dashboard' | 'projects' | 'services' | 'git' | 'tasks' | 'logs';
+export type ViewType = 'dashboard' | 'projects' | 'services' | 'git' | 'tasks' | 'logs' | 'database';
 
 interface NavigationProps {
   currentView: ViewType;
@@ -16,6 +16,7 @@ export function Navigation({ currentView, onViewChange, isDarkMode }: Navigation
     { id: 'git', label: 'Git' },
     { id: 'tasks', label: 'Tasks' },
     { id: 'logs', label: 'Logs' },
+    { id: 'database', label: 'Database' },
   ];
 
   return (
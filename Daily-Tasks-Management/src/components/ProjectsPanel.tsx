@@ -1,6 +1,7 @@
 import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { Project } from '../types/Projects';
+import { errorMessage } from '../types/Errors';
 import '../styles/projects-panel.css';
 
 interface ProjectsPanelProps {
@@ -26,7 +27,7 @@ export function ProjectsPanel({ isDarkMode }: ProjectsPanelProps) {
       const projectsData = await invoke<Project[]>('get_all_projects');
       setProjects(projectsData);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to load projects:', err);
     } finally {
       setLoading(false);
@@ -50,7 +51,7 @@ export function ProjectsPanel({ isDarkMode }: ProjectsPanelProps) {
       setNewProjectName('');
       setShowAddForm(false);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to add project:', err);
     }
   };
@@ -63,7 +64,7 @@ export function ProjectsPanel({ isDarkMode }: ProjectsPanelProps) {
       setProjects(prev => prev.filter(p => p.id !== id));
       setError(null);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to remove project:', err);
     }
   };
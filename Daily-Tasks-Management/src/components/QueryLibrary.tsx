@@ -0,0 +1,164 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { QueryHistoryEntry, SavedQuery } from '../types/Database';
+import { errorMessage } from '../types/Errors';
+
+interface QueryLibraryProps {
+  databaseId: string;
+  currentQuery: string;
+  // Bumped by the browser after each run so the history reloads
+  historyVersion: number;
+  onSelect: (query: string) => void;
+  onError: (message: string) => void;
+  isDarkMode: boolean;
+}
+
+type LibraryTab = 'history' | 'saved';
+
+export function QueryLibrary({ databaseId, currentQuery, historyVersion, onSelect, onError, isDarkMode }: QueryLibraryProps) {
+  const [tab, setTab] = useState<LibraryTab>('history');
+  const [search, setSearch] = useState('');
+  const [history, setHistory] = useState<QueryHistoryEntry[]>([]);
+  const [savedQueries, setSavedQueries] = useState<SavedQuery[]>([]);
+  const [saveName, setSaveName] = useState('');
+  const theme = isDarkMode ? 'dark' : 'light';
+
+  useEffect(() => {
+    loadHistory();
+  }, [databaseId, search, historyVersion]);
+
+  useEffect(() => {
+    loadSavedQueries();
+  }, [search]);
+
+  const loadHistory = async () => {
+    try {
+      setHistory(await invoke<QueryHistoryEntry[]>('get_query_history', { search, databaseId }));
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const loadSavedQueries = async () => {
+    try {
+      setSavedQueries(await invoke<SavedQuery[]>('get_saved_queries', { search }));
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const handleSave = async () => {
+    try {
+      await invoke<SavedQuery>('save_query', { name: saveName, query: currentQuery, databaseId });
+      setSaveName('');
+      setTab('saved');
+      await loadSavedQueries();
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const handleDelete = async (id: number) => {
+    try {
+      await invoke('delete_saved_query', { id });
+      await loadSavedQueries();
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const handleClearHistory = async () => {
+    try {
+      await invoke('clear_query_history');
+      setHistory([]);
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  return (
+    <div className={`query-library ${theme}`}>
+      <div className="library-tabs">
+        <button
+          onClick={() => setTab('history')}
+          className={`library-tab ${tab === 'history' ? 'active' : ''} ${theme}`}
+        >
+          History
+        </button>
+        <button
+          onClick={() => setTab('saved')}
+          className={`library-tab ${tab === 'saved' ? 'active' : ''} ${theme}`}
+        >
+          Saved
+        </button>
+        <input
+          type="text"
+          value={search}
+          onChange={(e) => setSearch(e.target.value)}
+          placeholder="Search queries"
+          className={`form-input ${theme}`}
+        />
+      </div>
+
+      {tab === 'history' ? (
+        <>
+          <ul className="library-list">
+            {history.map((entry) => (
+              <li key={entry.id} className={`library-item ${theme}`} onClick={() => onSelect(entry.query)}>
+                <code className="library-query">{entry.query}</code>
+                <span className={`library-meta ${entry.error ? 'failed' : ''}`}>
+                  {entry.error ?? (entry.rowCount != null ? `${entry.rowCount} row(s)` : 'paged')} · {entry.durationMs} ms ·{' '}
+                  {new Date(entry.executedAt).toLocaleString()}
+                </span>
+              </li>
+            ))}
+            {history.length === 0 && <li className={`empty-state ${theme}`}>No queries yet</li>}
+          </ul>
+          {history.length > 0 && (
+            <button onClick={handleClearHistory} className={`btn-secondary ${theme}`}>
+              Clear history
+            </button>
+          )}
+        </>
+      ) : (
+        <>
+          <div className="library-save">
+            <input
+              type="text"
+              value={saveName}
+              onChange={(e) => setSaveName(e.target.value)}
+              placeholder="Name for the current query"
+              className={`form-input ${theme}`}
+            />
+            <button
+              onClick={handleSave}
+              className={`btn-secondary ${theme}`}
+              disabled={!saveName.trim() || !currentQuery.trim()}
+            >
+              Save
+            </button>
+          </div>
+          <ul className="library-list">
+            {savedQueries.map((saved) => (
+              <li key={saved.id} className={`library-item ${theme}`} onClick={() => onSelect(saved.query)}>
+                <span className="library-name">{saved.name}</span>
+                {saved.description && <span className="library-meta">{saved.description}</span>}
+                <code className="library-query">{saved.query}</code>
+                <button
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    handleDelete(saved.id);
+                  }}
+                  className={`btn-secondary ${theme}`}
+                >
+                  Delete
+                </button>
+              </li>
+            ))}
+            {savedQueries.length === 0 && <li className={`empty-state ${theme}`}>No saved queries</li>}
+          </ul>
+        </>
+      )}
+    </div>
+  );
+}
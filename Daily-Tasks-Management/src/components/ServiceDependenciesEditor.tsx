@@ -0,0 +1,59 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Service } from '../types/Services';
+import { errorMessage } from '../types/Errors';
+
+interface ServiceDependenciesEditorProps {
+  service: Service;
+  services: Service[];
+  onClose: () => void;
+  onError: (message: string) => void;
+  isDarkMode: boolean;
+}
+
+export function ServiceDependenciesEditor({ service, services, onClose, onError, isDarkMode }: ServiceDependenciesEditorProps) {
+  const [dependsOn, setDependsOn] = useState<number[] | null>(null);
+  const theme = isDarkMode ? 'dark' : 'light';
+
+  useEffect(() => {
+    invoke<number[]>('get_service_dependencies', { id: service.id })
+      .then(setDependsOn)
+      .catch((err) => onError(errorMessage(err)));
+  }, [service.id]);
+
+  if (!dependsOn) return null;
+
+  const toggle = (id: number) => {
+    setDependsOn(dependsOn.includes(id) ? dependsOn.filter((d) => d !== id) : [...dependsOn, id]);
+  };
+
+  const handleSave = async () => {
+    try {
+      await invoke('set_service_dependencies', { id: service.id, dependsOn });
+      onClose();
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  return (
+    <div className={`service-environment ${theme}`}>
+      <h4 className={`form-label ${theme}`}>Must be running before {service.name}</h4>
+      {services.filter((s) => s.id !== service.id).map((other) => (
+        <label key={other.id} className={`form-checkbox-label ${theme}`}>
+          <input
+            type="checkbox"
+            checked={dependsOn.includes(other.id)}
+            onChange={() => toggle(other.id)}
+            className="form-checkbox"
+          />
+          {other.name}
+        </label>
+      ))}
+      <div className="service-actions">
+        <button onClick={handleSave} className={`btn-primary ${theme}`}>Save</button>
+        <button onClick={onClose} className={`btn-secondary ${theme}`}>Close</button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,180 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { ResolvedEnvVar, ServiceEnvVar, ServiceEnvironment } from '../types/Services';
+import { errorMessage } from '../types/Errors';
+
+interface ServiceEnvironmentEditorProps {
+  serviceId: number;
+  onClose: () => void;
+  onError: (message: string) => void;
+  isDarkMode: boolean;
+}
+
+const MASK = '••••••••';
+
+export function ServiceEnvironmentEditor({ serviceId, onClose, onError, isDarkMode }: ServiceEnvironmentEditorProps) {
+  const [environment, setEnvironment] = useState<ServiceEnvironment | null>(null);
+  const [candidates, setCandidates] = useState<string[]>([]);
+  const [newFile, setNewFile] = useState('');
+  const [preview, setPreview] = useState<ResolvedEnvVar[] | null>(null);
+  const theme = isDarkMode ? 'dark' : 'light';
+
+  useEffect(() => {
+    loadEnvironment();
+  }, [serviceId]);
+
+  const loadEnvironment = async () => {
+    try {
+      setEnvironment(await invoke<ServiceEnvironment>('get_service_environment', { id: serviceId }));
+      setCandidates(await invoke<string[]>('find_service_env_files', { id: serviceId }));
+      setPreview(null);
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  if (!environment) return null;
+
+  const updateVariable = (index: number, change: Partial<ServiceEnvVar>) => {
+    const variables = environment.variables.map((v, i) => (i === index ? { ...v, ...change } : v));
+    setEnvironment({ ...environment, variables });
+  };
+
+  const moveFile = (index: number, offset: number) => {
+    const envFiles = [...environment.envFiles];
+    const [file] = envFiles.splice(index, 1);
+    envFiles.splice(index + offset, 0, file);
+    setEnvironment({ ...environment, envFiles });
+  };
+
+  const addFile = (path: string) => {
+    if (!path.trim() || environment.envFiles.includes(path)) return;
+    setEnvironment({ ...environment, envFiles: [...environment.envFiles, path.trim()] });
+    setNewFile('');
+  };
+
+  const handleSave = async () => {
+    // An empty secret input means the stored value stays
+    const variables = environment.variables.map((v) => (v.secret && !v.value ? { ...v, value: null } : v));
+    try {
+      setEnvironment(await invoke<ServiceEnvironment>('set_service_environment', {
+        id: serviceId,
+        environment: { ...environment, variables },
+      }));
+      setPreview(null);
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const handlePreview = async () => {
+    try {
+      setPreview(await invoke<ResolvedEnvVar[]>('preview_service_environment', { id: serviceId }));
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  return (
+    <div className={`service-environment ${theme}`}>
+      <h4 className={`form-label ${theme}`}>Env files (later files override earlier ones)</h4>
+      <ul className="env-list">
+        {environment.envFiles.map((path, idx) => (
+          <li key={path} className="env-row">
+            <span className={`service-working-dir ${theme}`}>{path}</span>
+            <button onClick={() => moveFile(idx, -1)} disabled={idx === 0} className={`btn-secondary ${theme}`}>↑</button>
+            <button
+              onClick={() => moveFile(idx, 1)}
+              disabled={idx === environment.envFiles.length - 1}
+              className={`btn-secondary ${theme}`}
+            >
+              ↓
+            </button>
+            <button
+              onClick={() => setEnvironment({ ...environment, envFiles: environment.envFiles.filter((_, i) => i !== idx) })}
+              className={`btn-icon ${theme}`}
+              title="Remove env file"
+            >
+              ×
+            </button>
+          </li>
+        ))}
+      </ul>
+      <div className="env-row">
+        <input
+          type="text"
+          list={`env-files-${serviceId}`}
+          value={newFile}
+          onChange={(e) => setNewFile(e.target.value)}
+          placeholder=".env"
+          className={`form-input ${theme}`}
+        />
+        <datalist id={`env-files-${serviceId}`}>
+          {candidates.map((path) => <option key={path} value={path} />)}
+        </datalist>
+        <button onClick={() => addFile(newFile)} className={`btn-secondary ${theme}`}>Add file</button>
+      </div>
+
+      <h4 className={`form-label ${theme}`}>Variables (override env files)</h4>
+      {environment.variables.map((variable, idx) => (
+        <div key={idx} className="env-row">
+          <input
+            type="text"
+            value={variable.name}
+            onChange={(e) => updateVariable(idx, { name: e.target.value })}
+            placeholder="NAME"
+            className={`form-input ${theme}`}
+          />
+          <input
+            type={variable.secret ? 'password' : 'text'}
+            value={variable.value ?? ''}
+            onChange={(e) => updateVariable(idx, { value: e.target.value })}
+            // Stored secrets are left alone unless a new value is typed
+            placeholder={variable.secret ? MASK : 'value'}
+            className={`form-input ${theme}`}
+          />
+          <label className={`form-checkbox-label ${theme}`}>
+            <input
+              type="checkbox"
+              checked={variable.secret}
+              onChange={(e) => updateVariable(idx, { secret: e.target.checked })}
+              className="form-checkbox"
+            />
+            Secret
+          </label>
+          <button
+            onClick={() => setEnvironment({ ...environment, variables: environment.variables.filter((_, i) => i !== idx) })}
+            className={`btn-icon ${theme}`}
+            title="Remove variable"
+          >
+            ×
+          </button>
+        </div>
+      ))}
+      <div className="service-actions">
+        <button
+          onClick={() => setEnvironment({ ...environment, variables: [...environment.variables, { name: '', value: '', secret: false }] })}
+          className={`btn-secondary ${theme}`}
+        >
+          Add variable
+        </button>
+        <button onClick={handleSave} className={`btn-primary ${theme}`}>Save</button>
+        <button onClick={handlePreview} className={`btn-secondary ${theme}`}>Preview</button>
+        <button onClick={onClose} className={`btn-secondary ${theme}`}>Close</button>
+      </div>
+
+      {preview && (
+        <ul className="env-list">
+          {preview.map((variable) => (
+            <li key={variable.name} className={`service-working-dir ${theme}`}>
+              {variable.name}={variable.value ?? MASK}
+              <span className="env-source">
+                {variable.source.kind === 'file' ? variable.source.path : 'service'}
+              </span>
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+}
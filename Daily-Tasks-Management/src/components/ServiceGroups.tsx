@@ -0,0 +1,160 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { GroupStartReport, Service, ServiceGroup } from '../types/Services';
+import { errorMessage } from '../types/Errors';
+
+interface ServiceGroupsProps {
+  services: Service[];
+  // Called after a group was started or stopped, to refresh service states
+  onChanged: () => void;
+  onError: (message: string) => void;
+  isDarkMode: boolean;
+}
+
+export function ServiceGroups({ services, onChanged, onError, isDarkMode }: ServiceGroupsProps) {
+  const [groups, setGroups] = useState<ServiceGroup[]>([]);
+  const [editing, setEditing] = useState<ServiceGroup | null>(null);
+  const [busy, setBusy] = useState<number | null>(null);
+  const [message, setMessage] = useState<string | null>(null);
+  const theme = isDarkMode ? 'dark' : 'light';
+
+  useEffect(() => {
+    loadGroups();
+  }, []);
+
+  const loadGroups = async () => {
+    try {
+      setGroups(await invoke<ServiceGroup[]>('get_service_groups'));
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const serviceName = (id: number) => services.find((s) => s.id === id)?.name ?? `#${id}`;
+
+  const handleSave = async () => {
+    if (!editing) return;
+    try {
+      if (editing.id === 0) {
+        await invoke('create_service_group', { name: editing.name, serviceIds: editing.serviceIds });
+      } else {
+        await invoke('update_service_group', { group: editing });
+      }
+      setEditing(null);
+      await loadGroups();
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const handleDelete = async (group: ServiceGroup) => {
+    if (!confirm(`Delete the group "${group.name}"? Its services are kept.`)) return;
+    try {
+      await invoke('delete_service_group', { id: group.id });
+      await loadGroups();
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const handleStart = async (group: ServiceGroup) => {
+    setBusy(group.id);
+    setMessage(null);
+    try {
+      const report = await invoke<GroupStartReport>('start_service_group', { id: group.id });
+      const parts = [`${report.started.length} started`, `${report.alreadyRunning.length} already running`];
+      if (report.failed) {
+        parts.push(`${report.failed.name} failed: ${report.failed.error}`);
+        if (report.skipped.length > 0) {
+          parts.push(`not started: ${report.skipped.map(serviceName).join(', ')}`);
+        }
+      }
+      setMessage(`${group.name}: ${parts.join('; ')}`);
+    } catch (err) {
+      onError(errorMessage(err));
+    } finally {
+      setBusy(null);
+      onChanged();
+    }
+  };
+
+  const handleStop = async (group: ServiceGroup) => {
+    setBusy(group.id);
+    setMessage(null);
+    try {
+      const stopped = await invoke<number[]>('stop_service_group', { id: group.id });
+      setMessage(`${group.name}: ${stopped.length} stopped`);
+    } catch (err) {
+      onError(errorMessage(err));
+    } finally {
+      setBusy(null);
+      onChanged();
+    }
+  };
+
+  const toggleMember = (id: number) => {
+    if (!editing) return;
+    const serviceIds = editing.serviceIds.includes(id)
+      ? editing.serviceIds.filter((s) => s !== id)
+      : [...editing.serviceIds, id];
+    setEditing({ ...editing, serviceIds });
+  };
+
+  return (
+    <div className={`service-groups ${theme}`}>
+      <div className="service-actions">
+        <h3 className={`service-name ${theme}`}>Groups</h3>
+        <button
+          onClick={() => setEditing({ id: 0, name: '', serviceIds: [], createdAt: '' })}
+          className={`btn-secondary ${theme}`}
+        >
+          + New group
+        </button>
+      </div>
+
+      {message && <div className={`service-working-dir ${theme}`}>{message}</div>}
+
+      {groups.map((group) => (
+        <div key={group.id} className="env-row">
+          <span className={`service-name ${theme}`}>{group.name}</span>
+          <span className={`service-working-dir ${theme}`}>{group.serviceIds.map(serviceName).join(', ')}</span>
+          <button onClick={() => handleStart(group)} disabled={busy !== null} className={`btn-start ${theme}`}>
+            {busy === group.id ? 'Working...' : 'Start'}
+          </button>
+          <button onClick={() => handleStop(group)} disabled={busy !== null} className={`btn-stop ${theme}`}>
+            Stop
+          </button>
+          <button onClick={() => setEditing(group)} className={`btn-secondary ${theme}`}>Edit</button>
+          <button onClick={() => handleDelete(group)} className={`btn-icon ${theme}`} title="Delete group">×</button>
+        </div>
+      ))}
+
+      {editing && (
+        <div className={`service-environment ${theme}`}>
+          <input
+            type="text"
+            value={editing.name}
+            onChange={(e) => setEditing({ ...editing, name: e.target.value })}
+            placeholder="Full stack"
+            className={`form-input ${theme}`}
+          />
+          {services.map((service) => (
+            <label key={service.id} className={`form-checkbox-label ${theme}`}>
+              <input
+                type="checkbox"
+                checked={editing.serviceIds.includes(service.id)}
+                onChange={() => toggleMember(service.id)}
+                className="form-checkbox"
+              />
+              {service.name}
+            </label>
+          ))}
+          <div className="service-actions">
+            <button onClick={handleSave} className={`btn-primary ${theme}`}>Save</button>
+            <button onClick={() => setEditing(null)} className={`btn-secondary ${theme}`}>Cancel</button>
+          </div>
+        </div>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,205 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { HealthChecks, Probe, ProbeCheck } from '../types/Services';
+import { errorMessage } from '../types/Errors';
+
+interface ServiceHealthEditorProps {
+  serviceId: number;
+  onClose: () => void;
+  onError: (message: string) => void;
+  isDarkMode: boolean;
+}
+
+type ProbeName = 'readiness' | 'liveness';
+type ProbeKind = ProbeCheck['kind'] | 'none';
+
+const KINDS: { value: ProbeKind; label: string }[] = [
+  { value: 'none', label: 'None' },
+  { value: 'http', label: 'HTTP GET' },
+  { value: 'tcp', label: 'TCP port' },
+  { value: 'log', label: 'Log pattern' },
+  { value: 'command', label: 'Command' },
+];
+
+function defaultCheck(kind: ProbeCheck['kind']): ProbeCheck {
+  switch (kind) {
+    case 'http':
+      return { kind, url: 'http://localhost:3000/', expectedStatus: 200 };
+    case 'tcp':
+      return { kind, port: 3000 };
+    case 'log':
+      return { kind, pattern: '' };
+    case 'command':
+      return { kind, command: '' };
+  }
+}
+
+export function ServiceHealthEditor({ serviceId, onClose, onError, isDarkMode }: ServiceHealthEditorProps) {
+  const [checks, setChecks] = useState<HealthChecks | null>(null);
+  const theme = isDarkMode ? 'dark' : 'light';
+
+  useEffect(() => {
+    loadChecks();
+  }, [serviceId]);
+
+  const loadChecks = async () => {
+    try {
+      setChecks(await invoke<HealthChecks>('get_health_checks', { id: serviceId }));
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  if (!checks) return null;
+
+  const setProbe = (name: ProbeName, probe: Probe | null) => setChecks({ ...checks, [name]: probe });
+
+  const setKind = (name: ProbeName, kind: ProbeKind) => {
+    if (kind === 'none') return setProbe(name, null);
+    const current = checks[name];
+    setProbe(name, {
+      intervalMs: current?.intervalMs ?? 2000,
+      timeoutMs: current?.timeoutMs ?? 1000,
+      failureThreshold: current?.failureThreshold ?? 3,
+      check: defaultCheck(kind),
+    });
+  };
+
+  const handleSave = async () => {
+    try {
+      await invoke('set_health_checks', { id: serviceId, checks });
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const renderTarget = (name: ProbeName, probe: Probe) => {
+    const check = probe.check;
+    const update = (changed: ProbeCheck) => setProbe(name, { ...probe, check: changed });
+    switch (check.kind) {
+      case 'http':
+        return (
+          <>
+            <input
+              type="text"
+              value={check.url}
+              onChange={(e) => update({ ...check, url: e.target.value })}
+              placeholder="http://localhost:3000/health"
+              className={`form-input ${theme}`}
+            />
+            <input
+              type="number"
+              min={100}
+              max={599}
+              value={check.expectedStatus}
+              onChange={(e) => update({ ...check, expectedStatus: Number(e.target.value) || 0 })}
+              className={`form-input ${theme}`}
+            />
+          </>
+        );
+      case 'tcp':
+        return (
+          <input
+            type="number"
+            min={1}
+            max={65535}
+            value={check.port}
+            onChange={(e) => update({ ...check, port: Number(e.target.value) || 0 })}
+            className={`form-input ${theme}`}
+          />
+        );
+      case 'log':
+        return (
+          <input
+            type="text"
+            value={check.pattern}
+            onChange={(e) => update({ ...check, pattern: e.target.value })}
+            placeholder="listening on \d+"
+            className={`form-input ${theme}`}
+          />
+        );
+      case 'command':
+        return (
+          <input
+            type="text"
+            value={check.command}
+            onChange={(e) => update({ ...check, command: e.target.value })}
+            placeholder="pg_isready -p 5432"
+            className={`form-input ${theme}`}
+          />
+        );
+    }
+  };
+
+  const renderProbe = (name: ProbeName, title: string) => {
+    const probe = checks[name];
+    // A log line seen once says nothing about whether the service is still alive
+    const kinds = name === 'liveness' ? KINDS.filter((k) => k.value !== 'log') : KINDS;
+    const updateNumber = (field: 'intervalMs' | 'timeoutMs' | 'failureThreshold', value: string) => {
+      if (probe) setProbe(name, { ...probe, [field]: Math.max(0, Number(value) || 0) });
+    };
+
+    return (
+      <>
+        <h4 className={`form-label ${theme}`}>{title}</h4>
+        <div className="env-row">
+          <select
+            value={probe?.check.kind ?? 'none'}
+            onChange={(e) => setKind(name, e.target.value as ProbeKind)}
+            className={`form-input ${theme}`}
+          >
+            {kinds.map((kind) => <option key={kind.value} value={kind.value}>{kind.label}</option>)}
+          </select>
+          {probe && renderTarget(name, probe)}
+        </div>
+        {probe && (
+          <div className="env-row">
+            <label className={`form-label ${theme}`}>
+              Every (ms)
+              <input
+                type="number"
+                min={100}
+                value={probe.intervalMs}
+                onChange={(e) => updateNumber('intervalMs', e.target.value)}
+                className={`form-input ${theme}`}
+              />
+            </label>
+            <label className={`form-label ${theme}`}>
+              Timeout (ms)
+              <input
+                type="number"
+                min={1}
+                value={probe.timeoutMs}
+                onChange={(e) => updateNumber('timeoutMs', e.target.value)}
+                className={`form-input ${theme}`}
+              />
+            </label>
+            {name === 'liveness' && (
+              <label className={`form-label ${theme}`}>
+                Failures before restart
+                <input
+                  type="number"
+                  min={1}
+                  value={probe.failureThreshold}
+                  onChange={(e) => updateNumber('failureThreshold', e.target.value)}
+                  className={`form-input ${theme}`}
+                />
+              </label>
+            )}
+          </div>
+        )}
+      </>
+    );
+  };
+
+  return (
+    <div className={`service-environment ${theme}`}>
+      {renderProbe('readiness', 'Readiness (applies from the next start)')}
+      {renderProbe('liveness', 'Liveness (restarts the service unless its restart policy is never)')}
+      <div className="service-actions">
+        <button onClick={handleSave} className={`btn-primary ${theme}`}>Save</button>
+        <button onClick={onClose} className={`btn-secondary ${theme}`}>Close</button>
+      </div>
+    </div>
+  );
+}
@@ -0,0 +1,80 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { LogEntry, LogFormat, LogParser } from '../types/Logs';
+import { errorMessage } from '../types/Errors';
+
+interface ServiceLogParserEditorProps {
+  serviceId: number;
+  onClose: () => void;
+  onError: (message: string) => void;
+  isDarkMode: boolean;
+}
+
+const FORMATS: { value: LogFormat; label: string }[] = [
+  { value: 'auto', label: 'Detect (JSON, logfmt, level prefix)' },
+  { value: 'json', label: 'JSON' },
+  { value: 'logfmt', label: 'logfmt' },
+  { value: 'prefix', label: 'Level prefix like [WARN] or error:' },
+  { value: 'plain', label: 'Plain (level by stream)' },
+];
+
+const LEVELS: LogEntry['level'][] = ['debug', 'info', 'warn', 'error'];
+
+export function ServiceLogParserEditor({ serviceId, onClose, onError, isDarkMode }: ServiceLogParserEditorProps) {
+  const [parser, setParser] = useState<LogParser | null>(null);
+  const theme = isDarkMode ? 'dark' : 'light';
+
+  useEffect(() => {
+    invoke<LogParser>('get_log_parser', { id: serviceId })
+      .then(setParser)
+      .catch(err => onError(errorMessage(err)));
+  }, [serviceId]);
+
+  if (!parser) return null;
+
+  const handleSave = async () => {
+    try {
+      await invoke('set_log_parser', { id: serviceId, parser });
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  return (
+    <div className={`service-environment ${theme}`}>
+      <h4 className={`form-label ${theme}`}>Log parsing (applies from the next start)</h4>
+      <div className="env-row">
+        <select
+          value={parser.format}
+          onChange={(e) => setParser({ ...parser, format: e.target.value as LogFormat })}
+          className={`form-input ${theme}`}
+        >
+          {FORMATS.map((format) => <option key={format.value} value={format.value}>{format.label}</option>)}
+        </select>
+        <label className={`form-label ${theme}`}>
+          Other stderr lines
+          <select
+            value={parser.stderrLevel}
+            onChange={(e) => setParser({ ...parser, stderrLevel: e.target.value as LogEntry['level'] })}
+            className={`form-input ${theme}`}
+          >
+            {LEVELS.map((level) => <option key={level} value={level}>{level}</option>)}
+          </select>
+        </label>
+        <label className={`form-label ${theme}`}>
+          <input
+            type="checkbox"
+            checked={parser.keepAnsi}
+            onChange={(e) => setParser({ ...parser, keepAnsi: e.target.checked })}
+            className="form-checkbox"
+          />
+          Keep colors
+        </label>
+      </div>
+      <div className="service-actions">
+        <button onClick={handleSave} className={`btn-primary ${theme}`}>Save</button>
+        <button onClick={onClose} className={`btn-secondary ${theme}`}>Close</button>
+      </div>
+    </div>
+  );
+}
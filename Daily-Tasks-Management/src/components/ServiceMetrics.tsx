@@ -0,0 +1,52 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { ResourceSample } from '../types/Services';
+
+interface ServiceMetricsProps {
+  serviceId: number;
+  isDarkMode: boolean;
+}
+
+// Matches the backend's sampling interval
+const REFRESH_MS = 2000;
+const SPARKLINE_SAMPLES = 60;
+
+function formatBytes(bytes: number): string {
+  if (bytes >= 1024 * 1024 * 1024) return `${(bytes / 1024 / 1024 / 1024).toFixed(1)} GB`;
+  return `${(bytes / 1024 / 1024).toFixed(1)} MB`;
+}
+
+export function ServiceMetrics({ serviceId, isDarkMode }: ServiceMetricsProps) {
+  const [samples, setSamples] = useState<ResourceSample[]>([]);
+  const theme = isDarkMode ? 'dark' : 'light';
+
+  useEffect(() => {
+    const load = () =>
+      invoke<ResourceSample[]>('get_service_metrics', { id: serviceId, limit: SPARKLINE_SAMPLES })
+        .then(setSamples)
+        .catch(err => console.error('Failed to load service metrics:', err));
+    load();
+    const timer = setInterval(load, REFRESH_MS);
+    return () => clearInterval(timer);
+  }, [serviceId]);
+
+  const latest = samples[samples.length - 1];
+  if (!latest) return null;
+
+  const peak = Math.max(100, ...samples.map(s => s.cpuPercent));
+  const points = samples
+    .map((s, i) => `${(i / Math.max(1, SPARKLINE_SAMPLES - 1)) * 100},${20 - (s.cpuPercent / peak) * 20}`)
+    .join(' ');
+
+  return (
+    <div className={`service-metrics service-working-dir ${theme}`}>
+      <svg className="service-metrics-sparkline" viewBox="0 0 100 20" preserveAspectRatio="none">
+        <polyline points={points} fill="none" stroke="currentColor" strokeWidth="1" />
+      </svg>
+      CPU {latest.cpuPercent.toFixed(1)}% · {formatBytes(latest.rssBytes)}
+      {latest.threads != null && ` · ${latest.threads} threads`}
+      {latest.processes > 1 && ` · ${latest.processes} processes`}
+      {latest.openPorts.length > 0 && ` · ports ${latest.openPorts.join(', ')}`}
+    </div>
+  );
+}
@@ -0,0 +1,122 @@
+import { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { CrashRecord, RestartMode, RestartPolicy, describeExit } from '../types/Services';
+import { errorMessage } from '../types/Errors';
+
+interface ServiceRestartEditorProps {
+  serviceId: number;
+  onClose: () => void;
+  onError: (message: string) => void;
+  isDarkMode: boolean;
+}
+
+const MODES: { value: RestartMode; label: string }[] = [
+  { value: 'never', label: 'Never' },
+  { value: 'on-failure', label: 'On failure' },
+  { value: 'always', label: 'Always' },
+];
+
+export function ServiceRestartEditor({ serviceId, onClose, onError, isDarkMode }: ServiceRestartEditorProps) {
+  const [policy, setPolicy] = useState<RestartPolicy | null>(null);
+  const [crashes, setCrashes] = useState<CrashRecord[]>([]);
+  const [expanded, setExpanded] = useState<number | null>(null);
+  const theme = isDarkMode ? 'dark' : 'light';
+
+  useEffect(() => {
+    loadPolicy();
+  }, [serviceId]);
+
+  const loadPolicy = async () => {
+    try {
+      setPolicy(await invoke<RestartPolicy>('get_restart_policy', { id: serviceId }));
+      setCrashes(await invoke<CrashRecord[]>('get_service_crashes', { id: serviceId }));
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  if (!policy) return null;
+
+  const updateNumber = (field: 'maxRetries' | 'initialBackoffMs' | 'maxBackoffMs', value: string) => {
+    setPolicy({ ...policy, [field]: Math.max(0, Number(value) || 0) });
+  };
+
+  const handleSave = async () => {
+    try {
+      await invoke('set_restart_policy', { id: serviceId, policy });
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  return (
+    <div className={`service-environment ${theme}`}>
+      <h4 className={`form-label ${theme}`}>Restart policy (applies from the next start)</h4>
+      <div className="env-row">
+        <select
+          value={policy.mode}
+          onChange={(e) => setPolicy({ ...policy, mode: e.target.value as RestartMode })}
+          className={`form-input ${theme}`}
+        >
+          {MODES.map((mode) => <option key={mode.value} value={mode.value}>{mode.label}</option>)}
+        </select>
+        <label className={`form-label ${theme}`}>
+          Max retries
+          <input
+            type="number"
+            min={0}
+            value={policy.maxRetries}
+            onChange={(e) => updateNumber('maxRetries', e.target.value)}
+            className={`form-input ${theme}`}
+          />
+        </label>
+        <label className={`form-label ${theme}`}>
+          First delay (ms)
+          <input
+            type="number"
+            min={1}
+            value={policy.initialBackoffMs}
+            onChange={(e) => updateNumber('initialBackoffMs', e.target.value)}
+            className={`form-input ${theme}`}
+          />
+        </label>
+        <label className={`form-label ${theme}`}>
+          Longest delay (ms)
+          <input
+            type="number"
+            min={1}
+            value={policy.maxBackoffMs}
+            onChange={(e) => updateNumber('maxBackoffMs', e.target.value)}
+            className={`form-input ${theme}`}
+          />
+        </label>
+      </div>
+      <div className="service-actions">
+        <button onClick={handleSave} className={`btn-primary ${theme}`}>Save</button>
+        <button onClick={loadPolicy} className={`btn-secondary ${theme}`}>Refresh crashes</button>
+        <button onClick={onClose} className={`btn-secondary ${theme}`}>Close</button>
+      </div>
+
+      <h4 className={`form-label ${theme}`}>Crashes</h4>
+      {crashes.length === 0 ? (
+        <div className={`service-working-dir ${theme}`}>No crashes since the app started.</div>
+      ) : (
+        <ul className="env-list">
+          {crashes.map((crash, idx) => (
+            <li key={`${crash.timestamp}-${idx}`} className={`service-working-dir ${theme}`}>
+              <button onClick={() => setExpanded(expanded === idx ? null : idx)} className={`btn-secondary ${theme}`}>
+                {expanded === idx ? '▾' : '▸'}
+              </button>
+              {' '}{new Date(crash.timestamp).toLocaleString()}: {describeExit(crash.exit)} after{' '}
+              {Math.round(crash.uptimeMs / 1000)}s{crash.reason ? ` (${crash.reason})` : ''}
+              {crash.willRestart ? ', restarted' : ''}
+              {expanded === idx && (
+                <pre className="crash-logs">{crash.lastLogs.join('\n')}</pre>
+              )}
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+}
@@ -1,6 +1,18 @@
 import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { Service } from '../types/Services';
+import { listen } from '@tauri-apps/api/event';
+import {
+  AUTO_START_EVENT, AutoStartEvent, SERVICE_STATUS_EVENT, Service, ServiceStatus, describeExit,
+} from '../types/Services';
+import { AppSettings } from '../types/Settings';
+import { ServiceEnvironmentEditor } from './ServiceEnvironmentEditor';
+import { ServiceRestartEditor } from './ServiceRestartEditor';
+import { ServiceLogParserEditor } from './ServiceLogParserEditor';
+import { ServiceHealthEditor } from './ServiceHealthEditor';
+import { ServiceMetrics } from './ServiceMetrics';
+import { ServiceDependenciesEditor } from './ServiceDependenciesEditor';
+import { ServiceGroups } from './ServiceGroups';
+import { errorMessage } from '../types/Errors';
 import '../styles/services-panel.css';
 
 interface ServicesPanelProps {
@@ -12,19 +24,73 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const [showAddForm, setShowAddForm] = useState(false);
-  const [serviceStatuses, setServiceStatuses] = useState<Record<number, boolean>>({});
+  const [serviceStatuses, setServiceStatuses] = useState<Record<number, ServiceStatus>>({});
+  const [environmentFor, setEnvironmentFor] = useState<number | null>(null);
+  const [restartFor, setRestartFor] = useState<number | null>(null);
+  const [healthFor, setHealthFor] = useState<number | null>(null);
+  const [logParserFor, setLogParserFor] = useState<number | null>(null);
+  const [dependenciesFor, setDependenciesFor] = useState<number | null>(null);
+  const [autoStartEvents, setAutoStartEvents] = useState<AutoStartEvent[]>([]);
+  const [settings, setSettings] = useState<AppSettings | null>(null);
+  const [safeMode, setSafeMode] = useState(false);
   const [newService, setNewService] = useState({
     name: '',
     command: '',
     workingDir: '',
     projectId: undefined as number | undefined,
     autoStart: false,
+    shell: false,
   });
 
   useEffect(() => {
     loadServices();
+    loadSettings();
   }, []);
 
+  // Auto-start may have begun before this panel mounted, so the events so
+  // far are loaded first and later ones appended
+  useEffect(() => {
+    invoke<AutoStartEvent[]>('get_auto_start_events')
+      .then(setAutoStartEvents)
+      .catch(err => console.error('Failed to load auto-start events:', err));
+
+    const unlisten = listen<AutoStartEvent>(AUTO_START_EVENT, ({ payload }) => {
+      setAutoStartEvents(prev => [...prev, payload]);
+      if (payload.kind === 'started' || payload.kind === 'failed') {
+        refreshStatus(payload.serviceId);
+      }
+    });
+    return () => {
+      unlisten.then(stop => stop());
+    };
+  }, []);
+
+  const loadSettings = async () => {
+    try {
+      setSettings(await invoke<AppSettings>('get_app_settings'));
+      setSafeMode(await invoke<boolean>('is_safe_mode'));
+      const loadError = await invoke<string | null>('get_settings_load_error');
+      if (loadError) setError(loadError);
+    } catch (err) {
+      console.error('Failed to load settings:', err);
+    }
+  };
+
+  const handleToggleSkipAutoStart = () => {
+    if (settings) saveSettings({ ...settings, skipAutoStart: !settings.skipAutoStart });
+  };
+
+  const saveSettings = async (updated: AppSettings) => {
+    try {
+      await invoke('update_app_settings', { newSettings: updated });
+      setSettings(updated);
+      setError(null);
+    } catch (err) {
+      setError(errorMessage(err));
+      console.error('Failed to update settings:', err);
+    }
+  };
+
   const loadServices = async () => {
     try {
       setLoading(true);
@@ -32,25 +98,52 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
       const servicesData = await invoke<Service[]>('get_all_services');
       setServices(servicesData);
       
-      // Check status for each service
-      const statuses: Record<number, boolean> = {};
-      for (const service of servicesData) {
-        try {
-          const isRunning = await invoke<boolean>('get_service_status', { id: service.id });
-          statuses[service.id] = isRunning;
-        } catch {
-          statuses[service.id] = false;
-        }
-      }
-      setServiceStatuses(statuses);
+      await refreshStatuses(servicesData);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to load services:', err);
     } finally {
       setLoading(false);
     }
   };
 
+  const refreshStatuses = async (servicesData: Service[]) => {
+    const statuses: Record<number, ServiceStatus> = {};
+    for (const service of servicesData) {
+      try {
+        statuses[service.id] = await invoke<ServiceStatus>('get_service_state', { id: service.id });
+      } catch (err) {
+        console.error('Failed to get service state:', err);
+      }
+    }
+    setServiceStatuses(statuses);
+  };
+
+  const refreshStatus = async (id: number) => {
+    try {
+      const status = await invoke<ServiceStatus>('get_service_state', { id });
+      setServiceStatuses(prev => ({ ...prev, [id]: status }));
+    } catch (err) {
+      console.error('Failed to get service state:', err);
+    }
+  };
+
+  // Readiness and health change between polls
+  useEffect(() => {
+    const unlisten = listen<ServiceStatus>(SERVICE_STATUS_EVENT, ({ payload }) => {
+      setServiceStatuses(prev => ({ ...prev, [payload.serviceId]: payload }));
+    });
+    return () => {
+      unlisten.then(stop => stop());
+    };
+  }, []);
+
+  // The supervisor restarts crashed services in the background
+  useEffect(() => {
+    const timer = setInterval(() => refreshStatuses(services), 3000);
+    return () => clearInterval(timer);
+  }, [services]);
+
   const handleAddService = async () => {
     if (!newService.name.trim() || !newService.command.trim()) {
       setError('Service name and command are required');
@@ -65,6 +158,7 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
         workingDir: newService.workingDir.trim() || undefined,
         projectId: newService.projectId || undefined,
         autoStart: newService.autoStart,
+        shell: newService.shell,
       });
       setServices(prev => [service, ...prev]);
       setNewService({
@@ -73,10 +167,11 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
         workingDir: '',
         projectId: undefined,
         autoStart: false,
+        shell: false,
       });
       setShowAddForm(false);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to add service:', err);
     }
   };
@@ -84,10 +179,10 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
   const handleStartService = async (id: number) => {
     try {
       await invoke('start_service', { id });
-      setServiceStatuses(prev => ({ ...prev, [id]: true }));
+      await refreshStatus(id);
       setError(null);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to start service:', err);
     }
   };
@@ -95,14 +190,26 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
   const handleStopService = async (id: number) => {
     try {
       await invoke('stop_service', { id });
-      setServiceStatuses(prev => ({ ...prev, [id]: false }));
+      await refreshStatus(id);
       setError(null);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to stop service:', err);
     }
   };
 
+  const handleToggleShell = async (service: Service) => {
+    const updated = { ...service, shell: !service.shell };
+    try {
+      await invoke('update_service', { service: updated });
+      setServices(prev => prev.map(s => s.id === service.id ? updated : s));
+      setError(null);
+    } catch (err) {
+      setError(errorMessage(err));
+      console.error('Failed to update service:', err);
+    }
+  };
+
   const handleRemoveService = async (id: number) => {
     if (!confirm('Are you sure you want to remove this service?')) return;
 
@@ -111,11 +218,54 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
       setServices(prev => prev.filter(s => s.id !== id));
       setError(null);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
       console.error('Failed to remove service:', err);
     }
   };
 
+  const autoStartSummary = () => {
+    const last = autoStartEvents[autoStartEvents.length - 1];
+    if (!last) return null;
+    const failures = autoStartEvents.filter(
+      (e): e is Extract<AutoStartEvent, { kind: 'failed' }> => e.kind === 'failed'
+    );
+    switch (last.kind) {
+      case 'skipped':
+        return { text: `Auto-start skipped: ${last.reason}`, failures };
+      case 'finished':
+        return { text: `Auto-start finished: ${last.started} started, ${last.failed} failed`, failures };
+      case 'starting':
+        return { text: `Auto-starting ${last.name} (${last.index} of ${last.total})...`, failures };
+      default:
+        return { text: `Auto-starting services...`, failures };
+    }
+  };
+
+  const summary = autoStartSummary();
+
+  const describeStatus = (status?: ServiceStatus) => {
+    const exit = status?.lastExit ? ` (${describeExit(status.lastExit)})` : '';
+    switch (status?.state) {
+      case 'running': {
+        const restarted = status.restarts > 0 ? `, restarted ${status.restarts}×` : '';
+        if (status.healthy === false) return `Unhealthy: ${status.liveness?.message ?? 'liveness probe failed'}${restarted}`;
+        if (!status.ready) {
+          const waiting = status.readiness && !status.readiness.ok ? ` (${status.readiness.message})` : '';
+          return `Starting${waiting}${restarted}`;
+        }
+        return `Running${restarted}`;
+      }
+      case 'restarting':
+        return `Restarting${exit}`;
+      case 'exited':
+        return `Exited${exit}`;
+      case 'gave-up':
+        return `Crashed, gave up after ${status.restarts} restarts${exit}`;
+      default:
+        return 'Stopped';
+    }
+  };
+
   if (loading) {
     return (
       <div className={`services-panel ${isDarkMode ? 'dark' : 'light'}`}>
@@ -138,6 +288,43 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
         </button>
       </div>
 
+      {summary && (
+        <div className={`auto-start-status ${isDarkMode ? 'dark' : 'light'}`}>
+          <div>{summary.text}</div>
+          {summary.failures.map(f => (
+            <div key={f.serviceId} className="auto-start-failure">
+              {f.name}: {f.error}
+            </div>
+          ))}
+        </div>
+      )}
+
+      {settings && (
+        <label className={`form-checkbox-label ${isDarkMode ? 'dark' : 'light'}`}>
+          <input
+            type="checkbox"
+            checked={settings.skipAutoStart}
+            onChange={handleToggleSkipAutoStart}
+            className="form-checkbox"
+          />
+          Skip auto-start on launch{safeMode ? ' (this launch is in safe mode)' : ''}
+        </label>
+      )}
+
+      {settings && (
+        <label className={`form-label ${isDarkMode ? 'dark' : 'light'}`}>
+          Seconds a stopped service gets before it is killed
+          <input
+            type="number"
+            min={0}
+            max={120}
+            value={settings.stopGracePeriodMs / 1000}
+            onChange={(e) => saveSettings({ ...settings, stopGracePeriodMs: Math.round((Number(e.target.value) || 0) * 1000) })}
+            className={`form-input ${isDarkMode ? 'dark' : 'light'}`}
+          />
+        </label>
+      )}
+
       {error && (
         <div className="error-message">
           <div className="error-content">
@@ -192,6 +379,17 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
               Auto-start on app launch
             </label>
           </div>
+          <div className="form-group">
+            <label className={`form-checkbox-label ${isDarkMode ? 'dark' : 'light'}`}>
+              <input
+                type="checkbox"
+                checked={newService.shell}
+                onChange={(e) => setNewService({ ...newService, shell: e.target.checked })}
+                className="form-checkbox"
+              />
+              Run in shell (pipes, redirects, $VARS)
+            </label>
+          </div>
           <button
             onClick={handleAddService}
             className={`btn-primary ${isDarkMode ? 'dark' : 'light'}`}
@@ -201,6 +399,15 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
         </div>
       )}
 
+      {services.length > 0 && (
+        <ServiceGroups
+          services={services}
+          onChanged={() => refreshStatuses(services)}
+          onError={setError}
+          isDarkMode={isDarkMode}
+        />
+      )}
+
       {services.length === 0 ? (
         <div className={`empty-state ${isDarkMode ? 'dark' : 'light'}`}>
           <p>No services registered. Add your first service to get started.</p>
@@ -208,7 +415,8 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
       ) : (
         <div className="services-list">
           {services.map((service) => {
-            const isRunning = serviceStatuses[service.id] || false;
+            const status = serviceStatuses[service.id];
+            const isRunning = status?.state === 'running';
             return (
               <div key={service.id} className={`service-card ${isDarkMode ? 'dark' : 'light'}`}>
                 <div className="service-card-header">
@@ -218,7 +426,7 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
                     </h3>
                     <div className={`service-status ${isRunning ? 'running' : 'stopped'} ${isDarkMode ? 'dark' : 'light'}`}>
                       <span className="status-dot"></span>
-                      {isRunning ? 'Running' : 'Stopped'}
+                      {describeStatus(status)}
                     </div>
                   </div>
                   <button
@@ -237,6 +445,7 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
                     {service.workingDir}
                   </div>
                 )}
+                {isRunning && <ServiceMetrics serviceId={service.id} isDarkMode={isDarkMode} />}
                 <div className="service-actions">
                   {isRunning ? (
                     <button
@@ -253,7 +462,87 @@ export function ServicesPanel({ isDarkMode }: ServicesPanelProps) {
                       Start
                     </button>
                   )}
+                  <label className={`form-checkbox-label ${isDarkMode ? 'dark' : 'light'}`}>
+                    <input
+                      type="checkbox"
+                      checked={service.shell}
+                      onChange={() => handleToggleShell(service)}
+                      className="form-checkbox"
+                    />
+                    Shell
+                  </label>
+                  <button
+                    onClick={() => setEnvironmentFor(environmentFor === service.id ? null : service.id)}
+                    className={`btn-secondary ${isDarkMode ? 'dark' : 'light'}`}
+                  >
+                    Environment
+                  </button>
+                  <button
+                    onClick={() => setRestartFor(restartFor === service.id ? null : service.id)}
+                    className={`btn-secondary ${isDarkMode ? 'dark' : 'light'}`}
+                  >
+                    Restarts
+                  </button>
+                  <button
+                    onClick={() => setHealthFor(healthFor === service.id ? null : service.id)}
+                    className={`btn-secondary ${isDarkMode ? 'dark' : 'light'}`}
+                  >
+                    Health
+                  </button>
+                  <button
+                    onClick={() => setLogParserFor(logParserFor === service.id ? null : service.id)}
+                    className={`btn-secondary ${isDarkMode ? 'dark' : 'light'}`}
+                  >
+                    Log format
+                  </button>
+                  <button
+                    onClick={() => setDependenciesFor(dependenciesFor === service.id ? null : service.id)}
+                    className={`btn-secondary ${isDarkMode ? 'dark' : 'light'}`}
+                  >
+                    Depends on
+                  </button>
                 </div>
+                {environmentFor === service.id && (
+                  <ServiceEnvironmentEditor
+                    serviceId={service.id}
+                    onClose={() => setEnvironmentFor(null)}
+                    onError={setError}
+                    isDarkMode={isDarkMode}
+                  />
+                )}
+                {restartFor === service.id && (
+                  <ServiceRestartEditor
+                    serviceId={service.id}
+                    onClose={() => setRestartFor(null)}
+                    onError={setError}
+                    isDarkMode={isDarkMode}
+                  />
+                )}
+                {healthFor === service.id && (
+                  <ServiceHealthEditor
+                    serviceId={service.id}
+                    onClose={() => setHealthFor(null)}
+                    onError={setError}
+                    isDarkMode={isDarkMode}
+                  />
+                )}
+                {logParserFor === service.id && (
+                  <ServiceLogParserEditor
+                    serviceId={service.id}
+                    onClose={() => setLogParserFor(null)}
+                    onError={setError}
+                    isDarkMode={isDarkMode}
+                  />
+                )}
+                {dependenciesFor === service.id && (
+                  <ServiceDependenciesEditor
+                    service={service}
+                    services={services}
+                    onClose={() => setDependenciesFor(null)}
+                    onError={setError}
+                    isDarkMode={isDarkMode}
+                  />
+                )}
               </div>
             );
           })}
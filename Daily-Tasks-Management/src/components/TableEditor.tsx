@@ -0,0 +1,270 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import {
+  CellValue,
+  ChangesetSummary,
+  EditableRows,
+  RowEdit,
+  formatCell,
+  parseParamValue,
+} from '../types/Database';
+import { errorMessage } from '../types/Errors';
+
+interface TableEditorProps {
+  databaseId: string;
+  table: string;
+  limit: number;
+  offset: number;
+  // Called after a commit so row counts can be reloaded
+  onCommitted: () => void;
+  onError: (message: string) => void;
+  isDarkMode: boolean;
+}
+
+// Blobs are shown as previews and cannot be edited as text
+const editableText = (cell: CellValue): string | null =>
+  cell.type === 'blob' ? null : cell.type === 'null' ? 'NULL' : formatCell(cell);
+
+function describeEdit(edit: RowEdit): string {
+  const key = 'key' in edit
+    ? Object.entries(edit.key).map(([column, value]) => `${column} = ${formatCell(value)}`).join(', ')
+    : '';
+  switch (edit.kind) {
+    case 'insert':
+      return `Insert into ${edit.table}`;
+    case 'update':
+      return `Update ${edit.table} (${key}): ${Object.keys(edit.values).join(', ')}`;
+    case 'delete':
+      return `Delete from ${edit.table} (${key})`;
+  }
+}
+
+export function TableEditor({ databaseId, table, limit, offset, onCommitted, onError, isDarkMode }: TableEditorProps) {
+  const [rows, setRows] = useState<EditableRows | null>(null);
+  const [pending, setPending] = useState<RowEdit[]>([]);
+  const [preview, setPreview] = useState<string[] | null>(null);
+  // Row being edited and its inputs by column name
+  const [editing, setEditing] = useState<{ index: number; values: Record<string, string> } | null>(null);
+  const [newRow, setNewRow] = useState<Record<string, string> | null>(null);
+  const [summary, setSummary] = useState<ChangesetSummary | null>(null);
+  const theme = isDarkMode ? 'dark' : 'light';
+
+  useEffect(() => {
+    loadRows();
+  }, [databaseId, table, limit, offset]);
+
+  useEffect(() => {
+    setPending([]);
+    setPreview(null);
+    setSummary(null);
+  }, [databaseId, table]);
+
+  const loadRows = async () => {
+    try {
+      setRows(await invoke<EditableRows>('get_editable_rows', { databaseId, table, limit, offset }));
+      setEditing(null);
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const rowKey = (index: number): Record<string, CellValue> =>
+    Object.fromEntries(rows!.key.map((column, i) => [column, rows!.keys[index][i]]));
+
+  const queue = (edit: RowEdit) => {
+    setPending([...pending, edit]);
+    setPreview(null);
+    setSummary(null);
+  };
+
+  const startEdit = (index: number) => {
+    const values: Record<string, string> = {};
+    rows!.data.columns.forEach((column, i) => {
+      const text = editableText(rows!.data.rows[index][i]);
+      if (text !== null) values[column.name] = text;
+    });
+    setEditing({ index, values });
+  };
+
+  const queueUpdate = () => {
+    if (!editing || !rows) return;
+    const values: Record<string, CellValue> = {};
+    rows.data.columns.forEach((column, i) => {
+      const input = editing.values[column.name];
+      if (input !== undefined && input !== editableText(rows.data.rows[editing.index][i])) {
+        values[column.name] = parseParamValue(input);
+      }
+    });
+    if (Object.keys(values).length > 0) {
+      queue({ kind: 'update', table, key: rowKey(editing.index), values });
+    }
+    setEditing(null);
+  };
+
+  const queueInsert = () => {
+    if (!newRow) return;
+    // Empty inputs fall back to the column default
+    const values = Object.fromEntries(
+      Object.entries(newRow)
+        .filter(([, input]) => input !== '')
+        .map(([column, input]) => [column, parseParamValue(input)]),
+    );
+    queue({ kind: 'insert', table, values });
+    setNewRow(null);
+  };
+
+  const handlePreview = async () => {
+    try {
+      setPreview(await invoke<string[]>('preview_database_changes', { databaseId, changes: pending }));
+    } catch (err) {
+      onError(errorMessage(err));
+    }
+  };
+
+  const handleCommit = async () => {
+    try {
+      setSummary(await invoke<ChangesetSummary>('apply_database_changes', { databaseId, changes: pending }));
+      setPending([]);
+      setPreview(null);
+      await loadRows();
+      onCommitted();
+    } catch (err) {
+      // Nothing was written; the changeset stays queued so it can be fixed
+      onError(errorMessage(err));
+    }
+  };
+
+  if (!rows) return null;
+
+  return (
+    <div className={`table-editor ${theme}`}>
+      <div className="editor-actions">
+        <button onClick={() => setNewRow({})} className={`btn-secondary ${theme}`}>
+          Add row
+        </button>
+        <span className={`info-text ${theme}`}>Rows are matched by {rows.key.join(', ')}</span>
+      </div>
+
+      <div className="data-table-container">
+        <table className={`data-table ${theme}`}>
+          <thead>
+            <tr>
+              {rows.data.columns.map((col) => (
+                <th key={col.name} className={`table-header ${theme}`}>
+                  {col.name}
+                  {col.declaredType && <span className="column-type">{col.declaredType}</span>}
+                </th>
+              ))}
+              <th className={`table-header ${theme}`} />
+            </tr>
+          </thead>
+          <tbody>
+            {newRow && (
+              <tr className={`table-row ${theme}`}>
+                {rows.data.columns.map((col) => (
+                  <td key={col.name} className={`table-cell ${theme}`}>
+                    <input
+                      type="text"
+                      value={newRow[col.name] ?? ''}
+                      onChange={(e) => setNewRow({ ...newRow, [col.name]: e.target.value })}
+                      placeholder="default"
+                      className={`form-input ${theme}`}
+                    />
+                  </td>
+                ))}
+                <td className={`table-cell ${theme}`}>
+                  <button onClick={queueInsert} className={`btn-primary ${theme}`}>Queue</button>
+                  <button onClick={() => setNewRow(null)} className={`btn-secondary ${theme}`}>Cancel</button>
+                </td>
+              </tr>
+            )}
+            {rows.data.rows.map((row, idx) => (
+              <tr key={idx} className={`table-row ${theme}`}>
+                {row.map((cell, cellIdx) => {
+                  const column = rows.data.columns[cellIdx].name;
+                  const input = editing?.index === idx ? editing.values[column] : undefined;
+                  return (
+                    <td key={cellIdx} className={`table-cell cell-${cell.type} ${theme}`}>
+                      {editing && input !== undefined ? (
+                        <input
+                          type="text"
+                          value={input}
+                          onChange={(e) =>
+                            setEditing({ ...editing, values: { ...editing.values, [column]: e.target.value } })
+                          }
+                          className={`form-input ${theme}`}
+                        />
+                      ) : (
+                        formatCell(cell)
+                      )}
+                    </td>
+                  );
+                })}
+                <td className={`table-cell ${theme}`}>
+                  {editing?.index === idx ? (
+                    <>
+                      <button onClick={queueUpdate} className={`btn-primary ${theme}`}>Queue</button>
+                      <button onClick={() => setEditing(null)} className={`btn-secondary ${theme}`}>Cancel</button>
+                    </>
+                  ) : (
+                    <>
+                      <button onClick={() => startEdit(idx)} className={`btn-secondary ${theme}`}>Edit</button>
+                      <button
+                        onClick={() => queue({ kind: 'delete', table, key: rowKey(idx) })}
+                        className={`btn-secondary ${theme}`}
+                      >
+                        Delete
+                      </button>
+                    </>
+                  )}
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      </div>
+
+      {pending.length > 0 && (
+        <div className="pending-changes">
+          <h4 className={`section-title ${theme}`}>Pending changes</h4>
+          <ul className="library-list">
+            {pending.map((edit, idx) => (
+              <li key={idx} className={`library-item ${theme}`}>
+                <span className="library-query">{describeEdit(edit)}</span>
+                <button
+                  onClick={() => {
+                    setPending(pending.filter((_, i) => i !== idx));
+                    setPreview(null);
+                  }}
+                  className={`btn-secondary ${theme}`}
+                >
+                  Remove
+                </button>
+              </li>
+            ))}
+          </ul>
+          {preview && <pre className={`schema-sql-text ${theme}`}>{preview.join('\n')}</pre>}
+          <div className="editor-actions">
+            <button onClick={handlePreview} className={`btn-secondary ${theme}`}>Preview SQL</button>
+            <button onClick={handleCommit} className={`btn-primary ${theme}`}>Commit</button>
+            <button
+              onClick={() => {
+                setPending([]);
+                setPreview(null);
+              }}
+              className={`btn-secondary ${theme}`}
+            >
+              Discard
+            </button>
+          </div>
+        </div>
+      )}
+
+      {summary && (
+        <span className={`info-text ${theme}`}>
+          Committed {summary.statements} change(s), {summary.rowsAffected} row(s) affected
+        </span>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,104 @@
+import { useState } from 'react';
+import { ColumnInfo, TableSchema, ViewSchema } from '../types/Database';
+
+interface TableSchemaViewProps {
+  schema: TableSchema | ViewSchema;
+  isDarkMode: boolean;
+}
+
+function describeColumn(column: ColumnInfo): string {
+  const parts = [column.declaredType || 'ANY'];
+  if (column.primaryKey > 0) parts.push('PRIMARY KEY');
+  if (!column.nullable) parts.push('NOT NULL');
+  if (column.defaultValue != null) parts.push(`DEFAULT ${column.defaultValue}`);
+  return parts.join(' ');
+}
+
+export function TableSchemaView({ schema, isDarkMode }: TableSchemaViewProps) {
+  const [showSql, setShowSql] = useState(false);
+  const theme = isDarkMode ? 'dark' : 'light';
+  const indexes = 'indexes' in schema ? schema.indexes : [];
+  const foreignKeys = 'foreignKeys' in schema ? schema.foreignKeys : [];
+
+  return (
+    <div className={`schema-view ${theme}`}>
+      <ul className="schema-tree">
+        <li>
+          <span className={`schema-group ${theme}`}>Columns</span>
+          <ul>
+            {schema.columns.map((column) => (
+              <li key={column.name} className={`schema-item ${theme}`}>
+                <span className="schema-name">{column.name}</span>
+                <span className={`schema-detail ${theme}`}>{describeColumn(column)}</span>
+              </li>
+            ))}
+          </ul>
+        </li>
+
+        {indexes.length > 0 && (
+          <li>
+            <span className={`schema-group ${theme}`}>Indexes</span>
+            <ul>
+              {indexes.map((index) => (
+                <li key={index.name} className={`schema-item ${theme}`}>
+                  <span className="schema-name">{index.name}</span>
+                  <span className={`schema-detail ${theme}`}>
+                    {index.unique ? 'UNIQUE ' : ''}({index.columns.join(', ')}){index.partial ? ' partial' : ''}
+                  </span>
+                </li>
+              ))}
+            </ul>
+          </li>
+        )}
+
+        {foreignKeys.length > 0 && (
+          <li>
+            <span className={`schema-group ${theme}`}>Foreign keys</span>
+            <ul>
+              {foreignKeys.map((fk) => (
+                <li key={fk.id} className={`schema-item ${theme}`}>
+                  <span className="schema-name">({fk.columns.join(', ')})</span>
+                  <span className={`schema-detail ${theme}`}>
+                    → {fk.referencedTable}({fk.referencedColumns.map((c) => c ?? 'rowid').join(', ')})
+                    {' '}ON DELETE {fk.onDelete} ON UPDATE {fk.onUpdate}
+                  </span>
+                </li>
+              ))}
+            </ul>
+          </li>
+        )}
+
+        {schema.triggers.length > 0 && (
+          <li>
+            <span className={`schema-group ${theme}`}>Triggers</span>
+            <ul>
+              {schema.triggers.map((trigger) => (
+                <li key={trigger.name} className={`schema-item ${theme}`}>
+                  <span className="schema-name">{trigger.name}</span>
+                </li>
+              ))}
+            </ul>
+          </li>
+        )}
+      </ul>
+
+      {schema.sql && (
+        <div className="schema-sql">
+          <button
+            onClick={() => setShowSql(!showSql)}
+            className={`btn-secondary ${theme}`}
+          >
+            {showSql ? 'Hide' : 'Show'} CREATE SQL
+          </button>
+          {showSql && (
+            <pre className={`schema-sql-text ${theme}`}>
+              {[schema.sql, ...indexes.map((i) => i.sql), ...schema.triggers.map((t) => t.sql)]
+                .filter(Boolean)
+                .join(';\n\n')}
+            </pre>
+          )}
+        </div>
+      )}
+    </div>
+  );
+}
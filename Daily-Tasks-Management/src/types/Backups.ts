@@ -0,0 +1,14 @@
+export type BackupReason = 'scheduled' | 'manual' | 'migration' | 'import' | 'optimize' | 'restore' | 'edit';
+
+export interface Snapshot {
+  id: string;
+  reason: BackupReason;
+  createdAt: string;
+  sizeBytes: number;
+}
+
+export interface SnapshotDetails {
+  snapshot: Snapshot;
+  integrity: string;
+  tableRowCounts: Record<string, number>;
+}
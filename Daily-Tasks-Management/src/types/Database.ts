@@ -3,8 +3,325 @@ export interface TableInfo {
   rowCount: number;
 }
 
+// Integers are decimal strings so values past 2^53 keep every digit
+export type CellValue =
+  | { type: 'null' }
+  | { type: 'integer'; value: string }
+  | { type: 'real'; value: number }
+  | { type: 'text'; value: string }
+  | { type: 'blob'; size: number; hex: string; base64: string };
+
+export interface ResultColumn {
+  name: string;
+  declaredType?: string;
+}
+
 export interface QueryResult {
-  columns: string[];
-  rows: string[][];
+  columns: ResultColumn[];
+  rows: CellValue[][];
   rowCount: number;
+  // Stopped at the row cap with rows left unread
+  truncated: boolean;
+}
+
+export function formatCell(cell: CellValue): string {
+  switch (cell.type) {
+    case 'null':
+      return 'NULL';
+    case 'real':
+      return String(cell.value);
+    case 'blob':
+      return `<${cell.size} bytes> ${cell.hex}${cell.size * 2 > cell.hex.length ? '…' : ''}`;
+    default:
+      return cell.value;
+  }
+}
+
+// `?` placeholders take positional[index - 1], `:name` ones named[name]
+export interface QueryParams {
+  positional: CellValue[];
+  named: Record<string, CellValue>;
+}
+
+export interface QueryParameter {
+  index: number;
+  name?: string;
+}
+
+/** Reads a typed-in parameter: integers, decimals, NULL, otherwise text. */
+export function parseParamValue(input: string): CellValue {
+  const trimmed = input.trim();
+  if (trimmed.toUpperCase() === 'NULL') return { type: 'null' };
+  if (/^-?\d+$/.test(trimmed)) return { type: 'integer', value: trimmed };
+  if (trimmed !== '' && !Number.isNaN(Number(trimmed))) return { type: 'real', value: Number(trimmed) };
+  return { type: 'text', value: input };
+}
+
+export function buildQueryParams(parameters: QueryParameter[], values: Record<number, string>): QueryParams {
+  const params: QueryParams = { positional: [], named: {} };
+  for (const parameter of parameters) {
+    const value = parseParamValue(values[parameter.index] ?? '');
+    if (parameter.name) {
+      params.named[parameter.name] = value;
+      params.positional[parameter.index - 1] = { type: 'null' };
+    } else {
+      params.positional[parameter.index - 1] = value;
+    }
+  }
+  return params;
+}
+
+export interface QueryOptions {
+  pageSize?: number;
+  maxRows?: number;
+  // Execution time across all pages; waiting between fetches does not count
+  timeoutMs?: number;
+}
+
+// 'running' means more rows may follow
+export type QueryStatus = 'running' | 'done' | 'truncated' | 'cancelled' | 'failed';
+
+export interface QueryProgress {
+  handle: number;
+  status: QueryStatus;
+  rowsRead: number;
+  elapsedMs: number;
+  executionMs: number;
+  steps: number;
+}
+
+export interface QueryStarted {
+  handle: number;
+  columns: ResultColumn[];
+}
+
+export interface QueryPage {
+  handle: number;
+  columns: ResultColumn[];
+  rows: CellValue[][];
+  offset: number;
+  progress: QueryProgress;
+}
+
+export interface QueryHistoryEntry {
+  id: number;
+  databaseId: string;
+  query: string;
+  durationMs: number;
+  rowCount?: number;
+  error?: string;
+  executedAt: string;
+}
+
+export interface SavedQuery {
+  id: number;
+  name: string;
+  query: string;
+  databaseId?: string;
+  description?: string;
+  createdAt: string;
+  updatedAt: string;
+}
+
+export type ExportFormat = 'csv' | 'json' | 'ndjson' | 'sql' | 'markdown';
+
+export type ExportSource =
+  | { kind: 'table'; name: string }
+  | { kind: 'query'; sql: string; params?: QueryParams };
+
+export interface ExportSummary {
+  path: string;
+  format: ExportFormat;
+  rowCount: number;
+  sizeBytes: number;
+}
+
+export interface DiscoveredDatabase {
+  id: string;
+  projectId: number;
+  path: string;
+  relativePath: string;
+  sizeBytes: number;
+  modifiedAt?: string;
+}
+
+export interface ProjectDatabases {
+  projectId: number;
+  projectName: string;
+  databases: DiscoveredDatabase[];
+}
+
+// Id of the active workspace's own database
+export const WORKSPACE_DATABASE_ID = 'workspace';
+
+// Database ids of the form `profile:<id>` browse a connection profile
+export const PROFILE_DATABASE_PREFIX = 'profile:';
+
+export function profileDatabaseId(profileId: number): string {
+  return `${PROFILE_DATABASE_PREFIX}${profileId}`;
+}
+
+export function isProfileDatabase(databaseId: string): boolean {
+  return databaseId.startsWith(PROFILE_DATABASE_PREFIX);
+}
+
+// Server databases number their placeholders $1, $2, ...
+export function dollarParameters(query: string): QueryParameter[] {
+  const indexes = [...query.matchAll(/\$(\d+)/g)].map((match) => Number(match[1]));
+  const count = indexes.length > 0 ? Math.max(...indexes) : 0;
+  return Array.from({ length: count }, (_, i) => ({ index: i + 1 }));
+}
+
+export type ConnectorKind = 'postgres';
+
+// The password is never returned; it lives in the app's secret store
+export interface ConnectionProfile {
+  id: number;
+  projectId: number;
+  name: string;
+  kind: ConnectorKind;
+  host: string;
+  port: number;
+  database: string;
+  username: string;
+  createdAt: string;
+}
+
+export interface ConnectionProfileForm {
+  projectId: number;
+  name: string;
+  kind: ConnectorKind;
+  host: string;
+  port: number;
+  database: string;
+  username: string;
+  password?: string;
+}
+
+export interface ColumnInfo {
+  name: string;
+  declaredType: string;
+  nullable: boolean;
+  defaultValue?: string;
+  primaryKey: number;
+}
+
+export interface IndexInfo {
+  name: string;
+  unique: boolean;
+  origin: 'c' | 'u' | 'pk';
+  partial: boolean;
+  columns: string[];
+  sql?: string;
+}
+
+export interface ForeignKeyInfo {
+  id: number;
+  referencedTable: string;
+  columns: string[];
+  referencedColumns: (string | null)[];
+  onUpdate: string;
+  onDelete: string;
+}
+
+export interface TriggerInfo {
+  name: string;
+  sql?: string;
+}
+
+export interface TableSchema {
+  name: string;
+  sql?: string;
+  columns: ColumnInfo[];
+  indexes: IndexInfo[];
+  foreignKeys: ForeignKeyInfo[];
+  triggers: TriggerInfo[];
+}
+
+export interface ViewSchema {
+  name: string;
+  sql?: string;
+  columns: ColumnInfo[];
+  triggers: TriggerInfo[];
+}
+
+export interface DatabaseSchema {
+  tables: TableSchema[];
+  views: ViewSchema[];
+}
+
+export type MigrationMode = 'schema' | 'full';
+
+export interface DiffOptions {
+  includeData?: boolean;
+  maxRows?: number;
+  migration?: MigrationMode;
+}
+
+export type ChangeKind = 'added' | 'removed' | 'changed';
+
+export interface SchemaChange<T> {
+  name: string;
+  change: ChangeKind;
+  before?: T;
+  after?: T;
+}
+
+export interface RowChange {
+  change: ChangeKind;
+  key: CellValue[];
+  before?: CellValue[];
+  after?: CellValue[];
+  changedColumns: string[];
+}
+
+export interface DataDiff {
+  key: string[];
+  columns: string[];
+  added: number;
+  removed: number;
+  changed: number;
+  rows: RowChange[];
+  truncated: boolean;
+}
+
+export interface TableDiff {
+  name: string;
+  // Absent when only the rows differ
+  change?: ChangeKind;
+  columns: SchemaChange<ColumnInfo>[];
+  indexes: SchemaChange<IndexInfo>[];
+  triggers: SchemaChange<TriggerInfo>[];
+  foreignKeysChanged: boolean;
+  data?: DataDiff;
+  dataSkipped?: string;
+}
+
+export interface DatabaseDiff {
+  tables: TableDiff[];
+  views: SchemaChange<ViewSchema>[];
+  migration?: string;
+}
+
+export interface WriteMode {
+  enabled: boolean;
+  // The app's own task database; writes need an explicit override
+  appDatabase: boolean;
+}
+
+// Keys hold every primary key column, or just `rowid`
+export type RowEdit =
+  | { kind: 'insert'; table: string; values: Record<string, CellValue> }
+  | { kind: 'update'; table: string; key: Record<string, CellValue>; values: Record<string, CellValue> }
+  | { kind: 'delete'; table: string; key: Record<string, CellValue> };
+
+export interface ChangesetSummary {
+  statements: number;
+  rowsAffected: number;
+}
+
+export interface EditableRows {
+  key: string[];
+  keys: CellValue[][];
+  data: QueryResult;
 }
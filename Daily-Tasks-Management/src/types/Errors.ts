@@ -0,0 +1,38 @@
+export type ErrorCode =
+  | 'not_found'
+  | 'validation'
+  | 'conflict'
+  | 'io'
+  | 'database'
+  | 'git'
+  | 'process'
+  | 'timeout'
+  | 'internal';
+
+export interface ErrorDetails {
+  entity?: string;
+  id?: string;
+  field?: string;
+  path?: string;
+}
+
+export interface AppError {
+  code: ErrorCode;
+  message: string;
+  details: ErrorDetails;
+  sourceChain: string[];
+}
+
+export function isAppError(err: unknown): err is AppError {
+  return typeof err === 'object' && err !== null && 'code' in err && 'message' in err;
+}
+
+// Turns whatever a rejected invoke() returned into a displayable message
+export function errorMessage(err: unknown): string {
+  if (isAppError(err)) {
+    return err.sourceChain.length > 0
+      ? `${err.message}: ${err.sourceChain[0]}`
+      : err.message;
+  }
+  return String(err);
+}
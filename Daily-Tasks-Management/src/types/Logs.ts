@@ -4,4 +4,66 @@ export interface LogEntry {
   level: 'info' | 'error' | 'warn' | 'debug';
   message: string;
   timestamp: string;
+  fields: Record<string, string>; // empty for unstructured lines
+}
+
+// Filters for query_service_logs; omitted fields match everything
+export interface LogQuery {
+  serviceId?: number;
+  levels?: string[];
+  since?: string;
+  until?: string;
+  search?: string;
+  cursor?: number;
+  limit?: number;
+}
+
+export interface LogPage {
+  entries: LogEntry[]; // newest first
+  nextCursor: number | null;
+}
+
+export interface LogRetention {
+  maxAgeDays: number | null;
+  maxLines: number | null;
+  maxBytes: number | null;
+}
+
+export type LogFormat = 'auto' | 'json' | 'logfmt' | 'prefix' | 'plain';
+
+export interface LogParser {
+  format: LogFormat;
+  keepAnsi: boolean;
+  stderrLevel: LogEntry['level'];
+}
+
+export interface AnsiSegment {
+  text: string;
+  color: number | null; // 0-7, or 8-15 for bright colors
+  bold: boolean;
+}
+
+// Splits a message kept with its ANSI codes into styled runs. Only colors
+// and bold are rendered; other escape sequences are dropped.
+export function ansiSegments(message: string): AnsiSegment[] {
+  const segments: AnsiSegment[] = [];
+  let color: number | null = null;
+  let bold = false;
+  const pattern = /\x1b\[([0-9;]*)([@-~])|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b./g;
+  let last = 0;
+  for (const match of message.matchAll(pattern)) {
+    if (match.index! > last) segments.push({ text: message.slice(last, match.index), color, bold });
+    last = match.index! + match[0].length;
+    if (match[2] !== 'm') continue;
+    for (const code of (match[1] || '0').split(';').map(Number)) {
+      if (code === 0) { color = null; bold = false; }
+      else if (code === 1) bold = true;
+      else if (code === 22) bold = false;
+      else if (code >= 30 && code <= 37) color = code - 30;
+      else if (code >= 90 && code <= 97) color = code - 90 + 8;
+      else if (code === 39) color = null;
+    }
+  }
+  if (last < message.length) segments.push({ text: message.slice(last), color, bold });
+  return segments;
 }
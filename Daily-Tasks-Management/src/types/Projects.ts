@@ -12,3 +12,13 @@ export interface ProjectFormData {
   name: string;
   path: string;
 }
+
+export type ProjectDeleteMode = 'cascade' | 'detach';
+
+export interface ProjectDeletionImpact {
+  projectId: number;
+  serviceIds: number[];
+  taskIds: number[];
+  gitRepoIds: number[];
+  connectionProfileIds: number[];
+}
@@ -5,6 +5,8 @@ export interface Service {
   workingDir?: string;
   projectId?: number;
   autoStart: boolean;
+  // Run the command through the system shell instead of splitting it
+  shell: boolean;
   createdAt: string;
 }
 
@@ -14,4 +16,149 @@ export interface ServiceFormData {
   workingDir?: string;
   projectId?: number;
   autoStart: boolean;
+  shell?: boolean;
+}
+
+// Secret values are never sent back; `value` is then undefined. Saving a
+// secret without a value keeps the stored one.
+export interface ServiceEnvVar {
+  name: string;
+  value?: string | null;
+  secret: boolean;
+}
+
+export interface ServiceEnvironment {
+  variables: ServiceEnvVar[];
+  // Loaded in order; relative paths start at the working directory
+  envFiles: string[];
+}
+
+export type EnvSource = { kind: 'file'; path: string } | { kind: 'service' };
+
+export interface ResolvedEnvVar {
+  name: string;
+  // null when masked
+  value: string | null;
+  secret: boolean;
+  source: EnvSource;
+}
+
+// Progress of starting `autoStart` services when the app launches
+export type AutoStartEvent =
+  | { kind: 'skipped'; reason: string }
+  | { kind: 'starting'; serviceId: number; name: string; index: number; total: number }
+  | { kind: 'started'; serviceId: number; name: string; pid: number }
+  | { kind: 'failed'; serviceId: number; name: string; error: string }
+  | { kind: 'finished'; started: number; failed: number };
+
+export const AUTO_START_EVENT = 'service-auto-start';
+
+export type RestartMode = 'never' | 'on-failure' | 'always';
+
+// The delay doubles with each restart in a row, up to maxBackoffMs
+export interface RestartPolicy {
+  mode: RestartMode;
+  maxRetries: number;
+  initialBackoffMs: number;
+  maxBackoffMs: number;
+}
+
+// A process killed by a signal has no exit code
+export interface ExitInfo {
+  code?: number | null;
+  signal?: number | null;
+}
+
+export type ServiceState = 'running' | 'restarting' | 'exited' | 'gave-up' | 'stopped';
+
+export type ProbeCheck =
+  | { kind: 'http'; url: string; expectedStatus: number }
+  | { kind: 'tcp'; port: number }
+  // Readiness only
+  | { kind: 'log'; pattern: string }
+  | { kind: 'command'; command: string };
+
+export interface Probe {
+  check: ProbeCheck;
+  intervalMs: number;
+  timeoutMs: number;
+  failureThreshold: number;
+}
+
+// Readiness is probed after each start until it passes, liveness from then on
+export interface HealthChecks {
+  readiness?: Probe | null;
+  liveness?: Probe | null;
+}
+
+export interface ProbeResult {
+  ok: boolean;
+  message: string;
+  timestamp: string;
+  consecutiveFailures: number;
+}
+
+export interface ServiceStatus {
+  serviceId: number;
+  state: ServiceState;
+  pid?: number | null;
+  restarts: number;
+  lastExit?: ExitInfo | null;
+  nextRestartAt?: string | null;
+  ready: boolean;
+  // null without a liveness probe or before its first run
+  healthy?: boolean | null;
+  readiness?: ProbeResult | null;
+  liveness?: ProbeResult | null;
+}
+
+// Sent with the new status whenever state, readiness or health changes
+export const SERVICE_STATUS_EVENT = 'service-status';
+
+export interface CrashRecord {
+  serviceId: number;
+  pid: number;
+  exit: ExitInfo;
+  timestamp: string;
+  uptimeMs: number;
+  // Output just before the exit, oldest first
+  lastLogs: string[];
+  willRestart: boolean;
+  // Set when the supervisor ended the process, e.g. a failed liveness probe
+  reason?: string | null;
+}
+
+// Summed over the service's process and everything it started
+export interface ResourceSample {
+  timestamp: string;
+  pid: number;
+  processes: number;
+  // 100 is one core
+  cpuPercent: number;
+  rssBytes: number;
+  // null where the platform does not list threads
+  threads?: number | null;
+  openPorts: number[];
+}
+
+export function describeExit(exit: ExitInfo): string {
+  if (exit.code != null) return `exit code ${exit.code}`;
+  if (exit.signal != null) return `signal ${exit.signal}`;
+  return 'unknown exit status';
+}
+
+// Started together; dependencies outside the group are started first
+export interface ServiceGroup {
+  id: number;
+  name: string;
+  serviceIds: number[];
+  createdAt: string;
+}
+
+export interface GroupStartReport {
+  started: number[];
+  alreadyRunning: number[];
+  failed?: { serviceId: number; name: string; error: string } | null;
+  // Left stopped because a service before them failed
+  skipped: number[];
 }
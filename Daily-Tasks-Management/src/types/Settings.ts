@@ -0,0 +1,6 @@
+export interface AppSettings {
+  // Leave auto-start services stopped when the app launches
+  skipAutoStart: boolean;
+  // Time a stopped service gets to exit before it is killed; applies from the next start
+  stopGracePeriodMs: number;
+}
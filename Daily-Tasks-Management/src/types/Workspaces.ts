@@ -0,0 +1,5 @@
+export interface Workspace {
+  name: string;
+  databasePath: string;
+  active: boolean;
+}